@@ -24,3 +24,342 @@ AS 'MODULE_PATHNAME', 'vector_clusters_wrapper';
 DROP PROCEDURE IF EXISTS paradedb.create_bm25_test_table(table_name pg_catalog."varchar", schema_name pg_catalog."varchar", table_type paradedb.testtable);
 CREATE OR REPLACE PROCEDURE paradedb.create_paradedb_test_table(table_name VARCHAR DEFAULT 'bm25_test_table', schema_name VARCHAR DEFAULT 'paradedb', table_type paradedb.TestTable DEFAULT 'Items')
 LANGUAGE c AS 'MODULE_PATHNAME', 'create_paradedb_test_table_wrapper';
+
+-- Named analyzers: paradedb.analyzers stores tokenizer configurations registered
+-- with pdb.create_analyzer, which indexes reference with `analyzer=<name>`.
+CREATE TABLE IF NOT EXISTS paradedb.analyzers(
+    name text NOT NULL PRIMARY KEY,
+    tokenizer text NOT NULL,
+    filters jsonb NOT NULL DEFAULT '{}',
+    typmod integer NOT NULL
+);
+SELECT pg_catalog.pg_extension_config_dump('paradedb.analyzers', '');
+GRANT SELECT ON TABLE paradedb.analyzers TO PUBLIC;
+
+DROP FUNCTION IF EXISTS pdb."create_analyzer"(TEXT, TEXT, jsonb);
+CREATE  FUNCTION pdb."create_analyzer"(
+	"name" TEXT, /* &str */
+	"tokenizer" TEXT, /* &str */
+	"filters" jsonb DEFAULT '{}' /* pgrx::datum::json::JsonB */
+) RETURNS void
+STRICT
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'create_analyzer_wrapper';
+
+DROP FUNCTION IF EXISTS pdb."drop_analyzer"(TEXT, bool);
+CREATE  FUNCTION pdb."drop_analyzer"(
+	"name" TEXT, /* &str */
+	"if_exists" bool DEFAULT false /* bool */
+) RETURNS void
+STRICT
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'drop_analyzer_wrapper';
+
+DROP FUNCTION IF EXISTS pdb."tokenize"(TEXT, TEXT);
+CREATE  FUNCTION pdb."tokenize"(
+	"analyzer" TEXT, /* &str */
+	"input_text" TEXT /* &str */
+) RETURNS TABLE (
+	"token" TEXT,  /* String */
+	"position" INT  /* i32 */
+)
+STABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'tokenize_with_analyzer_wrapper';
+
+-- Analyzer debugging: the tokens after the tokenizer and after each of its filters.
+DROP FUNCTION IF EXISTS paradedb."tokenize_explain"(jsonb, TEXT);
+CREATE  FUNCTION paradedb."tokenize_explain"(
+	"tokenizer_setting" jsonb, /* pgrx::datum::json::JsonB */
+	"input_text" TEXT /* &str */
+) RETURNS TABLE (
+	"stage" TEXT,  /* alloc::string::String */
+	"token" TEXT,  /* alloc::string::String */
+	"position" INT,  /* i32 */
+	"position_length" INT,  /* i32 */
+	"offset_from" INT,  /* i32 */
+	"offset_to" INT  /* i32 */
+)
+IMMUTABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'tokenize_explain_wrapper';
+
+DROP FUNCTION IF EXISTS pdb."tokenize_explain"(TEXT, TEXT);
+CREATE  FUNCTION pdb."tokenize_explain"(
+	"analyzer" TEXT, /* &str */
+	"input_text" TEXT /* &str */
+) RETURNS TABLE (
+	"stage" TEXT,  /* alloc::string::String */
+	"token" TEXT,  /* alloc::string::String */
+	"position" INT,  /* i32 */
+	"position_length" INT,  /* i32 */
+	"offset_from" INT,  /* i32 */
+	"offset_to" INT  /* i32 */
+)
+STABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'tokenize_explain_with_analyzer_wrapper';
+
+-- Vector opclasses for pgvector's halfvec and sparsevec types and Postgres' bit type, which
+-- are indexed as dense vectors. As for `vector`, they only tag the attribute with its metric.
+CREATE OPERATOR CLASS public.halfvec_l2_ops DEFAULT FOR TYPE public.halfvec USING bm25 AS
+    STORAGE public.halfvec;
+
+CREATE OPERATOR CLASS public.halfvec_cosine_ops FOR TYPE public.halfvec USING bm25 AS
+    STORAGE public.halfvec;
+
+CREATE OPERATOR CLASS public.halfvec_ip_ops FOR TYPE public.halfvec USING bm25 AS
+    STORAGE public.halfvec;
+
+CREATE OPERATOR CLASS public.sparsevec_l2_ops DEFAULT FOR TYPE public.sparsevec USING bm25 AS
+    STORAGE public.sparsevec;
+
+CREATE OPERATOR CLASS public.sparsevec_cosine_ops FOR TYPE public.sparsevec USING bm25 AS
+    STORAGE public.sparsevec;
+
+CREATE OPERATOR CLASS public.sparsevec_ip_ops FOR TYPE public.sparsevec USING bm25 AS
+    STORAGE public.sparsevec;
+
+CREATE OPERATOR CLASS public.bit_hamming_ops DEFAULT FOR TYPE bit USING bm25 AS
+    STORAGE bit;
+
+CREATE OPERATOR CLASS public.bit_jaccard_ops FOR TYPE bit USING bm25 AS
+    STORAGE bit;
+
+CREATE OPERATOR CLASS public.halfvec_l2_ops DEFAULT FOR TYPE public.halfvec USING paradedb AS
+    STORAGE public.halfvec;
+
+CREATE OPERATOR CLASS public.halfvec_cosine_ops FOR TYPE public.halfvec USING paradedb AS
+    STORAGE public.halfvec;
+
+CREATE OPERATOR CLASS public.halfvec_ip_ops FOR TYPE public.halfvec USING paradedb AS
+    STORAGE public.halfvec;
+
+CREATE OPERATOR CLASS public.sparsevec_l2_ops DEFAULT FOR TYPE public.sparsevec USING paradedb AS
+    STORAGE public.sparsevec;
+
+CREATE OPERATOR CLASS public.sparsevec_cosine_ops FOR TYPE public.sparsevec USING paradedb AS
+    STORAGE public.sparsevec;
+
+CREATE OPERATOR CLASS public.sparsevec_ip_ops FOR TYPE public.sparsevec USING paradedb AS
+    STORAGE public.sparsevec;
+
+CREATE OPERATOR CLASS public.bit_hamming_ops DEFAULT FOR TYPE bit USING paradedb AS
+    STORAGE bit;
+
+CREATE OPERATOR CLASS public.bit_jaccard_ops FOR TYPE bit USING paradedb AS
+    STORAGE bit;
+
+-- Hybrid search: fuse a text query with a vector search in one query, scored by
+-- reciprocal rank fusion or by blending min-max normalized scores.
+DROP FUNCTION IF EXISTS pdb."rrf"(SearchQueryInput, FieldName, anyelement, real, real, real, INT);
+CREATE  FUNCTION pdb."rrf"(
+	"query" SearchQueryInput, /* pg_search::query::SearchQueryInput */
+	"field" FieldName, /* pg_search::api::FieldName */
+	"vector" anyelement, /* pgrx::datum::anyelement::AnyElement */
+	"k" real DEFAULT 60.0, /* f32 */
+	"text_weight" real DEFAULT 1.0, /* f32 */
+	"vector_weight" real DEFAULT 1.0, /* f32 */
+	"window_size" INT DEFAULT 100 /* i32 */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+IMMUTABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'rrf_wrapper';
+
+DROP FUNCTION IF EXISTS pdb."score_blend"(SearchQueryInput, FieldName, anyelement, real, real, INT);
+CREATE  FUNCTION pdb."score_blend"(
+	"query" SearchQueryInput, /* pg_search::query::SearchQueryInput */
+	"field" FieldName, /* pg_search::api::FieldName */
+	"vector" anyelement, /* pgrx::datum::anyelement::AnyElement */
+	"text_weight" real DEFAULT 0.5, /* f32 */
+	"vector_weight" real DEFAULT 0.5, /* f32 */
+	"window_size" INT DEFAULT 100 /* i32 */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+IMMUTABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'score_blend_wrapper';
+
+-- pdb.vector_within matches the rows whose vector is within a distance of a query vector.
+DROP FUNCTION IF EXISTS pdb."vector_within"(FieldName, anyelement, double precision);
+CREATE  FUNCTION pdb."vector_within"(
+	"field" FieldName, /* pg_search::api::FieldName */
+	"vector" anyelement, /* pgrx::datum::anyelement::AnyElement */
+	"max_distance" double precision /* f64 */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+IMMUTABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'vector_within_wrapper';
+
+-- pdb.max_sim scores the rows of a multi-vector (vector[]) field by sum-of-MaxSim.
+DROP FUNCTION IF EXISTS pdb."max_sim"(FieldName, anyelement);
+CREATE  FUNCTION pdb."max_sim"(
+	"field" FieldName, /* pg_search::api::FieldName */
+	"vectors" anyelement /* pgrx::datum::anyelement::AnyElement */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+IMMUTABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'max_sim_wrapper';
+
+-- pdb.vector_recall measures per-segment recall@k of vector search against an
+-- exact search that probes every cluster.
+DROP FUNCTION IF EXISTS pdb."vector_recall"(regclass, TEXT, anyelement, INT);
+CREATE  FUNCTION pdb."vector_recall"(
+	"index" regclass, /* PgRelation */
+	"field" TEXT, /* String */
+	"sample_queries" anyelement, /* pgrx::datum::anyelement::AnyElement */
+	"k" INT DEFAULT 10 /* i32 */
+) RETURNS TABLE (
+	"segno" TEXT,  /* String */
+	"num_queries" bigint,  /* i64 */
+	"recall" double precision  /* f64 */
+)
+STRICT
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'vector_recall_wrapper';
+
+-- pdb.rebalance_vectors retrains the vector centroids of every clustered segment.
+DROP FUNCTION IF EXISTS pdb."rebalance_vectors"(regclass);
+CREATE  FUNCTION pdb."rebalance_vectors"(
+	"index" regclass /* PgRelation */
+) RETURNS void
+STRICT
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'rebalance_vectors_wrapper';
+
+-- pdb.vector_distance projects the distance a vector ORDER BY ... LIMIT scan
+-- ordered each row by.
+DROP FUNCTION IF EXISTS pdb."vector_distance"(anyelement);
+CREATE  FUNCTION pdb."vector_distance"(
+	"relation_reference" anyelement /* pgrx::datum::anyelement::AnyElement */
+) RETURNS double precision /* f64 */
+STRICT STABLE PARALLEL SAFE COST 1
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'vector_distance_from_relation_wrapper';
+DROP FUNCTION IF EXISTS pdb."vector_distance"(anyelement, double precision);
+CREATE  FUNCTION pdb."vector_distance"(
+	"relation_reference" anyelement, /* pgrx::datum::anyelement::AnyElement */
+	"distance" double precision /* f64 */
+) RETURNS double precision /* f64 */
+STRICT STABLE PARALLEL SAFE COST 1
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'vector_distance_from_expr_wrapper';
+ALTER FUNCTION pdb.vector_distance(anyelement) SUPPORT paradedb.placeholder_support;
+
+-- pdb.function_score adjusts a query's scores with decays and factors of its
+-- documents' fast field values.
+DROP FUNCTION IF EXISTS pdb."function_score"(SearchQueryInput, jsonb, TEXT, TEXT);
+CREATE  FUNCTION pdb."function_score"(
+	"query" SearchQueryInput, /* pg_search::query::SearchQueryInput */
+	"functions" jsonb, /* pgrx::datum::json::JsonB */
+	"score_mode" TEXT DEFAULT 'multiply', /* alloc::string::String */
+	"boost_mode" TEXT DEFAULT 'multiply' /* alloc::string::String */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+STABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'function_score_wrapper';
+
+-- pdb.combined_fields scores a query across several text fields with BM25F,
+-- as if they were one field.
+DROP FUNCTION IF EXISTS pdb."combined_fields"(TEXT, TEXT[], bool);
+CREATE  FUNCTION pdb."combined_fields"(
+	"value" TEXT, /* alloc::string::String */
+	"fields" TEXT[], /* alloc::vec::Vec<alloc::string::String> */
+	"conjunction_mode" bool DEFAULT false /* bool */
+) RETURNS SearchQueryInput /* pg_search::query::SearchQueryInput */
+IMMUTABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'combined_fields_wrapper';
+
+-- pdb.percolate returns the keys of the queries stored in an index's table that
+-- match a document.
+DROP FUNCTION IF EXISTS pdb."percolate"(regclass, jsonb);
+CREATE  FUNCTION pdb."percolate"(
+	"index" regclass, /* PgRelation */
+	"document" jsonb /* pgrx::datum::json::JsonB */
+) RETURNS TABLE (
+	"key" TEXT  /* alloc::string::String */
+)
+STRICT
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'percolate_wrapper';
+
+-- pdb.percolate_terms lists the terms a document needs for a stored query to
+-- match it, for pdb.percolate to skip the others.
+DROP FUNCTION IF EXISTS pdb."percolate_terms"(regclass, paradedb.SearchQueryInput);
+CREATE  FUNCTION pdb."percolate_terms"(
+	"index" regclass, /* PgRelation */
+	"query" paradedb.SearchQueryInput /* pg_search::query::SearchQueryInput */
+) RETURNS TEXT[] /* alloc::vec::Vec<alloc::string::String> */
+IMMUTABLE STRICT PARALLEL SAFE
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'percolate_terms_wrapper';
+
+-- pdb.add_field and pdb.drop_field change the fields of an index without
+-- rebuilding it, and pdb.schema_changes lists those changes.
+DROP FUNCTION IF EXISTS pdb."add_field"(regclass, TEXT, jsonb);
+CREATE  FUNCTION pdb."add_field"(
+	"index" regclass, /* PgRelation */
+	"field" TEXT, /* alloc::string::String */
+	"config" jsonb /* pgrx::datum::json::JsonB */
+) RETURNS void
+STRICT
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'add_field_wrapper';
+
+DROP FUNCTION IF EXISTS pdb."drop_field"(regclass, TEXT);
+CREATE  FUNCTION pdb."drop_field"(
+	"index" regclass, /* PgRelation */
+	"field" TEXT /* alloc::string::String */
+) RETURNS void
+STRICT
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'drop_field_wrapper';
+
+DROP FUNCTION IF EXISTS pdb."schema_changes"(regclass);
+CREATE  FUNCTION pdb."schema_changes"(
+	"index" regclass /* PgRelation */
+) RETURNS TABLE (
+	"version" INT,  /* i32 */
+	"field" TEXT,  /* alloc::string::String */
+	"change" TEXT,  /* alloc::string::String */
+	"unfilled_segments" bigint  /* i64 */
+)
+STRICT
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'schema_changes_wrapper';
+
+CREATE VIEW pdb.index_progress as
+select v.pid,
+       v.datid,
+       v.datname,
+       v.relid,
+       v.index_relid,
+       case when p.param20 = 1 then 'MERGE' else v.command end as command,
+       v.phase,
+       v.blocks_total,
+       v.blocks_done,
+       v.tuples_total,
+       v.tuples_done,
+       case when p.param20 = 1 then p.param19 end              as segments_total,
+       p.param18                                               as segments_done
+from pg_stat_progress_create_index v
+         join pg_stat_get_progress_info('CREATE INDEX') p on p.pid = v.pid
+where p.param9::oid in (select oid from pg_am where amname in ('paradedb', 'bm25'));
+
+GRANT SELECT ON pdb.index_progress TO PUBLIC;
+
+-- pdb.merge_queue lists the background merges queued for, and run by, the merge scheduler.
+DROP FUNCTION IF EXISTS pdb."merge_queue"();
+CREATE  FUNCTION pdb."merge_queue"() RETURNS TABLE (
+	"database_name" TEXT,  /* alloc::string::String */
+	"index_oid" oid,  /* pgrx_pg_sys::submodules::oids::Oid */
+	"index_name" TEXT,  /* alloc::string::String */
+	"layer" TEXT,  /* alloc::string::String */
+	"state" TEXT,  /* alloc::string::String */
+	"pid" INT,  /* core::option::Option<i32> */
+	"enqueued_at" timestamp with time zone,  /* core::option::Option<pgrx::datum::time_stamp_with_timezone::TimestampWithTimeZone> */
+	"started_at" timestamp with time zone  /* core::option::Option<pgrx::datum::time_stamp_with_timezone::TimestampWithTimeZone> */
+)
+STRICT
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'merge_queue_wrapper';
@@ -442,6 +442,13 @@ fn vector_info(
                     ))
                 .then_some(f)
             });
+        if search_reader
+            .schema()
+            .search_field(&field)
+            .is_some_and(|search_field| search_field.vector_quantization().is_some())
+        {
+            anyhow::bail!("`{field}` is quantized, so its vectors aren't clustered");
+        }
         let Some(vector_field) = resolved else {
             anyhow::bail!("`{field}` is not a vector field of the index");
         };
@@ -524,6 +531,13 @@ fn vector_clusters(
                     ))
                 .then_some(f)
             });
+        if search_reader
+            .schema()
+            .search_field(&field)
+            .is_some_and(|search_field| search_field.vector_quantization().is_some())
+        {
+            anyhow::bail!("`{field}` is quantized, so its vectors aren't clustered");
+        }
         let Some(vector_field) = resolved else {
             anyhow::bail!("`{field}` is not a vector field of the index");
         };
@@ -1097,6 +1111,32 @@ GRANT SELECT ON pdb.index_layer_info TO PUBLIC;
     requires = [index_info, combined_layer_sizes]
 );
 
+// The progress of the builds and background merges of ParadeDB indexes.  Background merges also
+// appear in `pg_stat_progress_create_index`, without a `command`.
+extension_sql!(
+    r#"create view pdb.index_progress as
+select v.pid,
+       v.datid,
+       v.datname,
+       v.relid,
+       v.index_relid,
+       case when p.param20 = 1 then 'MERGE' else v.command end as command,
+       v.phase,
+       v.blocks_total,
+       v.blocks_done,
+       v.tuples_total,
+       v.tuples_done,
+       case when p.param20 = 1 then p.param19 end              as segments_total,
+       p.param18                                               as segments_done
+from pg_stat_progress_create_index v
+         join pg_stat_get_progress_info('CREATE INDEX') p on p.pid = v.pid
+where p.param9::oid in (select oid from pg_am where amname in ('paradedb', 'bm25'));
+
+GRANT SELECT ON pdb.index_progress TO PUBLIC;
+"#,
+    name = "pdb_index_progress"
+);
+
 // =============================================================================
 // pdb schema functions for index verification
 // =============================================================================
@@ -2027,4 +2067,244 @@ pub mod pdb {
 
         Ok(TableIterator::new(results))
     }
+
+    /// Rebalances the vector clusters of `index` by merging each of its clustered segments on its
+    /// own, which retrains the segment's centroids.
+    ///
+    /// Merges do this by themselves for segments whose clusters have drifted past
+    /// `paradedb.vector_rebalance_size_ratio` or `paradedb.vector_rebalance_empty_fraction`;
+    /// this forces it for every clustered segment.
+    ///
+    /// # Example
+    /// ```sql
+    /// SELECT pdb.rebalance_vectors('items_idx');
+    /// ```
+    #[pg_extern]
+    pub fn rebalance_vectors(index: PgRelation) -> Result<()> {
+        let index = {
+            let oid = index.oid();
+            drop(index);
+            // reopen the index with a RowExclusiveLock b/c we are going to be changing its physical structure
+            PgSearchRelation::with_lock(oid, pg_sys::RowExclusiveLock as _)
+        };
+        let index_kind = IndexKind::for_index(index.clone())?;
+        for index in index_kind.partitions() {
+            if index.is_usable() {
+                unsafe { crate::postgres::merge::rebalance_vectors(&index) };
+            }
+        }
+        Ok(())
+    }
+
+    /// Measures the recall@k of vector search on each segment of `index` that stores the vector
+    /// `field`.
+    ///
+    /// Each of `sample_queries` is searched for in every segment twice: as vector ORDER BY queries
+    /// are, probing at most `paradedb.vector_cluster_max_probe` of the segment's clusters, and by
+    /// brute force, scoring every live vector of the segment. A segment's recall is the fraction
+    /// of its exact top `k` that the first search also found, averaged over the queries.
+    ///
+    /// # Example
+    /// ```sql
+    /// SELECT * FROM pdb.vector_recall(
+    ///     'items_idx',
+    ///     'embedding',
+    ///     (SELECT array_agg(embedding) FROM (SELECT embedding FROM items TABLESAMPLE SYSTEM (1)) s),
+    ///     k => 10
+    /// );
+    /// ```
+    #[allow(clippy::type_complexity)]
+    #[pg_extern]
+    pub fn vector_recall(
+        index: PgRelation,
+        field: String,
+        sample_queries: AnyElement,
+        k: default!(i32, 10),
+    ) -> Result<
+        TableIterator<
+            'static,
+            (
+                name!(segno, String),
+                name!(num_queries, i64),
+                name!(recall, f64),
+            ),
+        >,
+    > {
+        use crate::vector::{PgVector, VectorType, exact_probe_params, probe_params};
+        use pgrx::{AnyElement, Array, FromDatum};
+        use tantivy::collector::{Collector, TopDocs};
+        use tantivy::query::{AllQuery, EnableScoring, Query};
+        use tantivy::vector::ivf::AdaptiveProbeParams;
+        use tantivy::{SegmentOrdinal, SegmentReader};
+
+        if k < 1 {
+            anyhow::bail!("k must be at least 1, got {k}");
+        }
+        let element_type = unsafe { pg_sys::get_element_type(sample_queries.oid()) };
+        if VectorType::from_oid(element_type).is_none() {
+            anyhow::bail!("sample_queries must be an array of vector, halfvec, sparsevec or bit");
+        }
+        let queries = unsafe {
+            Array::<pg_sys::Datum>::from_datum(sample_queries.datum(), false)
+                .expect("sample_queries should not be NULL")
+                .iter()
+                .flatten()
+                .map(|datum| {
+                    PgVector::from_polymorphic_datum(datum, false, element_type)
+                        .expect("array element should not be NULL")
+                        .0
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let index = PgSearchRelation::with_lock(index.oid(), pg_sys::AccessShareLock as _);
+        let index_kind = IndexKind::for_index(index.clone())?;
+        if !index.is_usable() {
+            return Ok(TableIterator::new(Vec::new()));
+        }
+
+        let mut rows = Vec::new();
+        for index in index_kind.partitions() {
+            if !index.is_usable() {
+                continue;
+            }
+            let search_reader = SearchIndexReader::empty(&index, MvccSatisfies::Snapshot)?;
+            let Some(search_field) = search_reader.schema().search_field(&field) else {
+                anyhow::bail!("`{field}` is not a vector field of the index");
+            };
+            let SearchFieldType::Vector(_, dims, metric) = search_field.field_type() else {
+                anyhow::bail!("`{field}` is not a vector field of the index");
+            };
+            if search_field.vector_quantization().is_some() {
+                anyhow::bail!(
+                    "`{field}` is quantized, and pdb.vector_recall only measures clustered searches"
+                );
+            }
+            // `<%>` is always answered by scoring every bit string, so there's no recall to measure
+            if metric == crate::vector::metric::VectorMetric::Jaccard {
+                anyhow::bail!(
+                    "`{field}` is indexed with bit_jaccard_ops, whose searches are exact"
+                );
+            }
+            if let Some(query) = queries.iter().find(|query| query.len() != dims) {
+                anyhow::bail!(
+                    "field `{field}` has {dims} dimensions, but a sample query has {}",
+                    query.len()
+                );
+            }
+
+            let searcher = search_reader.searcher();
+            let weight = AllQuery.weight(EnableScoring::disabled_from_searcher(searcher))?;
+            // the top `k` of the `limit` nearest vectors a search finds.  A `limit` of the
+            // segment's `max_doc` is never reached, so that search scores every live vector
+            let top_k = |segment_ord: SegmentOrdinal,
+                         segment_reader: &SegmentReader,
+                         query: &[f32],
+                         limit: usize,
+                         params: AdaptiveProbeParams| {
+                let collector = TopDocs::with_limit(limit.max(1))
+                    .order_by_similarity(search_field.field(), query.to_vec())
+                    .with_adaptive_params(params);
+                let fruit =
+                    collector.collect_segment(weight.as_ref(), segment_ord, segment_reader)?;
+                Ok::<_, tantivy::TantivyError>(
+                    collector
+                        .merge_fruits(vec![fruit])?
+                        .results
+                        .into_iter()
+                        .take(k as usize)
+                        .map(|(_, doc)| doc.doc_id)
+                        .collect::<HashSet<_>>(),
+                )
+            };
+
+            for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+                if segment_reader
+                    .vector_index(search_field.field())?
+                    .info()
+                    .is_none()
+                {
+                    continue;
+                }
+                let mut total_recall = 0.0;
+                let mut num_queries = 0;
+                let segment_ord = segment_ord as SegmentOrdinal;
+                for query in &queries {
+                    let exact = top_k(
+                        segment_ord,
+                        segment_reader,
+                        query,
+                        segment_reader.max_doc() as usize,
+                        exact_probe_params(),
+                    )?;
+                    if exact.is_empty() {
+                        continue;
+                    }
+                    let approximate = top_k(
+                        segment_ord,
+                        segment_reader,
+                        query,
+                        if crate::gucs::vector_exact_search() {
+                            segment_reader.max_doc() as usize
+                        } else {
+                            k as usize
+                        },
+                        probe_params(),
+                    )?;
+                    total_recall +=
+                        approximate.intersection(&exact).count() as f64 / exact.len() as f64;
+                    num_queries += 1;
+                }
+                if num_queries > 0 {
+                    rows.push((
+                        segment_reader.segment_id().short_uuid_string(),
+                        num_queries,
+                        total_recall / num_queries as f64,
+                    ));
+                }
+            }
+        }
+
+        Ok(TableIterator::new(rows))
+    }
+
+    /// Lists the background merges queued for the merge scheduler, and those it's running,
+    /// across all databases.  Empty when `paradedb.enable_merge_scheduler` is off.
+    ///
+    /// # Example
+    /// ```sql
+    /// SELECT * FROM pdb.merge_queue();
+    /// ```
+    #[allow(clippy::type_complexity)]
+    #[pg_extern]
+    pub fn merge_queue() -> TableIterator<
+        'static,
+        (
+            name!(database_name, String),
+            name!(index_oid, pg_sys::Oid),
+            name!(index_name, String),
+            name!(layer, String),
+            name!(state, String),
+            name!(pid, Option<i32>),
+            name!(enqueued_at, Option<pgrx::datum::TimestampWithTimeZone>),
+            name!(started_at, Option<pgrx::datum::TimestampWithTimeZone>),
+        ),
+    > {
+        let rows = crate::postgres::merge_scheduler::queued_merges()
+            .into_iter()
+            .map(|merge| {
+                (
+                    merge.database_name,
+                    merge.index_oid,
+                    merge.index_name,
+                    merge.layer.to_string(),
+                    merge.state.to_string(),
+                    merge.pid,
+                    merge.enqueued_at,
+                    merge.started_at,
+                )
+            })
+            .collect::<Vec<_>>();
+        TableIterator::new(rows)
+    }
 }
@@ -0,0 +1,59 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+/// Contains the combined fields query's builder function, which lives in the `pdb` schema.
+#[pgrx::pg_schema]
+mod pdb {
+    use crate::api::FieldName;
+    use crate::query::SearchQueryInput;
+    use pgrx::{default, pg_extern};
+
+    /// Scores `value` in the text `fields` with BM25F, as if they were one field.
+    ///
+    /// A field's term frequencies and lengths can be weighted with a `^` suffix, like `title^2`.
+    #[pg_extern(immutable, parallel_safe)]
+    pub fn combined_fields(
+        value: String,
+        fields: Vec<String>,
+        conjunction_mode: default!(bool, "false"),
+    ) -> anyhow::Result<SearchQueryInput> {
+        if fields.is_empty() {
+            anyhow::bail!("combined_fields needs at least one field");
+        }
+        let fields = fields
+            .iter()
+            .map(|field| parse_field(field))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(SearchQueryInput::CombinedFields {
+            fields,
+            value,
+            conjunction_mode,
+        })
+    }
+
+    fn parse_field(field: &str) -> anyhow::Result<(FieldName, f32)> {
+        let Some((name, weight)) = field.rsplit_once('^') else {
+            return Ok((FieldName::from(field.to_string()), 1.0));
+        };
+        match weight.parse::<f32>() {
+            Ok(weight) if weight.is_finite() && weight > 0.0 => {
+                Ok((FieldName::from(name.to_string()), weight))
+            }
+            _ => anyhow::bail!("the weight of field `{name}` must be a positive number"),
+        }
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+/// Contains the function score query's builder function, which lives in the `pdb` schema.
+#[pgrx::pg_schema]
+mod pdb {
+    use crate::api::FieldName;
+    use crate::postgres::datetime::PostgresDateTime;
+    use crate::query::SearchQueryInput;
+    use crate::query::function_score::{
+        DecayFunction, Modifier, ScoreFunction, ScoreFunctionKind, parse_duration_micros,
+    };
+    use pgrx::{JsonB, default, pg_extern};
+    use serde::de::DeserializeOwned;
+    use serde_json::{Map, Value};
+
+    /// Adjusts the scores of `query`'s documents with `functions` of their fast field values.
+    ///
+    /// Each function is a JSON object with one of a `gauss`, `exp` or `linear` decay, or a
+    /// `field_value_factor`, an optional `weight` and an optional `filter` query.
+    #[pg_extern(stable, parallel_safe)]
+    pub fn function_score(
+        query: SearchQueryInput,
+        functions: JsonB,
+        score_mode: default!(String, "'multiply'"),
+        boost_mode: default!(String, "'multiply'"),
+    ) -> anyhow::Result<SearchQueryInput> {
+        let Value::Array(entries) = functions.0 else {
+            anyhow::bail!("functions must be a JSON array");
+        };
+        let functions = entries
+            .into_iter()
+            .map(parse_function)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(SearchQueryInput::FunctionScore {
+            query: Box::new(query),
+            functions,
+            score_mode: parse_mode("score_mode", &score_mode)?,
+            boost_mode: parse_mode("boost_mode", &boost_mode)?,
+        })
+    }
+
+    fn parse_mode<T: DeserializeOwned>(name: &str, mode: &str) -> anyhow::Result<T> {
+        serde_json::from_value(Value::String(mode.to_lowercase()))
+            .map_err(|_| anyhow::anyhow!("unknown {name} `{mode}`"))
+    }
+
+    fn parse_function(entry: Value) -> anyhow::Result<ScoreFunction> {
+        let Value::Object(mut entry) = entry else {
+            anyhow::bail!("each function must be a JSON object");
+        };
+        let weight = match entry.remove("weight") {
+            None => 1.0,
+            Some(weight) => match weight.as_f64() {
+                Some(weight) if weight.is_finite() => weight as f32,
+                _ => anyhow::bail!("weight must be a number"),
+            },
+        };
+        let filter = match entry.remove("filter") {
+            None => None,
+            Some(Value::String(query_string)) => Some(Box::new(SearchQueryInput::Parse {
+                query_string,
+                lenient: None,
+                conjunction_mode: None,
+            })),
+            Some(filter) => {
+                Some(Box::new(serde_json::from_value(filter).map_err(|err| {
+                    anyhow::anyhow!("invalid filter query: {err}")
+                })?))
+            }
+        };
+
+        let mut kinds = entry.into_iter();
+        let kind = match (kinds.next(), kinds.next()) {
+            (None, _) => ScoreFunctionKind::Weight,
+            (Some((name, Value::Object(params))), None) => match name.as_str() {
+                "gauss" => parse_decay(DecayFunction::Gauss, params)?,
+                "exp" => parse_decay(DecayFunction::Exp, params)?,
+                "linear" => parse_decay(DecayFunction::Linear, params)?,
+                "field_value_factor" => parse_field_value_factor(params)?,
+                _ => anyhow::bail!("unknown score function `{name}`"),
+            },
+            (Some((name, _)), None) => anyhow::bail!("`{name}` must be a JSON object"),
+            (Some(_), Some(_)) => anyhow::bail!("each function must have only one kind"),
+        };
+        Ok(ScoreFunction {
+            filter,
+            weight,
+            kind,
+        })
+    }
+
+    fn parse_decay(
+        function: DecayFunction,
+        mut params: Map<String, Value>,
+    ) -> anyhow::Result<ScoreFunctionKind> {
+        let field = parse_field(&mut params)?;
+        let dates = matches!(params.get("origin"), Some(Value::String(_)));
+        let origin = match params.remove("origin") {
+            Some(Value::String(origin)) => PostgresDateTime::try_from_timestamptz_str(&origin)
+                .map_err(|_| anyhow::anyhow!("origin `{origin}` is not a timestamp"))?
+                .into_inner() as f64,
+            Some(Value::Number(origin)) => origin.as_f64().unwrap_or_default(),
+            _ => anyhow::bail!("a decay function needs a number or timestamp `origin`"),
+        };
+        let mut distance = |name: &str| match params.remove(name) {
+            None => Ok(None),
+            Some(Value::String(duration)) if dates => {
+                parse_duration_micros(&duration).map(Some).ok_or_else(|| {
+                    anyhow::anyhow!("{name} `{duration}` is not a duration like `7d` or `12h`")
+                })
+            }
+            Some(Value::Number(distance)) if !dates => Ok(distance.as_f64()),
+            Some(_) if dates => anyhow::bail!("{name} must be a duration like `7d` or `12h`"),
+            Some(_) => anyhow::bail!("{name} must be a number"),
+        };
+        let scale = distance("scale")?
+            .ok_or_else(|| anyhow::anyhow!("a decay function needs a `scale`"))?;
+        let offset = distance("offset")?.unwrap_or(0.0);
+        let decay = match params.remove("decay") {
+            None => 0.5,
+            Some(decay) => decay
+                .as_f64()
+                .ok_or_else(|| anyhow::anyhow!("decay must be a number"))?,
+        };
+        reject_unknown(&params)?;
+
+        if scale.is_nan() || scale <= 0.0 {
+            anyhow::bail!("scale must be greater than zero");
+        }
+        if offset.is_nan() || offset < 0.0 {
+            anyhow::bail!("offset must be zero or greater");
+        }
+        if decay.is_nan() || decay <= 0.0 || decay >= 1.0 {
+            anyhow::bail!("decay must be between 0 and 1, got {decay}");
+        }
+        Ok(ScoreFunctionKind::Decay {
+            function,
+            field,
+            origin,
+            scale,
+            offset,
+            decay,
+            dates,
+        })
+    }
+
+    fn parse_field_value_factor(
+        mut params: Map<String, Value>,
+    ) -> anyhow::Result<ScoreFunctionKind> {
+        let field = parse_field(&mut params)?;
+        let mut number = |name: &str| match params.remove(name) {
+            None => Ok(None),
+            Some(value) => value
+                .as_f64()
+                .map(Some)
+                .ok_or_else(|| anyhow::anyhow!("{name} must be a number")),
+        };
+        let factor = number("factor")?.unwrap_or(1.0);
+        let missing = number("missing")?;
+        let modifier = match params.remove("modifier") {
+            None => Modifier::None,
+            Some(Value::String(modifier)) => parse_mode("modifier", &modifier)?,
+            Some(_) => anyhow::bail!("modifier must be a string"),
+        };
+        reject_unknown(&params)?;
+
+        Ok(ScoreFunctionKind::FieldValueFactor {
+            field,
+            factor,
+            modifier,
+            missing,
+        })
+    }
+
+    fn parse_field(params: &mut Map<String, Value>) -> anyhow::Result<FieldName> {
+        match params.remove("field") {
+            Some(Value::String(field)) => Ok(FieldName::from(field)),
+            _ => anyhow::bail!("a score function needs a `field`"),
+        }
+    }
+
+    fn reject_unknown(params: &Map<String, Value>) -> anyhow::Result<()> {
+        match params.keys().next() {
+            Some(key) => anyhow::bail!("unknown score function parameter `{key}`"),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+/// Contains the hybrid search functions, which fuse a text query with a vector search and live
+/// in the `pdb` schema.
+#[pgrx::pg_schema]
+mod pdb {
+    use crate::api::FieldName;
+    use crate::query::SearchQueryInput;
+    use crate::query::hybrid::Fusion;
+    use crate::vector::{PgVector, VectorType};
+    use pgrx::{AnyElement, FromDatum, default, pg_extern};
+
+    /// Fuses the results of `query` with the nearest neighbours of `vector` in `field` by
+    /// reciprocal rank fusion.
+    #[pg_extern(immutable, parallel_safe)]
+    pub fn rrf(
+        query: SearchQueryInput,
+        field: FieldName,
+        vector: AnyElement,
+        k: default!(f32, 60.0),
+        text_weight: default!(f32, 1.0),
+        vector_weight: default!(f32, 1.0),
+        window_size: default!(i32, 100),
+    ) -> anyhow::Result<SearchQueryInput> {
+        if k.is_nan() || k < 0.0 {
+            anyhow::bail!("k must be zero or greater, got {k}");
+        }
+        hybrid(
+            query,
+            field,
+            vector,
+            Fusion::Rrf {
+                k,
+                text_weight,
+                vector_weight,
+            },
+            window_size,
+        )
+    }
+
+    /// Fuses the results of `query` with the nearest neighbours of `vector` in `field` by
+    /// blending their min-max normalized scores.
+    #[pg_extern(immutable, parallel_safe)]
+    pub fn score_blend(
+        query: SearchQueryInput,
+        field: FieldName,
+        vector: AnyElement,
+        text_weight: default!(f32, 0.5),
+        vector_weight: default!(f32, 0.5),
+        window_size: default!(i32, 100),
+    ) -> anyhow::Result<SearchQueryInput> {
+        hybrid(
+            query,
+            field,
+            vector,
+            Fusion::ScoreBlend {
+                text_weight,
+                vector_weight,
+            },
+            window_size,
+        )
+    }
+
+    fn hybrid(
+        query: SearchQueryInput,
+        field: FieldName,
+        vector: AnyElement,
+        fusion: Fusion,
+        window_size: i32,
+    ) -> anyhow::Result<SearchQueryInput> {
+        let (text_weight, vector_weight) = fusion.weights();
+        if text_weight.is_nan()
+            || text_weight < 0.0
+            || vector_weight.is_nan()
+            || vector_weight < 0.0
+        {
+            anyhow::bail!("text_weight and vector_weight must be zero or greater");
+        }
+        if window_size < 1 {
+            anyhow::bail!("window_size must be at least 1, got {window_size}");
+        }
+        if VectorType::from_oid(vector.oid()).is_none() {
+            anyhow::bail!("vector must be a vector, halfvec, sparsevec or bit value");
+        }
+        let vector =
+            unsafe { PgVector::from_polymorphic_datum(vector.datum(), false, vector.oid()) }
+                .expect("vector should not be NULL")
+                .0;
+
+        Ok(SearchQueryInput::Hybrid {
+            query: Box::new(query),
+            field,
+            vector,
+            fusion,
+            window_size: window_size as usize,
+            filter: None,
+        })
+    }
+}
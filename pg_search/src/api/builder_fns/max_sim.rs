@@ -0,0 +1,42 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+/// Contains the multi-vector search's builder function, which lives in the `pdb` schema.
+#[pgrx::pg_schema]
+mod pdb {
+    use crate::api::FieldName;
+    use crate::query::SearchQueryInput;
+    use crate::vector::VectorType;
+    use crate::vector::multi::vectors_from_array;
+    use pgrx::{AnyElement, pg_extern, pg_sys};
+
+    /// Matches the rows with vectors in the multi-vector `field`, scored by the sum, over
+    /// `vectors`, of each one's greatest inner product with any of the row's vectors.
+    #[pg_extern(immutable, parallel_safe)]
+    pub fn max_sim(field: FieldName, vectors: AnyElement) -> anyhow::Result<SearchQueryInput> {
+        let element_type = unsafe { pg_sys::get_element_type(vectors.oid()) };
+        if VectorType::from_oid(element_type).is_none() {
+            anyhow::bail!("vectors must be an array of vector, halfvec, sparsevec or bit");
+        }
+        let vectors = unsafe { vectors_from_array(vectors.datum(), element_type) };
+        if vectors.is_empty() {
+            anyhow::bail!("vectors must hold at least one vector");
+        }
+
+        Ok(SearchQueryInput::MaxSim { field, vectors })
+    }
+}
@@ -15,10 +15,15 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+mod combined_fields;
+mod function_score;
+mod hybrid;
+mod max_sim;
 mod mlt;
 mod paradedb;
 mod pdb;
 mod proximity;
+mod vector_within;
 
 pub use paradedb::*;
 pub use pdb::*;
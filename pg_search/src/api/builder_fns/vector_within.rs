@@ -0,0 +1,51 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+/// Contains the vector range search's builder function, which lives in the `pdb` schema.
+#[pgrx::pg_schema]
+mod pdb {
+    use crate::api::FieldName;
+    use crate::query::SearchQueryInput;
+    use crate::vector::{PgVector, VectorType};
+    use pgrx::{AnyElement, FromDatum, pg_extern};
+
+    /// Matches the rows whose `field` is within `max_distance` of `vector`, by the distance of
+    /// the operator class `field` is indexed with.
+    #[pg_extern(immutable, parallel_safe)]
+    pub fn vector_within(
+        field: FieldName,
+        vector: AnyElement,
+        max_distance: f64,
+    ) -> anyhow::Result<SearchQueryInput> {
+        if max_distance.is_nan() {
+            anyhow::bail!("max_distance must be a number");
+        }
+        if VectorType::from_oid(vector.oid()).is_none() {
+            anyhow::bail!("vector must be a vector, halfvec, sparsevec or bit value");
+        }
+        let vector =
+            unsafe { PgVector::from_polymorphic_datum(vector.datum(), false, vector.oid()) }
+                .expect("vector should not be NULL")
+                .0;
+
+        Ok(SearchQueryInput::VectorWithin {
+            field,
+            vector,
+            max_distance,
+        })
+    }
+}
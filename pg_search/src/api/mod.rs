@@ -23,6 +23,8 @@ pub mod aggregate;
 pub mod builder_fns;
 pub mod config;
 pub mod operator;
+mod percolate;
+mod schema_changes;
 pub mod tokenize;
 pub mod tokenizers;
 pub mod version;
@@ -408,7 +410,8 @@ impl QueryVector {
                 let mut isnull = false;
                 let datum = pg_sys::ExecEvalExpr(expr_state, econtext, &mut isnull);
                 assert!(!isnull, "vector ORDER BY operand evaluated to NULL");
-                let floats = PgVector::from_datum(datum, false)
+                let typoid = pg_sys::exprType(prepared.as_ptr().cast());
+                let floats = PgVector::from_polymorphic_datum(datum, false, typoid)
                     .expect("vector ORDER BY operand should not be NULL")
                     .0;
                 *self = QueryVector::Resolved(floats);
@@ -440,8 +443,8 @@ impl QueryVector {
         let slot = &(*param_list)
             .params
             .as_slice((*param_list).numParams as usize)[idx];
-        let (value, isnull) = if (slot.pflags & pg_sys::PARAM_FLAG_CONST as u16) != 0 {
-            (slot.value, slot.isnull)
+        let (value, isnull, typoid) = if (slot.pflags & pg_sys::PARAM_FLAG_CONST as u16) != 0 {
+            (slot.value, slot.isnull, slot.ptype)
         } else if let Some(fetch) = (*param_list).paramFetch {
             let mut prmdata = pg_sys::ParamExternData {
                 value: pg_sys::Datum::null(),
@@ -451,13 +454,13 @@ impl QueryVector {
             };
             let prm = fetch(param_list, paramid, false, &mut prmdata);
             assert!(!prm.is_null(), "paramFetch returned NULL for ${paramid}");
-            ((*prm).value, (*prm).isnull)
+            ((*prm).value, (*prm).isnull, (*prm).ptype)
         } else {
-            (slot.value, slot.isnull)
+            (slot.value, slot.isnull, slot.ptype)
         };
         assert!(!isnull, "vector ORDER BY parameter ${paramid} is NULL");
 
-        let floats = PgVector::from_datum(value, false)
+        let floats = PgVector::from_polymorphic_datum(value, false, typoid)
             .expect("vector ORDER BY parameter should not be NULL")
             .0;
         *self = QueryVector::Resolved(floats);
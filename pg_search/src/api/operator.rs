@@ -43,9 +43,10 @@ use crate::postgres::composite::get_composite_type_fields;
 use crate::postgres::customscan::opexpr::{
     UnwrapFromExpr, expr_matches_node, vars_equal_ignoring_varno,
 };
+use crate::postgres::customscan::qual_inspect::choose_query_bm25_index;
 use crate::postgres::deparse::deparse_expr;
 use crate::postgres::rel::PgSearchRelation;
-use crate::postgres::rel_get_bm25_index;
+use crate::postgres::rel_get_bm25_indexes;
 use crate::postgres::utils::ToPalloc;
 #[cfg(feature = "pg18")]
 use crate::postgres::var::resolve_rte_group_var;
@@ -421,16 +422,52 @@ pub unsafe fn tantivy_field_name_from_node(
     if heaprelid == pg_sys::Oid::INVALID {
         return None;
     }
-    let (heaprel, indexrel) = rel_get_bm25_index(heaprelid).unwrap_or_else(|| {
+    let (heaprel, indexrels) = rel_get_bm25_indexes(heaprelid).unwrap_or_else(|| {
         panic!(
             "`{}` does not contain a `USING paradedb` index",
             PgSearchRelation::open(heaprelid).name()
         )
     });
 
-    let field_name =
-        field_name_from_node(VarContext::from_planner(root), &heaprel, &indexrel, node)?;
-    Some((indexrel, Some(field_name)))
+    // when the node is a column of a table in this query, use the index that the scan of that
+    // table will use, so that all of the table's search conditions agree on one
+    let var = find_vars(node)[0];
+    let varno = (*var).varno as pg_sys::Index;
+    let parse = (*root).parse;
+    if (*var).varlevelsup == 0
+        && !parse.is_null()
+        && varno > 0
+        && varno as usize <= PgList::<pg_sys::RangeTblEntry>::from_pg((*parse).rtable).len()
+        && (*pg_sys::rt_fetch(varno, (*parse).rtable)).relid == heaprelid
+        && let Some((_, indexrel)) = choose_query_bm25_index(parse, varno, heaprelid)
+    {
+        if let Some(field_name) =
+            field_name_from_node(VarContext::from_planner(root), &heaprel, &indexrel, node)
+        {
+            return Some((indexrel, Some(field_name)));
+        }
+        if let Some((other, field_name)) = indexrels.iter().find_map(|other| {
+            let field_name =
+                field_name_from_node(VarContext::from_planner(root), &heaprel, other, node)?;
+            Some((other, field_name))
+        }) {
+            pgrx::error!(
+                "`{}` is only indexed by `{}`, but the other search conditions on `{}` use `{}`, and a scan can only use one index.  Create a bm25 index that covers all of their fields",
+                field_name,
+                other.name(),
+                heaprel.name(),
+                indexrel.name()
+            );
+        }
+        return None;
+    }
+
+    // the most recent of the relation's indexes that indexes the node
+    indexrels.into_iter().find_map(|indexrel| {
+        let field_name =
+            field_name_from_node(VarContext::from_planner(root), &heaprel, &indexrel, node)?;
+        Some((indexrel, Some(field_name)))
+    })
 }
 
 pub(crate) unsafe fn row_expr_from_indexed_expr(
@@ -1206,6 +1243,54 @@ CREATE OPERATOR CLASS public.vector_cosine_ops FOR TYPE public.vector USING para
 
 CREATE OPERATOR CLASS public.vector_ip_ops FOR TYPE public.vector USING paradedb AS
     STORAGE public.vector;
+
+CREATE OPERATOR CLASS public.halfvec_l2_ops DEFAULT FOR TYPE public.halfvec USING bm25 AS
+    STORAGE public.halfvec;
+
+CREATE OPERATOR CLASS public.halfvec_cosine_ops FOR TYPE public.halfvec USING bm25 AS
+    STORAGE public.halfvec;
+
+CREATE OPERATOR CLASS public.halfvec_ip_ops FOR TYPE public.halfvec USING bm25 AS
+    STORAGE public.halfvec;
+
+CREATE OPERATOR CLASS public.sparsevec_l2_ops DEFAULT FOR TYPE public.sparsevec USING bm25 AS
+    STORAGE public.sparsevec;
+
+CREATE OPERATOR CLASS public.sparsevec_cosine_ops FOR TYPE public.sparsevec USING bm25 AS
+    STORAGE public.sparsevec;
+
+CREATE OPERATOR CLASS public.sparsevec_ip_ops FOR TYPE public.sparsevec USING bm25 AS
+    STORAGE public.sparsevec;
+
+CREATE OPERATOR CLASS public.bit_hamming_ops DEFAULT FOR TYPE bit USING bm25 AS
+    STORAGE bit;
+
+CREATE OPERATOR CLASS public.bit_jaccard_ops FOR TYPE bit USING bm25 AS
+    STORAGE bit;
+
+CREATE OPERATOR CLASS public.halfvec_l2_ops DEFAULT FOR TYPE public.halfvec USING paradedb AS
+    STORAGE public.halfvec;
+
+CREATE OPERATOR CLASS public.halfvec_cosine_ops FOR TYPE public.halfvec USING paradedb AS
+    STORAGE public.halfvec;
+
+CREATE OPERATOR CLASS public.halfvec_ip_ops FOR TYPE public.halfvec USING paradedb AS
+    STORAGE public.halfvec;
+
+CREATE OPERATOR CLASS public.sparsevec_l2_ops DEFAULT FOR TYPE public.sparsevec USING paradedb AS
+    STORAGE public.sparsevec;
+
+CREATE OPERATOR CLASS public.sparsevec_cosine_ops FOR TYPE public.sparsevec USING paradedb AS
+    STORAGE public.sparsevec;
+
+CREATE OPERATOR CLASS public.sparsevec_ip_ops FOR TYPE public.sparsevec USING paradedb AS
+    STORAGE public.sparsevec;
+
+CREATE OPERATOR CLASS public.bit_hamming_ops DEFAULT FOR TYPE bit USING paradedb AS
+    STORAGE bit;
+
+CREATE OPERATOR CLASS public.bit_jaccard_ops FOR TYPE bit USING paradedb AS
+    STORAGE bit;
 "#,
     name = "bm25_ops_anyelement_operator",
     requires = [
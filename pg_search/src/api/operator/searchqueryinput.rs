@@ -23,7 +23,7 @@ use crate::index::mvcc::MvccSatisfies;
 use crate::index::reader::index::SearchIndexReader;
 use crate::postgres::planner_warnings::{warn_filter_spilled, warn_sequential_scan};
 use crate::postgres::rel::PgSearchRelation;
-use crate::postgres::rel_get_bm25_index;
+use crate::postgres::rel_get_bm25_index_with_fields;
 use crate::postgres::types::TantivyValue;
 use crate::query::SearchQueryInput;
 use crate::{PARAMETERIZED_SELECTIVITY, UNKNOWN_SELECTIVITY, nodecast};
@@ -377,9 +377,11 @@ pub fn query_input_restrict(
                 pg_sys::NodeTag::T_Const => {
                     let const_ = rhs.cast::<pg_sys::Const>();
                     let (heaprelid, _, _) = find_var_relation(var, info);
-                    let indexrel = rel_get_bm25_index(heaprelid)?.1;
                     let search_query_input =
                         SearchQueryInput::from_datum((*const_).constvalue, (*const_).constisnull)?;
+                    let mut field_names = HashSet::default();
+                    search_query_input.extract_field_names(&mut field_names);
+                    let indexrel = rel_get_bm25_index_with_fields(heaprelid, &field_names)?.1;
                     estimate_selectivity(&indexrel, search_query_input)
                 }
                 pg_sys::NodeTag::T_Param => Some(PARAMETERIZED_SELECTIVITY),
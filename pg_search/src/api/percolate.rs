@@ -0,0 +1,436 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Percolation runs every query stored in a table against one document, instead of one query
+//! against every document. The table's index describes how the document is indexed: its columns
+//! are the document's fields, which are left NULL in the rows that store queries.
+//!
+//! Most stored queries can only match a document that has one of their terms, so
+//! `pdb.percolate_terms` lists them, and percolation only runs the stored queries that list one of
+//! the document's terms.  An expression index over `pdb.percolate_terms` finds those without
+//! reading every stored query.
+
+use crate::api::operator::searchqueryinput_typoid;
+use crate::api::{FieldName, HashSet};
+use crate::index::writer::index::single_document_index;
+use crate::postgres::composite::CompositeSlotValues;
+use crate::postgres::heap::ExpressionState;
+use crate::postgres::rel::PgSearchRelation;
+use crate::postgres::utils::{resolve_field_value, row_to_search_document};
+use crate::query::SearchQueryInput;
+use crate::query::pdb_query::pdb::Query as PdbQuery;
+use crate::schema::{FieldSource, SearchFieldConfig, SearchIndexSchema};
+use anyhow::Result;
+use pgrx::datum::DatumWithOid;
+use pgrx::spi::quote_identifier;
+use pgrx::{JsonB, Spi, pg_sys};
+use tantivy::collector::Count;
+use tantivy::query::QueryParser;
+use tantivy::schema::Field;
+use tantivy::{Index, IndexReader, ReloadPolicy, Searcher, TantivyDocument};
+
+#[pgrx::pg_schema]
+mod pdb {
+    use super::*;
+    use pgrx::prelude::*;
+
+    /// Returns the keys of the queries stored in the table of `index` that match `document`.
+    ///
+    /// The table must have one `paradedb.searchqueryinput` column holding the stored queries.
+    /// `document` is a JSON object with the table's columns as keys, and is indexed with the
+    /// same fields and tokenizers as `index`, in a temporary single-document index.
+    ///
+    /// # Example
+    /// ```sql
+    /// CREATE TABLE alerts (id SERIAL PRIMARY KEY, title TEXT, query paradedb.searchqueryinput);
+    /// CREATE INDEX alerts_idx ON alerts USING paradedb (id, title) WITH (key_field = 'id');
+    /// INSERT INTO alerts (query) VALUES (pdb.match('title', 'keyboard'));
+    ///
+    /// SELECT * FROM pdb.percolate('alerts_idx', '{"title": "a mechanical keyboard"}');
+    /// ```
+    #[pg_extern]
+    pub fn percolate(
+        index: PgRelation,
+        document: JsonB,
+    ) -> Result<TableIterator<'static, (name!(key, String),)>> {
+        let index = PgSearchRelation::with_lock(index.oid(), pg_sys::AccessShareLock as _);
+        let heap = index
+            .heap_relation()
+            .ok_or_else(|| anyhow::anyhow!("`{}` is not an index", index.name()))?;
+        let query_column = query_column(&heap)?;
+
+        let document = unsafe { index_document(&index, &heap, document)? };
+        let document_index = single_document_index(&index, document)?;
+        let keys = matching_keys(&index, &heap, &query_column, &document_index)?;
+        Ok(TableIterator::new(keys.into_iter().map(|key| (key,))))
+    }
+
+    /// The terms of `query` against `index`, one of which a document needs to have for `query`
+    /// to match it, or `{*}` if `query` can match a document without any of its terms.
+    ///
+    /// `pdb.percolate` only runs the stored queries whose terms the document has, so a GIN index
+    /// over this function on the column of stored queries saves it from reading all of them.
+    ///
+    /// # Example
+    /// ```sql
+    /// CREATE INDEX alerts_terms_idx ON alerts
+    /// USING gin (pdb.percolate_terms('alerts_idx'::regclass, query));
+    /// ```
+    #[pg_extern(immutable, parallel_safe)]
+    pub fn percolate_terms(index: PgRelation, query: SearchQueryInput) -> Result<Vec<String>> {
+        let index = PgSearchRelation::with_lock(index.oid(), pg_sys::AccessShareLock as _);
+        let terms = query_terms(&index, query)?;
+        if terms.is_empty() {
+            return Ok(vec![ANY_DOCUMENT.to_string()]);
+        }
+        Ok(terms.into_iter().collect())
+    }
+}
+
+/// What [`pdb::percolate_terms`] returns for a query that can match a document without any of its
+/// terms.  Every document "has" it.
+const ANY_DOCUMENT: &str = "*";
+
+/// Finds the column of `heap` that stores the queries to percolate.
+fn query_column(heap: &PgSearchRelation) -> Result<String> {
+    let typoid = searchqueryinput_typoid();
+    let tupdesc = heap.tuple_desc();
+    let mut columns = tupdesc
+        .iter()
+        .filter(|attribute| !attribute.is_dropped() && attribute.type_oid().value() == typoid)
+        .map(|attribute| attribute.name().to_string());
+    match (columns.next(), columns.next()) {
+        (Some(column), None) => Ok(column),
+        (None, _) => anyhow::bail!(
+            "table `{}` has no paradedb.searchqueryinput column of queries to percolate",
+            heap.name()
+        ),
+        (Some(_), Some(_)) => anyhow::bail!(
+            "table `{}` has more than one paradedb.searchqueryinput column",
+            heap.name()
+        ),
+    }
+}
+
+/// Builds the document `index` would index for a row of `heap` with the values in `document`.
+///
+/// The row is made with `jsonb_populate_record`, so that the index's expressions can be evaluated
+/// over it just like over a row of the heap.
+unsafe fn index_document(
+    index: &PgSearchRelation,
+    heap: &PgSearchRelation,
+    document: JsonB,
+) -> Result<TantivyDocument> {
+    let schema = index.schema()?;
+    let categorized_fields = schema.categorized_fields();
+    let created_by_version = index.created_by_version();
+    let expression_state = ExpressionState::new(index);
+    let heaptupdesc = heap.tuple_desc();
+
+    Spi::connect(|client| {
+        let args = [DatumWithOid::from(document)];
+        let row = client
+            .select(
+                &format!(
+                    "SELECT jsonb_populate_record(NULL::{}.{}, $1)",
+                    quote_identifier(heap.namespace()),
+                    quote_identifier(heap.name())
+                ),
+                Some(1),
+                &args,
+            )?
+            .first()
+            .get_datum_by_ordinal(1)?
+            .expect("jsonb_populate_record should return a row");
+
+        let htup = pgrx::heap_tuple::PgHeapTuple::from_composite_datum(row).into_pg();
+        let mut values = vec![pg_sys::Datum::null(); heaptupdesc.len()];
+        let mut isnull = vec![false; heaptupdesc.len()];
+        pg_sys::heap_deform_tuple(
+            htup,
+            heaptupdesc.as_ptr(),
+            values.as_mut_ptr(),
+            isnull.as_mut_ptr(),
+        );
+
+        let slot = pg_sys::MakeSingleTupleTableSlot(heap.rd_att, &pg_sys::TTSOpsHeapTuple);
+        pg_sys::ExecStoreHeapTuple(htup, slot, false);
+        let expr_results = expression_state.evaluate(slot);
+
+        let unpacked_composites = CompositeSlotValues::from_composites(
+            categorized_fields.iter().filter_map(|(_, cat)| {
+                if let FieldSource::CompositeField {
+                    expression_idx,
+                    composite_type_oid,
+                    ..
+                } = cat.source
+                {
+                    let (datum, is_null) = expr_results[expression_idx];
+                    Some((expression_idx, datum, is_null, composite_type_oid))
+                } else {
+                    None
+                }
+            }),
+        );
+
+        // matching queries are returned by their own keys, so the document needn't have one
+        let fields = categorized_fields
+            .iter()
+            .filter_map(|(field, categorized)| {
+                let (datum, is_null) = resolve_field_value(
+                    &categorized.source,
+                    &values,
+                    &isnull,
+                    &expr_results,
+                    &unpacked_composites,
+                );
+                if is_null && categorized.is_key_field {
+                    return None;
+                }
+                Some((datum, is_null, field, categorized))
+            });
+        let mut search_document = TantivyDocument::new();
+        row_to_search_document(fields, &mut search_document, created_by_version)?;
+        pg_sys::ExecDropSingleTupleTableSlot(slot);
+
+        Ok(search_document)
+    })
+}
+
+/// Runs the queries stored in `query_column` of `heap` that could match `document_index`'s document
+/// against it, and returns the keys of those that match.
+fn matching_keys(
+    index: &PgSearchRelation,
+    heap: &PgSearchRelation,
+    query_column: &str,
+    document_index: &Index,
+) -> Result<Vec<String>> {
+    let schema = index.schema()?;
+    let key_field_name = schema.key_field_name();
+    let reader: IndexReader = document_index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()?;
+    let searcher = reader.searcher();
+    let parser = || {
+        QueryParser::for_index(
+            document_index,
+            schema.fields().map(|(field, _)| field).collect::<Vec<_>>(),
+        )
+    };
+
+    let mut document_terms = document_terms(&schema, &searcher)?;
+    document_terms.push(ANY_DOCUMENT.to_string());
+
+    Spi::connect(|client| {
+        let args = [DatumWithOid::from(document_terms)];
+        let rows = client.select(
+            &format!(
+                "SELECT {key}::text, {query} FROM {}.{} WHERE {query} IS NOT NULL \
+                 AND pdb.percolate_terms('{}'::regclass, {query}) && $1",
+                quote_identifier(heap.namespace()),
+                quote_identifier(heap.name()),
+                index.oid().to_u32(),
+                key = quote_identifier(key_field_name.root()),
+                query = quote_identifier(query_column),
+            ),
+            None,
+            &args,
+        )?;
+
+        let mut keys = Vec::new();
+        for row in rows {
+            let key = row
+                .get::<String>(1)?
+                .expect("the key field should not be NULL");
+            let query = row
+                .get::<SearchQueryInput>(2)?
+                .expect("NULL queries should have been filtered out");
+            let query = query
+                .into_tantivy_query(
+                    &schema,
+                    index.created_by_version(),
+                    &parser,
+                    &searcher,
+                    index.oid(),
+                    index.rel_oid(),
+                    None,
+                    None,
+                )
+                .map_err(|err| anyhow::anyhow!("the query with key `{key}` is invalid: {err}"))?;
+            if searcher.search(query.as_ref(), &Count)? > 0 {
+                keys.push(key);
+            }
+        }
+        Ok(keys)
+    })
+}
+
+/// The key of `bytes`, the value of a term of `field`, that [`query_terms`] and [`document_terms`]
+/// compare.  Terms that aren't UTF-8 may share a key, which only makes more queries candidates.
+fn term_key(field: Field, bytes: &[u8]) -> String {
+    format!("{}:{}", field.field_id(), String::from_utf8_lossy(bytes))
+}
+
+/// The keys of the terms of the text and JSON fields of the document in `searcher`.
+fn document_terms(schema: &SearchIndexSchema, searcher: &Searcher) -> Result<Vec<String>> {
+    let mut terms = Vec::new();
+    for (field, field_entry) in schema.fields() {
+        if !is_percolated_field(schema, field_entry.name()) {
+            continue;
+        }
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(field)?;
+            let mut stream = inverted_index.terms().stream()?;
+            while stream.advance() {
+                terms.push(term_key(field, stream.key()));
+            }
+        }
+    }
+    Ok(terms)
+}
+
+/// Does `field_name` index its terms, so that a document's terms can be read back from it?
+fn is_percolated_field(schema: &SearchIndexSchema, field_name: &str) -> bool {
+    schema.search_field(field_name).is_some_and(|search_field| {
+        matches!(
+            search_field.field_config(),
+            SearchFieldConfig::Text { indexed: true, .. }
+                | SearchFieldConfig::Json { indexed: true, .. }
+        )
+    })
+}
+
+/// The keys of the terms one of which a document needs for `query` to match it, or none if it
+/// can match without any of them.
+fn query_terms(index: &PgSearchRelation, query: SearchQueryInput) -> Result<HashSet<String>> {
+    let schema = index.schema()?;
+    // the terms of a query are those it's built with, which only takes the index's tokenizers
+    let empty_index = single_document_index(index, TantivyDocument::new())?;
+    let reader: IndexReader = empty_index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()?;
+    let searcher = reader.searcher();
+    let parser = || {
+        QueryParser::for_index(
+            &empty_index,
+            schema.fields().map(|(field, _)| field).collect::<Vec<_>>(),
+        )
+    };
+
+    let mut terms = HashSet::default();
+    let Some(leaves) = required_leaves(&query) else {
+        return Ok(terms);
+    };
+    for (field, leaf) in leaves {
+        let Some(search_field) = schema.search_field(field.root()) else {
+            return Ok(HashSet::default());
+        };
+        if !is_percolated_field(&schema, &field.root()) {
+            return Ok(HashSet::default());
+        }
+        let leaf = SearchQueryInput::FieldedQuery {
+            field: field.clone(),
+            query: leaf.clone(),
+        }
+        .into_tantivy_query(
+            &schema,
+            index.created_by_version(),
+            &parser,
+            &searcher,
+            index.oid(),
+            index.rel_oid(),
+            None,
+            None,
+        )?;
+        let mut leaf_terms = HashSet::default();
+        for segment_reader in searcher.segment_readers() {
+            leaf.query_terms(search_field.field(), segment_reader, &mut |term, _| {
+                leaf_terms.insert(term_key(term.field(), term.serialized_value_bytes()));
+            });
+        }
+        if leaf_terms.is_empty() {
+            // a leaf without terms can't narrow down the documents
+            return Ok(HashSet::default());
+        }
+        terms.extend(leaf_terms);
+    }
+    Ok(terms)
+}
+
+/// Leaves of `query` one of which a document has to match for `query` to match it, each of which
+/// only matches documents with one of its terms.  `None` if there aren't any.
+fn required_leaves(query: &SearchQueryInput) -> Option<Vec<(&FieldName, &PdbQuery)>> {
+    match query {
+        SearchQueryInput::FieldedQuery { field, query } => {
+            required_leaf(query).then(|| vec![(field, query)])
+        }
+        // any one required clause narrows the query down
+        SearchQueryInput::Boolean { must, should, .. } => {
+            must.iter().find_map(required_leaves).or_else(|| {
+                if !must.is_empty() || should.is_empty() {
+                    return None;
+                }
+                // without any, the query needs one of its optional clauses
+                let mut leaves = Vec::new();
+                for query in should {
+                    leaves.extend(required_leaves(query)?);
+                }
+                Some(leaves)
+            })
+        }
+        SearchQueryInput::DisjunctionMax { disjuncts, .. } if !disjuncts.is_empty() => {
+            let mut leaves = Vec::new();
+            for query in disjuncts {
+                leaves.extend(required_leaves(query)?);
+            }
+            Some(leaves)
+        }
+        SearchQueryInput::Boost { query, .. }
+        | SearchQueryInput::ConstScore { query, .. }
+        | SearchQueryInput::WithIndex { query, .. }
+        | SearchQueryInput::FunctionScore { query, .. }
+        | SearchQueryInput::HeapFilter {
+            indexed_query: query,
+            ..
+        }
+        | SearchQueryInput::ScoreFilter {
+            query: Some(query), ..
+        } => required_leaves(query),
+        _ => None,
+    }
+}
+
+/// Does `query` only match documents with one of the terms it's built with?  Fuzzy, prefix and
+/// regex queries match terms of their own, and query strings can negate theirs.
+fn required_leaf(query: &PdbQuery) -> bool {
+    match query {
+        PdbQuery::Term { .. }
+        | PdbQuery::Phrase { .. }
+        | PdbQuery::PhraseArray { .. }
+        | PdbQuery::TokenizedPhrase { .. } => true,
+        PdbQuery::Match {
+            distance, prefix, ..
+        }
+        | PdbQuery::MatchArray {
+            distance, prefix, ..
+        } => distance.unwrap_or(0) == 0 && !prefix.unwrap_or(false),
+        PdbQuery::ScoreAdjusted { query, .. } => required_leaf(query),
+        _ => false,
+    }
+}
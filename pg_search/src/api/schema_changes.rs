@@ -0,0 +1,290 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Adding and dropping fields of an index without rebuilding it.
+//!
+//! A field can only be added for a column that's already indexed, under a new name, the same way
+//! the `column` key of `text_fields` and `json_fields` indexes one column more than once.  New
+//! segments index the field right away, and the background merger rebuilds the segments that
+//! existed beforehand from the heap.  Until it's done, queries on the new field skip them.
+
+use crate::api::{FieldName, HashSet};
+use crate::index::utils::{load_schema_changes, replace_schema};
+use crate::postgres::merge::launch_backfill;
+use crate::postgres::rel::PgSearchRelation;
+use crate::postgres::storage::metadata::MetaPage;
+use crate::schema::{SearchFieldConfig, SearchFieldType};
+use anyhow::Result;
+use pgrx::spi::{quote_identifier, quote_literal};
+use pgrx::{JsonB, Spi, pg_sys};
+use serde_json::{Map, Value};
+use tantivy::index::SegmentId;
+use tantivy::schema::Schema;
+
+#[pgrx::pg_schema]
+mod pdb {
+    use super::*;
+    use pgrx::prelude::*;
+
+    /// Adds the field `field` to `index`, indexing the column named by the `column` key of
+    /// `config`, which is otherwise configured like an entry of `text_fields` or `json_fields`.
+    ///
+    /// The segments the index already has are backfilled in the background, and queries on
+    /// `field` skip them until they are.
+    ///
+    /// # Example
+    /// ```sql
+    /// SELECT pdb.add_field(
+    ///   'mock_items_idx',
+    ///   'description_ngram',
+    ///   '{"column": "description", "tokenizer": {"type": "ngram", "min_gram": 3, "max_gram": 3}}'
+    /// );
+    /// ```
+    #[pg_extern]
+    pub fn add_field(index: PgRelation, field: String, config: JsonB) -> Result<()> {
+        let index = open_for_change(index, c"pdb.add_field()")?;
+        let schema = index.schema()?;
+        let field_name = FieldName::from(field.clone());
+
+        if field_name.is_ctid() {
+            anyhow::bail!("the name `ctid` is reserved by pg_search");
+        }
+        if schema.tantivy_schema().get_field(&field).is_ok() {
+            if schema.changes().is_dropped(&field) {
+                anyhow::bail!(
+                    "field `{field}` was dropped from `{}` and can't be reused until the index is rebuilt with REINDEX",
+                    index.name()
+                );
+            }
+            anyhow::bail!("`{}` already has a field named `{field}`", index.name());
+        }
+
+        let Some(column) = config.0.get("column").and_then(Value::as_str) else {
+            anyhow::bail!("the config of an added field must name the `column` it indexes");
+        };
+        let column = FieldName::from(column.to_string());
+        let options = index.options();
+        let option_name = match options
+            .attributes()
+            .get(&column)
+            .map(|attribute| attribute.tantivy_type)
+        {
+            Some(SearchFieldType::Text(_)) => "text_fields",
+            Some(SearchFieldType::Json(_)) => "json_fields",
+            Some(_) => anyhow::bail!("column `{column}` must be a text or JSON column"),
+            None => anyhow::bail!("column `{column}` is not indexed by `{}`", index.name()),
+        };
+        if options.field_config_or_default(&column).alias().is_some() {
+            anyhow::bail!("cannot alias an already aliased column");
+        }
+
+        let mut builder = Schema::builder();
+        for (_, field_entry) in schema.tantivy_schema().fields() {
+            builder.add_field(field_entry.clone());
+        }
+        let new_field = if option_name == "text_fields" {
+            let field_config = SearchFieldConfig::text_from_json(config.0.clone())?;
+            builder.add_text_field(&field, field_config)
+        } else {
+            let field_config = SearchFieldConfig::json_from_json(config.0.clone())?;
+            builder.add_json_field(&field, field_config)
+        };
+        let new_schema = builder.build();
+        // the columnar storage of a segment can't be rebuilt one field at a time
+        if new_schema.get_field_entry(new_field).is_fast() {
+            anyhow::bail!(
+                "an added field can't be fast, including one with the keyword tokenizer.  Add it to the index with REINDEX instead"
+            );
+        }
+
+        let mut field_configs = field_configs(&index, option_name)?;
+        field_configs.insert(field.clone(), config.0);
+        set_field_configs(&index, option_name, &field_configs)?;
+
+        let (segment_ids, unfilled) = unsafe { segment_ids(&index) };
+        let mut changes = load_schema_changes(&index)?;
+        changes.retain_segments(&segment_ids);
+        changes.add_field(field_name, unfilled.clone());
+        unsafe {
+            replace_schema(&index, &new_schema, &changes)?;
+            if !unfilled.is_empty() {
+                launch_backfill(&index);
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops the field `field` from `index`.  Only a field added by `pdb.add_field()` or by the
+    /// `column` key of `text_fields` or `json_fields` can be dropped.
+    ///
+    /// The field's data stays in the index's segments until it's rebuilt with `REINDEX`, and its
+    /// name can't be reused until then.
+    #[pg_extern]
+    pub fn drop_field(index: PgRelation, field: String) -> Result<()> {
+        let index = open_for_change(index, c"pdb.drop_field()")?;
+        let schema = index.schema()?;
+
+        if schema.search_field(&field).is_none() {
+            anyhow::bail!("`{}` has no field named `{field}`", index.name());
+        }
+        let option_name = ["text_fields", "json_fields"]
+            .into_iter()
+            .find(|option_name| {
+                let configs = match *option_name {
+                    "text_fields" => index.options().aliased_text_configs(),
+                    _ => index.options().aliased_json_configs(),
+                };
+                configs
+                    .iter()
+                    .any(|(field_name, _)| field_name.as_ref() == field)
+            })
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "`{field}` indexes a column of `{}` directly.  Only fields with a `column` key can be dropped",
+                    index.name()
+                )
+            })?;
+
+        let mut field_configs = field_configs(&index, option_name)?;
+        field_configs.remove(&field);
+        set_field_configs(&index, option_name, &field_configs)?;
+
+        let (segment_ids, _) = unsafe { segment_ids(&index) };
+        let mut changes = load_schema_changes(&index)?;
+        changes.retain_segments(&segment_ids);
+        changes.drop_field(FieldName::from(field));
+        unsafe { replace_schema(&index, schema.tantivy_schema(), &changes) }
+    }
+
+    /// Lists the fields added to and dropped from `index` since it was built, along with how many
+    /// segments are still missing each added field.
+    #[pg_extern]
+    pub fn schema_changes(
+        index: PgRelation,
+    ) -> Result<
+        TableIterator<
+            'static,
+            (
+                name!(version, i32),
+                name!(field, String),
+                name!(change, String),
+                name!(unfilled_segments, i64),
+            ),
+        >,
+    > {
+        let index = PgSearchRelation::with_lock(index.oid(), pg_sys::AccessShareLock as _);
+        let changes = load_schema_changes(&index)?;
+        let visible = unsafe { visible_segment_ids(&index) };
+
+        let mut rows = Vec::new();
+        for added in &changes.added {
+            let unfilled = added
+                .unfilled
+                .iter()
+                .filter(|segment_id| visible.contains(segment_id))
+                .count();
+            rows.push((
+                added.version as i32,
+                added.name.to_string(),
+                "add".to_string(),
+                unfilled as i64,
+            ));
+        }
+        for dropped in &changes.dropped {
+            rows.push((
+                dropped.version as i32,
+                dropped.name.to_string(),
+                "drop".to_string(),
+                0,
+            ));
+        }
+        rows.sort_by_key(|(version, ..)| *version);
+        Ok(TableIterator::new(rows))
+    }
+}
+
+/// Opens `index` to change its schema, which is done outside of a transaction block so that the
+/// change can't be rolled back after the index's new segments have been written with it.
+fn open_for_change(index: PgRelation, caller: &std::ffi::CStr) -> Result<PgSearchRelation> {
+    let oid = index.oid();
+    drop(index);
+
+    unsafe {
+        pg_sys::PreventInTransactionBlock(true, caller.as_ptr());
+        if pg_sys::get_rel_relkind(oid) as u8 == pg_sys::RELKIND_PARTITIONED_INDEX {
+            anyhow::bail!("the fields of a partitioned index can't be changed");
+        }
+    }
+
+    let index = PgSearchRelation::with_lock(oid, pg_sys::AccessExclusiveLock as _);
+    if !index.is_usable() {
+        anyhow::bail!("`{}` is not a valid index", index.name());
+    }
+    Ok(index)
+}
+
+/// The field configs of the `text_fields` or `json_fields` option of `index`.
+fn field_configs(index: &PgSearchRelation, option_name: &str) -> Result<Map<String, Value>> {
+    let option_value = Spi::get_one_with_args::<String>(
+        "SELECT option_value
+         FROM pg_options_to_table((SELECT reloptions FROM pg_class WHERE oid = $1))
+         WHERE option_name = $2",
+        &[index.oid().into(), option_name.into()],
+    )?;
+    match option_value {
+        Some(option_value) => Ok(serde_json::from_str(&option_value)?),
+        None => Ok(Map::new()),
+    }
+}
+
+fn set_field_configs(
+    index: &PgSearchRelation,
+    option_name: &str,
+    field_configs: &Map<String, Value>,
+) -> Result<()> {
+    Spi::run(&format!(
+        "ALTER INDEX {}.{} SET ({option_name} = {})",
+        quote_identifier(index.namespace()),
+        quote_identifier(index.name()),
+        quote_literal(serde_json::to_string(field_configs)?)
+    ))?;
+    Ok(())
+}
+
+/// The ids of all the segments of `index`, and of those that were written to disk and so are
+/// missing any field added from now on.  Mutable segments are indexed whenever they're read, with
+/// the index's current schema.
+unsafe fn segment_ids(index: &PgSearchRelation) -> (HashSet<SegmentId>, Vec<SegmentId>) {
+    let entries = MetaPage::open(index).segment_metas().list(None);
+    let segment_ids = entries.iter().map(|entry| entry.segment_id()).collect();
+    let unfilled = entries
+        .iter()
+        .filter(|entry| !entry.is_mutable())
+        .map(|entry| entry.segment_id())
+        .collect();
+    (segment_ids, unfilled)
+}
+
+unsafe fn visible_segment_ids(index: &PgSearchRelation) -> HashSet<SegmentId> {
+    MetaPage::open(index)
+        .segment_metas()
+        .list(None)
+        .into_iter()
+        .filter(|entry| entry.visible())
+        .map(|entry| entry.segment_id())
+        .collect()
+}
@@ -15,6 +15,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use crate::api::tokenizers::TokenizerTables;
 use pgrx::{JsonB, iter::TableIterator, name, pg_extern};
 use strum::VariantNames;
 use tokenizers::SearchTokenizer;
@@ -27,12 +28,47 @@ pub fn tokenize(
 ) -> TableIterator<'_, (name!(token, String), name!(position, i32))> {
     let tokenizer_setting = serde_json::to_value(tokenizer_setting)
         .expect("invalid tokenizer setting, expected paradedb.tokenizer()");
-    let tokenizer = SearchTokenizer::from_json_value(&tokenizer_setting)
+    let tokenizer = tokenizer_from_setting(&tokenizer_setting);
+
+    TableIterator::new(tokens(&tokenizer, input_text))
+}
+
+/// Tokenize text with a given tokenizer setting, returning the tokens after the tokenizer and
+/// after each of its filters
+#[pg_extern(immutable, parallel_safe)]
+pub fn tokenize_explain(
+    tokenizer_setting: JsonB,
+    input_text: &str,
+) -> TableIterator<
+    '_,
+    (
+        name!(stage, String),
+        name!(token, String),
+        name!(position, i32),
+        name!(position_length, i32),
+        name!(offset_from, i32),
+        name!(offset_to, i32),
+    ),
+> {
+    let tokenizer_setting = serde_json::to_value(tokenizer_setting)
+        .expect("invalid tokenizer setting, expected paradedb.tokenizer()");
+    let tokenizer = tokenizer_from_setting(&tokenizer_setting);
+
+    TableIterator::new(stages(&tokenizer, input_text))
+}
+
+/// Parses a tokenizer setting, with the analyzer and tables it refers to as they are now.
+fn tokenizer_from_setting(tokenizer_setting: &serde_json::Value) -> SearchTokenizer {
+    let mut tokenizer = SearchTokenizer::from_json_value(tokenizer_setting)
         .expect("invalid tokenizer setting, expected paradedb.tokenizer()");
+    TokenizerTables::resolve(&mut tokenizer).unwrap_or_else(|e| pgrx::error!("{e}"));
+    tokenizer
+}
 
+fn tokens(tokenizer: &SearchTokenizer, input_text: &str) -> Vec<(String, i32)> {
     let mut analyzer = tokenizer
         .to_tantivy_tokenizer()
-        .expect("failed to convert tokenizer to tantivy tokenizer");
+        .unwrap_or_else(|e| pgrx::error!("{e}"));
 
     let mut stream = analyzer.token_stream(input_text);
 
@@ -41,8 +77,69 @@ pub fn tokenize(
         let token = stream.token();
         result.push((token.text.to_string(), token.position as i32));
     }
+    result
+}
+
+fn stages(
+    tokenizer: &SearchTokenizer,
+    input_text: &str,
+) -> Vec<(String, String, i32, i32, i32, i32)> {
+    tokenizer
+        .explain(input_text)
+        .unwrap_or_else(|e| pgrx::error!("{e}"))
+        .into_iter()
+        .flat_map(|stage| {
+            stage.tokens.into_iter().map(move |token| {
+                (
+                    stage.name.to_string(),
+                    token.text,
+                    token.position as i32,
+                    token.position_length as i32,
+                    token.offset_from as i32,
+                    token.offset_to as i32,
+                )
+            })
+        })
+        .collect()
+}
+
+#[pgrx::pg_schema]
+mod pdb {
+    use crate::api::tokenizers::analyzers::lookup_analyzer;
+    use pgrx::{iter::TableIterator, name, pg_extern};
+
+    /// Tokenize text with an analyzer created by `pdb.create_analyzer`
+    #[pg_extern(name = "tokenize", stable, parallel_safe)]
+    fn tokenize_with_analyzer(
+        analyzer: &str,
+        input_text: &str,
+    ) -> TableIterator<'static, (name!(token, String), name!(position, i32))> {
+        let tokenizer = lookup_analyzer(analyzer)
+            .unwrap_or_else(|| pgrx::error!("analyzer `{analyzer}` does not exist"));
+        TableIterator::new(super::tokens(&tokenizer, input_text))
+    }
 
-    TableIterator::new(result)
+    /// Tokenize text with an analyzer created by `pdb.create_analyzer`, returning the tokens after
+    /// the tokenizer and after each of its filters
+    #[pg_extern(name = "tokenize_explain", stable, parallel_safe)]
+    fn tokenize_explain_with_analyzer(
+        analyzer: &str,
+        input_text: &str,
+    ) -> TableIterator<
+        'static,
+        (
+            name!(stage, String),
+            name!(token, String),
+            name!(position, i32),
+            name!(position_length, i32),
+            name!(offset_from, i32),
+            name!(offset_to, i32),
+        ),
+    > {
+        let tokenizer = lookup_analyzer(analyzer)
+            .unwrap_or_else(|| pgrx::error!("analyzer `{analyzer}` does not exist"));
+        TableIterator::new(super::stages(&tokenizer, input_text))
+    }
 }
 
 /// List all available tokenizers names in ParadeDB
@@ -0,0 +1,276 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Named analyzers: a tokenizer and its filters, registered once in `paradedb.analyzers` with
+//! `pdb.create_analyzer` and used by any number of indexes with
+//! `pdb.<tokenizer>('analyzer=<name>')`, or with an `analyzer` in a tokenizer's configuration.
+//!
+//! An analyzer is saved as the typmod it would have as a `pdb.<tokenizer>` cast. It's only looked
+//! up when an index is built, which keeps the analyzer's tokenizer and typmod with its
+//! [`TokenizerTables`](super::TokenizerTables), so the index keeps the definition it was built
+//! with even if the analyzer is later dropped.
+
+use crate::api::tokenizers::typmod::{Typmod, load_typmod, save_typmod};
+use crate::api::tokenizers::{
+    TokenizerTables, apply_typmod, split_tokenizer_params, tokenizer_from_name,
+};
+use pgrx::datum::DatumWithOid;
+use pgrx::{JsonB, Spi, default, extension_sql, pg_extern};
+use std::ffi::CString;
+use tokenizers::SearchTokenizer;
+use tokenizers::manager::SearchTokenizerFilters;
+
+/// Typmod properties that configure the field rather than how its text is tokenized, so they may
+/// accompany `analyzer` but can't be part of one.
+const FIELD_PROPERTIES: [&str; 8] = [
+    "alias",
+    "columnar",
+    "fieldnorms",
+    "k1",
+    "b",
+    "similarity",
+    "mu",
+    "search_tokenizer",
+];
+
+extension_sql!(
+    r#"
+CREATE TABLE paradedb.analyzers(
+    name text NOT NULL PRIMARY KEY,
+    tokenizer text NOT NULL,
+    filters jsonb NOT NULL DEFAULT '{}',
+    typmod integer NOT NULL
+);
+SELECT pg_catalog.pg_extension_config_dump('paradedb.analyzers', '');
+
+-- Analyzers are shared definitions, so every role can read them but only the table owner, or
+-- roles it grants INSERT and DELETE to, can create and drop them.
+GRANT SELECT ON TABLE paradedb.analyzers TO PUBLIC;
+"#,
+    name = "analyzers",
+    requires = ["typmod_cache"]
+);
+
+#[pgrx::pg_schema]
+mod pdb {
+    use super::*;
+
+    /// Registers a tokenizer and its filters under `name`, so that indexes can use them with
+    /// `pdb.<tokenizer>('analyzer=<name>')`.
+    #[pg_extern]
+    fn create_analyzer(name: &str, tokenizer: &str, filters: default!(JsonB, "'{}'")) {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            pgrx::error!("analyzer names may only contain letters, digits and `_`");
+        }
+        if Spi::get_one_with_args::<bool>(
+            "SELECT EXISTS (SELECT 1 FROM paradedb.analyzers WHERE name = $1)",
+            &[DatumWithOid::from(name)],
+        )
+        .unwrap_or_else(|e| pgrx::error!("could not read paradedb.analyzers: {e}"))
+        .unwrap_or(false)
+        {
+            pgrx::error!("analyzer `{name}` already exists");
+        }
+
+        let (tokenizer_name, properties) = analyzer_properties(tokenizer, &filters.0);
+        let Some(mut search_tokenizer) = tokenizer_from_name(tokenizer_name) else {
+            pgrx::error!("unknown tokenizer: {tokenizer_name}");
+        };
+        if matches!(search_tokenizer, SearchTokenizer::Keyword) {
+            pgrx::error!("analyzers can't use the literal tokenizer, which has no options");
+        }
+
+        let properties = properties
+            .into_iter()
+            .map(|property| {
+                CString::new(property)
+                    .unwrap_or_else(|_| pgrx::error!("analyzer options may not contain NUL bytes"))
+            })
+            .collect::<Vec<_>>();
+        let typmod = save_typmod(properties.iter().map(|p| Some(p.as_c_str())))
+            .unwrap_or_else(|e| pgrx::error!("could not save analyzer `{name}`: {e}"));
+
+        // validates the options, and loads any tables or dictionaries they refer to
+        apply_typmod(&mut search_tokenizer, typmod);
+        TokenizerTables::load([&search_tokenizer]);
+
+        Spi::connect_mut(|client| {
+            client.update(
+                "INSERT INTO paradedb.analyzers (name, tokenizer, filters, typmod) VALUES ($1, $2, $3, $4)",
+                None,
+                &[
+                    DatumWithOid::from(name),
+                    DatumWithOid::from(tokenizer),
+                    DatumWithOid::from(filters),
+                    DatumWithOid::from(typmod),
+                ],
+            )?;
+            Ok::<_, pgrx::spi::Error>(())
+        })
+        .unwrap_or_else(|e| pgrx::error!("could not save analyzer `{name}`: {e}"));
+    }
+
+    /// Removes the analyzer named `name`. Indexes that were created with it keep tokenizing the
+    /// way it did.
+    #[pg_extern]
+    fn drop_analyzer(name: &str, if_exists: default!(bool, false)) {
+        let dropped = Spi::connect_mut(|client| {
+            let rows = client.update(
+                "DELETE FROM paradedb.analyzers WHERE name = $1 RETURNING name",
+                None,
+                &[DatumWithOid::from(name)],
+            )?;
+            Ok::<_, pgrx::spi::Error>(!rows.is_empty())
+        })
+        .unwrap_or_else(|e| pgrx::error!("could not drop analyzer `{name}`: {e}"));
+
+        if !dropped && !if_exists {
+            pgrx::error!("analyzer `{name}` does not exist");
+        }
+    }
+}
+
+/// Returns the tokenizer of the analyzer named `name`, with the tables it reads as they are now,
+/// or `None` if there's no such analyzer.
+pub fn lookup_analyzer(name: &str) -> Option<SearchTokenizer> {
+    let (tokenizer, typmod) = read_analyzer(name)?;
+    let mut tokenizer = analyzer_tokenizer(name, &tokenizer, typmod);
+    TokenizerTables::resolve(&mut tokenizer).unwrap_or_else(|e| pgrx::error!("{e}"));
+    Some(tokenizer)
+}
+
+/// Reads the tokenizer and typmod of the analyzer named `name`, which `tokenizer` refers to, and
+/// errors if it can't take the place of `tokenizer`.
+pub(super) fn load_analyzer(name: &str, tokenizer: &SearchTokenizer) -> (String, Typmod) {
+    let (analyzer_tokenizer, typmod) =
+        read_analyzer(name).unwrap_or_else(|| pgrx::error!("analyzer `{name}` does not exist"));
+
+    let tokenizer_name = tokenizer_name(&analyzer_tokenizer);
+    let matches = tokenizer_from_name(tokenizer_name)
+        .is_some_and(|t| std::mem::discriminant(&t) == std::mem::discriminant(tokenizer));
+    if !matches {
+        pgrx::error!(
+            "analyzer `{name}` uses the `{tokenizer_name}` tokenizer, use it with `pdb.{tokenizer_name}`"
+        );
+    }
+
+    let only_analyzer = SearchTokenizerFilters {
+        analyzer: Some(name.to_string()),
+        ..Default::default()
+    };
+    if *tokenizer.filters() != only_analyzer {
+        pgrx::error!("`analyzer` cannot be combined with other filters");
+    }
+    (analyzer_tokenizer, typmod)
+}
+
+/// Builds the tokenizer of the analyzer named `name` from its tokenizer and typmod.
+pub(super) fn analyzer_tokenizer(name: &str, tokenizer: &str, typmod: Typmod) -> SearchTokenizer {
+    let tokenizer_name = tokenizer_name(tokenizer);
+    let Some(mut search_tokenizer) = tokenizer_from_name(tokenizer_name) else {
+        pgrx::error!("analyzer `{name}` uses an unknown tokenizer: {tokenizer_name}");
+    };
+    apply_typmod(&mut search_tokenizer, typmod);
+    search_tokenizer
+}
+
+fn read_analyzer(name: &str) -> Option<(String, Typmod)> {
+    Spi::connect(|client| {
+        let mut rows = client.select(
+            "SELECT tokenizer, typmod FROM paradedb.analyzers WHERE name = $1",
+            Some(1),
+            &[DatumWithOid::from(name)],
+        )?;
+        match rows.next() {
+            Some(row) => Ok(row.get::<String>(1)?.zip(row.get::<i32>(2)?)),
+            None => Ok::<_, pgrx::spi::Error>(None),
+        }
+    })
+    .unwrap_or_else(|e| pgrx::error!("could not read analyzer `{name}`: {e}"))
+}
+
+/// Returns the analyzer that `typmod` names, and errors if it's combined with anything other than
+/// field properties, like `pdb.simple('analyzer=english_std', 'lowercase=false')`.
+pub fn analyzer_from_typmod(typmod: Typmod) -> Option<String> {
+    let parsed = load_typmod(typmod).ok()?;
+    let name = parsed.get("analyzer")?.as_str()?;
+    for property in parsed.iter() {
+        match property.key() {
+            Some("analyzer") => {}
+            Some(key) if FIELD_PROPERTIES.contains(&key) => {}
+            _ => pgrx::error!("`analyzer` cannot be combined with `{property}`"),
+        }
+    }
+    Some(name.to_string())
+}
+
+/// The name of the tokenizer in a tokenizer expression like `ngram(3,3)`.
+fn tokenizer_name(tokenizer: &str) -> &str {
+    tokenizer
+        .split_once('(')
+        .map_or(tokenizer, |(name, _)| name)
+        .trim()
+}
+
+/// Splits a tokenizer expression like `ngram(3,3)` into the tokenizer's name and the typmod
+/// properties for its parameters, followed by one `key=value` property per filter.
+fn analyzer_properties<'a>(
+    tokenizer: &'a str,
+    filters: &serde_json::Value,
+) -> (&'a str, Vec<String>) {
+    let mut properties = match tokenizer.split_once('(') {
+        Some((_, params)) => {
+            let Some(params) = params.trim_end().strip_suffix(')') else {
+                pgrx::error!("invalid tokenizer `{tokenizer}`: missing closing parenthesis");
+            };
+            split_tokenizer_params(params)
+                .into_iter()
+                .map(|param| param.trim().to_string())
+                .filter(|param| !param.is_empty())
+                .collect()
+        }
+        None => Vec::new(),
+    };
+
+    let Some(filters) = filters.as_object() else {
+        pgrx::error!("analyzer filters must be a JSON object, found: {filters}");
+    };
+    for (key, value) in filters {
+        if FIELD_PROPERTIES.contains(&key.as_str()) || key == "analyzer" {
+            pgrx::error!("`{key}` cannot be part of an analyzer");
+        }
+
+        let value = match value {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Bool(_) | serde_json::Value::Number(_) => value.to_string(),
+            serde_json::Value::Array(values) => values
+                .iter()
+                .map(|v| match v {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+            _ => pgrx::error!(
+                "the `{key}` filter must be a string, number, boolean or array, found: {value}"
+            ),
+        };
+        properties.push(format!("{key}={value}"));
+    }
+
+    (tokenizer_name(tokenizer), properties)
+}
@@ -0,0 +1,92 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::api::tokenizers::typmod::ParsedTypmod;
+use pgrx::Spi;
+use pgrx::datum::DatumWithOid;
+use tokenizers::manager::SearchDecompounder;
+
+/// Builds the decompounder configuration from the `decompounder_words` and `decompounder_table`
+/// typmod properties, with Elasticsearch's defaults for the options that weren't given.  The
+/// table's words are filled in by [`TokenizerTables`](super::TokenizerTables).
+pub fn decompounder_from_typmod(parsed: &ParsedTypmod) -> Option<SearchDecompounder> {
+    let words = parsed
+        .get("decompounder_words")
+        .and_then(|p| p.as_str())
+        .map(SearchDecompounder::parse_words);
+    let table = parsed
+        .get("decompounder_table")
+        .and_then(|p| p.as_str())
+        .map(String::from);
+
+    if words.is_none() && table.is_none() {
+        return None;
+    }
+
+    let defaults = SearchDecompounder::default();
+    let size = |key: &str, default: usize| {
+        parsed
+            .get(key)
+            .and_then(|p| p.as_usize())
+            .unwrap_or(default)
+    };
+    let decompounder = SearchDecompounder {
+        words: words.unwrap_or_default(),
+        table,
+        table_words: vec![],
+        min_word_size: size("min_word_size", defaults.min_word_size),
+        min_subword_size: size("min_subword_size", defaults.min_subword_size),
+        max_subword_size: size("max_subword_size", defaults.max_subword_size),
+        only_longest_match: parsed
+            .get("only_longest_match")
+            .and_then(|p| p.as_bool())
+            .unwrap_or(defaults.only_longest_match),
+    };
+    if let Err(e) = decompounder.validate() {
+        pgrx::error!("invalid decompounder: {e}");
+    }
+
+    Some(decompounder)
+}
+
+/// Reads the `word text` column of a dictionary table.
+pub(super) fn load_decompounder_table(table: &str) -> Vec<String> {
+    let relation =
+        Spi::get_one_with_args::<String>("SELECT $1::regclass::text", &[DatumWithOid::from(table)])
+            .unwrap_or_else(|e| {
+                pgrx::error!("decompounder_table `{table}` could not be found: {e}")
+            })
+            .unwrap_or_else(|| pgrx::error!("decompounder_table `{table}` could not be found"));
+
+    Spi::connect(|client| {
+        let args: [DatumWithOid; 0] = [];
+        let rows = client.select(
+            &format!("SELECT word FROM {relation} WHERE word IS NOT NULL ORDER BY word"),
+            None,
+            &args,
+        )?;
+
+        let mut words = Vec::new();
+        for row in rows {
+            if let Some(word) = row.get::<String>(1)? {
+                words.push(word);
+            }
+        }
+        Ok::<_, pgrx::spi::Error>(words)
+    })
+    .unwrap_or_else(|e| pgrx::error!("failed to load decompounder words from `{relation}`: {e}"))
+}
@@ -326,6 +326,12 @@ pub(crate) mod pdb {
                     super::super::apply_typmod(&mut tokenizer, typmod);
                 }
 
+                // without an index to keep a copy of the tables the tokenizer reads, read them now
+                let tables = super::super::TokenizerTables::load([&tokenizer]);
+                tables
+                    .apply(&mut tokenizer)
+                    .expect("the tables the tokenizer reads should have just been read");
+
                 unsafe { tokenize(s, tokenizer) }
             }
 
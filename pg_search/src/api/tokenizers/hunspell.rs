@@ -0,0 +1,100 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::api::tokenizers::typmod::ParsedTypmod;
+use crate::gucs;
+use anyhow::Context;
+use pgrx::Spi;
+use pgrx::datum::DatumWithOid;
+use tokenizers::hunspell;
+use tokenizers::manager::SearchHunspell;
+
+/// Lets the tokenizers load Hunspell dictionaries from `paradedb.hunspell_dictionary_path`, or
+/// from a table.
+pub fn init() {
+    hunspell::set_dictionary_loader(load_dictionary);
+}
+
+/// Builds the Hunspell configuration from the `hunspell`, `hunspell_table` and
+/// `hunspell_longest_only` typmod properties.  The version of the dictionary to use is filled in
+/// by [`TokenizerTables`](super::TokenizerTables).
+pub fn hunspell_from_typmod(parsed: &ParsedTypmod) -> Option<SearchHunspell> {
+    let dictionary = parsed.get("hunspell").and_then(|p| p.as_str())?;
+    Some(SearchHunspell {
+        dictionary: dictionary.to_string(),
+        table: parsed
+            .get("hunspell_table")
+            .and_then(|p| p.as_str())
+            .map(String::from),
+        longest_only: parsed
+            .get("hunspell_longest_only")
+            .and_then(|p| p.as_bool())
+            .unwrap_or(false),
+        version: None,
+    })
+}
+
+/// Loads a dictionary as it is now, and returns its version and its `.aff` and `.dic` files.
+pub(super) fn load_hunspell(dictionary: &str, table: Option<&str>) -> (u64, Vec<u8>, Vec<u8>) {
+    hunspell::read(dictionary, table).unwrap_or_else(|e| {
+        pgrx::error!("could not load hunspell dictionary `{dictionary}`: {e:#}")
+    })
+}
+
+fn load_dictionary(name: &str, table: Option<&str>) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    match table {
+        Some(table) => load_from_table(name, table),
+        None => load_from_files(name),
+    }
+}
+
+/// Reads `<name>.aff` and `<name>.dic` from `paradedb.hunspell_dictionary_path`.
+fn load_from_files(name: &str) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    // the name becomes part of a path, so it must not be able to leave the directory
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        anyhow::bail!("dictionary names may only contain letters, digits, `_` and `-`");
+    }
+
+    let directory = gucs::hunspell_dictionary_path();
+    let read = |extension: &str| {
+        let path = directory.join(format!("{name}.{extension}"));
+        std::fs::read(&path).with_context(|| format!("could not read `{}`", path.display()))
+    };
+    Ok((read("aff")?, read("dic")?))
+}
+
+/// Reads the `aff bytea` and `dic bytea` columns of the table's row whose `name` is `name`.
+fn load_from_table(name: &str, table: &str) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    let relation =
+        Spi::get_one_with_args::<String>("SELECT $1::regclass::text", &[DatumWithOid::from(table)])
+            .with_context(|| format!("hunspell_table `{table}` could not be found"))?
+            .with_context(|| format!("hunspell_table `{table}` could not be found"))?;
+
+    let files = Spi::get_two_with_args::<Vec<u8>, Vec<u8>>(
+        &format!("SELECT aff, dic FROM {relation} WHERE name = $1"),
+        &[DatumWithOid::from(name)],
+    )
+    .with_context(|| format!("could not read `{name}` from `{relation}`"))?;
+    match files {
+        (Some(aff), Some(dic)) => Ok((aff, dic)),
+        _ => anyhow::bail!("`{relation}` has no `aff` and `dic` for `{name}`"),
+    }
+}
@@ -30,11 +30,18 @@ use tokenizers::SearchTokenizer;
 use tokenizers::chinese_convert::ConvertMode;
 use tokenizers::manager::{LinderaLanguage, SearchTokenizerFilters};
 
+pub(crate) mod analyzers;
+mod decompounder;
 pub(crate) mod definitions;
+pub(crate) mod hunspell;
+mod synonyms;
+mod tables;
 mod typmod;
 
+use crate::query::similarity::Similarity;
 use crate::schema::{IndexRecordOption, SearchFieldConfig};
 
+pub use crate::api::tokenizers::tables::TokenizerTables;
 pub use crate::api::tokenizers::typmod::{
     AliasTypmod, EdgeNgramTypmod, GenericTypmod, JiebaTypmod, LinderaTypmod, NgramTypmod,
     RegexTypmod, Typmod, UncheckedTypmod, UnicodeWordsTypmod,
@@ -147,7 +154,7 @@ pub(crate) fn tokenizer_from_expression(expr: &str) -> Option<SearchTokenizer> {
 
 fn parse_tokenizer_params(inner: &str) -> typmod::ParsedTypmod {
     let mut parsed = typmod::ParsedTypmod::new();
-    for part in inner.split(',') {
+    for part in split_tokenizer_params(inner) {
         let trimmed = part.trim();
         if !trimmed.is_empty()
             && let Ok(prop) = trimmed.parse::<typmod::Property>()
@@ -158,6 +165,35 @@ fn parse_tokenizer_params(inner: &str) -> typmod::ParsedTypmod {
     parsed
 }
 
+fn split_tokenizer_params(inner: &str) -> Vec<String> {
+    // whether `part` begins a new `key=value` property, as opposed to continuing the value of the
+    // previous one, like the second half of `synonyms=tv,television` or `token_chars=letter,digit`
+    fn starts_property(part: &str) -> bool {
+        match part.split_once('=') {
+            Some((key, value)) => {
+                let key = key.trim();
+                !key.is_empty()
+                    && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                    && !value.starts_with('>')
+            }
+            None => false,
+        }
+    }
+
+    let mut parts: Vec<String> = Vec::new();
+    for part in inner.split(',') {
+        match parts.last_mut() {
+            // positional properties never follow keyed ones, so this comma is part of a value
+            Some(prev) if !starts_property(part) && starts_property(prev) => {
+                prev.push(',');
+                prev.push_str(part);
+            }
+            _ => parts.push(part.to_string()),
+        }
+    }
+    parts
+}
+
 fn apply_expression_params(tokenizer: &mut SearchTokenizer, parsed: &typmod::ParsedTypmod) {
     match tokenizer {
         SearchTokenizer::Ngram {
@@ -348,6 +384,12 @@ pub fn search_field_config_from_type(
 
     let k1 = parsed_typmod.get("k1").and_then(|p| p.as_f32());
     let b = parsed_typmod.get("b").and_then(|p| p.as_f32());
+    let mu = parsed_typmod.get("mu").and_then(|p| p.as_f32());
+    let similarity_name = parsed_typmod.get("similarity").and_then(|p| p.as_str());
+    let similarity = match Similarity::from_name(similarity_name.unwrap_or("bm25"), mu) {
+        Ok(similarity) => similarity,
+        Err(e) => pgrx::error!("invalid similarity: {e}"),
+    };
 
     if inner_typoid == pg_sys::JSONOID || inner_typoid == pg_sys::JSONBOID {
         Some(SearchFieldConfig::Json {
@@ -362,6 +404,7 @@ pub fn search_field_config_from_type(
             expand_dots: true,
             k1,
             b,
+            similarity,
         })
     } else {
         Some(SearchFieldConfig::Text {
@@ -375,11 +418,24 @@ pub fn search_field_config_from_type(
             column: None,
             k1,
             b,
+            similarity,
         })
     }
 }
 
 pub fn apply_typmod(tokenizer: &mut SearchTokenizer, typmod: Typmod) {
+    // the analyzer's configuration is filled in by `TokenizerTables`
+    if let Some(analyzer) = analyzers::analyzer_from_typmod(typmod) {
+        let Some(filters) = tokenizer.filters_mut() else {
+            pgrx::error!("the literal tokenizer does not accept any options");
+        };
+        *filters = SearchTokenizerFilters {
+            analyzer: Some(analyzer),
+            ..Default::default()
+        };
+        return;
+    }
+
     match tokenizer {
         SearchTokenizer::Ngram {
             min_gram,
@@ -529,7 +585,7 @@ where
 {
     let mut analyzer = tokenizer
         .to_tantivy_tokenizer()
-        .expect("failed to convert tokenizer to tantivy tokenizer");
+        .unwrap_or_else(|e| pgrx::error!("{e}"));
 
     let mut tokens = Vec::new();
     let mut tokenize = |s: &str| {
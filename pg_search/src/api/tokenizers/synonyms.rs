@@ -0,0 +1,105 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::api::tokenizers::typmod::ParsedTypmod;
+use pgrx::Spi;
+use pgrx::datum::DatumWithOid;
+use tokenizers::manager::SearchSynonyms;
+use tokenizers::synonym::SynonymMap;
+
+/// Builds the synonym configuration from the `synonyms` and `synonyms_table` typmod properties.
+/// The table's rules are filled in by [`TokenizerTables`](super::TokenizerTables).
+pub fn synonyms_from_typmod(parsed: &ParsedTypmod) -> Option<SearchSynonyms> {
+    let rules = parsed
+        .get("synonyms")
+        .and_then(|p| p.as_str())
+        .map(SearchSynonyms::parse_rules);
+    let table = parsed
+        .get("synonyms_table")
+        .and_then(|p| p.as_str())
+        .map(String::from);
+
+    if rules.is_none() && table.is_none() {
+        return None;
+    }
+
+    let rules = rules.unwrap_or_default();
+    if let Err(e) = SynonymMap::from_rules(&rules, false) {
+        pgrx::error!("invalid synonyms: {e}");
+    }
+
+    Some(SearchSynonyms {
+        rules,
+        table,
+        table_rules: vec![],
+    })
+}
+
+/// Reads the rows of a synonym table as Solr-style rules.
+///
+/// The table needs a `term text` column, a `synonyms text[]` column and an `equivalent boolean`
+/// column.  A row whose `equivalent` is true makes `term` and all of its `synonyms` equivalent to
+/// each other, otherwise `term` is replaced by its `synonyms`.
+pub(super) fn load_synonyms_table(table: &str) -> Vec<String> {
+    // resolving the name through `regclass` both validates it and quotes it for us
+    let relation =
+        Spi::get_one_with_args::<String>("SELECT $1::regclass::text", &[DatumWithOid::from(table)])
+            .unwrap_or_else(|e| pgrx::error!("synonyms_table `{table}` could not be found: {e}"))
+            .unwrap_or_else(|| pgrx::error!("synonyms_table `{table}` could not be found"));
+
+    let rules = Spi::connect(|client| {
+        let args: [DatumWithOid; 0] = [];
+        let rows = client.select(
+            &format!(
+                "SELECT term, synonyms, coalesce(equivalent, false) FROM {relation} ORDER BY term"
+            ),
+            None,
+            &args,
+        )?;
+
+        let mut rules = Vec::new();
+        for row in rows {
+            let Some(term) = row.get::<String>(1)? else {
+                continue;
+            };
+            let synonyms = row
+                .get::<Vec<Option<String>>>(2)?
+                .unwrap_or_default()
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+            if synonyms.is_empty() {
+                continue;
+            }
+
+            let equivalent = row.get::<bool>(3)?.unwrap_or(false);
+            let synonyms = synonyms.join(", ");
+            rules.push(if equivalent {
+                format!("{term}, {synonyms}")
+            } else {
+                format!("{term} => {synonyms}")
+            });
+        }
+        Ok::<_, pgrx::spi::Error>(rules)
+    })
+    .unwrap_or_else(|e| pgrx::error!("failed to load synonyms from `{relation}`: {e}"));
+
+    if let Err(e) = SynonymMap::from_rules(&rules, false) {
+        pgrx::error!("invalid synonyms in `{relation}`: {e}");
+    }
+    rules
+}
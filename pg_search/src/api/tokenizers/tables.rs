@@ -0,0 +1,237 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! The contents of the tables that token filters read, like a `synonyms_table` or a
+//! `decompounder_table`, and the definitions of the analyzers that tokenizers name.
+//!
+//! An index reads each of them once, when it's built, and keeps a copy in its metapage that its
+//! tokenizers use from then on.  That way every segment is written and searched with the same
+//! contents however the tables change afterwards, and loading the index's configuration never
+//! runs a query.  `REINDEX` reads the tables again.
+//!
+//! The `.aff` and `.dic` files of Hunspell dictionaries are kept the same way, along with the
+//! version of each, which tells them apart from other versions of a dictionary in the cache that
+//! the tokenizers share.
+
+use crate::api::tokenizers::analyzers::{analyzer_tokenizer, load_analyzer};
+use crate::api::tokenizers::decompounder::load_decompounder_table;
+use crate::api::tokenizers::hunspell::load_hunspell;
+use crate::api::tokenizers::synonyms::load_synonyms_table;
+use crate::api::tokenizers::typmod::Typmod;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tokenizers::SearchTokenizer;
+use tokenizers::manager::SearchHunspell;
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct TokenizerTables {
+    /// The rules of each `synonyms_table`, by the name it was given as.
+    #[serde(default)]
+    synonyms: BTreeMap<String, Vec<String>>,
+    /// The words of each `decompounder_table`, by the name it was given as.
+    #[serde(default)]
+    decompounder: BTreeMap<String, Vec<String>>,
+    /// Each Hunspell dictionary, by its name and the table it was given with.
+    #[serde(default)]
+    hunspell: Vec<HunspellVersion>,
+    /// The tokenizer and typmod of each analyzer, by name.  Typmods are never removed, so they
+    /// outlive the analyzer.
+    #[serde(default)]
+    analyzers: BTreeMap<String, (String, Typmod)>,
+}
+
+/// A version of a Hunspell dictionary, see [`tokenizers::hunspell::content_hash`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct HunspellVersion {
+    dictionary: String,
+    table: Option<String>,
+    version: u64,
+    /// The dictionary's files, which are empty for indexes built before they were kept.
+    #[serde(default, with = "hex_bytes")]
+    aff: Vec<u8>,
+    #[serde(default, with = "hex_bytes")]
+    dic: Vec<u8>,
+}
+
+/// Hex-encodes the files of Hunspell dictionaries, which JSON has no type for.
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer, de};
+    use std::fmt::Write;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut hex = String::with_capacity(bytes.len() * 2);
+        for byte in bytes {
+            write!(hex, "{byte:02x}").expect("writing to a String should not fail");
+        }
+        serializer.serialize_str(&hex)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        if hex.len() % 2 != 0 {
+            return Err(de::Error::custom("hex string has an odd length"));
+        }
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(de::Error::custom))
+            .collect()
+    }
+}
+
+impl TokenizerTables {
+    /// Reads the analyzers and tables that `tokenizers` refer to.
+    pub fn load<'a>(tokenizers: impl IntoIterator<Item = &'a SearchTokenizer>) -> Self {
+        let mut tables = Self::default();
+        for tokenizer in tokenizers {
+            // the tables an analyzer refers to are those of its own tokenizer
+            let analyzer;
+            let tokenizer = match tokenizer.filters().analyzer.as_ref() {
+                Some(name) => {
+                    let (analyzer_tokenizer_name, typmod) = tables
+                        .analyzers
+                        .entry(name.clone())
+                        .or_insert_with(|| load_analyzer(name, tokenizer));
+                    analyzer = analyzer_tokenizer(name, analyzer_tokenizer_name, *typmod);
+                    &analyzer
+                }
+                None => tokenizer,
+            };
+
+            let filters = tokenizer.filters();
+            if let Some(table) = filters.synonyms.as_ref().and_then(|s| s.table.as_ref())
+                && !tables.synonyms.contains_key(table)
+            {
+                tables
+                    .synonyms
+                    .insert(table.clone(), load_synonyms_table(table));
+            }
+            if let Some(table) = filters.decompounder.as_ref().and_then(|d| d.table.as_ref())
+                && !tables.decompounder.contains_key(table)
+            {
+                tables
+                    .decompounder
+                    .insert(table.clone(), load_decompounder_table(table));
+            }
+            if let Some(hunspell) = filters.hunspell.as_ref()
+                && tables.hunspell_version(hunspell).is_none()
+            {
+                let (version, aff, dic) =
+                    load_hunspell(&hunspell.dictionary, hunspell.table.as_deref());
+                tables.hunspell.push(HunspellVersion {
+                    dictionary: hunspell.dictionary.clone(),
+                    table: hunspell.table.clone(),
+                    version,
+                    aff,
+                    dic,
+                });
+            }
+        }
+        tables
+    }
+
+    /// Fills in the analyzer and the tables that `tokenizer` refers to as they are now, for a
+    /// tokenizer that isn't part of an index, like one given to a query.
+    pub fn resolve(tokenizer: &mut SearchTokenizer) -> Result<()> {
+        let tables = Self::load([&*tokenizer]);
+        tables.apply(tokenizer)
+    }
+
+    /// Fills in the analyzer and the contents of the tables that `tokenizer` refers to.
+    pub fn apply(&self, tokenizer: &mut SearchTokenizer) -> Result<()> {
+        if let Some(name) = tokenizer.filters().analyzer.clone() {
+            let (analyzer, typmod) = self
+                .analyzers
+                .get(&name)
+                .ok_or_else(|| not_read(format!("analyzer `{name}`")))?;
+            *tokenizer = analyzer_tokenizer(&name, analyzer, *typmod);
+            // keeps the name the tokenizer was registered with
+            if let Some(filters) = tokenizer.filters_mut() {
+                filters.analyzer = Some(name);
+            }
+        }
+
+        let Some(filters) = tokenizer.filters_mut() else {
+            return Ok(());
+        };
+        if let Some(synonyms) = filters.synonyms.as_mut()
+            && let Some(table) = synonyms.table.as_ref()
+        {
+            synonyms.table_rules = self
+                .synonyms
+                .get(table)
+                .cloned()
+                .ok_or_else(|| not_read(format!("synonyms_table `{table}`")))?;
+        }
+        if let Some(decompounder) = filters.decompounder.as_mut()
+            && let Some(table) = decompounder.table.as_ref()
+        {
+            decompounder.table_words = self
+                .decompounder
+                .get(table)
+                .cloned()
+                .ok_or_else(|| not_read(format!("decompounder_table `{table}`")))?;
+        }
+        if let Some(hunspell) = filters.hunspell.as_mut() {
+            let kept = self.hunspell_version(hunspell).ok_or_else(|| {
+                not_read(format!("hunspell dictionary `{}`", hunspell.dictionary))
+            })?;
+            // serves the version the index was built with, however the dictionary changes
+            if !kept.aff.is_empty() {
+                tokenizers::hunspell::insert(
+                    &kept.dictionary,
+                    kept.table.as_deref(),
+                    kept.version,
+                    &kept.aff,
+                    &kept.dic,
+                )?;
+            }
+            hunspell.version = Some(kept.version);
+        }
+        // the rules and words of the tables can't be checked until they're filled in
+        filters.validate()
+    }
+
+    /// Describes the analyzer, table or Hunspell dictionary that `tokenizer` refers to, if any.
+    /// They're only read when an index is built.
+    pub fn read_at_build(tokenizer: &SearchTokenizer) -> Option<String> {
+        let filters = tokenizer.filters();
+        if let Some(name) = filters.analyzer.as_ref() {
+            return Some(format!("analyzer `{name}`"));
+        }
+        if let Some(table) = filters.synonyms.as_ref().and_then(|s| s.table.as_ref()) {
+            return Some(format!("synonyms_table `{table}`"));
+        }
+        if let Some(table) = filters.decompounder.as_ref().and_then(|d| d.table.as_ref()) {
+            return Some(format!("decompounder_table `{table}`"));
+        }
+        if let Some(hunspell) = filters.hunspell.as_ref() {
+            return Some(format!("hunspell dictionary `{}`", hunspell.dictionary));
+        }
+        None
+    }
+
+    fn hunspell_version(&self, hunspell: &SearchHunspell) -> Option<&HunspellVersion> {
+        self.hunspell
+            .iter()
+            .find(|v| v.dictionary == hunspell.dictionary && v.table == hunspell.table)
+    }
+}
+
+fn not_read(what: String) -> anyhow::Error {
+    anyhow::anyhow!("{what} wasn't read when the index was built.  REINDEX the index to read it")
+}
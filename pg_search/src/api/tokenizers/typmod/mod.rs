@@ -18,6 +18,9 @@
 mod definitions;
 mod validation;
 
+use crate::api::tokenizers::decompounder::decompounder_from_typmod;
+use crate::api::tokenizers::hunspell::hunspell_from_typmod;
+use crate::api::tokenizers::synonyms::synonyms_from_typmod;
 use parking_lot::Mutex;
 use pgrx::datum::DatumWithOid;
 use pgrx::pg_sys::BuiltinOid;
@@ -34,7 +37,9 @@ use std::str::FromStr;
 use std::sync::OnceLock;
 use tantivy::tokenizer::Language;
 use thiserror::Error;
-use tokenizers::manager::SearchTokenizerFilters;
+use tokenizers::char_filter::CharFilter;
+use tokenizers::manager::{SearchShingles, SearchTokenizerFilters};
+use tokenizers::word_delimiter::WordDelimiterOptions;
 pub use validation::{TypmodSchema, ValidationError};
 
 pub use definitions::*;
@@ -375,7 +380,7 @@ impl<'mcx> TryFrom<&Array<'mcx, &'mcx CStr>> for ParsedTypmod {
 
 impl From<&ParsedTypmod> for SearchTokenizerFilters {
     fn from(value: &ParsedTypmod) -> Self {
-        SearchTokenizerFilters {
+        let filters = SearchTokenizerFilters {
             remove_long: value.get("remove_long").and_then(|p| p.as_usize()),
             remove_short: value.get("remove_short").and_then(|p| p.as_usize()),
             lowercase: value.get("lowercase").and_then(|p| p.as_bool()),
@@ -416,8 +421,141 @@ impl From<&ParsedTypmod> for SearchTokenizerFilters {
             ascii_folding: value.get("ascii_folding").and_then(|p| p.as_bool()),
             trim: value.get("trim").and_then(|p| p.as_bool()),
             normalizer: value.get("normalizer").and_then(|p| p.as_normalizer()),
+            synonyms: synonyms_from_typmod(value),
+            char_filters: char_filters_from_typmod(value),
+            phonetic: value
+                .get("phonetic")
+                .and_then(|p| p.as_str())
+                .map(|phonetic| {
+                    phonetic
+                        .parse()
+                        .unwrap_or_else(|_| panic!("unknown phonetic algorithm: {phonetic}"))
+                }),
+            phonetic_replace: value.get("phonetic_replace").and_then(|p| p.as_bool()),
+            shingles: shingles_from_typmod(value),
+            word_delimiter: word_delimiter_from_typmod(value),
+            decompounder: decompounder_from_typmod(value),
+            hunspell: hunspell_from_typmod(value),
+            analyzer: value
+                .get("analyzer")
+                .and_then(|p| p.as_str())
+                .map(String::from),
+        };
+        // the filters are built whenever a document or query is tokenized, so check they can be
+        if let Err(e) = filters.validate() {
+            pgrx::error!("invalid tokenizer options: {e}");
+        }
+        filters
+    }
+}
+
+/// Builds the shingle options, if any of them were given, with Elasticsearch's defaults for the
+/// others.
+fn shingles_from_typmod(value: &ParsedTypmod) -> Option<SearchShingles> {
+    let min_shingle_size = value.get("min_shingle_size").and_then(|p| p.as_usize());
+    let max_shingle_size = value.get("max_shingle_size").and_then(|p| p.as_usize());
+    let output_unigrams = value.get("output_unigrams").and_then(|p| p.as_bool());
+    let filler_token = value.get("filler_token").and_then(|p| p.as_str());
+    if min_shingle_size.is_none()
+        && max_shingle_size.is_none()
+        && output_unigrams.is_none()
+        && filler_token.is_none()
+    {
+        return None;
+    }
+
+    let defaults = SearchShingles::default();
+    let min_shingle_size = min_shingle_size.unwrap_or(defaults.min_shingle_size);
+    let shingles = SearchShingles {
+        min_shingle_size,
+        // the maximum follows a larger minimum unless it's given
+        max_shingle_size: max_shingle_size
+            .unwrap_or_else(|| defaults.max_shingle_size.max(min_shingle_size)),
+        output_unigrams: output_unigrams.unwrap_or(defaults.output_unigrams),
+        filler_token: filler_token
+            .map(String::from)
+            .unwrap_or(defaults.filler_token),
+    };
+    if let Err(e) = shingles.validate() {
+        pgrx::error!("invalid shingles: {e}");
+    }
+    Some(shingles)
+}
+
+/// Builds the word delimiter options if `word_delimiter=true` or any of its options were given,
+/// with Elasticsearch's defaults for the others.
+fn word_delimiter_from_typmod(value: &ParsedTypmod) -> Option<WordDelimiterOptions> {
+    let option = |key: &str| value.get(key).and_then(|p| p.as_bool());
+    let enabled = option("word_delimiter");
+    let split_on_case_change = option("split_on_case_change");
+    let split_on_numerics = option("split_on_numerics");
+    let preserve_original = option("preserve_original");
+    let catenate_words = option("catenate_words");
+    let catenate_numbers = option("catenate_numbers");
+    let catenate_all = option("catenate_all");
+    match enabled {
+        Some(false) => return None,
+        Some(true) => {}
+        None if split_on_case_change.is_none()
+            && split_on_numerics.is_none()
+            && preserve_original.is_none()
+            && catenate_words.is_none()
+            && catenate_numbers.is_none()
+            && catenate_all.is_none() =>
+        {
+            return None;
+        }
+        None => {}
+    }
+
+    let defaults = WordDelimiterOptions::default();
+    Some(WordDelimiterOptions {
+        split_on_case_change: split_on_case_change.unwrap_or(defaults.split_on_case_change),
+        split_on_numerics: split_on_numerics.unwrap_or(defaults.split_on_numerics),
+        preserve_original: preserve_original.unwrap_or(defaults.preserve_original),
+        catenate_words: catenate_words.unwrap_or(defaults.catenate_words),
+        catenate_numbers: catenate_numbers.unwrap_or(defaults.catenate_numbers),
+        catenate_all: catenate_all.unwrap_or(defaults.catenate_all),
+    })
+}
+
+/// Builds the char filters from the `html_strip`, `mapping` and `pattern_replace` properties, in
+/// the order they were given.  Each `pattern_replace` uses the `pattern_replacement` that follows
+/// it, and removes what it matches if there is none.
+fn char_filters_from_typmod(value: &ParsedTypmod) -> Option<Vec<CharFilter>> {
+    let mut char_filters = Vec::new();
+    for (idx, prop) in value.properties.iter().enumerate() {
+        let filter = match prop.key() {
+            Some("html_strip") if prop.as_bool() == Some(true) => CharFilter::HtmlStrip,
+            Some("mapping") => match prop.as_str().map(CharFilter::parse_mappings) {
+                Some(Ok(mappings)) => CharFilter::Mapping { mappings },
+                Some(Err(e)) => pgrx::error!("invalid mapping: {e}"),
+                None => continue,
+            },
+            Some("pattern_replace") => {
+                let Some(Ok(pattern)) = prop.as_regex() else {
+                    continue;
+                };
+                let replacement = value.properties[idx + 1..]
+                    .iter()
+                    .find(|p| p.key() == Some("pattern_replacement"))
+                    .and_then(|p| p.as_str())
+                    .unwrap_or_default();
+                CharFilter::PatternReplace {
+                    pattern: pattern.as_str().to_string(),
+                    replacement: replacement.to_string(),
+                }
+            }
+            _ => continue,
+        };
+
+        if let Err(e) = filter.validate() {
+            pgrx::error!("invalid char filter: {e}");
         }
+        char_filters.push(filter);
     }
+
+    (!char_filters.is_empty()).then_some(char_filters)
 }
 
 impl Index<usize> for ParsedTypmod {
@@ -473,6 +611,10 @@ impl ParsedTypmod {
         self.properties.push(property);
     }
 
+    pub fn iter(&self) -> std::slice::Iter<'_, Property> {
+        self.properties.iter()
+    }
+
     pub fn get(&self, key: &str) -> Option<&Property> {
         let prop = &self[key];
         if matches!(prop, Property::NoSuchProperty) {
@@ -268,6 +268,73 @@ impl TypmodSchema {
                 rule!("alpha_num_only", ValueConstraint::Boolean),
                 rule!("ascii_folding", ValueConstraint::Boolean),
                 rule!("trim", ValueConstraint::Boolean),
+                rule!("synonyms", ValueConstraint::String),
+                rule!("synonyms_table", ValueConstraint::String),
+                rule!("html_strip", ValueConstraint::Boolean),
+                rule!("mapping", ValueConstraint::String),
+                rule!("pattern_replace", ValueConstraint::Regex),
+                rule!("pattern_replacement", ValueConstraint::String),
+                rule!(
+                    "phonetic",
+                    ValueConstraint::StringChoice(vec![
+                        "soundex",
+                        "metaphone",
+                        "double_metaphone",
+                        "beider_morse"
+                    ])
+                ),
+                rule!("phonetic_replace", ValueConstraint::Boolean),
+                rule!(
+                    "min_shingle_size",
+                    ValueConstraint::Integer {
+                        min: Some(2),
+                        max: None
+                    }
+                ),
+                rule!(
+                    "max_shingle_size",
+                    ValueConstraint::Integer {
+                        min: Some(2),
+                        max: None
+                    }
+                ),
+                rule!("output_unigrams", ValueConstraint::Boolean),
+                rule!("filler_token", ValueConstraint::String),
+                rule!("word_delimiter", ValueConstraint::Boolean),
+                rule!("split_on_case_change", ValueConstraint::Boolean),
+                rule!("split_on_numerics", ValueConstraint::Boolean),
+                rule!("preserve_original", ValueConstraint::Boolean),
+                rule!("catenate_words", ValueConstraint::Boolean),
+                rule!("catenate_numbers", ValueConstraint::Boolean),
+                rule!("catenate_all", ValueConstraint::Boolean),
+                rule!("decompounder_words", ValueConstraint::String),
+                rule!("decompounder_table", ValueConstraint::String),
+                rule!(
+                    "min_word_size",
+                    ValueConstraint::Integer {
+                        min: Some(1),
+                        max: None
+                    }
+                ),
+                rule!(
+                    "min_subword_size",
+                    ValueConstraint::Integer {
+                        min: Some(1),
+                        max: None
+                    }
+                ),
+                rule!(
+                    "max_subword_size",
+                    ValueConstraint::Integer {
+                        min: Some(1),
+                        max: None
+                    }
+                ),
+                rule!("only_longest_match", ValueConstraint::Boolean),
+                rule!("hunspell", ValueConstraint::String),
+                rule!("hunspell_table", ValueConstraint::String),
+                rule!("hunspell_longest_only", ValueConstraint::Boolean),
+                rule!("analyzer", ValueConstraint::String),
                 rule!("fieldnorms", ValueConstraint::Boolean),
                 rule!(
                     "normalizer",
@@ -289,6 +356,23 @@ impl TypmodSchema {
                         max: Some(1.0)
                     }
                 ),
+                rule!(
+                    "similarity",
+                    ValueConstraint::StringChoice(vec![
+                        "bm25",
+                        "tfidf",
+                        "dfr",
+                        "dirichlet",
+                        "constant"
+                    ])
+                ),
+                rule!(
+                    "mu",
+                    ValueConstraint::Float {
+                        min: Some(0.0),
+                        max: None
+                    }
+                ),
             ]
         });
 
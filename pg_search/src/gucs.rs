@@ -20,8 +20,10 @@ use pgrx::{
     GucContext, GucFlags, GucRegistry, GucSetting, PgLogLevel, PgSqlErrorCode, function_name,
     pg_sys,
 };
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
 use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::time::Duration;
 use tantivy::aggregation::DEFAULT_BUCKET_LIMIT;
 
 use crate::postgres::options::MAX_MUTABLE_SEGMENT_ROWS;
@@ -100,6 +102,22 @@ static PER_TUPLE_COST: GucSetting<f64> = GucSetting::<f64>::new(100_000_000.0);
 
 static GLOBAL_TARGET_SEGMENT_COUNT: GucSetting<i32> = GucSetting::<i32>::new(0);
 static GLOBAL_ENABLE_BACKGROUND_MERGING: GucSetting<bool> = GucSetting::<bool>::new(true);
+
+/// Whether background merges are queued for the merge scheduler worker to launch, rather than
+/// launched by the backend that finds them.
+static ENABLE_MERGE_SCHEDULER: GucSetting<bool> = GucSetting::<bool>::new(false);
+
+/// How many background merges the merge scheduler runs at once, across all indexes.
+static MAX_CONCURRENT_MERGES: GucSetting<i32> = GucSetting::<i32>::new(2);
+
+/// How often the merge scheduler checks its queue, in milliseconds.
+static MERGE_SCHEDULER_NAPTIME: GucSetting<i32> = GucSetting::<i32>::new(1000);
+
+/// The most a background merge writes per second, in kilobytes.  0 means no limit.
+static MERGE_IO_RATE_LIMIT: GucSetting<i32> = GucSetting::<i32>::new(0);
+
+/// The times of day the merge scheduler doesn't start merges, like `09:00-17:00,22:00-23:30`.
+static MERGE_QUIET_WINDOWS: GucSetting<Option<CString>> = GucSetting::<Option<CString>>::new(None);
 static GLOBAL_MUTABLE_SEGMENT_ROWS: GucSetting<i32> = GucSetting::<i32>::new(-1);
 static EXPLAIN_RECURSIVE_ESTIMATES: GucSetting<bool> = GucSetting::<bool>::new(false);
 
@@ -229,6 +247,15 @@ pub fn vector_cluster_max_probe() -> f32 {
     VECTOR_CLUSTER_MAX_PROBE.get() as f32
 }
 
+/// Makes vector searches return the exact nearest neighbours, by scoring
+/// every vector of every segment they search instead of probing at most
+/// `paradedb.vector_cluster_max_probe` of its IVF clusters.
+static VECTOR_EXACT_SEARCH: GucSetting<bool> = GucSetting::<bool>::new(false);
+
+pub fn vector_exact_search() -> bool {
+    VECTOR_EXACT_SEARCH.get()
+}
+
 /// Fixed per-probe cost — the IVF cluster OPEN — in rows of full work.
 /// Testing knob for calibrating the probe-budget work model; defaults to
 /// the fitted value in tantivy.
@@ -239,6 +266,31 @@ pub fn vector_fixed_probe_cost_rows() -> f64 {
     VECTOR_FIXED_PROBE_COST_ROWS.get()
 }
 
+/// Merges a clustered segment on its own, retraining its centroids, once its
+/// largest cluster is this many times the average cluster size and rows have
+/// been deleted from it since it was written. `0` disables the check.
+static VECTOR_REBALANCE_SIZE_RATIO: GucSetting<f64> = GucSetting::<f64>::new(8.0);
+
+pub fn vector_rebalance_size_ratio() -> f64 {
+    VECTOR_REBALANCE_SIZE_RATIO.get()
+}
+
+/// Like `paradedb.vector_rebalance_size_ratio`, for the fraction of a
+/// clustered segment's clusters that are empty. `0` disables the check.
+static VECTOR_REBALANCE_EMPTY_FRACTION: GucSetting<f64> = GucSetting::<f64>::new(0.25);
+
+pub fn vector_rebalance_empty_fraction() -> f64 {
+    VECTOR_REBALANCE_EMPTY_FRACTION.get()
+}
+
+/// The fraction of a clustered segment's rows that must have been deleted since it was written
+/// before merges check whether its clusters have drifted.
+static VECTOR_REBALANCE_MIN_DELETED_FRACTION: GucSetting<f64> = GucSetting::<f64>::new(0.1);
+
+pub fn vector_rebalance_min_deleted_fraction() -> f64 {
+    VECTOR_REBALANCE_MIN_DELETED_FRACTION.get()
+}
+
 /// Doc-count boundary at which a merged segment's vector storage switches
 /// from flat (exact scan) to IVF (clustered). Captured into the index's
 /// stored `IndexSettings` at CREATE INDEX time, so it applies to every merge
@@ -256,6 +308,33 @@ pub fn vector_clustering_threshold() -> usize {
     VECTOR_CLUSTERING_THRESHOLD.get().max(1) as usize
 }
 
+/// How many times more candidates than a vector ORDER BY's `LIMIT` (plus `OFFSET`) are ranked by
+/// the quantized codes of a `vector_quantization` index and re-ranked by their full-precision
+/// vectors. Higher values trade latency for recall.
+static VECTOR_RERANK_OVERSAMPLE: GucSetting<i32> = GucSetting::<i32>::new(4);
+
+pub fn vector_rerank_oversample() -> usize {
+    VECTOR_RERANK_OVERSAMPLE.get().max(1) as usize
+}
+
+/// The directory Hunspell dictionaries are read from, as `<name>.aff` and `<name>.dic` files.
+/// A relative path is resolved against the data directory.
+static HUNSPELL_DICTIONARY_PATH: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(Some(c"hunspell"));
+
+pub fn hunspell_dictionary_path() -> PathBuf {
+    let path = HUNSPELL_DICTIONARY_PATH
+        .get()
+        .map(|path| PathBuf::from(path.to_string_lossy().into_owned()))
+        .unwrap_or_default();
+    if path.is_absolute() {
+        return path;
+    }
+    // SAFETY: `DataDir` is set before any backend runs queries, and is never freed
+    let data_dir = unsafe { CStr::from_ptr(pg_sys::DataDir) };
+    PathBuf::from(data_dir.to_string_lossy().into_owned()).join(path)
+}
+
 pub fn init() {
     // Note that Postgres is very specific about the naming convention of variables.
     // They must be namespaced... we use 'paradedb.<variable>' below.
@@ -452,6 +531,68 @@ pub fn init() {
         GucFlags::default(),
     );
 
+    GucRegistry::define_bool_guc(
+        c"paradedb.enable_merge_scheduler",
+        c"Schedule background merges from a dedicated worker",
+        c"When on, background merges are queued for a long-lived merge scheduler worker, which \
+          launches them subject to `paradedb.max_concurrent_merges` and \
+          `paradedb.merge_quiet_windows`. When off, the backend that finds an index to merge \
+          launches its background merger right away. Requires pg_search in \
+          `shared_preload_libraries`, and is off by default.",
+        &ENABLE_MERGE_SCHEDULER,
+        GucContext::Postmaster,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_int_guc(
+        c"paradedb.max_concurrent_merges",
+        c"The most background merges the merge scheduler runs at once",
+        c"Counts the merges of all indexes in all databases. Each merge uses one of \
+          `max_worker_processes`.",
+        &MAX_CONCURRENT_MERGES,
+        1,
+        1024,
+        GucContext::Sighup,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_int_guc(
+        c"paradedb.merge_scheduler_naptime",
+        c"How often the merge scheduler checks its queue",
+        c"The longest a queued background merge waits before the merge scheduler considers it. \
+          Accepts standard Postgres time units.",
+        &MERGE_SCHEDULER_NAPTIME,
+        10,
+        i32::MAX,
+        GucContext::Sighup,
+        GucFlags::UNIT_MS,
+    );
+
+    GucRegistry::define_int_guc(
+        c"paradedb.merge_io_rate_limit",
+        c"The most a background merge writes per second",
+        c"Background merges sleep whenever they've written faster than this, until a VACUUM is \
+          waiting for them. Accepts standard Postgres byte units (e.g. '50MB'). 0, the default, \
+          disables the limit.",
+        &MERGE_IO_RATE_LIMIT,
+        0,
+        i32::MAX,
+        GucContext::Sighup,
+        GucFlags::UNIT_KB,
+    );
+
+    GucRegistry::define_string_guc(
+        c"paradedb.merge_quiet_windows",
+        c"The times of day the merge scheduler doesn't start background merges",
+        c"A comma-separated list of `HH:MM-HH:MM` windows in the server's `timezone`, like \
+          '09:00-17:00,22:00-06:00'. A window that ends before it starts runs past midnight. \
+          Merges that are running when a window starts run to completion, and queued merges \
+          start once it ends.",
+        &MERGE_QUIET_WINDOWS,
+        GucContext::Sighup,
+        GucFlags::default(),
+    );
+
     GucRegistry::define_bool_guc(
         c"paradedb.explain_recursive_estimates",
         c"Enable recursive estimates in EXPLAIN VERBOSE",
@@ -472,6 +613,15 @@ pub fn init() {
         GucFlags::default(),
     );
 
+    GucRegistry::define_bool_guc(
+        c"paradedb.vector_exact_search",
+        c"Make vector searches exact",
+        c"Makes vector ORDER BY queries and the vector searches of hybrid queries exact by scoring every vector of every segment, ignoring paradedb.vector_cluster_max_probe. Useful for measuring recall; expect brute-force latency on large indexes.",
+        &VECTOR_EXACT_SEARCH,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
     GucRegistry::define_float_guc(
         c"paradedb.vector_fixed_probe_cost_rows",
         c"Fixed per-probe cost (the cluster OPEN) in rows of full work, for the IVF probe budget (testing knob)",
@@ -483,6 +633,39 @@ pub fn init() {
         GucFlags::default(),
     );
 
+    GucRegistry::define_float_guc(
+        c"paradedb.vector_rebalance_size_ratio",
+        c"Largest-to-average cluster size ratio at which merges retrain a segment's vector centroids",
+        c"A clustered segment with deleted rows whose largest IVF cluster is more than this many times the average cluster size is merged on its own, which retrains its centroids. 0 disables the check.",
+        &VECTOR_REBALANCE_SIZE_RATIO,
+        0.0,
+        1_000_000.0,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_float_guc(
+        c"paradedb.vector_rebalance_empty_fraction",
+        c"Fraction of empty clusters at which merges retrain a segment's vector centroids",
+        c"A clustered segment with deleted rows in which more than this fraction of IVF clusters is empty is merged on its own, which retrains its centroids. 0 disables the check.",
+        &VECTOR_REBALANCE_EMPTY_FRACTION,
+        0.0,
+        1.0,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_float_guc(
+        c"paradedb.vector_rebalance_min_deleted_fraction",
+        c"Fraction of a segment's rows that must be deleted before merges check its vector clusters for skew",
+        c"Merges only check the IVF clusters of a clustered segment for skew once at least this fraction of its rows has been deleted since it was written.",
+        &VECTOR_REBALANCE_MIN_DELETED_FRACTION,
+        0.0,
+        1.0,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
     GucRegistry::define_int_guc(
         c"paradedb.vector_clustering_threshold",
         c"Doc-count boundary at which merged segments switch from flat to IVF vector storage",
@@ -494,6 +677,27 @@ pub fn init() {
         GucFlags::default(),
     );
 
+    GucRegistry::define_int_guc(
+        c"paradedb.vector_rerank_oversample",
+        c"Candidates per requested row that a vector ORDER BY on a quantized index re-ranks",
+        c"A vector ORDER BY on an index built WITH (vector_quantization = ...) ranks this many times its LIMIT plus OFFSET rows by their quantized codes, then re-ranks them by the distance of their full-precision vectors, read from the table. Higher values improve recall at the cost of more table reads.",
+        &VECTOR_RERANK_OVERSAMPLE,
+        1,
+        10_000,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"paradedb.hunspell_dictionary_path",
+        c"The directory Hunspell dictionaries are read from",
+        c"The `hunspell` tokenizer option reads `<name>.aff` and `<name>.dic` from this directory. \
+          A relative path is resolved against the data directory. Defaults to `hunspell`.",
+        &HUNSPELL_DICTIONARY_PATH,
+        GucContext::Sighup,
+        GucFlags::default(),
+    );
+
     GucRegistry::define_int_guc(
         c"paradedb.global_mutable_segment_rows",
         c"a global mutable segment rows override",
@@ -760,6 +964,30 @@ pub fn global_enable_background_merging() -> bool {
     GLOBAL_ENABLE_BACKGROUND_MERGING.get()
 }
 
+pub fn enable_merge_scheduler() -> bool {
+    ENABLE_MERGE_SCHEDULER.get()
+}
+
+pub fn max_concurrent_merges() -> usize {
+    MAX_CONCURRENT_MERGES.get().max(1) as usize
+}
+
+pub fn merge_scheduler_naptime() -> Duration {
+    Duration::from_millis(MERGE_SCHEDULER_NAPTIME.get().max(10) as u64)
+}
+
+/// In bytes per second, or 0 for no limit.
+pub fn merge_io_rate_limit() -> u64 {
+    MERGE_IO_RATE_LIMIT.get().max(0) as u64 * 1024
+}
+
+pub fn merge_quiet_windows() -> String {
+    MERGE_QUIET_WINDOWS
+        .get()
+        .map(|windows| windows.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
 // NB:  MEMORY_BUDGET_NUM_BYTES_MIN comes from [`tantivy::index_writer::MEMORY_BUDGET_NUM_BYTES_MIN`], which is not publicly exposed
 mod limits {
     const MARGIN_IN_BYTES: usize = 1_000_000;
@@ -898,6 +898,54 @@ pub fn index_memory_segment(
     mvcc_style: &MvccSatisfies,
 ) -> anyhow::Result<RamDirectory> {
     use crate::index::writer::index::SerialIndexWriter;
+
+    let directory = RamDirectory::create();
+    let ctids = segment
+        .mutable_snapshot(indexrel)
+        .map_err(|e| anyhow::anyhow!("Could not snapshot mutable segment: {e}"))?;
+
+    let mut writer = SerialIndexWriter::in_memory(
+        indexrel,
+        segment_meta.id(),
+        directory.clone(),
+        // TODO: Remove argument.
+        1337,
+    )?;
+
+    index_heap_rows(
+        indexrel,
+        ctids,
+        heap_fetch_state,
+        expression_state,
+        mvcc_style,
+        |doc, ctid| {
+            writer.insert(doc, ctid, || {
+                unreachable!("No limits configured: should not finalize.")
+            })?;
+            Ok(())
+        },
+    )?;
+
+    writer.finalize_nocommit()?.expect(
+        "Segment should be non-empty because we created a `SegmentMeta` for it in the first place.",
+    );
+
+    Ok(directory)
+}
+
+/// Indexes the heap rows at `ctids` with the index's current schema, handing each document and
+/// its ctid to `insert`.
+///
+/// A ctid whose row can no longer be fetched is handed an empty document, so that every ctid keeps
+/// a document of its own.
+pub fn index_heap_rows(
+    indexrel: &PgSearchRelation,
+    ctids: impl IntoIterator<Item = u64>,
+    heap_fetch_state: &HeapFetchState,
+    expression_state: &ExpressionState,
+    mvcc_style: &MvccSatisfies,
+    mut insert: impl FnMut(tantivy::TantivyDocument, u64) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
     use crate::postgres::utils::{
         resolve_field_value, row_to_search_document, u64_to_item_pointer,
     };
@@ -912,7 +960,7 @@ pub fn index_memory_segment(
     /// RAII guard that guarantees an active snapshot for the duration of the heap reads and
     /// detoasting performed while materializing a mutable segment.
     ///
-    /// Most callers of [`index_memory_segment`] (ordinary DML, queries, VACUUM, background merge
+    /// Most callers of [`index_heap_rows`] (ordinary DML, queries, VACUUM, background merge
     /// workers) already run with an active snapshot. The logical-replication apply worker, however,
     /// applies remote changes without pushing one, and `pg_detoast_datum` on an out-of-line TOAST
     /// value requires a snapshot (`get_toast_snapshot` errors otherwise). This guard pushes the
@@ -947,19 +995,6 @@ pub fn index_memory_segment(
     // below. Held until this function returns so it covers the entire materialization loop.
     let _snapshot_guard = unsafe { ActiveSnapshotGuard::ensure() };
 
-    let directory = RamDirectory::create();
-    let ctids = segment
-        .mutable_snapshot(indexrel)
-        .map_err(|e| anyhow::anyhow!("Could not snapshot mutable segment: {e}"))?;
-
-    let mut writer = SerialIndexWriter::in_memory(
-        indexrel,
-        segment_meta.id(),
-        directory.clone(),
-        // TODO: Remove argument.
-        1337,
-    )?;
-
     let heaprel = indexrel
         .heap_relation()
         .expect("Should have a heap relation.");
@@ -1002,9 +1037,7 @@ pub fn index_memory_segment(
                 heaprel.fork_number(),
             )
         } {
-            writer.insert(tantivy::TantivyDocument::new(), ctid, || {
-                unreachable!("No limits configured: should not finalize.")
-            })?;
+            insert(tantivy::TantivyDocument::new(), ctid)?;
             continue 'next_ctid;
         }
 
@@ -1039,9 +1072,7 @@ pub fn index_memory_segment(
                     // Either the tuple is not visible to `fetch_snapshot` (query-visible mode) or
                     // heap page pruning removed it (SnapshotAny mode). In both cases there is no
                     // content to index for this ctid, so insert an empty document.
-                    writer.insert(tantivy::TantivyDocument::new(), ctid, || {
-                        unreachable!("No limits configured: should not finalize.")
-                    })?;
+                    insert(tantivy::TantivyDocument::new(), ctid)?;
                     continue 'next_ctid;
                 }
 
@@ -1100,9 +1131,7 @@ pub fn index_memory_segment(
                     } else {
                         // There are no more entries in the HOT chain, so no copy of the tuple is
                         // visible in any transaction.
-                        writer.insert(tantivy::TantivyDocument::new(), ctid, || {
-                            unreachable!("No limits configured: should not finalize.")
-                        })?;
+                        insert(tantivy::TantivyDocument::new(), ctid)?;
                         continue 'next_ctid;
                     }
                 }
@@ -1185,9 +1214,7 @@ pub fn index_memory_segment(
                 panic!("Failed to create document from row: {e}");
             });
 
-            writer.insert(doc, ctid, || {
-                unreachable!("No limits configured: should not finalize.")
-            })?;
+            insert(doc, ctid)?;
 
             // Eagerly release the buffer pin now that all datum values have
             // been detoasted into palloc'd memory. Without this, the pin would
@@ -1198,11 +1225,7 @@ pub fn index_memory_segment(
         }
     }
 
-    writer.finalize_nocommit()?.expect(
-        "Segment should be non-empty because we created a `SegmentMeta` for it in the first place.",
-    );
-
-    Ok(directory)
+    Ok(())
 }
 
 #[cfg(any(test, feature = "pg_test"))]
@@ -15,14 +15,18 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use crate::api::tokenizers::TokenizerTables;
 use crate::api::{HashMap, HashSet};
 use crate::index::mvcc::{MvccSatisfies, PinCushion};
 use crate::postgres::rel::PgSearchRelation;
+use crate::postgres::storage::LinkedBytesList;
 use crate::postgres::storage::block::{
     DeleteEntry, FileEntry, LinkedList, MVCCEntry, PgItem, SegmentFileDetails, SegmentMetaEntry,
     SegmentMetaEntryImmutable, VECTOR_CENTROIDS_EXT, VECTOR_VEC_EXT,
 };
+use crate::postgres::storage::custom_rmgr;
 use crate::postgres::storage::metadata::MetaPage;
+use crate::schema::SchemaChanges;
 use anyhow::Result;
 use pgrx::pg_sys;
 use std::path::PathBuf;
@@ -44,6 +48,51 @@ pub fn save_schema(indexrel: &PgSearchRelation, tantivy_schema: &Schema) -> Resu
     Ok(())
 }
 
+/// Replaces the index's schema, along with the record of how it changed since the index was built.
+///
+/// The change is transactional: other transactions keep reading the previous lists until this one
+/// commits, and they're freed once it has, or the new ones are if it aborts.
+///
+/// # Safety
+///
+/// The caller must hold an `AccessExclusiveLock` on the index until the end of the transaction.
+pub unsafe fn replace_schema(
+    indexrel: &PgSearchRelation,
+    tantivy_schema: &Schema,
+    changes: &SchemaChanges,
+) -> Result<()> {
+    let mut metapage = MetaPage::open(indexrel);
+    metapage.resolve_schema_change();
+
+    let mut schema = LinkedBytesList::create_with_fsm(indexrel).writer();
+    schema.write(&serde_json::to_vec(tantivy_schema)?)?;
+    let schema = schema.finalize_and_write()?;
+
+    let mut schema_changes = LinkedBytesList::create_with_fsm(indexrel).writer();
+    schema_changes.write(&serde_json::to_vec(changes)?)?;
+    let schema_changes = schema_changes.finalize_and_write()?;
+
+    metapage.set_schema(
+        schema.get_header_blockno(),
+        schema_changes.get_header_blockno(),
+    );
+    Ok(())
+}
+
+/// Keeps a copy of the tables the index's tokenizers read, which it uses from then on.
+pub fn save_tokenizer_tables(indexrel: &PgSearchRelation, tables: &TokenizerTables) -> Result<()> {
+    let Some(list) = MetaPage::open(indexrel).tokenizer_tables_bytes() else {
+        return Ok(());
+    };
+    if list.is_empty() {
+        let bytes = serde_json::to_vec(tables)?;
+        unsafe {
+            list.writer().write(&bytes)?;
+        }
+    }
+    Ok(())
+}
+
 pub fn save_settings(indexrel: &PgSearchRelation, tantivy_settings: &IndexSettings) -> Result<()> {
     let settings = MetaPage::open(indexrel).settings_bytes();
     if settings.is_empty() {
@@ -254,6 +303,20 @@ pub unsafe fn save_new_metas(
         )
     }
 
+    // what we're about to change, to describe it for `pg_waldump` once it's WAL-logged
+    let created_segments = created_entries
+        .iter()
+        .map(|entry| entry.segment_id())
+        .collect::<Vec<_>>();
+    let deleted_segments = deleted_entries
+        .iter()
+        .map(|(entry, _)| entry.segment_id())
+        .collect::<Vec<_>>();
+    let modified_segments = modified_entries
+        .iter()
+        .map(|(entry, _)| entry.segment_id())
+        .collect::<Vec<_>>();
+
     //
     // now change things on disk
     //
@@ -327,6 +390,13 @@ pub unsafe fn save_new_metas(
     // atomically replace the SegmentMetaEntry list, and then mark any orphaned files deleted.
     linked_list.commit();
 
+    custom_rmgr::emit_segments_record(
+        indexrel,
+        &created_segments,
+        &deleted_segments,
+        &modified_segments,
+    );
+
     Ok(())
 }
 
@@ -351,124 +421,109 @@ pub unsafe fn load_metas(
     let mut opstamp = None;
     let mut pin_cushion = PinCushion::default();
 
-    // Collect segments from each relevant list.
     let metapage = MetaPage::open(indexrel);
-    let mut segment_metas = metapage.segment_metas();
-    let mut exhausted_metas_lists = false;
+    let segment_metas = metapage.segment_metas();
 
     let is_largest_only = &MvccSatisfies::LargestSegment == solve_mvcc;
     let mut largest_doc_count = 0;
 
-    loop {
-        // Find all relevant segments in this list.
-        segment_metas.for_each(|bman, mut entry| {
-            // nobody sees recyclable segments
-            let accept = !entry.recyclable(bman) && (
-                // parallel workers only see a specific set of segments.  This relies on the leader having kept a pin on them
-                matches!(solve_mvcc, MvccSatisfies::ParallelWorker(view) if view.contains(&entry.segment_id()))
+    // Find all relevant segments.
+    segment_metas.for_each(|bman, mut entry| {
+        // nobody sees recyclable segments
+        let accept = !entry.recyclable(bman) && (
+            // parallel workers only see a specific set of segments.  This relies on the leader having kept a pin on them
+            matches!(solve_mvcc, MvccSatisfies::ParallelWorker(view) if view.contains(&entry.segment_id()))
 
-                    // vacuum sees everything that hasn't been deleted by a merge
-                    || (matches!(solve_mvcc, MvccSatisfies::Vacuum) && entry.xmax() == pg_sys::InvalidTransactionId)
+                // vacuum sees everything that hasn't been deleted by a merge
+                || (matches!(solve_mvcc, MvccSatisfies::Vacuum) && entry.xmax() == pg_sys::InvalidTransactionId)
 
-                    // a snapshot or ::LargestSegment can see any that are visible in its snapshot
-                    || (matches!(solve_mvcc, MvccSatisfies::Snapshot | MvccSatisfies::LargestSegment) && entry.visible())
+                // a snapshot or ::LargestSegment can see any that are visible in its snapshot
+                || (matches!(solve_mvcc, MvccSatisfies::Snapshot | MvccSatisfies::LargestSegment) && entry.visible())
 
-                    // mergeable can see any that are known to be mergeable
-                    || (matches!(solve_mvcc, MvccSatisfies::Mergeable) && entry.is_mergeable(indexrel))
-            );
-            if !accept {
-                return;
-            };
+                // mergeable can see any that are known to be mergeable
+                || (matches!(solve_mvcc, MvccSatisfies::Mergeable) && entry.is_mergeable(indexrel))
+        );
+        if !accept {
+            return;
+        };
 
-            // Replay the view's materialization bound so this reader's `DocId`s for the mutable
-            // segment line up with the reader that captured the view.
-            if let MvccSatisfies::ParallelWorker(view) = solve_mvcc
-                && let Some(bound) = view.mutable_bound(&entry.segment_id())
-            {
-                let segment_id = entry.segment_id();
-                if let Err(e) = entry.rewind_mutable(bound.max_doc, bound.num_deleted_docs) {
-                    panic!("load_metas: cannot replay the parallel view for segment {segment_id}: {e}");
-                }
+        // Replay the view's materialization bound so this reader's `DocId`s for the mutable
+        // segment line up with the reader that captured the view.
+        if let MvccSatisfies::ParallelWorker(view) = solve_mvcc
+            && let Some(bound) = view.mutable_bound(&entry.segment_id())
+        {
+            let segment_id = entry.segment_id();
+            if let Err(e) = entry.rewind_mutable(bound.max_doc, bound.num_deleted_docs) {
+                panic!("load_metas: cannot replay the parallel view for segment {segment_id}: {e}");
             }
+        }
 
-            total_segments += 1;
-            total_docs += entry.num_docs();
-
-            let mut need_entry = true;
-            if is_largest_only {
-                if entry.num_docs() > largest_doc_count {
-                    largest_doc_count = entry.num_docs();
-
-                    // the entry we're processing right now is known to be the largest so far
-                    // and it's the only one we want
-                    alive_segments.clear();
-                    alive_entries.clear();
-                    pin_cushion.clear();
-                } else {
-                    // we already have the largest so we don't need this entry
-                    need_entry = false;
-                }
+        total_segments += 1;
+        total_docs += entry.num_docs();
+
+        let mut need_entry = true;
+        if is_largest_only {
+            if entry.num_docs() > largest_doc_count {
+                largest_doc_count = entry.num_docs();
+
+                // the entry we're processing right now is known to be the largest so far
+                // and it's the only one we want
+                alive_segments.clear();
+                alive_entries.clear();
+                pin_cushion.clear();
+            } else {
+                // we already have the largest so we don't need this entry
+                need_entry = false;
             }
+        }
 
-            if need_entry {
-                pin_cushion.push(bman, &entry);
+        if need_entry {
+            pin_cushion.push(bman, &entry);
 
-                alive_segments.push(entry.as_tantivy().track(inventory));
-                alive_entries.push(entry);
+            alive_segments.push(entry.as_tantivy().track(inventory));
+            alive_entries.push(entry);
 
-                opstamp = opstamp.max(Some(entry.opstamp()));
-            }
-        });
-
-        match solve_mvcc {
-            MvccSatisfies::ParallelWorker(view) if alive_entries.len() != view.len() => {
-                // If we haven't tried the `segment_metas_garbage` list, try that next.
-                if !exhausted_metas_lists
-                    && let Some(garbage) = MetaPage::open(indexrel).segment_metas_garbage()
-                {
-                    segment_metas = garbage;
-                    exhausted_metas_lists = true;
-                    continue;
-                }
-
-                // TODO:  I believe this situation, where if the alive_entries.len() != only_these.len() is now dead code
-                //        @Stu asked to keep this around for awhile.  If there's ever a situation where `alive_entries` has
-                //        fewer entries than `only_these` then we should cancel the query, but it'd be indicative of a bug
-                //
-                // TODO (after some testing):  This situation does indeed happen and I believe that points to a bug, but
-                //        I don't know where it's coming from.  As such, cancelling the query is the expedient decision.
-                let expected = view.ids().collect::<HashSet<SegmentId>>();
-                let missing = expected
-                    .difference(&alive_entries.iter().map(|s| s.segment_id()).collect())
-                    .cloned()
-                    .collect::<HashSet<SegmentId>>();
-                let found = expected.difference(&missing).collect::<HashSet<_>>();
-
-                panic!(
-                    "load_metas: MvccSatisfies::ParallelWorker didn't load the correct segments. \
-                    found={found:?}, missing={missing:?}",
-                );
-            }
-            #[cfg(debug_assertions)]
-            MvccSatisfies::ParallelWorker(view) => {
-                // In debug mode only, actually do a set comparison to determine that we got the
-                // exact expected segments.
-                let actual = alive_entries
-                    .iter()
-                    .map(|s| s.segment_id())
-                    .collect::<HashSet<_>>();
-                let expected = view.ids().collect::<HashSet<_>>();
-                assert_eq!(
-                    actual, expected,
-                    "Got the wrong segments in parallel worker: \
-                     actual: {actual:?}, expected: {expected:?}"
-                );
-                break;
-            }
-            _ => {
-                // We've successfully collected all of the relevant entries.
-                break;
-            }
+            opstamp = opstamp.max(Some(entry.opstamp()));
+        }
+    });
+
+    match solve_mvcc {
+        MvccSatisfies::ParallelWorker(view) if alive_entries.len() != view.len() => {
+            // TODO:  I believe this situation, where if the alive_entries.len() != only_these.len() is now dead code
+            //        @Stu asked to keep this around for awhile.  If there's ever a situation where `alive_entries` has
+            //        fewer entries than `only_these` then we should cancel the query, but it'd be indicative of a bug
+            //
+            // TODO (after some testing):  This situation does indeed happen and I believe that points to a bug, but
+            //        I don't know where it's coming from.  As such, cancelling the query is the expedient decision.
+            let expected = view.ids().collect::<HashSet<SegmentId>>();
+            let missing = expected
+                .difference(&alive_entries.iter().map(|s| s.segment_id()).collect())
+                .cloned()
+                .collect::<HashSet<SegmentId>>();
+            let found = expected.difference(&missing).collect::<HashSet<_>>();
+
+            panic!(
+                "load_metas: MvccSatisfies::ParallelWorker didn't load the correct segments. \
+                found={found:?}, missing={missing:?}",
+            );
+        }
+        #[cfg(debug_assertions)]
+        MvccSatisfies::ParallelWorker(view) => {
+            // In debug mode only, actually do a set comparison to determine that we got the
+            // exact expected segments.
+            let actual = alive_entries
+                .iter()
+                .map(|s| s.segment_id())
+                .collect::<HashSet<_>>();
+            let expected = view.ids().collect::<HashSet<_>>();
+            assert_eq!(
+                actual, expected,
+                "Got the wrong segments in parallel worker: \
+                 actual: {actual:?}, expected: {expected:?}"
+            );
+        }
+        _ => {
+            // We've successfully collected all of the relevant entries.
         }
     }
 
@@ -499,3 +554,25 @@ pub fn load_index_schema(indexrel: &PgSearchRelation) -> tantivy::Result<Option<
     }
     Ok(serde_json::from_slice(&schema_bytes)?)
 }
+
+/// The copy of the tables the index's tokenizers read, which is empty if they don't read any or
+/// the index was built before it was kept.
+pub fn load_tokenizer_tables(indexrel: &PgSearchRelation) -> Result<TokenizerTables> {
+    let Some(list) = MetaPage::open(indexrel).tokenizer_tables_bytes() else {
+        return Ok(TokenizerTables::default());
+    };
+    let bytes = unsafe { list.read_all() };
+    if bytes.is_empty() {
+        return Ok(TokenizerTables::default());
+    }
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+pub fn load_schema_changes(indexrel: &PgSearchRelation) -> tantivy::Result<SchemaChanges> {
+    let metapage = MetaPage::open(indexrel);
+    let Some(schema_changes) = metapage.schema_changes_bytes() else {
+        return Ok(SchemaChanges::default());
+    };
+    let bytes = unsafe { schema_changes.read_all() };
+    Ok(serde_json::from_slice(&bytes)?)
+}
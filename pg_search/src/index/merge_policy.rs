@@ -20,13 +20,21 @@ use crate::index::writer::index::SearchIndexMerger;
 use crate::postgres::storage::block::SegmentMetaEntry;
 use crate::postgres::storage::merge::MergeLock;
 use crate::postgres::storage::metadata::MetaPage;
+use crate::vector::rebalance::SkewThresholds;
 use pgrx::pg_sys;
+use std::cell::RefCell;
 use std::cmp::Reverse;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tantivy::index::SegmentId;
 use tantivy::indexer::{MergeCandidate, MergePolicy};
 use tantivy::{Directory, Inventory, SegmentMeta};
 
+thread_local! {
+    /// The clustered segments found not to be skewed, with how many of their rows were deleted
+    /// then, so that they're only checked again once more are.
+    static BALANCED_SEGMENTS: RefCell<HashMap<SegmentId, usize>> = RefCell::default();
+}
+
 #[derive(Debug)]
 pub struct LayeredMergePolicy {
     layer_sizes: Vec<u64>,
@@ -35,6 +43,17 @@ pub struct LayeredMergePolicy {
 
     mergeable_segments: HashMap<SegmentId, SegmentMetaEntry>,
     already_processed: AtomicBool,
+
+    /// Merge every clustered segment on its own, not just the skewed ones.
+    rebalance_all_vectors: bool,
+    /// Clustered segments to merge on their own, which retrains their vector centroids.
+    rebalance_segments: HashSet<SegmentId>,
+
+    /// Backfill the segments missing a field added by `pdb.add_field()`, rather than leaving them
+    /// out of the merge.
+    backfill: bool,
+    /// Segments to rebuild on their own, with the fields added since they were written.
+    backfill_segments: HashSet<SegmentId>,
 }
 
 impl MergePolicy for LayeredMergePolicy {
@@ -60,6 +79,30 @@ impl LayeredMergePolicy {
 
             mergeable_segments: Default::default(),
             already_processed: Default::default(),
+
+            rebalance_all_vectors: false,
+            rebalance_segments: Default::default(),
+
+            backfill: false,
+            backfill_segments: Default::default(),
+        }
+    }
+
+    /// A layered policy that also rebuilds, one at a time, the segments missing a field added by
+    /// `pdb.add_field()`.  Backfilling reads the heap, so it's left to the background merger.
+    pub fn with_backfill(layer_sizes: Vec<u64>) -> LayeredMergePolicy {
+        Self {
+            backfill: true,
+            ..Self::new(layer_sizes)
+        }
+    }
+
+    /// A policy that merges each clustered segment on its own, retraining its vector centroids,
+    /// and otherwise only merges away mutable segments.
+    pub fn vector_rebalance() -> LayeredMergePolicy {
+        Self {
+            rebalance_all_vectors: true,
+            ..Self::new(Vec::new())
         }
     }
 
@@ -88,6 +131,60 @@ impl LayeredMergePolicy {
                 !non_mergeable_segments.contains(segment_id)
             })
             .collect();
+
+        // a segment missing an added field can't be merged with others, as the merged segment
+        // would be missing it too
+        let unfilled = merger.unfilled_segment_ids();
+        if self.backfill {
+            self.backfill_segments = self
+                .mergeable_segments
+                .keys()
+                .filter(|segment_id| unfilled.contains(segment_id))
+                .copied()
+                .collect();
+        } else {
+            self.mergeable_segments
+                .retain(|segment_id, _| !unfilled.contains(segment_id));
+        }
+
+        self.rebalance_segments = if self.rebalance_all_vectors {
+            self.clustered_segments()
+        } else if let Some(thresholds) = SkewThresholds::from_gucs() {
+            // a segment's clusters only drift as rows are deleted from it, and rebalancing purges
+            // those deletes, so a segment that's still skewed afterwards isn't rebalanced again.
+            // nor is one checked again until more of its rows are deleted
+            let churned = self
+                .mergeable_segments
+                .values()
+                .filter(|entry| {
+                    thresholds.may_have_drifted(entry.max_doc() as usize, entry.num_deleted_docs())
+                        && !BALANCED_SEGMENTS.with_borrow(|balanced| {
+                            balanced.get(&entry.segment_id()) == Some(&entry.num_deleted_docs())
+                        })
+                })
+                .collect::<Vec<_>>();
+            let skewed = merger
+                .skewed_vector_segments(churned.iter().copied(), thresholds)
+                .unwrap_or_else(|e| {
+                    pgrx::debug1!("do_merge: could not check vector cluster skew: {e}");
+                    Default::default()
+                });
+            BALANCED_SEGMENTS.with_borrow_mut(|balanced| {
+                for entry in churned {
+                    if !skewed.contains(&entry.segment_id()) {
+                        balanced.insert(entry.segment_id(), entry.num_deleted_docs());
+                    }
+                }
+            });
+            skewed
+        } else {
+            Default::default()
+        };
+        if !self.rebalance_segments.is_empty()
+            && unsafe { pg_sys::message_level_is_interesting(pg_sys::DEBUG1 as _) }
+        {
+            pgrx::debug1!("do_merge: rebalance_segments={:?}", self.rebalance_segments);
+        }
     }
 
     #[cfg(any(test, feature = "pg_test"))]
@@ -96,6 +193,18 @@ impl LayeredMergePolicy {
             .into_iter()
             .map(|entry| (entry.segment_id(), entry))
             .collect();
+        if self.rebalance_all_vectors {
+            self.rebalance_segments = self.clustered_segments();
+        }
+    }
+
+    /// The mergeable segments that store their vectors clustered.
+    fn clustered_segments(&self) -> HashSet<SegmentId> {
+        self.mergeable_segments
+            .values()
+            .filter(|entry| entry.has_vector_centroids())
+            .map(|entry| entry.segment_id())
+            .collect()
     }
 
     /// Run a simulation of what tantivy will do if it were to call our [`MergePolicy::compute_merge_candidates`]
@@ -216,11 +325,14 @@ impl LayeredMergePolicy {
                 .sum::<u64>();
 
         let mut candidates: Vec<(u64, MergeCandidate)> = Vec::new();
-        let mut merged_segments = HashSet::default();
+        // segments to backfill are only ever merged on their own, below
+        let mut merged_segments = self.backfill_segments.clone();
 
         // aggressively merge away any mutable or completely empty segments
         for (segment_id, segment_meta_entry) in &self.mergeable_segments {
-            if segment_meta_entry.is_mutable() {
+            if self.backfill_segments.contains(segment_id) {
+                continue;
+            } else if segment_meta_entry.is_mutable() {
                 // If a segment is mutable, then it makes sense to merge it away, even if it is the only item in the segment.
                 if let Some(segment_meta) = original_segments.iter().find(|s| s.id() == *segment_id)
                 {
@@ -327,6 +439,34 @@ impl LayeredMergePolicy {
             break;
         }
 
+        // rebuild the segments missing an added field on their own, which also rebalances them
+        for segment in original_segments {
+            if self.backfill_segments.contains(&segment.id())
+                && self.mergeable_segments.contains_key(&segment.id())
+            {
+                candidates.push((
+                    self.segment_size(segment, avg_doc_size),
+                    MergeCandidate(vec![segment.id()]),
+                ));
+            }
+        }
+
+        // merge the clustered segments that need rebalancing on their own, unless they're already
+        // being merged with others, which retrains their centroids all the same
+        for segment in original_segments {
+            if self.rebalance_segments.contains(&segment.id())
+                && self.mergeable_segments.contains_key(&segment.id())
+                && !candidates
+                    .iter()
+                    .any(|(_, candidate)| candidate.0.contains(&segment.id()))
+            {
+                candidates.push((
+                    self.segment_size(segment, avg_doc_size),
+                    MergeCandidate(vec![segment.id()]),
+                ));
+            }
+        }
+
         if !candidates.is_empty() {
             self.already_processed.store(true, Ordering::Relaxed);
         }
@@ -377,8 +517,8 @@ fn adjusted_byte_size(
 mod tests {
     use super::*;
     use crate::postgres::storage::block::{
-        DeleteEntry, FileEntry, SegmentMetaEntry, SegmentMetaEntryImmutable,
-        SegmentMetaEntryMutable,
+        DeleteEntry, FileEntry, SegmentMetaEntry, SegmentMetaEntryContent,
+        SegmentMetaEntryImmutable, SegmentMetaEntryMutable,
     };
     use pgrx::pg_sys;
     use pgrx::prelude::*;
@@ -457,6 +597,56 @@ mod tests {
         assert_eq!(largest_layer_size, 0);
     }
 
+    #[pg_test]
+    fn test_vector_rebalance_merges_each_clustered_segment_alone() {
+        let mut policy = LayeredMergePolicy::vector_rebalance();
+        let mut clustered = vec![
+            create_segment_meta_entry(700, 70, 0),
+            create_segment_meta_entry(700, 70, 5),
+        ];
+        for entry in &mut clustered {
+            if let SegmentMetaEntryContent::Immutable(content) = &mut entry.content {
+                content.centroids = Some(FileEntry {
+                    starting_block: pg_sys::InvalidBlockNumber,
+                    total_bytes: 0,
+                });
+            }
+        }
+        let clustered_ids: Vec<_> = clustered.iter().map(|s| s.segment_id()).collect();
+        let mut segments = clustered;
+        segments.push(create_segment_meta_entry(700, 70, 0));
+
+        policy.set_mergeable_segments_for_test(segments);
+        let (candidates, _) = policy.simulate();
+
+        assert_eq!(candidates.len(), 2);
+        for candidate in &candidates {
+            assert_eq!(candidate.0.len(), 1);
+            assert!(clustered_ids.contains(&candidate.0[0]));
+        }
+    }
+
+    #[pg_test]
+    fn test_backfill_segments_are_merged_alone() {
+        let mut policy = LayeredMergePolicy::with_backfill(vec![1000]);
+        let segments = vec![
+            create_segment_meta_entry(700, 70, 0),
+            create_segment_meta_entry(700, 70, 0),
+            create_segment_meta_entry(700, 70, 0),
+        ];
+        let segment_ids: Vec<_> = segments.iter().map(|s| s.segment_id()).collect();
+
+        policy.set_mergeable_segments_for_test(segments);
+        policy.backfill_segments = [segment_ids[2]].into_iter().collect();
+        let (candidates, _) = policy.simulate();
+
+        // the other two merge into the layer as usual, the backfilled one is rebuilt on its own
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].0.len(), 2);
+        assert!(!candidates[0].0.contains(&segment_ids[2]));
+        assert_eq!(candidates[1].0, vec![segment_ids[2]]);
+    }
+
     #[pg_test]
     fn test_layered_merge_policy_simple() {
         let mut policy = LayeredMergePolicy::new(vec![1000]);
@@ -25,24 +25,32 @@ use std::sync::Arc;
 use crate::aggregate::mvcc_collector::MVCCFilterCollector;
 use crate::api::operator::keyset::KeySet;
 use crate::api::version::Version;
-use crate::api::{FieldName, HashMap, OrderByFeature, OrderByInfo, SortDirection};
+use crate::api::{FieldName, HashMap, HashSet, OrderByFeature, OrderByInfo, SortDirection};
 use crate::index::fast_fields_helper::FFHelper;
 use crate::index::mvcc::{MVCCDirectory, MvccSatisfies, SegmentView};
 use crate::index::reader::io_stats;
-use crate::index::reader::scorer::{DeferredScorer, LazyWeight, ScorerIter};
+use crate::index::reader::scorer::{
+    DeferredScorer, LazyWeight, ScorerIter, with_field_similarities,
+};
+use crate::index::reader::sort_by_quantized_distance::SortByQuantizedDistance;
 use crate::index::reader::sort_by_range::SortByRange;
 use crate::index::setup_tokenizers;
-use crate::postgres::heap::VisibilityChecker;
+use crate::postgres::heap::{HeapFetchState, VisibilityChecker};
 use crate::postgres::options::{SortByDirection, SortByField};
 use crate::postgres::rel::PgSearchRelation;
 use crate::postgres::storage::buffer::PinnedBuffer;
 use crate::postgres::storage::metadata::MetaPage;
+use crate::postgres::utils::{FieldSource, extract_field_attributes};
 use crate::query::SearchQueryInput;
 use crate::query::estimate_tree::QueryWithEstimates;
+use crate::query::skip_segments::SkipSegments;
 use crate::scan::info::RowEstimate;
-use crate::schema::{SearchFieldType, SearchIndexSchema};
+use crate::schema::{SearchField, SearchFieldType, SearchIndexSchema};
+use crate::vector::PgVector;
+use crate::vector::metric::VectorMetric;
 
 use anyhow::Result;
+use pgrx::{FromDatum, pg_sys};
 use tantivy::aggregation::DistributedAggregationCollector;
 use tantivy::aggregation::intermediate_agg_result::IntermediateAggregationResults;
 use tantivy::collector::sort_key::{
@@ -54,7 +62,6 @@ use tantivy::index::{Index, Order, SegmentId};
 use tantivy::query::{EnableScoring, QueryClone, QueryParser, Weight};
 use tantivy::snippet::SnippetGenerator;
 use tantivy::vector::ProbeStats;
-use tantivy::vector::ivf::AdaptiveProbeParams;
 use tantivy::{
     DateTime, DocAddress, DocId, DocSet, Executor, IndexReader, ReloadPolicy, Score, Searcher,
     SegmentOrdinal, SegmentReader, TantivyDocument, query::Query, schema::OwnedValue,
@@ -71,6 +78,33 @@ pub struct DocsEstimate {
     pub query_cost: u64,
 }
 
+/// Wraps `query` so it skips the segments in `searcher` that haven't been backfilled with one of
+/// the queried `field_names`, see [`SchemaChanges`](crate::schema::SchemaChanges).
+pub(crate) fn skip_unfilled_segments(
+    schema: &SearchIndexSchema,
+    searcher: &Searcher,
+    field_names: &HashSet<String>,
+    query: Box<dyn Query>,
+) -> Box<dyn Query> {
+    let mut unfilled = schema.changes().unfilled_segments(field_names);
+    if unfilled.is_empty() {
+        return query;
+    }
+
+    let searched = searcher
+        .segment_readers()
+        .iter()
+        .map(|reader| reader.segment_id())
+        .collect::<HashSet<_>>();
+    unfilled.retain(|segment_id| searched.contains(segment_id));
+
+    if unfilled.is_empty() {
+        query
+    } else {
+        Box::new(SkipSegments::new(unfilled, query))
+    }
+}
+
 fn scale_largest_segment_estimate(value: u64, segment_doc_proportion: f64) -> u64 {
     if segment_doc_proportion > 0.0 {
         (value as f64 / segment_doc_proportion).ceil() as u64
@@ -504,6 +538,8 @@ impl SearchIndexReader {
 
         let index_created_by_version = index_relation.created_by_version();
         let need_scores = need_scores || search_query_input.need_scores();
+        let mut field_names = HashSet::default();
+        search_query_input.extract_field_names(&mut field_names);
         let query = {
             search_query_input
                 .into_tantivy_query(
@@ -523,6 +559,8 @@ impl SearchIndexReader {
                 )
                 .unwrap_or_else(|e| panic!("{e}"))
         };
+        let query = with_field_similarities(&schema, query);
+        let query = skip_unfilled_segments(&schema, &searcher, &field_names, query);
         let segment_ord_by_id = searcher
             .segment_readers()
             .iter()
@@ -633,7 +671,7 @@ impl SearchIndexReader {
         search_query_input: &SearchQueryInput,
         expr_context: Option<NonNull<pgrx::pg_sys::ExprContext>>,
     ) -> Box<dyn Query> {
-        search_query_input
+        let query = search_query_input
             .clone()
             .into_tantivy_query(
                 &self.schema,
@@ -653,7 +691,12 @@ impl SearchIndexReader {
                 expr_context,
                 None, // no planstate
             )
-            .unwrap_or_else(|e| panic!("{e}"))
+            .unwrap_or_else(|e| panic!("{e}"));
+
+        let query = with_field_similarities(&self.schema, query);
+        let mut field_names = HashSet::default();
+        search_query_input.extract_field_names(&mut field_names);
+        skip_unfilled_segments(&self.schema, &self.searcher, &field_names, query)
     }
 
     pub fn index_created_by_version(&self) -> Option<Version> {
@@ -1084,7 +1127,10 @@ impl SearchIndexReader {
             OrderByInfo {
                 feature:
                     OrderByFeature::VectorDistance {
-                        name, query_vector, ..
+                        name,
+                        query_vector,
+                        metric,
+                        ..
                     },
                 ..
             } => {
@@ -1102,18 +1148,54 @@ impl SearchIndexReader {
                     .resolved()
                     .expect("vector ORDER BY query vector was never resolved")
                     .to_vec();
+                if field.vector_quantization().is_some() {
+                    return self.top_by_quantized_distance(
+                        segment_ids,
+                        &field,
+                        query_vector,
+                        *metric,
+                        erased_features,
+                        n,
+                        offset,
+                        aux_collector,
+                    );
+                }
                 // Testing knob: push the GUC's work-model open cost into
                 // tantivy so this search's probe budget reflects it.
                 tantivy::vector::set_fixed_probe_cost_rows(
                     crate::gucs::vector_fixed_probe_cost_rows(),
                 );
-                let collector = TopDocs::with_limit(n)
-                    .and_offset(offset)
-                    .order_by_similarity(tantivy_field, query_vector)
-                    .with_adaptive_params(AdaptiveProbeParams {
-                        max_probe_fraction: crate::gucs::vector_cluster_max_probe(),
-                        ..Default::default()
-                    });
+                // no tantivy metric ranks by Jaccard distance, so a `bit_jaccard_ops` field is
+                // searched for a zero vector instead, which scores every bit string the same and
+                // leaves them ordered by the tie-breaks alone.  They're then stably sorted by the
+                // distance `jaccard_distances` computes, below
+                let jaccard = *metric == VectorMetric::Jaccard;
+                let ranked_vector = if jaccard {
+                    vec![0.0; query_vector.len()]
+                } else {
+                    query_vector.clone()
+                };
+                // exact search scores every vector of every segment: a top-K that can hold every
+                // document never fills, so no cluster is ever pruned.  `n` and `offset` are then
+                // applied to the sorted results below
+                let exact = jaccard || crate::gucs::vector_exact_search();
+                let collector = if exact {
+                    let max_doc = self
+                        .searcher
+                        .segment_readers()
+                        .iter()
+                        .map(|reader| reader.max_doc() as usize)
+                        .sum::<usize>();
+                    TopDocs::with_limit(max_doc.max(1))
+                } else {
+                    TopDocs::with_limit(n).and_offset(offset)
+                }
+                .order_by_similarity(tantivy_field, ranked_vector)
+                .with_adaptive_params(if exact {
+                    crate::vector::exact_probe_params()
+                } else {
+                    crate::vector::probe_params()
+                });
 
                 let mut erased_features = erased_features;
                 let score_index = erased_features.score_index();
@@ -1165,14 +1247,180 @@ impl SearchIndexReader {
                 let segment_ids = collected_ids.into_inner();
                 let mut segment_info = probe_stats_to_segment_info(&segment_ids, &fruit.stats);
                 io_stats::attach(&mut segment_info);
+                let results = if jaccard {
+                    let distances =
+                        self.jaccard_distances(&segment_ids, tantivy_field, &query_vector);
+                    let mut results = fruit
+                        .results
+                        .into_iter()
+                        .map(|(_, doc)| (distances.get(&doc).copied().unwrap_or(1.0), doc))
+                        .collect::<Vec<_>>();
+                    results.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+                    results.into_iter().skip(offset).take(n).collect()
+                } else if exact {
+                    fruit.results.into_iter().skip(offset).take(n).collect()
+                } else {
+                    fruit.results
+                };
                 TopKSearch::with_segment_info(
-                    TopKSearchResults::new_for_score(fruit.results, aggregation_results),
+                    TopKSearchResults::new_for_score(results, aggregation_results),
                     segment_info,
                 )
             }
         }
     }
 
+    /// The Jaccard distance between `query_vector` and the bit string of `field` of every
+    /// document of `segment_ids` that matches the query.
+    ///
+    /// Bits are indexed as `0.0`/`1.0` elements, so the inner product of two bit strings counts
+    /// the bits they share.  Two exhaustive searches under the field's inner product metric,
+    /// which scores with the negated inner product, find the bits each document shares with
+    /// the query and the bits it has set, from which `1 - |a ∧ b| / |a ∨ b|` follows.  Like
+    /// pgvector, two bit strings sharing no set bits are at distance `1`.
+    fn jaccard_distances(
+        &self,
+        segment_ids: &[SegmentId],
+        field: tantivy::schema::Field,
+        query_vector: &[f32],
+    ) -> HashMap<DocAddress, Score> {
+        let max_doc = self
+            .searcher
+            .segment_readers()
+            .iter()
+            .map(|reader| reader.max_doc() as usize)
+            .sum::<usize>();
+        let inner_products = |vector: Vec<f32>| {
+            let collector = TopDocs::with_limit(max_doc.max(1))
+                .order_by_similarity(field, vector)
+                .with_adaptive_params(crate::vector::exact_probe_params());
+            let (fruit, _) =
+                self.collect_maybe_auxiliary(segment_ids.iter().copied(), collector, None);
+            fruit.results.into_iter().map(|(score, doc)| (doc, -score))
+        };
+
+        let query_bits = query_vector.iter().sum::<f32>();
+        let doc_bits = inner_products(vec![1.0; query_vector.len()]).collect::<HashMap<_, _>>();
+        inner_products(query_vector.to_vec())
+            .map(|(doc, shared)| {
+                let union = query_bits + doc_bits.get(&doc).copied().unwrap_or(0.0) - shared;
+                let distance = if shared > 0.0 {
+                    1.0 - shared / union
+                } else {
+                    1.0
+                };
+                (doc, distance)
+            })
+            .collect()
+    }
+
+    /// A vector ORDER BY on a quantized field (see [`crate::vector::quantize`]).  The
+    /// `(n + offset) * paradedb.vector_rerank_oversample` documents whose codes are nearest
+    /// `query_vector`, or all of them under `paradedb.vector_exact_search`, are re-ranked by the
+    /// exact distance of their full-precision vectors, which are read from the table.
+    ///
+    /// Documents at the same exact distance keep the order of the ORDER BY's tie-breaks.  A
+    /// document whose row isn't visible can't be re-ranked, so it's put last, and left for the
+    /// caller's visibility check to drop.
+    #[allow(clippy::too_many_arguments)]
+    fn top_by_quantized_distance(
+        &self,
+        segment_ids: impl Iterator<Item = SegmentId>,
+        field: &SearchField,
+        query_vector: Vec<f32>,
+        metric: VectorMetric,
+        erased_features: ErasedFeatures,
+        n: usize,
+        offset: usize,
+        aux_collector: Option<TopKAuxiliaryCollector>,
+    ) -> TopKSearch {
+        let SearchFieldType::Vector(typoid, ..) = field.field_type() else {
+            unreachable!("a quantized field should be a vector field");
+        };
+        let limit = if crate::gucs::vector_exact_search() {
+            self.searcher
+                .segment_readers()
+                .iter()
+                .map(|reader| reader.max_doc() as usize)
+                .sum::<usize>()
+        } else {
+            (n + offset).saturating_mul(crate::gucs::vector_rerank_oversample())
+        };
+        let sort_by_codes =
+            SortByQuantizedDistance::new(field.field_name(), query_vector.clone(), metric);
+        // nearest first, and documents without a vector, which have no distance, last
+        let order: ComparatorEnum = SortDirection::AscNullsLast.into();
+        let (candidates, aggregation_results) = self.top_in_segments(
+            segment_ids,
+            (sort_by_codes, order),
+            erased_features,
+            limit.max(1),
+            0,
+            aux_collector,
+        );
+
+        let attributes = unsafe { extract_field_attributes(self.index_rel.as_ptr()) };
+        let Some(FieldSource::Heap { attno }) = attributes
+            .get(field.field_name())
+            .map(|attribute| attribute.source)
+        else {
+            panic!(
+                "quantized field `{}` should be a column",
+                field.field_name()
+            );
+        };
+        let heaprel = self
+            .index_rel
+            .heap_relation()
+            .expect("index should have a heap relation");
+        let heap_fetch_state = HeapFetchState::new(&heaprel);
+        let mut ctid_columns = HashMap::default();
+        let mut results = Vec::with_capacity(candidates.len());
+        for ((distance, _), doc) in candidates {
+            // a document without a vector has no code, and no distance
+            if distance.is_none() {
+                continue;
+            }
+            let ctids = ctid_columns.entry(doc.segment_ord).or_insert_with(|| {
+                self.searcher
+                    .segment_reader(doc.segment_ord)
+                    .fast_fields()
+                    .u64("ctid")
+                    .expect("ctid should be a u64 fast field")
+            });
+            let ctid = ctids.first(doc.doc_id).expect("ctid should be set");
+            let mut item_pointer = pg_sys::ItemPointerData::default();
+            crate::postgres::utils::u64_to_item_pointer(ctid, &mut item_pointer);
+
+            let vector = unsafe {
+                heap_fetch_state
+                    .fetch_eval_slot(&mut item_pointer, pg_sys::GetActiveSnapshot())
+                    .map(|slot| {
+                        PgVector::from_polymorphic_datum(
+                            *(*slot).tts_values.add(attno),
+                            *(*slot).tts_isnull.add(attno),
+                            typoid,
+                        )
+                    })
+            };
+            match vector {
+                Some(Some(PgVector(vector))) => {
+                    results.push((metric.score(&query_vector, &vector), doc))
+                }
+                // a NULL vector has no distance
+                Some(None) => {}
+                None => results.push((f32::INFINITY, doc)),
+            }
+        }
+        results.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        TopKSearchResults::new_for_score(
+            results.into_iter().skip(offset).take(n),
+            aggregation_results,
+        )
+        .into()
+    }
+
     /// Called by `search_top_k_in_segments`.
     ///
     /// `search_top_k_in_segments` is specialized for all combinations of:
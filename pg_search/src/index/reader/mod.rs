@@ -19,4 +19,5 @@ pub mod index;
 pub mod io_stats;
 pub mod scorer;
 pub mod segment_component;
+pub mod sort_by_quantized_distance;
 pub mod sort_by_range;
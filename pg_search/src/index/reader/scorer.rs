@@ -15,11 +15,67 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use crate::api::HashMap;
 use crate::index::reader::index::enable_scoring;
+use crate::query::proximity::query::ProximityQuery;
+use crate::query::similarity::{Similarity, SimilarityQuery};
+use crate::schema::SearchIndexSchema;
 use std::sync::{Arc, OnceLock};
-use tantivy::query::{PruningScorer, Query, Scorer, Weight};
+use tantivy::query::{BooleanQuery, PhraseQuery, PruningScorer, Query, Scorer, TermQuery, Weight};
+use tantivy::schema::Field;
 use tantivy::{DocAddress, DocId, DocSet, Score, Searcher, SegmentOrdinal, SegmentReader};
 
+/// Scores the terms and phrases that `query` matches in a field with a similarity other than
+/// BM25 by that similarity, wherever they are in `query`.  That way a query string, which
+/// parses to terms of whichever fields it names, scores each field the way it's configured.
+///
+/// Queries built for a field are rescored as they're built, see `pdb::Query::query`, so that
+/// score adjustments, whose inner query can't be reached from here, apply to the rescored one.
+pub fn with_field_similarities(
+    schema: &SearchIndexSchema,
+    query: Box<dyn Query>,
+) -> Box<dyn Query> {
+    let similarities = schema
+        .fields()
+        .filter_map(|(_, field_entry)| {
+            let search_field = schema.search_field(field_entry.name())?;
+            let similarity = search_field.field_config().similarity();
+            (!similarity.is_bm25()).then(|| (search_field.field(), similarity))
+        })
+        .collect::<HashMap<_, _>>();
+    if similarities.is_empty() {
+        return query;
+    }
+    rescore(query, &similarities)
+}
+
+fn rescore(query: Box<dyn Query>, similarities: &HashMap<Field, Similarity>) -> Box<dyn Query> {
+    let field = if let Some(boolean) = query.downcast_ref::<BooleanQuery>() {
+        let clauses = boolean
+            .clauses()
+            .iter()
+            .map(|(occur, clause)| (*occur, rescore(clause.box_clone(), similarities)))
+            .collect();
+        return Box::new(BooleanQuery::with_minimum_required_clauses(
+            clauses,
+            boolean.get_minimum_number_should_match(),
+        ));
+    } else if let Some(term) = query.downcast_ref::<TermQuery>() {
+        term.term().field()
+    } else if let Some(phrase) = query.downcast_ref::<PhraseQuery>() {
+        phrase.field()
+    } else if let Some(proximity) = query.downcast_ref::<ProximityQuery>() {
+        proximity.field()
+    } else {
+        return query;
+    };
+
+    match similarities.get(&field) {
+        Some(similarity) => Box::new(SimilarityQuery::new(field, *similarity, query)),
+        None => query,
+    }
+}
+
 /// Lazily builds one [`Weight`] and shares it across a search's segments.
 ///
 /// A scored weight aggregates corpus-level term statistics: `doc_freq` walks every
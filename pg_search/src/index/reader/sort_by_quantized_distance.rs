@@ -0,0 +1,121 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Top-N sorting by the approximate distance of a quantized vector field, the first pass of a
+//! vector ORDER BY on an index built with `vector_quantization` (see
+//! [`crate::vector::quantize`]).
+//!
+//! The field's bytes fast column keeps each distinct code once, in its dictionary, so each
+//! segment's dictionary is streamed and every code's distance computed once, when the segment's
+//! computer is created.  A document's sort key is then the distance of its code, and a document
+//! without a vector has none.
+
+use tantivy::collector::sort_key::ComparatorEnum;
+use tantivy::collector::sort_key::shared_threshold::SharedThresholdArcOpt;
+use tantivy::collector::{SegmentSortKeyComputer, SortKeyComputer};
+use tantivy::columnar::BytesColumn;
+use tantivy::{DocId, Score, SegmentReader};
+
+use crate::vector::metric::VectorMetric;
+use crate::vector::quantize::decode;
+
+/// Sorts by the distance, under `metric`, between `query_vector` and the vector the code of the
+/// quantized field `field_name` approximates.
+#[derive(Clone, Debug)]
+pub struct SortByQuantizedDistance {
+    field_name: String,
+    query_vector: Vec<f32>,
+    metric: VectorMetric,
+}
+
+impl SortByQuantizedDistance {
+    pub fn new(field_name: impl ToString, query_vector: Vec<f32>, metric: VectorMetric) -> Self {
+        Self {
+            field_name: field_name.to_string(),
+            query_vector,
+            metric,
+        }
+    }
+}
+
+impl SortKeyComputer for SortByQuantizedDistance {
+    type SortKey = Option<f64>;
+    type Child = SegmentSortByQuantizedDistance;
+    type Comparator = ComparatorEnum;
+
+    fn shared_threshold(
+        &self,
+    ) -> SharedThresholdArcOpt<
+        <<Self as SortKeyComputer>::Child as SegmentSortKeyComputer>::SegmentSortKey,
+    > {
+        // the candidates are re-ranked after they're collected, so the approximate distances
+        // aren't worth sharing between workers
+        None
+    }
+
+    fn segment_sort_key_computer(
+        &self,
+        segment_reader: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        let column = segment_reader.fast_fields().bytes(&self.field_name)?;
+
+        // the dictionary streams in term ordinal order, so a code's distance is at its ordinal
+        let mut distances = Vec::new();
+        if let Some(column) = &column {
+            let mut codes = column.dictionary().stream()?;
+            while codes.advance() {
+                let vector = decode(codes.key());
+                distances.push(self.metric.score(&self.query_vector, &vector) as f64);
+            }
+        }
+
+        Ok(SegmentSortByQuantizedDistance { column, distances })
+    }
+}
+
+pub struct SegmentSortByQuantizedDistance {
+    column: Option<BytesColumn>,
+    distances: Vec<f64>,
+}
+
+impl SegmentSortKeyComputer for SegmentSortByQuantizedDistance {
+    type SortKey = Option<f64>;
+    type SegmentSortKey = Option<f64>;
+    type SegmentComparator = ComparatorEnum;
+
+    fn segment_sort_key(&mut self, doc: DocId, _score: Score) -> Self::SegmentSortKey {
+        let ord = self.column.as_ref()?.term_ords(doc).next()?;
+        Some(self.distances[ord as usize])
+    }
+
+    fn convert_segment_sort_key(&self, sort_key: Self::SegmentSortKey) -> Self::SortKey {
+        sort_key
+    }
+
+    fn supports_bm25_pruning(&self) -> bool {
+        false
+    }
+
+    fn bm25_pruning_threshold(
+        &self,
+        _threshold: &Self::SegmentSortKey,
+        _segment_ord: tantivy::SegmentOrdinal,
+        _threshold_ord: tantivy::SegmentOrdinal,
+    ) -> Option<Score> {
+        None
+    }
+}
@@ -15,6 +15,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use crate::index::utils::load_tokenizer_tables;
 use crate::postgres::rel::PgSearchRelation;
 use anyhow::Result;
 use tantivy::Index;
@@ -101,7 +102,13 @@ pub fn setup_tokenizers(index_relation: &PgSearchRelation, index: &mut Index) ->
     // In 0.20.0 we changed the default tokenizer from `simple` to `unicode_words`
     tokenizers.push(SearchTokenizer::Simple(SearchTokenizerFilters::default()));
 
-    index.set_tokenizers(create_tokenizer_manager(tokenizers));
+    // the tables the tokenizers read are the ones the index was built with, not what they hold now
+    let tables = load_tokenizer_tables(index_relation)?;
+    for tokenizer in &mut tokenizers {
+        tables.apply(tokenizer)?;
+    }
+
+    index.set_tokenizers(create_tokenizer_manager(tokenizers)?);
     index.set_fast_field_tokenizers(create_normalizer_manager());
     Ok(())
 }
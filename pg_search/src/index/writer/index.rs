@@ -23,16 +23,19 @@ use tantivy::index::SegmentId;
 use tantivy::indexer::{AddOperation, IndexWriterOptions, SegmentWriter};
 use tantivy::schema::Field;
 use tantivy::{
-    Directory, Index, IndexMeta, IndexWriter, Opstamp, Segment, SegmentMeta, TantivyDocument,
-    directory::RamDirectory,
+    Directory, Index, IndexMeta, IndexWriter, Opstamp, Segment, SegmentMeta, SegmentReader,
+    TantivyDocument, directory::RamDirectory,
 };
 use thiserror::Error;
 
-use crate::index::mvcc::{MVCCDirectory, MvccSatisfies};
+use crate::gucs;
+use crate::index::mvcc::{MVCCDirectory, MvccSatisfies, index_heap_rows};
 use crate::index::{index_settings, setup_tokenizers};
+use crate::postgres::heap::{ExpressionState, HeapFetchState};
 use crate::postgres::rel::PgSearchRelation;
 use crate::postgres::storage::block::SegmentMetaEntry;
 use crate::vector::clusterer::set_ivf_clusterer;
+use crate::vector::rebalance::{ClusterSkew, SkewThresholds};
 use crate::{postgres::types::TantivyValueError, schema::SearchIndexSchema};
 use pgrx::pg_sys::panic::ErrorReport;
 use pgrx::{IntoDatum, PgLogLevel, PgSqlErrorCode, direct_function_call, function_name};
@@ -490,10 +493,46 @@ impl SerialIndexWriter {
     }
 }
 
+/// Indexes `document` on its own into a new in-memory index that has `index_relation`'s schema,
+/// settings and tokenizers, so that queries against the relation can be run against it.
+pub fn single_document_index(
+    index_relation: &PgSearchRelation,
+    mut document: TantivyDocument,
+) -> Result<Index> {
+    let schema = index_relation.schema()?;
+    let tantivy_schema: tantivy::schema::Schema = schema.clone().into();
+
+    let settings = index_settings(index_relation.options(), &tantivy_schema);
+    let mut index = Index::create(RamDirectory::create(), tantivy_schema, settings)?;
+    if schema.has_vector_field() {
+        set_ivf_clusterer(&mut index, index_relation.options());
+    }
+    setup_tokenizers(index_relation, &mut index)?;
+
+    // The document doesn't come from the heap, so it has no ctid of its own.
+    document.add_u64(schema.ctid_field(), 0);
+    let mut pending_segment = PendingSegment::new(&index, NonZeroUsize::new(usize::MAX).unwrap())?;
+    pending_segment.add_document(document)?;
+    let segment = pending_segment.finalize()?;
+
+    let previous_index_meta = index.load_metas()?;
+    let new_index_meta = IndexMeta {
+        segments: vec![segment.meta().clone()],
+        ..previous_index_meta.clone()
+    };
+    index
+        .directory()
+        .save_metas(&new_index_meta, &previous_index_meta, &mut ())?;
+    Ok(index)
+}
+
 pub struct SearchIndexMerger {
+    indexrel: PgSearchRelation,
     merged_segment_ids: HashSet<SegmentId>,
     index: Index,
     directory: MVCCDirectory,
+    vector_fields: Vec<Field>,
+    unfilled_segment_ids: HashSet<SegmentId>,
 }
 
 impl SearchIndexMerger {
@@ -504,13 +543,22 @@ impl SearchIndexMerger {
         let directory = mvcc_satisfies.directory(indexrel);
         let schema = indexrel.schema()?;
         let mut index = Index::open(directory.clone())?;
-        if schema.has_vector_field() {
+        let vector_fields = schema.vector_fields().collect::<Vec<_>>();
+        if !vector_fields.is_empty() {
             set_ivf_clusterer(&mut index, indexrel.options());
         }
+        let mut unfilled_segment_ids = schema.changes().all_unfilled_segments();
+        if !unfilled_segment_ids.is_empty() {
+            let all_entries = directory.all_entries();
+            unfilled_segment_ids.retain(|segment_id| all_entries.contains_key(segment_id));
+        }
         Ok(Self {
+            indexrel: Clone::clone(indexrel),
             index,
             merged_segment_ids: Default::default(),
             directory,
+            vector_fields,
+            unfilled_segment_ids,
         })
     }
 
@@ -518,6 +566,45 @@ impl SearchIndexMerger {
         self.directory.all_entries()
     }
 
+    /// The segments that are missing a field added by `pdb.add_field()`.  They can't be merged
+    /// with other segments, only backfilled on their own.
+    pub fn unfilled_segment_ids(&self) -> &HashSet<SegmentId> {
+        &self.unfilled_segment_ids
+    }
+
+    /// The segments among `entries` with a vector field whose clusters are skewed past
+    /// `thresholds`. Only segments that store their vectors clustered are opened.
+    pub fn skewed_vector_segments<'a>(
+        &self,
+        entries: impl Iterator<Item = &'a SegmentMetaEntry>,
+        thresholds: SkewThresholds,
+    ) -> Result<HashSet<SegmentId>> {
+        let clustered = entries
+            .filter(|entry| entry.has_vector_centroids())
+            .map(|entry| entry.segment_id())
+            .collect::<HashSet<_>>();
+        let mut skewed = HashSet::default();
+        if clustered.is_empty() {
+            return Ok(skewed);
+        }
+
+        for segment in self.index.searchable_segments()? {
+            if !clustered.contains(&segment.id()) {
+                continue;
+            }
+            let reader = SegmentReader::open(&segment)?;
+            for field in &self.vector_fields {
+                if ClusterSkew::of(&reader, *field)?
+                    .is_some_and(|skew| thresholds.is_exceeded_by(&skew))
+                {
+                    skewed.insert(segment.id());
+                    break;
+                }
+            }
+        }
+        Ok(skewed)
+    }
+
     pub fn searchable_segment_ids(&self) -> tantivy::Result<HashSet<SegmentId>> {
         Ok(self.index.searchable_segment_ids()?.into_iter().collect())
     }
@@ -540,6 +627,129 @@ impl SearchIndexMerger {
         }
         Ok(self)
     }
+
+    /// Rebuilds the segment from the heap with the index's current schema, so that it indexes the
+    /// fields added since it was written.
+    ///
+    /// The rebuilt segment gets a new [`SegmentId`], which is what marks it as backfilled, see
+    /// [`SchemaChanges`](crate::schema::SchemaChanges).  It's split into more than one segment if
+    /// it outgrows `maintenance_work_mem`, or the document limit of an index with vector fields.
+    fn backfill_segment(&mut self, segment_id: SegmentId) -> Result<Option<SegmentMeta>> {
+        let segment = self
+            .index
+            .searchable_segments()?
+            .into_iter()
+            .find(|segment| segment.id() == segment_id)
+            .ok_or_else(|| anyhow::anyhow!("segment {segment_id} is not in the index"))?;
+        let ctids = {
+            let segment_reader = SegmentReader::open(&segment)?;
+            let ctid_column = segment_reader.fast_fields().u64("ctid")?;
+            segment_reader
+                .doc_ids_alive()
+                .filter_map(|doc| ctid_column.first(doc))
+                .collect::<Vec<_>>()
+        };
+        let num_input_live = ctids.len() as u64;
+        if ctids.is_empty() {
+            // there's nothing to rebuild, so merging it away on its own leaves nothing behind
+            return self.merge_foreground(&[segment_id]);
+        }
+
+        setup_tokenizers(&self.indexrel, &mut self.index)?;
+        let schema = self.indexrel.schema()?;
+        let ctid_field = schema.ctid_field();
+        let memory_budget = gucs::adjust_maintenance_work_mem(1);
+        let max_docs_per_segment =
+            (!self.vector_fields.is_empty()).then_some(DEFAULT_MAX_DOCS_PER_SEGMENT as usize);
+        let heaprel = self
+            .indexrel
+            .heap_relation()
+            .expect("index should have a heap relation");
+        let heap_fetch_state = HeapFetchState::new(&heaprel);
+        let expression_state = ExpressionState::new(&self.indexrel);
+
+        let mut backfilled = Vec::new();
+        let mut pending_segment: Option<PendingSegment> = None;
+        index_heap_rows(
+            &self.indexrel,
+            ctids,
+            &heap_fetch_state,
+            &expression_state,
+            &MvccSatisfies::Mergeable,
+            |mut document, ctid| {
+                document.add_u64(ctid_field, ctid);
+                if pending_segment.is_none() {
+                    pending_segment = Some(PendingSegment::new(&self.index, memory_budget)?);
+                }
+                let pending = pending_segment.as_mut().unwrap();
+                pending.add_document(document)?;
+
+                if pending.mem_usage() >= memory_budget.get()
+                    || max_docs_per_segment.is_some_and(|max_docs| pending.max_doc() >= max_docs)
+                {
+                    let finalized = pending_segment.take().unwrap().finalize()?;
+                    backfilled.push(finalized.meta().clone());
+                }
+                Ok(())
+            },
+        )?;
+        if let Some(pending) = pending_segment {
+            backfilled.push(pending.finalize()?.meta().clone());
+        }
+
+        // Backfill doc-count conservation: `index_heap_rows` adds exactly one document for every
+        // ctid it's given, an empty one for a row that's gone from the heap, so the rebuilt
+        // segments hold exactly the input segment's live docs, and none of them deleted
+        dst::observe!(|| {
+            let output_live = backfilled
+                .iter()
+                .map(|meta| meta.num_docs() as u64)
+                .sum::<u64>();
+            // [dst correctness] backfill conserves live docs: output live == input live docs
+            dst::assert_always!(
+                output_live == num_input_live,
+                "pg_search: backfill live-doc conservation (output == input)",
+                &::serde_json::json!({
+                    "input_live_docs": num_input_live,
+                    "output_live_docs": output_live,
+                    "output_segment_count": backfilled.len(),
+                })
+            );
+        });
+
+        // replace the segment with its rebuilt self in one go
+        let current_metas = self.index.load_metas()?;
+        let previous_index_meta = IndexMeta {
+            segments: vec![segment.meta().clone()],
+            ..current_metas.clone()
+        };
+        let new_index_meta = IndexMeta {
+            segments: backfilled.clone(),
+            ..current_metas
+        };
+        self.index
+            .directory()
+            .save_metas(&new_index_meta, &previous_index_meta, &mut ())?;
+
+        unsafe {
+            // SAFETY:  the segment is not used after its pin is dropped
+            self.directory.drop_pins(&[segment_id])?;
+            self.merged_segment_ids.insert(segment_id);
+        }
+
+        Ok(backfilled.pop())
+    }
+
+    fn merge_foreground(&mut self, segment_ids: &[SegmentId]) -> Result<Option<SegmentMeta>> {
+        let mut writer: IndexWriter = self.index.writer_with_options(
+            IndexWriterOptions::builder()
+                .memory_budget_per_thread(15 * 1024 * 1024)
+                .num_merge_threads(0)
+                .num_worker_threads(0)
+                .build(),
+        )?;
+        Ok(writer.merge_foreground(segment_ids, true)?)
+    }
 }
 
 pub trait Mergeable {
@@ -567,14 +777,19 @@ impl Mergeable for SearchIndexMerger {
             "segment was already merged by this merger instance"
         );
 
-        let mut writer: IndexWriter = self.index.writer_with_options(
-            IndexWriterOptions::builder()
-                .memory_budget_per_thread(15 * 1024 * 1024)
-                .num_merge_threads(0)
-                .num_worker_threads(0)
-                .build(),
-        )?;
-        let new_segment = writer.merge_foreground(segment_ids, true)?;
+        if let [segment_id] = segment_ids
+            && self.unfilled_segment_ids.contains(segment_id)
+        {
+            return self.backfill_segment(*segment_id);
+        }
+        assert!(
+            segment_ids
+                .iter()
+                .all(|segment_id| !self.unfilled_segment_ids.contains(segment_id)),
+            "a segment missing an added field can only be backfilled, not merged with others"
+        );
+
+        let new_segment = self.merge_foreground(segment_ids)?;
 
         if let Some(new_segment) = new_segment.as_ref() {
             // Merge doc-count conservation: the merged output segment's live doc count must equal
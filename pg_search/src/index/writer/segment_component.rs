@@ -15,6 +15,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use crate::postgres::merge_scheduler;
 use crate::postgres::rel::PgSearchRelation;
 use crate::postgres::storage::block::{FileEntry, SegmentFileDetails};
 use crate::postgres::storage::{LinkedBytesList, LinkedBytesListWriter};
@@ -189,6 +190,7 @@ impl Write for InnerSegmentComponentWriter {
     fn write(&mut self, data: &[u8]) -> Result<usize> {
         let many = self.buffer.as_mut().unwrap().write(data)?;
         self.total_bytes.fetch_add(data.len(), Ordering::Relaxed);
+        merge_scheduler::throttle_merge_write(many);
         Ok(many)
     }
 
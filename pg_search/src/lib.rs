@@ -146,11 +146,14 @@ pub unsafe extern "C-unwind" fn _PG_init() {
     postgres::options::init();
     postgres::build_logging::init();
     gucs::init();
+    api::tokenizers::hunspell::init();
 
-    // RegisterCustomRmgr can only be called during shared_preload_libraries init. If pg_search
-    // was loaded via plain CREATE EXTENSION (no preload), skip
+    // RegisterCustomRmgr, shared memory requests and static background workers can only be set up
+    // during shared_preload_libraries init. If pg_search was loaded via plain CREATE EXTENSION
+    // (no preload), skip
     if pg_sys::process_shared_preload_libraries_in_progress {
         postgres::storage::custom_rmgr::register();
+        postgres::merge_scheduler::register();
     }
 
     #[cfg(not(any(feature = "pg17", feature = "pg18")))]
@@ -16,21 +16,23 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use crate::api::FieldName;
+use crate::api::tokenizers::TokenizerTables;
 use crate::api::version::VersionInfo;
 use crate::index::index_settings;
 use crate::index::mvcc::MvccSatisfies;
+use crate::index::utils::save_tokenizer_tables;
 use crate::postgres::build_parallel::build_index;
 use crate::postgres::options::BM25IndexOptions;
 use crate::postgres::rel::PgSearchRelation;
 use crate::postgres::storage::custom_rmgr;
 use crate::postgres::storage::metadata::MetaPage;
-use crate::postgres::utils::{ExtractedFieldAttribute, extract_field_attributes};
+use crate::postgres::utils::{ExtractedFieldAttribute, FieldSource, extract_field_attributes};
 use crate::schema::{SearchFieldConfig, SearchFieldType};
 use anyhow::Result;
 use pgrx::*;
 use std::ffi::CStr;
 use tantivy::Index;
-use tantivy::schema::Schema;
+use tantivy::schema::{BytesOptions, Schema};
 use tantivy::vector::VectorOptions;
 use tokenizers::SearchTokenizer;
 
@@ -58,27 +60,6 @@ pub extern "C-unwind" fn ambuild(
         build_empty(&index_relation);
     }
 
-    // ensure we only allow one ParadeDB index on this relation, accounting for a REINDEX
-    // and accounting for CONCURRENTLY.
-    unsafe {
-        let index_tuple = &(*index_relation.rd_index);
-        let is_reindex = !index_tuple.indisvalid;
-        let is_concurrent = (*index_info).ii_Concurrent;
-
-        if !is_reindex {
-            for existing_index in heap_relation.indices(pg_sys::AccessShareLock as _) {
-                if existing_index.oid() == index_relation.oid() {
-                    // the index we're about to build already exists on the table.
-                    continue;
-                }
-
-                if is_bm25_index(&existing_index) && !is_concurrent {
-                    panic!("a relation may only have one ParadeDB index");
-                }
-            }
-        }
-    }
-
     unsafe {
         let heap_tuples = build_index(
             heap_relation,
@@ -103,7 +84,7 @@ pub extern "C-unwind" fn ambuild(
         }
 
         if needs_wal {
-            custom_rmgr::emit_init_record();
+            custom_rmgr::emit_init_record(&index_relation);
         }
 
         let mut result = PgBox::<pg_sys::IndexBuildResult>::alloc0();
@@ -128,10 +109,25 @@ unsafe fn build_empty(index_relation: &PgSearchRelation) {
     }
 
     validate_index_config(index_relation);
+    save_tokenizer_tables(index_relation, &read_tokenizer_tables(index_relation))
+        .unwrap_or_else(|e| panic!("{e}"));
 
     create_index(index_relation).unwrap_or_else(|e| panic!("{e}"));
 }
 
+/// Reads the tables that the tokenizers of the index's fields, and its search tokenizers, refer to.
+fn read_tokenizer_tables(index_relation: &PgSearchRelation) -> TokenizerTables {
+    let options = index_relation.options();
+    let mut tokenizers = Vec::new();
+    for field_name in options.attributes().keys() {
+        let config = options.field_config_or_default(field_name);
+        tokenizers.extend(config.tokenizer().cloned());
+        tokenizers.extend(config.search_tokenizer().cloned());
+    }
+    tokenizers.extend(options.search_tokenizer());
+    TokenizerTables::load(&tokenizers)
+}
+
 unsafe fn validate_index_config(index_relation: &PgSearchRelation) {
     // quick check to make sure we have "WITH" options
     if index_relation.rd_options.is_null() {
@@ -315,6 +311,7 @@ fn am_handler_oid(amname: &CStr) -> Option<pg_sys::Oid> {
 
 fn create_index(index_relation: &PgSearchRelation) -> Result<()> {
     let options = index_relation.options();
+    let quantization = options.vector_quantization();
     let mut builder = Schema::builder();
 
     for (
@@ -322,6 +319,7 @@ fn create_index(index_relation: &PgSearchRelation) -> Result<()> {
         ExtractedFieldAttribute {
             tantivy_type,
             normalizer,
+            source,
             ..
         },
     ) in unsafe { extract_field_attributes(index_relation.as_ptr()) }
@@ -356,9 +354,24 @@ fn create_index(index_relation: &PgSearchRelation) -> Result<()> {
             SearchFieldType::NumericBytes(..) => {
                 builder.add_bytes_field(name.as_ref(), config.clone())
             }
+            // quantized vectors are stored as codes in a bytes fast field, and re-ranked by the
+            // full-precision vectors of their column, see `crate::vector::quantize`
+            SearchFieldType::Vector(..) if quantization.is_some() => {
+                if !matches!(source, FieldSource::Heap { .. }) {
+                    panic!(
+                        "`{name}` can't be quantized: vector_quantization needs its vectors to come from a column, not an expression"
+                    );
+                }
+                builder.add_bytes_field(name.as_ref(), BytesOptions::default().set_fast())
+            }
             SearchFieldType::Vector(_, dims, metric) => {
                 builder.add_vector_field(name.as_ref(), VectorOptions::new(dims, metric.into()))
             }
+            // the vectors of a multi-vector field aren't clustered, they're packed into a bytes
+            // fast field, see `crate::vector::multi`
+            SearchFieldType::MultiVector(..) => {
+                builder.add_bytes_field(name.as_ref(), BytesOptions::default().set_fast())
+            }
         };
     }
 
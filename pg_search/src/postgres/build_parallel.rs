@@ -32,6 +32,7 @@ use crate::postgres::build_partitioning::plan_partition_boundaries;
 use crate::postgres::composite::CompositeSlotValues;
 use crate::postgres::locks::Spinlock;
 use crate::postgres::merge::garbage_collect_index;
+use crate::postgres::progress::{self, BuildPhase, PROGRESS_PDB_SEGMENTS_DONE};
 use crate::postgres::ps_status::{
     COMMITTING, FINALIZING, GARBAGE_COLLECTING, INDEXING, MERGING, set_ps_display_remove_suffix,
     set_ps_display_suffix,
@@ -114,6 +115,10 @@ impl WorkerCoordination {
         self.nsegments_written += 1;
         self.nsegments_written
     }
+    fn segments_written(&mut self) -> usize {
+        let _lock = self.mutex.acquire();
+        self.nsegments_written
+    }
 }
 
 /// The parallel process for setting up a parallel index build
@@ -317,6 +322,7 @@ impl<'a> BuildWorker<'a> {
             )?;
 
             set_ps_display_suffix(INDEXING.as_ptr());
+            progress::set_phase(BuildPhase::ScanningTable);
             let reltuples = pg_sys::table_index_build_scan(
                 self.heaprel.as_ptr(),
                 self.indexrel.as_ptr(),
@@ -439,6 +445,7 @@ impl<'a> WorkerBuildState<'a> {
     /// segment's on-disk size (segments are memory-budget bound, so one sample is representative).
     fn on_segment_flushed(&mut self, segment_id: SegmentId) {
         let written = self.coordination.add_segments_written();
+        progress::update(PROGRESS_PDB_SEGMENTS_DONE, written as i64);
         let remaining = self.target_segment_count.saturating_sub(written);
 
         let mut segment_bytes = None;
@@ -470,6 +477,8 @@ impl<'a> WorkerBuildState<'a> {
         }
         let writer = self.writer.take().expect("writer should be set");
         if let Some((segment_meta, _)) = writer.commit()? {
+            let written = self.coordination.add_segments_written();
+            progress::update(PROGRESS_PDB_SEGMENTS_DONE, written as i64);
             self.unmerged_metas.push(segment_meta);
         }
         self.try_merge(true)?;
@@ -551,6 +560,11 @@ impl<'a> WorkerBuildState<'a> {
         );
         let mut merger = SearchIndexMerger::open(&self.indexrel, MvccSatisfies::Mergeable)?;
         unsafe { set_ps_display_suffix(MERGING.as_ptr()) };
+        progress::set_phase(if is_last_merge {
+            BuildPhase::FinalMerge
+        } else {
+            BuildPhase::MergingSegments
+        });
         merger.merge_segments(&segment_ids_to_merge)?;
 
         // garbage collect the index, returning to the fsm
@@ -561,6 +575,9 @@ impl<'a> WorkerBuildState<'a> {
         unsafe { garbage_collect_index(&self.indexrel, self.current_xid, self.next_xid) };
 
         self.nmerges += 1;
+        if !is_last_merge {
+            progress::set_phase(BuildPhase::ScanningTable);
+        }
 
         Ok(())
     }
@@ -644,6 +661,11 @@ unsafe extern "C-unwind" fn build_callback(
                 pg_sys::PROGRESS_CREATEIDX_TUPLES_DONE as i32,
                 build_state.coordination.tuples_done() as i64,
             );
+            // segments flushed by the other workers are only reported to the leader's view here
+            progress::update(
+                PROGRESS_PDB_SEGMENTS_DONE,
+                build_state.coordination.segments_written() as i64,
+            );
         }
     }
 
@@ -797,6 +819,12 @@ pub(super) fn build_index(
         total_tuples
     };
 
+    // `tuples_done` is only reported in batches while building, so report the final count
+    progress::update(
+        pg_sys::PROGRESS_CREATEIDX_TUPLES_DONE as i32,
+        total_tuples as i64,
+    );
+
     unsafe { set_ps_display_remove_suffix() };
     Ok(total_tuples)
 }
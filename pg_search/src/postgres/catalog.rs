@@ -156,6 +156,20 @@ pub fn is_pgvector_oid(oid: pg_sys::Oid) -> bool {
     vid != pg_sys::Oid::INVALID && oid == vid
 }
 
+pub fn is_pgvector_halfvec_oid(oid: pg_sys::Oid) -> bool {
+    static HALFVEC_OID: OnceLock<pg_sys::Oid> = OnceLock::new();
+    let hid = *HALFVEC_OID
+        .get_or_init(|| lookup_typoid(c"public", c"halfvec").unwrap_or(pg_sys::Oid::INVALID));
+    hid != pg_sys::Oid::INVALID && oid == hid
+}
+
+pub fn is_pgvector_sparsevec_oid(oid: pg_sys::Oid) -> bool {
+    static SPARSEVEC_OID: OnceLock<pg_sys::Oid> = OnceLock::new();
+    let sid = *SPARSEVEC_OID
+        .get_or_init(|| lookup_typoid(c"public", c"sparsevec").unwrap_or(pg_sys::Oid::INVALID));
+    sid != pg_sys::Oid::INVALID && oid == sid
+}
+
 /// Lookup a `pg_opfamily.opfname` by OID. Returns the opfamily name
 /// (e.g. `"vector_l2_ops"`) or `None` if the cache lookup fails.
 ///
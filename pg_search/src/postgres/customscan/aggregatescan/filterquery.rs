@@ -25,8 +25,9 @@
 //! inside a running `postgres` process, so dyld rejects the binary at startup.
 //! Tracked in paradedb/paradedb#3715 (ours) and pgcentralfoundation/pgrx#2281 (upstream).
 
+use crate::api::HashSet;
 use crate::index::mvcc::MvccSatisfies;
-use crate::index::reader::index::SearchIndexReader;
+use crate::index::reader::index::{SearchIndexReader, skip_unfilled_segments};
 use crate::postgres::PgSearchRelation;
 use crate::postgres::utils::ExprContextGuard;
 use crate::query::SearchQueryInput;
@@ -117,6 +118,8 @@ fn build_query(query_json: serde_json::Value, indexrelid: u32) -> anyhow::Result
         query.needs_tokenizer(),
     )?;
 
+    let mut field_names = HashSet::default();
+    query.extract_field_names(&mut field_names);
     let tantivy_query = query.into_tantivy_query(
         &schema,
         index_created_by_version,
@@ -132,6 +135,8 @@ fn build_query(query_json: serde_json::Value, indexrelid: u32) -> anyhow::Result
         NonNull::new(context.as_ptr()),
         None,
     )?;
+    let tantivy_query =
+        skip_unfilled_segments(&schema, reader.searcher(), &field_names, tantivy_query);
 
     Ok(Box::new(QueryWithContext {
         query: Box::new(tantivy_query),
@@ -101,11 +101,11 @@ use crate::postgres::customscan::joinscan::JoinScan;
 use crate::postgres::customscan::joinscan::planning::transparent_path_subpath;
 use crate::postgres::customscan::joinscan::scan_state::{build_physical_plan, build_task_context};
 use crate::postgres::customscan::projections::{create_placeholder_targetlist, placeholder_procid};
+use crate::postgres::customscan::qual_inspect::choose_rel_bm25_index;
 use crate::postgres::customscan::solve_expr::SolvePostgresExpressions;
 use crate::postgres::customscan::{CreateUpperPathsHookArgs, CustomScan, range_table};
 use crate::postgres::datetime::PostgresDateTime;
 use crate::postgres::pdb_owned_value::PdbOwnedValue;
-use crate::postgres::rel_get_bm25_index;
 use crate::postgres::types::{TantivyValue, is_datetime_type};
 use crate::postgres::utils::{
     ExprContextGuard, add_vars_to_tlist, is_unnest_func, make_text_const,
@@ -1296,7 +1296,9 @@ impl AggregateScan {
             return Vec::new();
         };
 
-        let Some((_table, index)) = rel_get_bm25_index(heap_relid) else {
+        let Some((_table, index)) =
+            (unsafe { choose_rel_bm25_index(builder.args().root, heap_rti, heap_relid) })
+        else {
             if has_paradedb_agg {
                 pgrx::error!("Cannot execute pdb.agg: table must have a BM25 index");
             }
@@ -37,7 +37,7 @@ use std::sync::atomic::Ordering;
 
 use crate::api::operator::{estimate_query_cost, estimate_selectivity_and_cost};
 use crate::api::window_aggregate::window_agg_oid;
-use crate::api::{HashMap, HashSet, Varno};
+use crate::api::{HashMap, HashSet, OrderByFeature, Varno};
 use crate::gucs;
 use crate::index::fast_fields_helper::WhichFastField;
 use crate::index::mvcc::MvccSatisfies;
@@ -50,6 +50,9 @@ use crate::postgres::customscan::basescan::projections::score::uses_scores;
 use crate::postgres::customscan::basescan::projections::snippet::{
     SnippetType, snippet_funcoids, snippet_positions_funcoids, snippets_funcoids, uses_snippets,
 };
+use crate::postgres::customscan::basescan::projections::vector_distance::{
+    uses_vector_distance, vector_distance_funcoids,
+};
 use crate::postgres::customscan::basescan::projections::window_agg::{
     WindowAggregateInfo, deserialize_window_agg_placeholders,
     resolve_window_aggregate_filters_at_plan_time,
@@ -73,8 +76,8 @@ use crate::postgres::customscan::projections::{
     inject_placeholders, maybe_needs_const_projections, pullout_funcexprs,
 };
 use crate::postgres::customscan::qual_inspect::{
-    PlannerContext, Qual, QualExtractState, extract_join_predicates, extract_quals, is_subplan,
-    optimize_quals_with_heap_expr,
+    PlannerContext, Qual, QualExtractState, choose_bm25_index_for_quals, extract_join_predicates,
+    extract_quals, is_subplan, optimize_quals_with_heap_expr, referenced_fields,
 };
 use crate::postgres::customscan::score_funcoids;
 use crate::postgres::customscan::solve_expr::SolvePostgresExpressions;
@@ -83,7 +86,7 @@ use crate::postgres::customscan::{
 };
 use crate::postgres::heap::{HeapFetchState, VisibilityChecker};
 use crate::postgres::rel::PgSearchRelation;
-use crate::postgres::rel_get_bm25_index;
+use crate::postgres::rel_get_bm25_indexes;
 use crate::postgres::storage::metadata::MetaPage;
 use crate::postgres::utils::{
     filter_implied_predicates, is_unnest_func, missing_partial_index_predicate,
@@ -682,7 +685,7 @@ impl CustomScan for BaseScan {
             }
 
             let rti = builder.args().rti;
-            let (table, bm25_index) = {
+            let (table, bm25_indexes) = {
                 let rte = builder.args().rte();
 
                 // we only support plain relation and join rte's
@@ -699,9 +702,7 @@ impl CustomScan for BaseScan {
                 }
 
                 // and that relation must have a `USING paradedb` index
-                let (table, bm25_index) = rel_get_bm25_index(rte.relid)?;
-
-                (table, bm25_index)
+                rel_get_bm25_indexes(rte.relid)?
             };
 
             let root = builder.args().root;
@@ -719,6 +720,28 @@ impl CustomScan for BaseScan {
             //
             let is_select =
                 (*(*builder.args().root).parse).commandType == pg_sys::CmdType::CMD_SELECT;
+            // when the relation has more than one `USING paradedb` index, use the one that
+            // has the most of the fields the query refers to
+            let mut field_names = HashSet::default();
+            referenced_fields(
+                restrict_info.as_ptr().cast(),
+                rti,
+                table.oid(),
+                &mut field_names,
+            );
+            if !(*rel).reltarget.is_null() {
+                referenced_fields(
+                    (*(*rel).reltarget).exprs.cast(),
+                    rti,
+                    table.oid(),
+                    &mut field_names,
+                );
+            }
+            let bm25_index = choose_bm25_index_for_quals(
+                bm25_indexes,
+                &field_names,
+                restrict_info.as_ptr().cast(),
+            );
             let quals = Self::extract_all_possible_quals(
                 &mut builder,
                 root,
@@ -1128,9 +1151,9 @@ impl CustomScan for BaseScan {
                 // Convert PostgresExpression filters to SearchQueryInput now that we have root
                 // Note: root was not available in the planner hook, so we needed to delay this until now.
                 let private_data = builder.custom_private();
-                if let Some(heaprelid) = private_data.heaprelid()
-                    && let Some((_, bm25_index)) = rel_get_bm25_index(heaprelid)
-                {
+                if let Some(indexrelid) = private_data.indexrelid() {
+                    let bm25_index =
+                        PgSearchRelation::with_lock(indexrelid, pg_sys::AccessShareLock as _);
                     let root = builder.args().root;
                     let rti = private_data
                         .range_table_index()
@@ -1335,6 +1358,11 @@ impl CustomScan for BaseScan {
                 score_funcoids,
                 builder.custom_state().execution_rti,
             );
+            builder.custom_state().need_vector_distances = uses_vector_distance(
+                builder.target_list().as_ptr().cast(),
+                vector_distance_funcoids(),
+                builder.custom_state().execution_rti,
+            );
 
             // Store join snippet predicates in the scan state
             builder.custom_state().join_predicates =
@@ -1689,7 +1717,8 @@ impl CustomScan for BaseScan {
                         let needs_special_projection = state.custom_state().need_scores()
                             || state.custom_state().need_snippets()
                             || state.custom_state().window_aggregate_results.is_some()
-                            || state.custom_state().vector_distance_placeholder;
+                            || state.custom_state().vector_distance_placeholder
+                            || state.custom_state().const_vector_distance_node.is_some();
 
                         if !needs_special_projection {
                             //
@@ -1721,6 +1750,14 @@ impl CustomScan for BaseScan {
                                 (*const_score_node).constisnull = false;
                             }
 
+                            if let Some((const_distance_node, metric)) =
+                                state.custom_state().const_vector_distance_node
+                            {
+                                (*const_distance_node).constvalue =
+                                    metric.distance_from_score(score).into_datum().unwrap();
+                                (*const_distance_node).constisnull = false;
+                            }
+
                             // Update window aggregate values
                             if let Some(agg_results) =
                                 &state.custom_state().window_aggregate_results
@@ -2040,6 +2077,7 @@ fn validate_topk_expectation(
                 .to_string(),
         ),
         PathKeyInfo::Unusable(UnusableReason::VectorMetricMismatch {
+            vector_type,
             field_metric,
             op_metric,
         }) => (
@@ -2048,14 +2086,14 @@ fn validate_topk_expectation(
                  the {} opclass ({:?})",
                 op_metric.operator(),
                 op_metric,
-                field_metric.opclass_name(),
+                field_metric.opclass_name(*vector_type),
                 field_metric,
             ),
             format!(
                 "Either change the ORDER BY operator to {} (matching the index opclass), \
                  or rebuild the index with the {} opclass on the vector column.",
                 field_metric.operator(),
-                op_metric.opclass_name(),
+                op_metric.opclass_name(*vector_type),
             ),
         ),
         PathKeyInfo::Unusable(UnusableReason::UnsafeCollation) => (
@@ -2353,16 +2391,38 @@ unsafe fn inject_pdb_placeholders(state: &mut CustomScanStateWrapper<BaseScan>)
     // forced projection we must do later.
     let planstate = state.planstate();
 
-    let (targetlist, const_score_node, const_snippet_nodes) = inject_placeholders(
-        (*(*planstate).plan).targetlist,
-        state.custom_state().planning_rti,
-        state.custom_state().score_funcoids,
-        state.custom_state().snippet_funcoids,
-        state.custom_state().snippets_funcoids,
-        state.custom_state().snippet_positions_funcoids,
-        &state.custom_state().var_attname_lookup,
-        &state.custom_state().snippet_generators,
-    );
+    // `pdb.vector_distance()` is only replaced when the TopK scan orders by a vector distance,
+    // whose scores it converts. Anywhere else the function is left to compute the distance from
+    // the heap vector.
+    let vector_distance_metric = match &state.custom_state().exec_method_type {
+        ExecMethodType::TopK {
+            orderby_info: Some(orderby_info),
+            ..
+        } if state.custom_state().need_vector_distances => {
+            orderby_info.first().and_then(|info| match info.feature {
+                OrderByFeature::VectorDistance { metric, .. } => Some(metric),
+                _ => None,
+            })
+        }
+        _ => None,
+    };
+
+    let (targetlist, const_score_node, const_vector_distance_node, const_snippet_nodes) =
+        inject_placeholders(
+            (*(*planstate).plan).targetlist,
+            state.custom_state().planning_rti,
+            state.custom_state().score_funcoids,
+            if vector_distance_metric.is_some() {
+                vector_distance_funcoids()
+            } else {
+                [pg_sys::InvalidOid; 2]
+            },
+            state.custom_state().snippet_funcoids,
+            state.custom_state().snippets_funcoids,
+            state.custom_state().snippet_positions_funcoids,
+            &state.custom_state().var_attname_lookup,
+            &state.custom_state().snippet_generators,
+        );
 
     // Now inject window aggregate placeholders
     let (targetlist, const_window_agg_nodes) = if !state.custom_state().window_aggregates.is_empty()
@@ -2383,6 +2443,8 @@ unsafe fn inject_pdb_placeholders(state: &mut CustomScanStateWrapper<BaseScan>)
 
     state.custom_state_mut().placeholder_targetlist = Some(targetlist);
     state.custom_state_mut().const_score_node = Some(const_score_node);
+    state.custom_state_mut().const_vector_distance_node =
+        vector_distance_metric.map(|metric| (const_vector_distance_node, metric));
     state.custom_state_mut().const_snippet_nodes = const_snippet_nodes;
     state.custom_state_mut().const_window_agg_nodes = const_window_agg_nodes;
     state.custom_state_mut().vector_distance_placeholder = vector_distance_placeholder;
@@ -2400,8 +2462,9 @@ unsafe fn inject_pdb_placeholders(state: &mut CustomScanStateWrapper<BaseScan>)
 ///
 /// Two deliberate restrictions:
 /// * `resjunk` only — a SELECT-ed `vec <-> q` must be computed exactly; the
-///   TopK score is an approximate, squared/normalized ordering key, not the
-///   pgvector distance.
+///   TopK score is a single-precision, squared/normalized ordering key, not the
+///   pgvector distance. `pdb.vector_distance()` is how a query selects the
+///   distance the scan already computed.
 /// * the caller invokes this only for TopK scans — any other plan has a Sort
 ///   that consumes the distance value, so it cannot be blanked.
 ///
@@ -2740,6 +2803,21 @@ fn base_query_has_search_predicates(
         SearchQueryInput::HeapFilter { indexed_query, .. } => {
             base_query_has_search_predicates(indexed_query, current_index_oid)
         }
+
+        // Hybrid queries score every document they match
+        SearchQueryInput::Hybrid { .. } => true,
+
+        // Function score queries exist to rank the documents they match
+        SearchQueryInput::FunctionScore { .. } => true,
+
+        // Combined fields queries are text searches over several fields
+        SearchQueryInput::CombinedFields { .. } => true,
+
+        // Vector range queries match by the distance of each row's vector
+        SearchQueryInput::VectorWithin { .. } => true,
+
+        // MaxSim queries score every row with vectors in a multi-vector field
+        SearchQueryInput::MaxSim { .. } => true,
     }
 }
 
@@ -17,4 +17,5 @@
 
 pub mod score;
 pub mod snippet;
+pub mod vector_distance;
 pub mod window_agg;
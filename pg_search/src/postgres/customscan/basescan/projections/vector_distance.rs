@@ -0,0 +1,162 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! `pdb.vector_distance()` projects the distance a TopK scan ordered a row by, so it doesn't
+//! have to be recomputed from the heap vector. Like `pdb.score()`, it's a placeholder that our
+//! custom scan replaces with a `Const` it fills in for every row.
+//!
+//! When the query orders by a vector distance of the same relation, planning rewrites the call to
+//! carry that distance expression (see [`with_order_by_distance`]), so that wherever our scan
+//! doesn't fill it in, like a `WHERE` clause or a plan without a TopK scan, the distance is
+//! computed from the heap vector instead.
+
+use crate::nodecast;
+use crate::postgres::customscan::orderby::find_target_entry_by_ref;
+use crate::postgres::var::find_vars;
+use crate::vector::metric::VectorMetric;
+use pgrx::pg_sys::expression_tree_walker;
+use pgrx::{IntoDatum, PgList, direct_function_call, pg_guard, pg_sys};
+use std::ptr::addr_of_mut;
+use std::sync::OnceLock;
+
+#[pgrx::pg_schema]
+mod pdb {
+    use pgrx::{AnyElement, extension_sql, pg_extern};
+
+    #[allow(unused_variables)]
+    #[pg_extern(name = "vector_distance", stable, parallel_safe, cost = 1)]
+    fn vector_distance_from_relation(relation_reference: AnyElement) -> f64 {
+        pgrx::error!(
+            "pdb.vector_distance() is only available in a query that orders by a vector distance of the same table"
+        );
+    }
+
+    /// What `pdb.vector_distance(relation)` is rewritten to in a query that orders by `distance`.
+    #[allow(unused_variables)]
+    #[pg_extern(name = "vector_distance", stable, parallel_safe, cost = 1)]
+    fn vector_distance_from_expr(relation_reference: AnyElement, distance: f64) -> f64 {
+        distance
+    }
+
+    extension_sql!(
+        r#"
+    ALTER FUNCTION pdb.vector_distance(anyelement) SUPPORT paradedb.placeholder_support;
+    "#,
+        name = "vector_distance_placeholder",
+        requires = [vector_distance_from_relation, placeholder_support]
+    );
+}
+
+/// The oids of `pdb.vector_distance(anyelement)` and `pdb.vector_distance(anyelement, float8)`.
+pub fn vector_distance_funcoids() -> [pg_sys::Oid; 2] {
+    static OID_CACHE: OnceLock<[pg_sys::Oid; 2]> = OnceLock::new();
+    *OID_CACHE.get_or_init(|| unsafe {
+        [
+            c"pdb.vector_distance(anyelement)",
+            c"pdb.vector_distance(anyelement, double precision)",
+        ]
+        .map(|signature| {
+            direct_function_call::<pg_sys::Oid>(pg_sys::regprocedurein, &[signature.into_datum()])
+                .expect("the `pdb.vector_distance` functions should exist")
+        })
+    })
+}
+
+/// Rewrites `fcall`, a call to `pdb.vector_distance(relation)`, into a call to
+/// `pdb.vector_distance(relation, <distance>)`, where `<distance>` is the vector distance that
+/// `parse` orders by first.  `None` if that isn't a vector distance of the same relation.
+pub unsafe fn with_order_by_distance(
+    parse: *mut pg_sys::Query,
+    fcall: *mut pg_sys::FuncExpr,
+) -> Option<*mut pg_sys::FuncExpr> {
+    let [from_relation, from_expr] = vector_distance_funcoids();
+    if parse.is_null() || (*fcall).funcid != from_relation {
+        return None;
+    }
+    let args = PgList::<pg_sys::Node>::from_pg((*fcall).args);
+    let var = nodecast!(Var, T_Var, args.get_ptr(0)?)?;
+
+    let sort_clause = PgList::<pg_sys::SortGroupClause>::from_pg((*parse).sortClause);
+    let target_list = PgList::<pg_sys::TargetEntry>::from_pg((*parse).targetList);
+    let target_entry =
+        find_target_entry_by_ref(&target_list, (*sort_clause.get_ptr(0)?).tleSortGroupRef)?;
+    let opexpr = nodecast!(OpExpr, T_OpExpr, (*target_entry).expr)?;
+    VectorMetric::from_opoid((*opexpr).opno)?;
+    if (*opexpr).opresulttype != pg_sys::FLOAT8OID {
+        return None;
+    }
+    let vars = find_vars(opexpr.cast());
+    if vars.is_empty()
+        || !vars.iter().all(|other| {
+            (**other).varno == (*var).varno && (**other).varlevelsup == (*var).varlevelsup
+        })
+    {
+        return None;
+    }
+
+    let mut args = PgList::<pg_sys::Node>::new();
+    args.push(pg_sys::copyObjectImpl(var.cast()).cast());
+    args.push(pg_sys::copyObjectImpl(opexpr.cast()).cast());
+    Some(pg_sys::makeFuncExpr(
+        from_expr,
+        pg_sys::FLOAT8OID,
+        args.into_pg(),
+        pg_sys::InvalidOid,
+        pg_sys::InvalidOid,
+        pg_sys::CoercionForm::COERCE_EXPLICIT_CALL,
+    ))
+}
+
+/// Does `node` call `pdb.vector_distance()` on the relation at `rti`?
+pub unsafe fn uses_vector_distance(
+    node: *mut pg_sys::Node,
+    funcoids: [pg_sys::Oid; 2],
+    rti: pg_sys::Index,
+) -> bool {
+    #[pg_guard]
+    unsafe extern "C-unwind" fn walker(
+        node: *mut pg_sys::Node,
+        data: *mut core::ffi::c_void,
+    ) -> bool {
+        if node.is_null() {
+            return false;
+        }
+
+        if let Some(funcexpr) = nodecast!(FuncExpr, T_FuncExpr, node) {
+            let data = data.cast::<Data>();
+            if (*data).funcoids.contains(&(*funcexpr).funcid) {
+                let args = PgList::<pg_sys::Node>::from_pg((*funcexpr).args);
+                if let Some(var) = nodecast!(Var, T_Var, args.get_ptr(0).unwrap())
+                    && (*var).varno as i32 == (*data).rti as i32
+                {
+                    return true;
+                }
+            }
+        }
+
+        expression_tree_walker(node, Some(walker), data)
+    }
+
+    struct Data {
+        funcoids: [pg_sys::Oid; 2],
+        rti: pg_sys::Index,
+    }
+
+    let mut data = Data { funcoids, rti };
+
+    walker(node, addr_of_mut!(data).cast())
+}
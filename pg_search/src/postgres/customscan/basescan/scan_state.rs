@@ -36,6 +36,7 @@ use crate::postgres::rel::PgSearchRelation;
 use crate::postgres::utils::u64_to_item_pointer;
 use crate::postgres::{ParallelScanArgs, ParallelScanState};
 use crate::query::SearchQueryInput;
+use crate::vector::metric::VectorMetric;
 
 use pgrx::heap_tuple::PgHeapTuple;
 use pgrx::{PgTupleDesc, pg_sys};
@@ -89,6 +90,11 @@ pub struct BaseScanState {
     /// it only so the projection path knows it must use `placeholder_targetlist`.
     pub vector_distance_placeholder: bool,
 
+    pub need_vector_distances: bool,
+    /// The `pdb.vector_distance()` placeholder and the metric that turns the TopK scan's scores
+    /// into its distances. Only set when the scan is a TopK ordered by a vector distance.
+    pub const_vector_distance_node: Option<(*mut pg_sys::Const, VectorMetric)>,
+
     pub const_snippet_nodes: HashMap<SnippetType, Vec<*mut pg_sys::Const>>,
 
     pub snippet_funcoids: [pg_sys::Oid; 2],
@@ -229,12 +229,45 @@ fn inject_estimates_into_json(
                 inject_estimates_into_json(query, first_child);
             }
         }
+        SearchQueryInput::Hybrid { .. } => {
+            let first_child = estimate_tree
+                .children()
+                .first()
+                .expect("Hybrid query should have a child");
+            let hybrid = obj
+                .get_mut("hybrid")
+                .expect("expected 'hybrid' key in JSON for Hybrid query")
+                .as_object_mut()
+                .expect("'hybrid' value should be an object");
+            let query = hybrid
+                .get_mut("query")
+                .expect("'hybrid' should have a 'query' field");
+            inject_estimates_into_json(query, first_child);
+        }
+        SearchQueryInput::FunctionScore { .. } => {
+            let first_child = estimate_tree
+                .children()
+                .first()
+                .expect("FunctionScore query should have a child");
+            let function_score = obj
+                .get_mut("function_score")
+                .expect("expected 'function_score' key in JSON for FunctionScore query")
+                .as_object_mut()
+                .expect("'function_score' value should be an object");
+            let query = function_score
+                .get_mut("query")
+                .expect("'function_score' should have a 'query' field");
+            inject_estimates_into_json(query, first_child);
+        }
         // Leaf query types - no children to process
         SearchQueryInput::All
         | SearchQueryInput::Empty
         | SearchQueryInput::MoreLikeThis { .. }
         | SearchQueryInput::Parse { .. }
         | SearchQueryInput::TermSet { .. }
+        | SearchQueryInput::CombinedFields { .. }
+        | SearchQueryInput::VectorWithin { .. }
+        | SearchQueryInput::MaxSim { .. }
         | SearchQueryInput::PostgresExpression { .. }
         | SearchQueryInput::FieldedQuery { .. }
         | SearchQueryInput::Uninitialized => {
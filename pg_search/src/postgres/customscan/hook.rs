@@ -31,7 +31,7 @@ use crate::postgres::customscan::{
     CreateUpperPathsHookArgs, CustomScan, JoinPathlistHookArgs, RelPathlistHookArgs,
 };
 use crate::postgres::planner_warnings::{clear_planner_warnings, emit_planner_warnings};
-use crate::postgres::rel_get_bm25_index;
+use crate::postgres::rel_get_bm25_indexes;
 use crate::postgres::utils::{expr_contains_any_operator, pg_search_extension_installed};
 use once_cell::sync::Lazy;
 use pgrx::{PgList, PgMemoryContexts, pg_guard, pg_sys};
@@ -432,35 +432,38 @@ pub unsafe fn try_extract_quals_from_query(
         }
 
         // Check if this relation has a BM25 index
-        let Some((_, bm25_index)) = rel_get_bm25_index(relid) else {
+        let Some((_, bm25_indexes)) = rel_get_bm25_indexes(relid) else {
             continue;
         };
 
-        // We found a relation with a BM25 index - try to extract quals
+        // We found a relation with a BM25 index - try to extract quals, against any of its
+        // indexes as the scan will choose the one that answers the most of the query
         // Use Query context since we don't have PlannerInfo yet
         let rti = (idx + 1) as pg_sys::Index; // RTI is 1-indexed
-        let mut state = QualExtractState::default();
         let context = PlannerContext::from_query(parse);
 
-        let quals = extract_quals(
-            &context,
-            rti,
-            quals_node,
-            RestrictInfoType::BaseRelation,
-            &bm25_index,
-            false, // Don't convert external to special qual
-            &mut state,
-            true, // Attempt pushdown
-        );
+        return bm25_indexes.iter().any(|bm25_index| {
+            let mut state = QualExtractState::default();
+            let quals = extract_quals(
+                &context,
+                rti,
+                quals_node,
+                RestrictInfoType::BaseRelation,
+                bm25_index,
+                false, // Don't convert external to special qual
+                &mut state,
+                true, // Attempt pushdown
+            );
 
-        // CRITICAL: In Query context, if we created HeapExpr but filter_pushdown is disabled,
-        // we must return false to prevent accepting queries we can't handle correctly.
-        // This prevents silent data loss when WHERE clauses can't be fully pushed down.
-        if state.uses_heap_expr && !crate::gucs::enable_filter_pushdown() {
-            return false;
-        }
+            // CRITICAL: In Query context, if we created HeapExpr but filter_pushdown is disabled,
+            // we must return false to prevent accepting queries we can't handle correctly.
+            // This prevents silent data loss when WHERE clauses can't be fully pushed down.
+            if state.uses_heap_expr && !crate::gucs::enable_filter_pushdown() {
+                return false;
+            }
 
-        return quals.is_some();
+            quals.is_some()
+        });
     }
 
     // No BM25 index found
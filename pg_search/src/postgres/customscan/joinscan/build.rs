@@ -340,9 +340,9 @@ pub enum ChildProjection {
 use crate::index::mvcc::MvccSatisfies;
 use crate::index::reader::index::SearchIndexReader;
 use crate::postgres::customscan::limit_offset::LimitOffset;
+use crate::postgres::customscan::qual_inspect::choose_rel_bm25_index;
 use crate::postgres::customscan::range_table::{get_plain_relation_relid, get_rte};
 use crate::postgres::rel::PgSearchRelation;
-use crate::postgres::rel_get_bm25_index;
 use crate::scan::info::{FieldInfo, RowEstimate};
 
 /// Source information collected during planning.
@@ -1742,7 +1742,7 @@ pub unsafe fn lookup_base_rel_info(
         None
     };
 
-    let bm25_index = rel_get_bm25_index(relid).map(|(_, idx)| idx);
+    let bm25_index = choose_rel_bm25_index(root, rti, relid).map(|(_, idx)| idx);
 
     Some((relid, alias, bm25_index))
 }
@@ -45,7 +45,6 @@ use crate::postgres::customscan::qual_inspect::{PlannerContext, QualExtractState
 use crate::postgres::customscan::range_table::bms_iter;
 use crate::postgres::customscan::score_funcoids;
 use crate::postgres::rel::PgSearchRelation;
-use crate::postgres::rel_get_bm25_index;
 use crate::postgres::utils::{
     expr_collect_vars, expr_contains_any_operator, missing_partial_index_predicate, strip_wrappers,
 };
@@ -210,7 +209,7 @@ pub(super) unsafe fn collect_join_sources_base_rel(
     rel: *mut pg_sys::RelOptInfo,
     rti: pg_sys::Index,
 ) -> Option<(RelNode, Vec<JoinKeyPair>)> {
-    let (relid, alias, _bm25_opt) = build::lookup_base_rel_info(root, rti)?;
+    let (relid, alias, bm25_index) = build::lookup_base_rel_info(root, rti)?;
 
     let mut side_info = JoinSourceCandidate::new(root.into(), rti).with_heaprelid(relid);
     if let Some(alias) = alias {
@@ -221,7 +220,7 @@ pub(super) unsafe fn collect_join_sources_base_rel(
     // otherwise the Semi/Anti/LeftMark wrapping has nothing useful to wrap.
     let mut classified = ClassifiedBaseRestrictInfo::empty();
 
-    if let Some((_, bm25_index)) = rel_get_bm25_index(relid) {
+    if let Some(bm25_index) = bm25_index {
         side_info = side_info.with_index(&bm25_index);
 
         let baserestrictinfo = PgList::<pg_sys::RestrictInfo>::from_pg((*rel).baserestrictinfo);
@@ -37,7 +37,6 @@ use crate::postgres::customscan::datafusion::translator::PredicateTranslator;
 use crate::postgres::customscan::pullup::resolve_fast_field;
 use crate::postgres::customscan::qual_inspect::{PlannerContext, QualExtractState, extract_quals};
 use crate::postgres::rel::PgSearchRelation;
-use crate::postgres::rel_get_bm25_index;
 use crate::postgres::utils::{expr_collect_rtis, expr_collect_vars, expr_contains_any_operator};
 use crate::query::SearchQueryInput;
 use pgrx::{PgList, pg_sys};
@@ -338,7 +337,10 @@ pub unsafe fn extract_single_table_predicate(
 ) -> Option<usize> {
     let indexrelid = side.indexrelid;
     let heaprelid = side.heaprelid;
-    let (_, bm25_idx) = rel_get_bm25_index(heaprelid)?;
+    if indexrelid == pg_sys::InvalidOid {
+        return None;
+    }
+    let bm25_idx = PgSearchRelation::open(indexrelid);
 
     // Create a RestrictInfo wrapping the expression for extract_quals
     let mut ri_list = PgList::<pg_sys::RestrictInfo>::new();
@@ -30,12 +30,13 @@ use crate::nodecast;
 use crate::postgres::customscan::basescan::exec_methods::fast_fields::find_matching_fast_field;
 use crate::postgres::customscan::builders::custom_path::OrderByStyle;
 use crate::postgres::customscan::collation_semantics::{CollationOperation, collation_supports};
+use crate::postgres::customscan::qual_inspect::choose_query_bm25_index;
 use crate::postgres::customscan::score_funcoids;
-use crate::postgres::rel_get_bm25_index;
 use crate::postgres::var::{
     VarContext, fieldname_from_var, find_one_var_and_fieldname, strip_identity_wrappers,
 };
 use crate::schema::{SearchField, SearchFieldType, SearchIndexSchema};
+use crate::vector::VectorType;
 use crate::vector::metric::VectorMetric;
 use pgrx::{FromDatum, IntoDatum, PgList, direct_function_call, pg_sys};
 
@@ -69,6 +70,7 @@ pub enum SortExpressionType {
     /// the user asked for; the planner falls back to a non-index
     /// sort. Only carried for diagnostics in the planner warning.
     VectorMetricMismatch {
+        vector_type: VectorType,
         field_metric: VectorMetric,
         op_metric: VectorMetric,
     },
@@ -87,6 +89,7 @@ pub enum UnusableReason {
     /// disagrees with the index attribute's opclass (e.g. `<=>` on a
     /// column built with `vector_l2_ops`).
     VectorMetricMismatch {
+        vector_type: VectorType,
         field_metric: VectorMetric,
         op_metric: VectorMetric,
     },
@@ -276,12 +279,13 @@ pub unsafe fn analyze_sort_expression(
 /// to `SearchFieldType::Vector` — pgvector convention. The metric
 /// travels via the index attribute's opclass (see
 /// `VectorMetric::from_index_attr`); the column type itself
-/// is just plain `vector`, so there is no cast wrapper to unwrap.
+/// is just plain `vector` (or `halfvec`, `sparsevec`, `bit`), so there
+/// is no cast wrapper to unwrap.
 unsafe fn resolve_vector_expr(
     node: *mut pg_sys::Node,
     context: VarContext,
     schema: &SearchIndexSchema,
-) -> Option<(*mut pg_sys::Var, VectorMetric)> {
+) -> Option<(*mut pg_sys::Var, VectorType, VectorMetric)> {
     if node.is_null() || (*node).type_ != pg_sys::NodeTag::T_Var {
         return None;
     }
@@ -289,8 +293,8 @@ unsafe fn resolve_vector_expr(
     let (relid, attno) = context.var_relation(var);
     let field_name = fieldname_from_var(relid, var, attno)?;
     let field_type = schema.get_field_type(field_name.root())?;
-    if let SearchFieldType::Vector(_, _, metric) = field_type {
-        return Some((var, metric));
+    if let SearchFieldType::Vector(typoid, _, metric) = field_type {
+        return Some((var, VectorType::from_oid(typoid)?, metric));
     }
     None
 }
@@ -323,11 +327,12 @@ unsafe fn extract_vector_distance(
     // other carries the query vector. The metric travels via the index
     // attribute's opclass (pgvector convention), not via a cast on the
     // column, so there's no wrapper expression to unwrap here.
-    let (var_node, field_metric, value_node) =
-        if let Some((var, metric)) = resolve_vector_expr(left, context, schema) {
-            (var, metric, right)
-        } else if let Some((var, metric)) = resolve_vector_expr(right, context, schema) {
-            (var, metric, left)
+    let (var_node, vector_type, field_metric, value_node) =
+        if let Some((var, vector_type, metric)) = resolve_vector_expr(left, context, schema) {
+            (var, vector_type, metric, right)
+        } else if let Some((var, vector_type, metric)) = resolve_vector_expr(right, context, schema)
+        {
+            (var, vector_type, metric, left)
         } else {
             return None;
         };
@@ -336,6 +341,7 @@ unsafe fn extract_vector_distance(
         return Some((
             var_node,
             SortExpressionType::VectorMetricMismatch {
+                vector_type,
                 field_metric,
                 op_metric,
             },
@@ -347,9 +353,11 @@ unsafe fn extract_vector_distance(
             return None;
         }
         let datum = (*const_node).constvalue;
-        let query_vector = unsafe { crate::vector::PgVector::from_datum(datum, false) }
-            .expect("vector ORDER BY constant should not be NULL")
-            .0;
+        let query_vector = unsafe {
+            crate::vector::PgVector::from_polymorphic_datum(datum, false, (*const_node).consttype)
+        }
+        .expect("vector ORDER BY constant should not be NULL")
+        .0;
         return Some((
             var_node,
             SortExpressionType::VectorDistance {
@@ -444,7 +452,7 @@ unsafe fn is_varno_valid_for_relation(
 }
 
 /// Find TargetEntry by ressortgroupref
-unsafe fn find_target_entry_by_ref(
+pub unsafe fn find_target_entry_by_ref(
     target_list: &PgList<pg_sys::TargetEntry>,
     ref_id: pg_sys::Index,
 ) -> Option<*mut pg_sys::TargetEntry> {
@@ -604,6 +612,7 @@ where
                         }
                     }
                     SortExpressionType::VectorMetricMismatch {
+                        vector_type,
                         field_metric,
                         op_metric,
                     } => {
@@ -612,6 +621,7 @@ where
                         // metric and we want the warning to surface
                         // that specific reason.
                         return PathKeyInfo::Unusable(UnusableReason::VectorMetricMismatch {
+                            vector_type,
                             field_metric,
                             op_metric,
                         });
@@ -716,10 +726,9 @@ pub unsafe fn validate_topk_compatibility(parse: *mut pg_sys::Query) -> bool {
                 return false;
             }
 
-            // Check if has BM25
-            let (_, bm25_index) = match rel_get_bm25_index(relid) {
-                Some(res) => res,
-                None => return false,
+            // Check if has BM25, and choose among its indexes by the fields the query refers to
+            let Some((_, bm25_index)) = choose_query_bm25_index(parse, varno, relid) else {
+                return false;
             };
 
             let schema = match SearchIndexSchema::open(&bm25_index) {
@@ -32,6 +32,7 @@ use crate::postgres::customscan::basescan::projections::snippet::{
     SnippetType, extract_snippet, extract_snippet_positions, extract_snippets, snippet_funcoids,
     snippet_positions_funcoids,
 };
+use crate::postgres::customscan::basescan::projections::vector_distance::with_order_by_distance;
 use crate::postgres::customscan::range_table::{rte_is_parent, rte_is_partitioned};
 use crate::postgres::customscan::score_funcoids;
 use crate::postgres::var::{VarContext, find_one_var_and_fieldname, find_vars};
@@ -332,6 +333,10 @@ pub unsafe fn placeholder_support(arg: Internal) -> ReturnedNodePointer {
         }
 
         let root = (*srs).root;
+        // `pdb.vector_distance(<anyelement>)` takes the distance the query orders by along, to be
+        // computed from the heap vector wherever our custom scan doesn't fill it in
+        let vector_distance = with_order_by_distance((*root).parse, (*srs).fcall);
+        let fcall = vector_distance.unwrap_or((*srs).fcall);
         let has_aggs = !(*root).parse.is_null() && (*(*root).parse).hasAggs;
 
         // We walk the jointree instead of checking hasJoinRTEs because
@@ -345,7 +350,9 @@ pub unsafe fn placeholder_support(arg: Internal) -> ReturnedNodePointer {
 
         if !has_joins && !has_aggs {
             // No joins and no aggregates - PlaceHolderVar provides no benefit
-            return ReturnedNodePointer(None);
+            return ReturnedNodePointer(
+                vector_distance.and_then(|fcall| NonNull::new(fcall.cast())),
+            );
         }
 
         let mut vars = find_vars((*srs).fcall.cast());
@@ -362,7 +369,7 @@ pub unsafe fn placeholder_support(arg: Internal) -> ReturnedNodePointer {
 
             make_placeholder_expr(
                 (*srs).root,
-                pg_sys::copyObjectImpl((*srs).fcall.cast()).cast(),
+                pg_sys::copyObjectImpl(fcall.cast()).cast(),
                 phrels,
             )
         });
@@ -494,6 +501,7 @@ pub unsafe fn inject_placeholders(
     targetlist: *mut pg_sys::List,
     rti: pg_sys::Index,
     score_funcoids: [pg_sys::Oid; 2],
+    vector_distance_funcoids: [pg_sys::Oid; 2],
     snippet_funcoids: [pg_sys::Oid; 2],
     snippets_funcoids: [pg_sys::Oid; 2],
     snippet_positions_funcoids: [pg_sys::Oid; 2],
@@ -502,6 +510,7 @@ pub unsafe fn inject_placeholders(
 ) -> (
     *mut pg_sys::List,
     *mut pg_sys::Const,
+    *mut pg_sys::Const,
     HashMap<SnippetType, Vec<*mut pg_sys::Const>>,
 ) {
     #[pg_guard]
@@ -521,6 +530,10 @@ pub unsafe fn inject_placeholders(
                 return Some(data.const_score_node.cast());
             }
 
+            if data.vector_distance_funcoids.contains(&(*funcexpr).funcid) {
+                return Some(data.const_vector_distance_node.cast());
+            }
+
             let mut this_snippet_type = None;
 
             if let Some(snippet_type) = extract_snippet(
@@ -601,6 +614,9 @@ pub unsafe fn inject_placeholders(
         score_funcoids: [pg_sys::Oid; 2],
         const_score_node: *mut pg_sys::Const,
 
+        vector_distance_funcoids: [pg_sys::Oid; 2],
+        const_vector_distance_node: *mut pg_sys::Const,
+
         snippet_funcoids: [pg_sys::Oid; 2],
         snippets_funcoids: [pg_sys::Oid; 2],
         snippet_positions_funcoids: [pg_sys::Oid; 2],
@@ -624,6 +640,17 @@ pub unsafe fn inject_placeholders(
             true,
         ),
 
+        vector_distance_funcoids,
+        const_vector_distance_node: pg_sys::makeConst(
+            pg_sys::FLOAT8OID,
+            -1,
+            pg_sys::Oid::INVALID,
+            size_of::<f64>() as _,
+            pg_sys::Datum::null(),
+            true,
+            true,
+        ),
+
         snippet_funcoids,
         snippets_funcoids,
         snippet_positions_funcoids,
@@ -635,6 +662,7 @@ pub unsafe fn inject_placeholders(
     (
         targetlist.cast(),
         data.const_score_node,
+        data.const_vector_distance_node,
         data.const_snippet_nodes,
     )
 }
@@ -15,6 +15,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use crate::api::HashSet;
 use crate::api::operator::{is_anyelement_search_opoid, searchqueryinput_typoid};
 use crate::gucs;
 use crate::nodecast;
@@ -26,6 +27,7 @@ use crate::postgres::deparse::deparse_expr;
 use crate::postgres::pdb_owned_value::PdbOwnedValue;
 use crate::postgres::rel::PgSearchRelation;
 use crate::postgres::var::VarContext;
+use crate::postgres::{index_field_count, rel_get_bm25_indexes};
 use crate::query::SearchQueryInput;
 use crate::query::heap_field_filter::HeapFieldFilter;
 use crate::query::pdb_query::pdb;
@@ -830,6 +832,225 @@ pub unsafe fn is_subplan(node: *mut pg_sys::Node, root: *mut pg_sys::PlannerInfo
     walker(node, std::ptr::null_mut())
 }
 
+/// Chooses which of a relation's bm25 `indexes`, most recent first, a scan should use, the way
+/// Postgres chooses among B-tree indexes: by how much of the query each one can answer.
+///
+/// That's the index that has the most of `field_names`, the fields the query refers to (see
+/// [`referenced_fields`]).  Ties go to the most recent index.  Choosing doesn't extract the
+/// query's quals, so the caller only extracts them once, against the index chosen.
+pub fn choose_bm25_index(
+    indexes: Vec<PgSearchRelation>,
+    field_names: &HashSet<String>,
+) -> PgSearchRelation {
+    if indexes.len() == 1 {
+        return indexes.into_iter().next().unwrap();
+    }
+
+    let mut best: Option<(usize, PgSearchRelation)> = None;
+    for index in indexes {
+        let count = index_field_count(&index, field_names);
+        if best.as_ref().is_none_or(|(best, _)| count > *best) {
+            let is_complete = count == field_names.len();
+            best = Some((count, index));
+            if is_complete {
+                break;
+            }
+        }
+    }
+
+    let (_, index) = best.expect("a relation's bm25 indexes should not be empty");
+    index
+}
+
+/// Like [`choose_bm25_index`], unless the `@@@` conditions in `quals` were already bound to one of
+/// `indexes` when they were simplified (see [`choose_query_bm25_index`]).  They're only valid
+/// against that index, so it's the one chosen.
+pub unsafe fn choose_bm25_index_for_quals(
+    indexes: Vec<PgSearchRelation>,
+    field_names: &HashSet<String>,
+    quals: *mut pg_sys::Node,
+) -> PgSearchRelation {
+    let bound = bound_bm25_indexes(quals);
+    let mut bound_indexes = indexes.iter().filter(|index| bound.contains(&index.oid()));
+    if let Some(index) = bound_indexes.next() {
+        if let Some(other) = bound_indexes.next() {
+            pgrx::error!(
+                "the search conditions on one table use both `{}` and `{}`, but a scan can only use one index.  Create a bm25 index that covers all of their fields",
+                other.name(),
+                index.name()
+            );
+        }
+        return index.clone();
+    }
+    choose_bm25_index(indexes, field_names)
+}
+
+/// Like [`choose_bm25_index`], for the relation of range table entry `rti` of the query `parse`,
+/// by the fields that its `WHERE` clause and target list refer to.
+///
+/// This is the index that `@@@` binds its conditions to when they're simplified, before the
+/// relation's restriction clauses exist, so every condition on the relation is bound to the same
+/// one, which its scan then uses.
+pub unsafe fn choose_query_bm25_index(
+    parse: *mut pg_sys::Query,
+    rti: pg_sys::Index,
+    relid: pg_sys::Oid,
+) -> Option<(PgSearchRelation, PgSearchRelation)> {
+    let (heaprel, indexes) = rel_get_bm25_indexes(relid)?;
+    let mut field_names = HashSet::default();
+    let mut quals = std::ptr::null_mut();
+    if !parse.is_null() {
+        referenced_fields((*parse).targetList.cast(), rti, relid, &mut field_names);
+        if !(*parse).jointree.is_null() {
+            quals = (*parse).jointree.cast();
+            referenced_fields(quals, rti, relid, &mut field_names);
+        }
+    }
+    Some((
+        heaprel,
+        choose_bm25_index_for_quals(indexes, &field_names, quals),
+    ))
+}
+
+/// The oids of the indexes that the `@@@` conditions in `node` were bound to when they were
+/// simplified.
+pub unsafe fn bound_bm25_indexes(node: *mut pg_sys::Node) -> HashSet<pg_sys::Oid> {
+    #[pg_guard]
+    unsafe extern "C-unwind" fn walker(
+        node: *mut pg_sys::Node,
+        data: *mut core::ffi::c_void,
+    ) -> bool {
+        if node.is_null() {
+            return false;
+        }
+        let oids = &mut *(data as *mut HashSet<pg_sys::Oid>);
+
+        match (*node).type_ {
+            pg_sys::NodeTag::T_RestrictInfo => {
+                let ri = node as *mut pg_sys::RestrictInfo;
+                return walker((*ri).clause.cast(), data);
+            }
+            pg_sys::NodeTag::T_Const => {
+                let const_ = node as *mut pg_sys::Const;
+                if (*const_).consttype == searchqueryinput_typoid()
+                    && let Some(oid) =
+                        SearchQueryInput::from_datum((*const_).constvalue, (*const_).constisnull)
+                            .and_then(|query| query.index_oid())
+                {
+                    oids.insert(oid);
+                }
+                return false;
+            }
+            _ => {}
+        }
+        pg_sys::expression_tree_walker(node, Some(walker), data)
+    }
+
+    let mut oids = HashSet::default();
+    walker(node, (&mut oids as *mut HashSet<pg_sys::Oid>).cast());
+    oids
+}
+
+/// Like [`choose_bm25_index`], for the relation of range table entry `rti`, by the fields that
+/// its restriction clauses and its target list refer to.
+pub unsafe fn choose_rel_bm25_index(
+    root: *mut pg_sys::PlannerInfo,
+    rti: pg_sys::Index,
+    relid: pg_sys::Oid,
+) -> Option<(PgSearchRelation, PgSearchRelation)> {
+    let (heaprel, indexes) = rel_get_bm25_indexes(relid)?;
+    let mut field_names = HashSet::default();
+    let mut quals = std::ptr::null_mut();
+    if (rti as usize) < (*root).simple_rel_array_size as usize {
+        let rel = *(*root).simple_rel_array.add(rti as usize);
+        if !rel.is_null() {
+            quals = (*rel).baserestrictinfo.cast();
+            referenced_fields(quals, rti, relid, &mut field_names);
+            if !(*rel).reltarget.is_null() {
+                referenced_fields(
+                    (*(*rel).reltarget).exprs.cast(),
+                    rti,
+                    relid,
+                    &mut field_names,
+                );
+            }
+        }
+    }
+    Some((
+        heaprel,
+        choose_bm25_index_for_quals(indexes, &field_names, quals),
+    ))
+}
+
+/// Adds the names of the fields that `node` refers to in range table entry `rti`, the relation
+/// `relid`, to `field_names`: the columns of it that `node` reads, and the fields that the
+/// `SearchQueryInput` constants in `node` search.
+pub unsafe fn referenced_fields(
+    node: *mut pg_sys::Node,
+    rti: pg_sys::Index,
+    relid: pg_sys::Oid,
+    field_names: &mut HashSet<String>,
+) {
+    struct Context<'a> {
+        rti: pg_sys::Index,
+        relid: pg_sys::Oid,
+        field_names: &'a mut HashSet<String>,
+    }
+
+    #[pg_guard]
+    unsafe extern "C-unwind" fn walker(
+        node: *mut pg_sys::Node,
+        data: *mut core::ffi::c_void,
+    ) -> bool {
+        if node.is_null() {
+            return false;
+        }
+        let context = &mut *(data as *mut Context);
+
+        match (*node).type_ {
+            pg_sys::NodeTag::T_RestrictInfo => {
+                let ri = node as *mut pg_sys::RestrictInfo;
+                return walker((*ri).clause.cast(), data);
+            }
+            pg_sys::NodeTag::T_Var => {
+                let var = node as *mut pg_sys::Var;
+                if (*var).varno as pg_sys::Index == context.rti
+                    && (*var).varlevelsup == 0
+                    && (*var).varattno > 0
+                {
+                    let attname = pg_sys::get_attname(context.relid, (*var).varattno, true);
+                    if !attname.is_null() {
+                        let attname = std::ffi::CStr::from_ptr(attname);
+                        context
+                            .field_names
+                            .insert(attname.to_string_lossy().into_owned());
+                    }
+                }
+                return false;
+            }
+            pg_sys::NodeTag::T_Const => {
+                let const_ = node as *mut pg_sys::Const;
+                if (*const_).consttype == searchqueryinput_typoid()
+                    && let Some(query) =
+                        SearchQueryInput::from_datum((*const_).constvalue, (*const_).constisnull)
+                {
+                    query.extract_field_names(context.field_names);
+                }
+                return false;
+            }
+            _ => {}
+        }
+        pg_sys::expression_tree_walker(node, Some(walker), data)
+    }
+
+    let mut context = Context {
+        rti,
+        relid,
+        field_names,
+    };
+    walker(node, (&mut context as *mut Context).cast());
+}
+
 #[allow(clippy::too_many_arguments)]
 pub unsafe fn extract_quals(
     context: &PlannerContext,
@@ -15,16 +15,20 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use crate::api::HashSet;
 use crate::index::merge_policy::LayeredMergePolicy;
 use crate::index::mvcc::MvccSatisfies;
 use crate::index::writer::index::{Mergeable, SearchIndexMerger};
 use crate::postgres::PgSearchRelation;
 use crate::postgres::delete::VacuumSignal;
 use crate::postgres::locks::AdvisoryLock;
+use crate::postgres::merge_scheduler::{self, IoRateLimit};
+use crate::postgres::progress::{self, PROGRESS_PDB_SEGMENTS_DONE, PROGRESS_PDB_SEGMENTS_TOTAL};
 use crate::postgres::ps_status::{MERGING, set_ps_display_suffix};
 use crate::postgres::storage::LinkedItemList;
 use crate::postgres::storage::block::{MVCCEntry, SegmentMetaEntry};
 use crate::postgres::storage::buffer::{Buffer, BufferManager};
+use crate::postgres::storage::custom_rmgr;
 use crate::postgres::storage::fsm::FreeSpaceManager;
 use crate::postgres::storage::merge::MergeLock;
 use crate::postgres::storage::metadata::MetaPage;
@@ -279,7 +283,10 @@ pub unsafe fn do_merge(
     }
 
     let layer_sizes = IndexLayerSizes::from(index);
-    let metadata = MetaPage::open(index);
+    let mut metadata = MetaPage::open(index);
+
+    // free whichever schema lists the last `pdb.add_field()` or `pdb.drop_field()` left unused
+    metadata.resolve_schema_change();
 
     // apply backpressure if there are too many mutable segments
     // this means forcing a foreground merge of the mutable segments
@@ -288,11 +295,25 @@ pub unsafe fn do_merge(
     let merge_lock = metadata.acquire_merge_lock();
     let foreground_layer_sizes = layer_sizes.foreground_layer_sizes.clone();
 
+    // segments missing a field added by `pdb.add_field()` are backfilled by the background merger,
+    // whether or not it has layers to merge.  The unfilled lists aren't pruned as segments are
+    // backfilled, so only the ones still in the index count
+    let mut unfilled_segment_ids = index.schema()?.changes().all_unfilled_segments();
+    if !unfilled_segment_ids.is_empty() {
+        let live_segment_ids = metadata
+            .segment_metas()
+            .list(None)
+            .into_iter()
+            .filter(|entry| entry.visible())
+            .map(|entry| entry.segment_id())
+            .collect::<HashSet<_>>();
+        unfilled_segment_ids.retain(|segment_id| live_segment_ids.contains(segment_id));
+    }
     let (needs_background_merge, largest_layer_size) =
-        if layer_sizes.user_configured_background_layers() {
+        if layer_sizes.user_configured_background_layers() || !unfilled_segment_ids.is_empty() {
             let combined_layers = layer_sizes.combined();
             let merger = SearchIndexMerger::open(index, MvccSatisfies::Mergeable)?;
-            let mut background_merge_policy = LayeredMergePolicy::new(combined_layers);
+            let mut background_merge_policy = LayeredMergePolicy::with_backfill(combined_layers);
 
             background_merge_policy.set_mergeable_segment_entries(&metadata, &merge_lock, &merger);
             let (merge_candidates, largest_layer_size) = background_merge_policy.simulate();
@@ -334,7 +355,33 @@ pub unsafe fn do_merge(
     Ok(())
 }
 
-/// Try to launch a background process to merge down the index.
+/// Merge each clustered segment of the index on its own, retraining its vector centroids.
+///
+/// Segments that a concurrent merge is already merging are skipped; their merge retrains them.
+pub unsafe fn rebalance_vectors(index: &PgSearchRelation) {
+    let metadata = MetaPage::open(index);
+    let cleanup_lock = metadata.cleanup_lock_shared();
+    let merge_lock = metadata.acquire_merge_lock();
+    merge_index(
+        index,
+        LayeredMergePolicy::vector_rebalance(),
+        merge_lock,
+        cleanup_lock,
+        false,
+        false,
+        pg_sys::GetCurrentFullTransactionId(),
+        pg_sys::ReadNextFullTransactionId(),
+    );
+}
+
+/// Try to launch a background process to backfill the segments missing a field added by
+/// `pdb.add_field()`.
+pub unsafe fn launch_backfill(index: &PgSearchRelation) {
+    try_launch_background_merger(index, LARGE_MERGE_THRESHOLD);
+}
+
+/// Try to launch a background process to merge down the index, or queue it for the merge
+/// scheduler to launch if that's enabled.
 /// Is not guaranteed to launch the process if there are not enough `max_worker_processes` available.
 unsafe fn try_launch_background_merger(index: &PgSearchRelation, largest_layer_size: u64) {
     let slot = MergeSlot::for_layer_size(index.oid(), largest_layer_size);
@@ -342,6 +389,10 @@ unsafe fn try_launch_background_merger(index: &PgSearchRelation, largest_layer_s
         return;
     }
 
+    if merge_scheduler::enqueue(index, slot.variant()) {
+        return;
+    }
+
     let dbname = CStr::from_ptr(pg_sys::get_database_name(pg_sys::MyDatabaseId))
         .to_string_lossy()
         .into_owned();
@@ -352,18 +403,31 @@ unsafe fn try_launch_background_merger(index: &PgSearchRelation, largest_layer_s
         index.name()
     );
 
-    if BackgroundWorkerBuilder::new(&worker_name)
+    launch_background_merger(&dbname, index.oid(), slot.variant(), &worker_name);
+}
+
+/// Launch a background process connected to `dbname` to merge down the index `index_oid`.
+/// Returns `false` if there are not enough `max_worker_processes` available.
+pub(crate) fn launch_background_merger(
+    dbname: &str,
+    index_oid: pg_sys::Oid,
+    variant: MergeSlotVariant,
+    worker_name: &str,
+) -> bool {
+    if BackgroundWorkerBuilder::new(worker_name)
         .enable_spi_access()
         .enable_shmem_access(None)
         .set_library("pg_search")
         .set_function("background_merge")
-        .set_argument(BackgroundMergeArgs::new(index.oid(), slot.variant()).into_datum())
-        .set_extra(&dbname)
+        .set_argument(BackgroundMergeArgs::new(index_oid, variant).into_datum())
+        .set_extra(dbname)
         .load_dynamic()
         .is_err()
     {
         pgrx::log!("not enough available `max_worker_processes` to launch a background merger");
+        return false;
     }
+    true
 }
 
 /// Actually do the merge
@@ -385,6 +449,8 @@ unsafe extern "C-unwind" fn background_merge(arg: pg_sys::Datum) {
         let current_xid = pg_sys::GetCurrentFullTransactionId();
         let next_xid = current_xid;
         let args = BackgroundMergeArgs::from_datum(arg, false).unwrap();
+        // removes the merge from the scheduler's queue once we're done, however that happens
+        let _claim = merge_scheduler::claim(args.index_oid(), args.slot_variant());
         let index = PgSearchRelation::try_open(
             args.index_oid(),
             pg_sys::AccessShareLock as pg_sys::LOCKMODE,
@@ -406,9 +472,10 @@ unsafe extern "C-unwind" fn background_merge(arg: pg_sys::Datum) {
             return;
         }
         let metadata = MetaPage::open(&index);
+        progress::start_background_merge(&index);
 
         let layer_sizes = IndexLayerSizes::from(&index);
-        let merge_policy = LayeredMergePolicy::new(layer_sizes.combined());
+        let merge_policy = LayeredMergePolicy::with_backfill(layer_sizes.combined());
 
         let cleanup_lock = metadata.cleanup_lock_shared();
         // this ensures there's only one merge running at a time for the given index,
@@ -441,6 +508,7 @@ unsafe extern "C-unwind" fn background_merge(arg: pg_sys::Datum) {
             caught.rethrow()
         })
         .execute();
+        progress::end_background_merge();
     })
 }
 
@@ -492,6 +560,19 @@ unsafe fn merge_index(
 
         let mut merge_result: anyhow::Result<Option<SegmentMeta>> = Ok(None);
 
+        let nsegments = merge_candidates
+            .iter()
+            .map(|candidate| candidate.0.len())
+            .sum::<usize>();
+        let mut nmerged = 0;
+        // the merge's writes are throttled as it writes them, until this is dropped
+        let _io_rate_limit = is_background
+            .then(IoRateLimit::for_background_merge)
+            .flatten()
+            .map(|limit| merge_scheduler::limit_merge_writes(indexrel.oid(), limit));
+        progress::update(PROGRESS_PDB_SEGMENTS_TOTAL, nsegments as i64);
+        progress::update(PROGRESS_PDB_SEGMENTS_DONE, nmerged as i64);
+
         for candidate in merge_candidates {
             if is_background && VacuumSignal::new(indexrel.oid()).wants_cancel() {
                 pgrx::debug1!("VACUUM waiting, exiting merge early");
@@ -504,6 +585,8 @@ unsafe fn merge_index(
             if merge_result.is_err() {
                 break;
             }
+            nmerged += candidate.0.len();
+            progress::update(PROGRESS_PDB_SEGMENTS_DONE, nmerged as i64);
             if gc_after_merge {
                 garbage_collect_index(indexrel, current_xid, next_xid);
                 need_gc = false;
@@ -543,29 +626,30 @@ unsafe fn merge_index(
 /// Garbage collect the segments, removing any which are no longer visible in transactions
 /// occurring in this process.
 ///
-/// If physical replicas might still be executing transactions on some segments, then they are
-/// moved to the `SEGMENT_METAS_GARBAGE` list until those replicas indicate that they are no longer
-/// in use, at which point they can be freed by `free_garbage`.
+/// Physical replicas learn which segments were removed through a WAL record, and replaying it
+/// waits for their own transactions to stop reading them, the same way the segments are only
+/// recyclable here once no local transaction has them pinned.
 ///
 pub unsafe fn garbage_collect_index(
     indexrel: &PgSearchRelation,
     current_xid: pg_sys::FullTransactionId,
     next_xid: pg_sys::FullTransactionId,
 ) {
-    // Remove items which are no longer visible to active local transactions from SEGMENT_METAS,
-    // and place them in SEGMENT_METAS_RECYLCABLE until they are no longer visible to remote
-    // transactions either.
+    // Remove items which are no longer visible to active local transactions from SEGMENT_METAS.
     //
-    // SEGMENT_METAS must be updated atomically so that a consistent list is visible for consumers:
-    // SEGMENT_METAS_GARBAGE need not be because it is only ever consumed on the physical
-    // replication primary.
+    // SEGMENT_METAS must be updated atomically so that a consistent list is visible for consumers.
     let mut segment_metas_linked_list = MetaPage::open(indexrel).segment_metas();
     let mut segment_metas = segment_metas_linked_list.atomically();
     let entries = segment_metas.garbage_collect(next_xid);
-
-    // Replication is not enabled: immediately free the entries. It doesn't matter when we
-    // commit the segment metas list in this case.
     segment_metas.commit();
+
+    // Replicas must stop reading the entries before their blocks can be reused, which is only
+    // once the removal is WAL-logged, so that no new transaction on a replica can find them.
+    let pintest_blocks = entries
+        .iter()
+        .map(|entry| entry.pintest_blockno())
+        .collect::<Vec<_>>();
+    custom_rmgr::emit_recycle_record(indexrel, &pintest_blocks);
     free_entries(indexrel, entries, current_xid);
 }
 
@@ -599,7 +683,7 @@ const SLOT_BITS: u32 = 8; // slot is u8
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
-enum MergeSlotVariant {
+pub(crate) enum MergeSlotVariant {
     Small = 0,
     Large = 1,
 }
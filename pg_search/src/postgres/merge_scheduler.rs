@@ -0,0 +1,623 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Schedules the background merges of every index in the cluster.
+//!
+//! Without the scheduler, the backend whose insert or `VACUUM` finds layers to merge launches a
+//! background merger for the index right away, so a burst of writes across many indexes starts as
+//! many merges at once.  With `paradedb.enable_merge_scheduler` on, that backend queues the merge
+//! in shared memory instead, and a long-lived scheduler worker launches the queued merges oldest
+//! first, at most `paradedb.max_concurrent_merges` at a time and none during
+//! `paradedb.merge_quiet_windows`.  The mergers themselves write no faster than
+//! `paradedb.merge_io_rate_limit`.
+
+use crate::gucs;
+use crate::postgres::delete::VacuumSignal;
+use crate::postgres::merge::{MergeSlotVariant, launch_background_merger};
+use crate::postgres::rel::PgSearchRelation;
+use anyhow::Context;
+use pgrx::bgworkers::*;
+use pgrx::datum::TimestampWithTimeZone;
+use pgrx::lwlock::PgLwLock;
+use pgrx::shmem::PGRXSharedMemory;
+use pgrx::{check_for_interrupts, pg_guard, pg_shmem_init, pg_sys};
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// How many merges can be queued or running at once, across all databases.
+const QUEUE_CAPACITY: usize = 256;
+const NAME_LEN: usize = pg_sys::NAMEDATALEN as usize;
+/// How long a launched merger has to claim its queue entry before it's presumed to have failed to
+/// start, for instance because its database was dropped.
+const LAUNCH_TIMEOUT_USECS: pg_sys::TimestampTz = 60 * 1_000_000;
+/// How far a merger can fall behind its write rate before it stops catching up, which would
+/// otherwise let it write in a burst after spending a while reading.
+const IO_RATE_BURST: Duration = Duration::from_secs(1);
+
+/// How many bytes a merge writes between checks of its [`IoRateLimit`], one block's worth.
+const IO_RATE_CHUNK: u64 = pg_sys::BLCKSZ as u64;
+
+static MERGE_QUEUE: PgLwLock<MergeQueue> = unsafe { PgLwLock::new(c"pg_search_merge_queue") };
+
+/// Whether [`register`] set up [`MERGE_QUEUE`], which it only does while `shared_preload_libraries`
+/// are loaded.  Backends inherit it from the postmaster, and must not touch the queue without it.
+static QUEUE_REGISTERED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum EntryState {
+    Free,
+    Queued,
+    Running,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct QueueEntry {
+    state: EntryState,
+    database_oid: pg_sys::Oid,
+    index_oid: pg_sys::Oid,
+    variant: MergeSlotVariant,
+    /// The database the merger connects to, which the scheduler can't look up as it isn't
+    /// connected to any
+    database_name: [u8; NAME_LEN],
+    /// The schema-qualified name of the index, for the merger's name
+    index_name: [u8; 2 * NAME_LEN],
+    enqueued_at: pg_sys::TimestampTz,
+    started_at: pg_sys::TimestampTz,
+    /// The merger running this entry, once it has claimed it, or 0
+    pid: i32,
+}
+
+impl QueueEntry {
+    const FREE: Self = Self {
+        state: EntryState::Free,
+        database_oid: pg_sys::InvalidOid,
+        index_oid: pg_sys::InvalidOid,
+        variant: MergeSlotVariant::Small,
+        database_name: [0; NAME_LEN],
+        index_name: [0; 2 * NAME_LEN],
+        enqueued_at: 0,
+        started_at: 0,
+        pid: 0,
+    };
+
+    fn is_for(
+        &self,
+        database_oid: pg_sys::Oid,
+        index_oid: pg_sys::Oid,
+        variant: MergeSlotVariant,
+    ) -> bool {
+        self.state != EntryState::Free
+            && self.database_oid == database_oid
+            && self.index_oid == index_oid
+            && self.variant == variant
+    }
+
+    /// Whether the merger launched for this entry has exited, or never started.
+    unsafe fn is_abandoned(&self, now: pg_sys::TimestampTz) -> bool {
+        if self.state != EntryState::Running {
+            return false;
+        }
+        if self.pid == 0 {
+            now - self.started_at > LAUNCH_TIMEOUT_USECS
+        } else {
+            pg_sys::BackendPidGetProc(self.pid).is_null()
+        }
+    }
+}
+
+struct MergeQueue {
+    entries: [QueueEntry; QUEUE_CAPACITY],
+}
+
+impl Default for MergeQueue {
+    fn default() -> Self {
+        Self {
+            entries: [QueueEntry::FREE; QUEUE_CAPACITY],
+        }
+    }
+}
+
+unsafe impl PGRXSharedMemory for MergeQueue {}
+
+impl MergeQueue {
+    fn find_mut(
+        &mut self,
+        database_oid: pg_sys::Oid,
+        index_oid: pg_sys::Oid,
+        variant: MergeSlotVariant,
+    ) -> Option<&mut QueueEntry> {
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.is_for(database_oid, index_oid, variant))
+    }
+}
+
+/// Allocates the merge queue and registers the scheduler worker, if the scheduler is enabled.
+/// Must only be called while `shared_preload_libraries` are loaded.
+pub unsafe fn register() {
+    if !gucs::enable_merge_scheduler() {
+        return;
+    }
+
+    pg_shmem_init!(MERGE_QUEUE);
+    QUEUE_REGISTERED.store(true, Ordering::Relaxed);
+    BackgroundWorkerBuilder::new("pg_search merge scheduler")
+        .set_library("pg_search")
+        .set_function("merge_scheduler_main")
+        .enable_shmem_access(None)
+        .set_start_time(BgWorkerStartTime::RecoveryFinished)
+        .set_restart_time(Some(Duration::from_secs(10)))
+        .load();
+}
+
+/// Whether background merges go through the merge scheduler.
+pub fn is_enabled() -> bool {
+    QUEUE_REGISTERED.load(Ordering::Relaxed)
+}
+
+/// Queues a background merge of `index`, unless one of the same `variant` is already queued or
+/// running.  Returns `false` if there's no scheduler to queue it for, or its queue is full, in
+/// which case the caller launches the merger itself.
+pub unsafe fn enqueue(index: &PgSearchRelation, variant: MergeSlotVariant) -> bool {
+    if !is_enabled() {
+        return false;
+    }
+
+    let database_name = CStr::from_ptr(pg_sys::get_database_name(pg_sys::MyDatabaseId));
+    let index_name = format!("{}.{}", index.namespace(), index.name());
+    let now = pg_sys::GetCurrentTimestamp();
+
+    let mut queue = MERGE_QUEUE.exclusive();
+    if queue
+        .find_mut(pg_sys::MyDatabaseId, index.oid(), variant)
+        .is_some()
+    {
+        return true;
+    }
+    let Some(entry) = queue
+        .entries
+        .iter_mut()
+        .find(|entry| entry.state == EntryState::Free)
+    else {
+        drop(queue);
+        pgrx::log!(
+            "the merge queue is full, launching a background merge of {index_name} directly"
+        );
+        return false;
+    };
+
+    *entry = QueueEntry {
+        state: EntryState::Queued,
+        database_oid: pg_sys::MyDatabaseId,
+        index_oid: index.oid(),
+        variant,
+        database_name: to_name(database_name.to_bytes()),
+        index_name: to_name(index_name.as_bytes()),
+        enqueued_at: now,
+        started_at: 0,
+        pid: 0,
+    };
+    true
+}
+
+/// The queue entry of the merge this background merger was launched for, which is removed once
+/// it's dropped.
+pub struct QueueClaim {
+    index_oid: pg_sys::Oid,
+    variant: MergeSlotVariant,
+}
+
+/// Claims the queue entry of the merge of `index_oid` this background merger was launched for, so
+/// that the scheduler can tell when the merger exits.
+pub unsafe fn claim(index_oid: pg_sys::Oid, variant: MergeSlotVariant) -> Option<QueueClaim> {
+    if !is_enabled() {
+        return None;
+    }
+
+    let mut queue = MERGE_QUEUE.exclusive();
+    let entry = queue.find_mut(pg_sys::MyDatabaseId, index_oid, variant)?;
+    entry.pid = pg_sys::MyProcPid;
+    Some(QueueClaim { index_oid, variant })
+}
+
+impl Drop for QueueClaim {
+    fn drop(&mut self) {
+        let mut queue = MERGE_QUEUE.exclusive();
+        unsafe {
+            if let Some(entry) = queue.find_mut(pg_sys::MyDatabaseId, self.index_oid, self.variant)
+                && entry.pid == pg_sys::MyProcPid
+            {
+                *entry = QueueEntry::FREE;
+            }
+        }
+    }
+}
+
+/// A merge in the queue, as listed by `pdb.merge_queue()`.
+pub struct QueuedMerge {
+    pub database_name: String,
+    pub index_oid: pg_sys::Oid,
+    pub index_name: String,
+    pub layer: &'static str,
+    pub state: &'static str,
+    pub pid: Option<i32>,
+    pub enqueued_at: Option<TimestampWithTimeZone>,
+    pub started_at: Option<TimestampWithTimeZone>,
+}
+
+/// The merges that are queued or running, oldest first.
+pub fn queued_merges() -> Vec<QueuedMerge> {
+    if !is_enabled() {
+        return Vec::new();
+    }
+
+    let mut entries = MERGE_QUEUE
+        .share()
+        .entries
+        .iter()
+        .filter(|entry| entry.state != EntryState::Free)
+        .copied()
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.enqueued_at);
+
+    entries
+        .into_iter()
+        .map(|entry| QueuedMerge {
+            database_name: from_name(&entry.database_name),
+            index_oid: entry.index_oid,
+            index_name: from_name(&entry.index_name),
+            layer: match entry.variant {
+                MergeSlotVariant::Small => "small",
+                MergeSlotVariant::Large => "large",
+            },
+            state: match entry.state {
+                EntryState::Running => "running",
+                _ => "queued",
+            },
+            pid: (entry.pid != 0).then_some(entry.pid),
+            enqueued_at: TimestampWithTimeZone::try_from(entry.enqueued_at).ok(),
+            started_at: (entry.state == EntryState::Running)
+                .then(|| TimestampWithTimeZone::try_from(entry.started_at).ok())
+                .flatten(),
+        })
+        .collect()
+}
+
+#[pg_guard]
+#[unsafe(no_mangle)]
+pub extern "C-unwind" fn merge_scheduler_main(_arg: pg_sys::Datum) {
+    BackgroundWorker::attach_signal_handlers(SignalWakeFlags::SIGHUP | SignalWakeFlags::SIGTERM);
+
+    let mut quiet_windows = load_quiet_windows();
+    while BackgroundWorker::wait_latch(Some(gucs::merge_scheduler_naptime())) {
+        if BackgroundWorker::sighup_received() {
+            unsafe { pg_sys::ProcessConfigFile(pg_sys::GucContext::PGC_SIGHUP) };
+            quiet_windows = load_quiet_windows();
+        }
+        unsafe { schedule(&quiet_windows) };
+    }
+}
+
+/// Forgets the merges whose merger exited, then launches as many queued merges as it can, oldest
+/// first.
+unsafe fn schedule(quiet_windows: &[QuietWindow]) {
+    let now = pg_sys::GetCurrentTimestamp();
+    let is_quiet = minute_of_day(now)
+        .is_some_and(|minute| quiet_windows.iter().any(|window| window.contains(minute)));
+
+    loop {
+        let entry = {
+            let mut queue = MERGE_QUEUE.exclusive();
+            for entry in queue.entries.iter_mut() {
+                if entry.is_abandoned(now) {
+                    *entry = QueueEntry::FREE;
+                }
+            }
+            if is_quiet {
+                return;
+            }
+
+            let running = queue
+                .entries
+                .iter()
+                .filter(|entry| entry.state == EntryState::Running)
+                .count();
+            if running >= gucs::max_concurrent_merges() {
+                return;
+            }
+            let Some(entry) = queue
+                .entries
+                .iter_mut()
+                .filter(|entry| entry.state == EntryState::Queued)
+                .min_by_key(|entry| entry.enqueued_at)
+            else {
+                return;
+            };
+            entry.state = EntryState::Running;
+            entry.started_at = now;
+            *entry
+        };
+
+        // launched outside of the lock, as registering a worker takes locks of its own
+        let launched = launch_background_merger(
+            &from_name(&entry.database_name),
+            entry.index_oid,
+            entry.variant,
+            &format!("background merger for {}", from_name(&entry.index_name)),
+        );
+        if !launched {
+            // leave it queued until a worker frees up
+            let mut queue = MERGE_QUEUE.exclusive();
+            if let Some(entry) = queue.find_mut(entry.database_oid, entry.index_oid, entry.variant)
+            {
+                entry.state = EntryState::Queued;
+            }
+            return;
+        }
+    }
+}
+
+/// A daily window of time, in minutes since midnight, which wraps past midnight if it ends
+/// before it starts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct QuietWindow {
+    start: u32,
+    end: u32,
+}
+
+impl QuietWindow {
+    fn contains(&self, minute: u32) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minute)
+        } else {
+            minute >= self.start || minute < self.end
+        }
+    }
+}
+
+fn load_quiet_windows() -> Vec<QuietWindow> {
+    let setting = gucs::merge_quiet_windows();
+    parse_quiet_windows(&setting).unwrap_or_else(|e| {
+        pgrx::warning!("ignoring paradedb.merge_quiet_windows: {e:#}");
+        Vec::new()
+    })
+}
+
+/// Parses a comma-separated list of `HH:MM-HH:MM` windows.
+fn parse_quiet_windows(setting: &str) -> anyhow::Result<Vec<QuietWindow>> {
+    let parse_time = |time: &str| -> anyhow::Result<u32> {
+        let (hours, minutes) = time
+            .trim()
+            .split_once(':')
+            .with_context(|| format!("`{time}` is not a time of the form HH:MM"))?;
+        let hours = hours.parse::<u32>().ok().filter(|hours| *hours < 24);
+        let minutes = minutes.parse::<u32>().ok().filter(|minutes| *minutes < 60);
+        match (hours, minutes) {
+            (Some(hours), Some(minutes)) => Ok(hours * 60 + minutes),
+            _ => anyhow::bail!("`{time}` is not a time of the form HH:MM"),
+        }
+    };
+
+    setting
+        .split(',')
+        .filter(|window| !window.trim().is_empty())
+        .map(|window| {
+            let (start, end) = window
+                .split_once('-')
+                .with_context(|| format!("`{window}` is not a window of the form HH:MM-HH:MM"))?;
+            Ok(QuietWindow {
+                start: parse_time(start)?,
+                end: parse_time(end)?,
+            })
+        })
+        .collect()
+}
+
+/// The minute of the day `timestamp` falls on, in the server's `timezone`.
+unsafe fn minute_of_day(timestamp: pg_sys::TimestampTz) -> Option<u32> {
+    let mut tz = 0;
+    let mut tm: pg_sys::pg_tm = std::mem::zeroed();
+    let mut fsec = 0;
+    if pg_sys::timestamp2tm(
+        timestamp,
+        &mut tz,
+        &mut tm,
+        &mut fsec,
+        std::ptr::null_mut(),
+        std::ptr::null_mut(),
+    ) != 0
+    {
+        return None;
+    }
+    Some((tm.tm_hour * 60 + tm.tm_min) as u32)
+}
+
+/// Limits the rate a background merge writes at to `paradedb.merge_io_rate_limit`.
+///
+/// A merge is throttled as it writes its new segment, block by block, see
+/// [`limit_merge_writes`].  It holds the index's cleanup lock throughout, which `VACUUM` waits on,
+/// so it stops sleeping as soon as `VACUUM` is waiting.
+#[derive(Debug, Copy, Clone)]
+pub struct IoRateLimit {
+    bytes_per_sec: u64,
+    started_at: Instant,
+    bytes: u64,
+}
+
+/// The [`IoRateLimit`] of the background merge this process is running, and the bytes it has
+/// written since it was last throttled.
+struct MergeWriteLimit {
+    index_oid: pg_sys::Oid,
+    io_rate_limit: IoRateLimit,
+    unthrottled_bytes: u64,
+}
+
+thread_local! {
+    static MERGE_WRITE_LIMIT: RefCell<Option<MergeWriteLimit>> = const { RefCell::new(None) };
+}
+
+/// Throttles the segment writes of this process to `io_rate_limit` while the returned guard is
+/// alive, for a background merge of `index_oid`.
+pub fn limit_merge_writes(index_oid: pg_sys::Oid, io_rate_limit: IoRateLimit) -> MergeWriteGuard {
+    MERGE_WRITE_LIMIT.set(Some(MergeWriteLimit {
+        index_oid,
+        io_rate_limit,
+        unthrottled_bytes: 0,
+    }));
+    MergeWriteGuard(())
+}
+
+/// Stops throttling segment writes when it's dropped, see [`limit_merge_writes`].
+pub struct MergeWriteGuard(());
+
+impl Drop for MergeWriteGuard {
+    fn drop(&mut self) {
+        MERGE_WRITE_LIMIT.set(None);
+    }
+}
+
+/// Accounts for `nbytes` just written to a segment, sleeping once every [`IO_RATE_CHUNK`] bytes for
+/// as long as the background merge this process is running has written faster than its limit.
+pub fn throttle_merge_write(nbytes: usize) {
+    MERGE_WRITE_LIMIT.with_borrow_mut(|limit| {
+        let Some(limit) = limit.as_mut() else {
+            return;
+        };
+        limit.unthrottled_bytes += nbytes as u64;
+        if limit.unthrottled_bytes < IO_RATE_CHUNK {
+            return;
+        }
+        let index_oid = limit.index_oid;
+        unsafe {
+            limit.io_rate_limit.throttle(limit.unthrottled_bytes, || {
+                VacuumSignal::new(index_oid).wants_cancel()
+            });
+        }
+        limit.unthrottled_bytes = 0;
+    });
+}
+
+impl IoRateLimit {
+    /// The limit of a background merge, if `paradedb.merge_io_rate_limit` is set.
+    pub fn for_background_merge() -> Option<Self> {
+        let bytes_per_sec = gucs::merge_io_rate_limit();
+        (bytes_per_sec > 0).then(|| Self::new(bytes_per_sec))
+    }
+
+    fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec,
+            started_at: Instant::now(),
+            bytes: 0,
+        }
+    }
+
+    /// Accounts for `nbytes` just written, sleeping for as long as the merge has written faster
+    /// than its limit, or until `stop()` says it should stop merging.
+    unsafe fn throttle(&mut self, nbytes: u64, stop: impl Fn() -> bool) {
+        self.bytes += nbytes;
+        let due = Duration::from_secs_f64(self.bytes as f64 / self.bytes_per_sec as f64);
+        let mut elapsed = self.started_at.elapsed();
+        if elapsed > due + IO_RATE_BURST {
+            *self = Self::new(self.bytes_per_sec);
+            return;
+        }
+
+        while elapsed < due && !stop() {
+            let timeout = (due - elapsed).min(Duration::from_millis(100));
+            pg_sys::WaitLatch(
+                pg_sys::MyLatch,
+                (pg_sys::WL_LATCH_SET | pg_sys::WL_TIMEOUT | pg_sys::WL_EXIT_ON_PM_DEATH) as i32,
+                timeout.as_millis() as _,
+                pg_sys::PG_WAIT_EXTENSION,
+            );
+            pg_sys::ResetLatch(pg_sys::MyLatch);
+            check_for_interrupts!();
+            elapsed = self.started_at.elapsed();
+        }
+    }
+}
+
+/// Copies `bytes` into a NUL-terminated name, truncating it to fit.
+fn to_name<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    let mut name = [0; N];
+    let len = bytes.len().min(N - 1);
+    name[..len].copy_from_slice(&bytes[..len]);
+    name
+}
+
+fn from_name(name: &[u8]) -> String {
+    let len = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+    String::from_utf8_lossy(&name[..len]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_quiet_windows() {
+        assert_eq!(parse_quiet_windows("").unwrap(), vec![]);
+        assert_eq!(
+            parse_quiet_windows("09:00-17:30, 22:00-06:00").unwrap(),
+            vec![
+                QuietWindow {
+                    start: 9 * 60,
+                    end: 17 * 60 + 30
+                },
+                QuietWindow {
+                    start: 22 * 60,
+                    end: 6 * 60
+                },
+            ]
+        );
+        assert!(parse_quiet_windows("09:00").is_err());
+        assert!(parse_quiet_windows("24:00-06:00").is_err());
+        assert!(parse_quiet_windows("09:00-17:60").is_err());
+    }
+
+    #[test]
+    fn test_quiet_window_contains() {
+        let daytime = QuietWindow {
+            start: 9 * 60,
+            end: 17 * 60,
+        };
+        assert!(daytime.contains(9 * 60));
+        assert!(daytime.contains(12 * 60));
+        assert!(!daytime.contains(17 * 60));
+        assert!(!daytime.contains(0));
+
+        let overnight = QuietWindow {
+            start: 22 * 60,
+            end: 6 * 60,
+        };
+        assert!(overnight.contains(23 * 60));
+        assert!(overnight.contains(0));
+        assert!(!overnight.contains(6 * 60));
+        assert!(!overnight.contains(12 * 60));
+    }
+
+    #[test]
+    fn test_names() {
+        let name = to_name::<8>(b"pg_search");
+        assert_eq!(&name, b"pg_sear\0");
+        assert_eq!(from_name(&name), "pg_sear");
+        assert_eq!(from_name(&to_name::<8>(b"db")), "db");
+    }
+}
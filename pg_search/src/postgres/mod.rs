@@ -20,10 +20,12 @@ use std::collections::BTreeMap;
 use std::io::Write;
 use std::ops::Range;
 
+use crate::api::HashSet;
 use crate::gucs;
 use crate::index::mvcc::{MutableSegmentBound, SegmentView, SegmentViewDocs, SegmentViewEntry};
 use crate::postgres::build::is_bm25_index;
 use crate::postgres::condition_variable::ConditionVariable;
+use crate::postgres::customscan::qual_inspect::choose_bm25_index;
 use crate::postgres::locks::Spinlock;
 use crate::postgres::rel::PgSearchRelation;
 use crate::postgres::shared_threshold::ParallelScanThresholdState;
@@ -39,8 +41,10 @@ mod cost;
 mod delete;
 pub mod deparse;
 pub mod insert;
-mod merge;
+pub mod merge;
+pub mod merge_scheduler;
 pub mod options;
+pub mod progress;
 mod ps_status;
 mod range;
 mod scan;
@@ -112,6 +116,7 @@ fn bm25_handler(_fcinfo: pg_sys::FunctionCallInfo) -> PgBox<pg_sys::IndexAmRouti
     amroutine.amvalidate = Some(validate::amvalidate);
     amroutine.ambuild = Some(build::ambuild);
     amroutine.ambuildempty = Some(build::ambuildempty);
+    amroutine.ambuildphasename = Some(progress::ambuildphasename);
     amroutine.aminsert = Some(insert::aminsert);
     #[cfg(any(feature = "pg17", feature = "pg18"))]
     {
@@ -137,27 +142,52 @@ fn bm25_handler(_fcinfo: pg_sys::FunctionCallInfo) -> PgBox<pg_sys::IndexAmRouti
     amroutine.into_pg_boxed()
 }
 
-/// Finds and returns the `USING paradedb` index on the specified relation with the highest OID,
+/// Finds and returns every `USING paradedb` index on the specified relation, highest OID first,
 /// along with the heap relation. Returns [`None`] if there isn't one.
 ///
 /// Filters out indexes that aren't yet `indisvalid` (e.g. mid-`CREATE INDEX CONCURRENTLY`
-/// or a failed `REINDEX`). When more than one valid bm25 index exists on the relation
-/// (only possible via `CREATE INDEX CONCURRENTLY`, which bypasses the single-bm25-index
-/// check), the highest-OID one is chosen so that the index added most recently wins.
-pub fn rel_get_bm25_index(
+/// or a failed `REINDEX`).  Callers that know which fields a query refers to should choose among
+/// them with [`rel_get_bm25_index_with_fields`] or
+/// [`customscan::qual_inspect::choose_rel_bm25_index`].
+pub fn rel_get_bm25_indexes(
     relid: pg_sys::Oid,
-) -> Option<(rel::PgSearchRelation, rel::PgSearchRelation)> {
+) -> Option<(rel::PgSearchRelation, Vec<rel::PgSearchRelation>)> {
     if relid == pg_sys::Oid::INVALID {
         return None;
     }
 
     let rel = PgSearchRelation::with_lock(relid, pg_sys::AccessShareLock as _);
-    let index = unsafe {
+    let mut indexes = unsafe {
         rel.indices(pg_sys::AccessShareLock as _)
             .filter(|index| pg_sys::get_index_isvalid(index.oid()) && is_bm25_index(index))
-            .max_by_key(|i| i.oid().to_u32())?
+            .collect::<Vec<_>>()
+    };
+    if indexes.is_empty() {
+        return None;
+    }
+    indexes.sort_by_key(|index| std::cmp::Reverse(index.oid().to_u32()));
+    Some((rel, indexes))
+}
+
+/// Finds the `USING paradedb` index on the specified relation that has the most of `field_names`,
+/// see [`choose_bm25_index`], along with the heap relation.  Returns [`None`] if there isn't one.
+pub fn rel_get_bm25_index_with_fields(
+    relid: pg_sys::Oid,
+    field_names: &HashSet<String>,
+) -> Option<(rel::PgSearchRelation, rel::PgSearchRelation)> {
+    let (rel, indexes) = rel_get_bm25_indexes(relid)?;
+    Some((rel, choose_bm25_index(indexes, field_names)))
+}
+
+/// How many of `field_names` does `index` have a field for?
+pub fn index_field_count(index: &PgSearchRelation, field_names: &HashSet<String>) -> usize {
+    let Ok(schema) = index.schema() else {
+        return 0;
     };
-    Some((rel, index))
+    field_names
+        .iter()
+        .filter(|field_name| schema.search_field(field_name).is_some())
+        .count()
 }
 
 // 16 bytes for segment UUID
@@ -15,17 +15,21 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::cell::{Ref, RefCell};
+use std::cell::{Cell, Ref, RefCell};
 use std::ffi::CStr;
 use std::num::NonZeroUsize;
 use std::rc::Rc;
 
 use crate::api::{FieldName, HashMap};
 use crate::gucs;
+use crate::nodecast;
 use crate::postgres::utils::{ExtractedFieldAttribute, extract_field_attributes};
+use crate::query::similarity::Similarity;
 use crate::schema::IndexRecordOption;
 use crate::schema::{SearchFieldConfig, SearchFieldType};
+use crate::vector::quantize::VectorQuantization;
 
+use crate::api::tokenizers::TokenizerTables;
 use crate::api::tokenizers::search_field_config_from_type;
 use crate::gucs::{global_enable_background_merging, global_target_segment_count};
 use anyhow::Result;
@@ -77,13 +81,73 @@ pub(crate) const DEFAULT_CENTROID_RATIO: f64 = 0.01;
 pub(crate) const DEFAULT_TRAINING_SAMPLES_PER_CENTROID: usize = 32;
 pub(crate) const DEFAULT_CLUSTER_REPLICATION: i32 = 1;
 
+thread_local! {
+    /// Whether an `ALTER INDEX ... SET` is running.  Its options are validated by the same
+    /// functions as those of `CREATE INDEX`.
+    static ALTERING_INDEX_OPTIONS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Restores [`ALTERING_INDEX_OPTIONS`] when a utility statement ends, however it ends.
+struct AlteringIndexOptions(bool);
+
+impl Drop for AlteringIndexOptions {
+    fn drop(&mut self) {
+        ALTERING_INDEX_OPTIONS.set(self.0);
+    }
+}
+
+/// Whether `stmt` is an `ALTER INDEX ... SET (...)`.
+unsafe fn sets_index_options(stmt: *mut pg_sys::Node) -> bool {
+    let Some(stmt) = nodecast!(AlterTableStmt, T_AlterTableStmt, stmt) else {
+        return false;
+    };
+    PgList::<pg_sys::AlterTableCmd>::from_pg((*stmt).cmds)
+        .iter_ptr()
+        .any(|cmd| (*cmd).subtype == pg_sys::AlterTableType::AT_SetRelOptions)
+}
+
+/// The analyzers and tables that tokenizers refer to are only read when an index is built, see
+/// [`TokenizerTables`], so an index can't be altered to use a tokenizer that refers to one.
+fn reject_tables_in_alter_index(tokenizer: &SearchTokenizer) {
+    if !ALTERING_INDEX_OPTIONS.get() {
+        return;
+    }
+    if let Some(what) = TokenizerTables::read_at_build(tokenizer) {
+        ErrorReport::new(
+            PgSqlErrorCode::ERRCODE_FEATURE_NOT_SUPPORTED,
+            format!("ALTER INDEX cannot change a tokenizer to one that uses {what}"),
+            function_name!(),
+        )
+        .set_hint(
+            "An index only reads analyzers, tables and dictionaries when it's built.  Create a new index with the tokenizer instead",
+        )
+        .report(PgLogLevel::ERROR);
+    }
+}
+
+fn reject_field_tables_in_alter_index(configs: &HashMap<FieldName, SearchFieldConfig>) {
+    for config in configs.values() {
+        config
+            .tokenizer()
+            .into_iter()
+            .for_each(reject_tables_in_alter_index);
+        config
+            .search_tokenizer()
+            .into_iter()
+            .for_each(reject_tables_in_alter_index);
+    }
+}
+
 #[pg_guard]
 extern "C-unwind" fn validate_text_fields(value: *const std::os::raw::c_char) {
     let json_str = cstr_to_rust_str(value);
     if json_str.is_empty() {
         return;
     }
-    deserialize_config_fields(json_str, &SearchFieldConfig::text_from_json);
+    reject_field_tables_in_alter_index(&deserialize_config_fields(
+        json_str,
+        &SearchFieldConfig::text_from_json,
+    ));
 }
 
 #[pg_guard]
@@ -119,7 +183,10 @@ extern "C-unwind" fn validate_json_fields(value: *const std::os::raw::c_char) {
     if json_str.is_empty() {
         return;
     }
-    deserialize_config_fields(json_str, &SearchFieldConfig::json_from_json);
+    reject_field_tables_in_alter_index(&deserialize_config_fields(
+        json_str,
+        &SearchFieldConfig::json_from_json,
+    ));
 }
 
 #[pg_guard]
@@ -180,8 +247,9 @@ extern "C-unwind" fn validate_search_tokenizer(value: *const std::os::raw::c_cha
     if s.is_empty() {
         return;
     }
-    crate::api::tokenizers::tokenizer_from_expression(&s)
+    let tokenizer = crate::api::tokenizers::tokenizer_from_expression(&s)
         .unwrap_or_else(|| panic!("invalid search_tokenizer: '{s}'"));
+    reject_tables_in_alter_index(&tokenizer);
 }
 
 /// The only legal `bounds_scope`: the merge folds centroid bounds over a
@@ -201,6 +269,13 @@ extern "C-unwind" fn validate_bounds_scope(value: *const std::os::raw::c_char) {
     }
 }
 
+#[pg_guard]
+extern "C-unwind" fn validate_vector_quantization(value: *const std::os::raw::c_char) {
+    if let Err(e) = VectorQuantization::from_option(&cstr_to_rust_str(value)) {
+        panic!("{e}");
+    }
+}
+
 #[pg_guard]
 extern "C-unwind" fn validate_layer_sizes(value: *const std::os::raw::c_char) {
     if value.is_null() {
@@ -240,7 +315,7 @@ fn cstr_to_rust_str(value: *const std::os::raw::c_char) -> String {
         .to_string()
 }
 
-const NUM_REL_OPTS: usize = 19;
+const NUM_REL_OPTS: usize = 20;
 #[pg_guard]
 pub unsafe extern "C-unwind" fn amoptions(
     reloptions: pg_sys::Datum,
@@ -382,6 +457,13 @@ pub unsafe extern "C-unwind" fn amoptions(
             #[cfg(feature = "pg18")]
             isset_offset: 0,
         },
+        pg_sys::relopt_parse_elt {
+            optname: "vector_quantization".as_pg_cstr(),
+            opttype: pg_sys::relopt_type::RELOPT_TYPE_STRING,
+            offset: std::mem::offset_of!(BM25IndexOptionsData, vector_quantization_offset) as i32,
+            #[cfg(feature = "pg18")]
+            isset_offset: 0,
+        },
     ];
     build_relopts(reloptions, validate, options)
 }
@@ -475,6 +557,10 @@ impl BM25IndexOptions {
         self.options_data().bounds_scope()
     }
 
+    pub fn vector_quantization(&self) -> Option<VectorQuantization> {
+        self.options_data().vector_quantization()
+    }
+
     pub fn training_samples_per_centroid(&self) -> usize {
         self.options_data().training_samples_per_centroid()
     }
@@ -835,6 +921,7 @@ struct BM25IndexOptionsData {
     cluster_replication: i32,
     partition_by_offset: i32,
     bounds_scope_offset: i32,
+    vector_quantization_offset: i32,
 }
 
 impl BM25IndexOptionsData {
@@ -892,6 +979,13 @@ impl BM25IndexOptionsData {
         self.training_samples_per_centroid.max(1) as usize
     }
 
+    /// How vector fields are quantized, validated at option-set time.  `None` keeps them at
+    /// full precision in the index's vector storage.
+    pub fn vector_quantization(&self) -> Option<VectorQuantization> {
+        let value = self.get_str(self.vector_quantization_offset, "".to_string());
+        VectorQuantization::from_option(&value).unwrap_or_else(|e| panic!("{e}"))
+    }
+
     /// Total cells a vector is written into (SPANN `ReplicaCount`): the primary
     /// plus up to `cluster_replication - 1` next-nearest cells, selected by
     /// tantivy at merge time in the field's metric. `1` is primary-only. Any
@@ -1027,6 +1121,34 @@ impl BM25IndexOptionsData {
 
 // it adds the tokenizer option to the list of relation options so we can parse it in amoptions
 pub unsafe fn init() {
+    static mut PREV_PROCESS_UTILITY_HOOK: pg_sys::ProcessUtility_hook_type = None;
+    PREV_PROCESS_UTILITY_HOOK = pg_sys::ProcessUtility_hook;
+    pg_sys::ProcessUtility_hook = Some(process_utility_hook);
+
+    #[allow(clippy::too_many_arguments)]
+    #[rustfmt::skip]
+    #[pg_guard]
+    unsafe extern "C-unwind" fn process_utility_hook(
+        pstmt: *mut pg_sys::PlannedStmt,
+        query_string: *const ::core::ffi::c_char,
+        read_only_tree: bool,
+        context: pg_sys::ProcessUtilityContext::Type,
+        params: pg_sys::ParamListInfo,
+        query_env: *mut pg_sys::QueryEnvironment,
+        dest: *mut pg_sys::DestReceiver,
+        qc: *mut pg_sys::QueryCompletion,
+    ) {
+        let _altering = AlteringIndexOptions(
+            ALTERING_INDEX_OPTIONS.replace(sets_index_options((*pstmt).utilityStmt)),
+        );
+
+        if let Some(prev_hook) = PREV_PROCESS_UTILITY_HOOK {
+            prev_hook(pstmt, query_string, read_only_tree, context, params, query_env, dest, qc);
+        } else {
+            pg_sys::standard_ProcessUtility(pstmt, query_string, read_only_tree, context, params, query_env, dest, qc);
+        }
+    }
+
     // adding our own relopt type because zombodb does, but one of the built-in Postgres ones might be more appropriate
     RELOPT_KIND_PDB = pg_sys::add_reloption_kind();
     pg_sys::add_string_reloption(
@@ -1144,6 +1266,14 @@ pub unsafe fn init() {
         Some(validate_bounds_scope),
         pg_sys::AccessExclusiveLock as pg_sys::LOCKMODE,
     );
+    pg_sys::add_string_reloption(
+        RELOPT_KIND_PDB,
+        "vector_quantization".as_pg_cstr(),
+        "How vector fields are quantized: 'none', 'int8' or 'binary'".as_pg_cstr(),
+        std::ptr::null(),
+        Some(validate_vector_quantization),
+        pg_sys::AccessExclusiveLock as pg_sys::LOCKMODE,
+    );
     pg_sys::add_real_reloption(
         RELOPT_KIND_PDB,
         "centroid_ratio".as_pg_cstr(),
@@ -1381,6 +1511,7 @@ fn key_field_config(field_type: SearchFieldType) -> SearchFieldConfig {
                 column: None,
                 k1: None,
                 b: None,
+                similarity: Similarity::Bm25,
             }
         }
         SearchFieldType::Ltree(_) => SearchFieldConfig::Facet,
@@ -1404,6 +1535,7 @@ fn key_field_config(field_type: SearchFieldType) -> SearchFieldConfig {
             column: None,
             k1: None,
             b: None,
+            similarity: Similarity::Bm25,
         },
         SearchFieldType::Range(_) => SearchFieldConfig::Range { fast: true },
         SearchFieldType::Bool(_) => SearchFieldConfig::Boolean {
@@ -1414,7 +1546,7 @@ fn key_field_config(field_type: SearchFieldType) -> SearchFieldConfig {
             indexed: true,
             fast: true,
         },
-        SearchFieldType::Vector(_, _, _) => {
+        SearchFieldType::Vector(_, _, _) | SearchFieldType::MultiVector(_, _) => {
             panic!("vector fields cannot be used as key fields")
         }
     }
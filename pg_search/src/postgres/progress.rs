@@ -0,0 +1,153 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Progress reporting for index builds and background merges.
+//!
+//! Builds report through `pg_stat_progress_create_index`, whose `phase` column names the build
+//! phases below through [`ambuildphasename`].  Background merges have no progress view of their
+//! own in Postgres, so they report as a `CREATE INDEX` command with no `command`, which
+//! `pdb.index_progress` tells apart by [`PROGRESS_PDB_COMMAND`].  Counters that
+//! `pg_stat_progress_create_index` has no column for go in the parameters it doesn't use.
+
+use crate::postgres::rel::PgSearchRelation;
+use pgrx::{pg_guard, pg_sys};
+use std::cell::Cell;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+/// How many segments have been written by a build, or merged by a merge.
+pub const PROGRESS_PDB_SEGMENTS_DONE: i32 = 17;
+/// How many segments a merge is going to merge.
+pub const PROGRESS_PDB_SEGMENTS_TOTAL: i32 = 18;
+/// What a backend is doing to a ParadeDB index, beyond the `CREATE INDEX` commands.
+pub const PROGRESS_PDB_COMMAND: i32 = 19;
+
+const PROGRESS_PDB_COMMAND_MERGE: i64 = 1;
+
+/// The phases of a build, which are `pg_stat_progress_create_index`'s subphases of
+/// `building index`.  Numbering starts at 2, after Postgres' own "initializing" subphase.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(i64)]
+pub enum BuildPhase {
+    ScanningTable = 2,
+    TrainingCentroids = 3,
+    MergingSegments = 4,
+    FinalMerge = 5,
+}
+
+impl BuildPhase {
+    fn name(self) -> &'static CStr {
+        match self {
+            BuildPhase::ScanningTable => c"scanning table",
+            BuildPhase::TrainingCentroids => c"training centroids",
+            BuildPhase::MergingSegments => c"merging segments",
+            BuildPhase::FinalMerge => c"final merge",
+        }
+    }
+
+    fn from_i64(phasenum: i64) -> Option<Self> {
+        match phasenum {
+            2 => Some(BuildPhase::ScanningTable),
+            3 => Some(BuildPhase::TrainingCentroids),
+            4 => Some(BuildPhase::MergingSegments),
+            5 => Some(BuildPhase::FinalMerge),
+            _ => None,
+        }
+    }
+}
+
+thread_local! {
+    /// The phase last reported by this backend, so that training centroids, which happens in the
+    /// middle of other phases, can go back to it.
+    static CURRENT_PHASE: Cell<Option<BuildPhase>> = const { Cell::new(None) };
+}
+
+#[pg_guard]
+pub unsafe extern "C-unwind" fn ambuildphasename(phasenum: i64) -> *mut c_char {
+    match BuildPhase::from_i64(phasenum) {
+        Some(phase) => phase.name().as_ptr() as *mut c_char,
+        None => std::ptr::null_mut(),
+    }
+}
+
+pub fn set_phase(phase: BuildPhase) {
+    CURRENT_PHASE.set(Some(phase));
+    update(pg_sys::PROGRESS_CREATEIDX_SUBPHASE as i32, phase as i64);
+}
+
+/// Reports that vector centroids are being trained until dropped, then goes back to the phase
+/// that was being reported beforehand.
+pub struct TrainingCentroids(Option<BuildPhase>);
+
+impl TrainingCentroids {
+    pub fn start() -> Self {
+        let previous = CURRENT_PHASE.get();
+        set_phase(BuildPhase::TrainingCentroids);
+        Self(previous)
+    }
+}
+
+impl Drop for TrainingCentroids {
+    fn drop(&mut self) {
+        if let Some(phase) = self.0 {
+            set_phase(phase);
+        }
+    }
+}
+
+/// Sets a progress parameter, if this backend is reporting the progress of a `CREATE INDEX`
+/// command.  The merges and vector training that happen during other commands, like `VACUUM`,
+/// must not overwrite their progress.
+pub fn update(param: i32, value: i64) {
+    unsafe {
+        let beentry = pg_sys::MyBEEntry;
+        if beentry.is_null()
+            || (*beentry).st_progress_command
+                != pg_sys::ProgressCommandType::PROGRESS_COMMAND_CREATE_INDEX
+        {
+            return;
+        }
+        pg_sys::pgstat_progress_update_param(param, value);
+    }
+}
+
+/// Starts reporting a background merge of `indexrel`, until [`end_background_merge`].
+pub unsafe fn start_background_merge(indexrel: &PgSearchRelation) {
+    pg_sys::pgstat_progress_start_command(
+        pg_sys::ProgressCommandType::PROGRESS_COMMAND_CREATE_INDEX,
+        (*indexrel.rd_index).indrelid,
+    );
+    update(PROGRESS_PDB_COMMAND, PROGRESS_PDB_COMMAND_MERGE);
+    update(
+        pg_sys::PROGRESS_CREATEIDX_INDEX_OID as i32,
+        indexrel.oid().to_u32() as i64,
+    );
+    update(
+        pg_sys::PROGRESS_CREATEIDX_ACCESS_METHOD_OID as i32,
+        (*indexrel.rd_rel).relam.to_u32() as i64,
+    );
+    update(
+        pg_sys::PROGRESS_CREATEIDX_PHASE as i32,
+        pg_sys::PROGRESS_CREATEIDX_PHASE_BUILD as i64,
+    );
+    set_phase(BuildPhase::MergingSegments);
+}
+
+pub unsafe fn end_background_merge() {
+    CURRENT_PHASE.set(None);
+    pg_sys::pgstat_progress_end_command();
+}
@@ -438,6 +438,14 @@ impl SegmentMetaEntry {
         matches!(&self.content, SegmentMetaEntryContent::Mutable(_))
     }
 
+    /// Whether this segment stores its vectors clustered (IVF), with trained centroids.
+    pub fn has_vector_centroids(&self) -> bool {
+        matches!(
+            &self.content,
+            SegmentMetaEntryContent::Immutable(content) if content.centroids.is_some()
+        )
+    }
+
     /// If this is a mutable segment which is not frozen, add the given items; otherwise, return an
     /// error.
     pub fn mutable_add_items(
@@ -15,38 +15,370 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+//! pg_search's custom WAL resource manager.
+//!
+//! Every change to an index's pages is WAL-logged through Postgres' generic WAL (see
+//! [`super::xlog`]), whose redo a standby already knows how to apply.  The records here describe
+//! what those page changes mean, for `pg_waldump` and `pg_get_wal_records_info()`, and carry what a
+//! hot standby needs beyond the pages themselves: a standby's readers pin the segments they read,
+//! just like on the primary, and the primary can't see those pins, so before the blocks of a
+//! recycled segment can be reused the standby waits for its readers to release them.
+
+use crate::postgres::rel::PgSearchRelation;
 use pgrx::{pg_guard, pg_sys};
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
+use tantivy::index::SegmentId;
 
 // see https://wiki.postgresql.org/wiki/CustomWALResourceManagers
 pub const RMGR_ID: pg_sys::RmgrId = 137;
 const RMGR_NAME: &CStr = c"pg_search";
 
+/// An index was built.  Its pages were logged as full page images.
 const XLOG_PG_SEARCH_INIT_INDEX: u8 = 0x10;
 const XLOG_PG_SEARCH_INIT_INDEX_NAME: &CStr = c"INIT_INDEX";
 
+/// Segments were created, by a commit or a merge, deleted, by a merge, and/or had their deletes
+/// changed, by a vacuum.
+const XLOG_PG_SEARCH_SEGMENTS: u8 = 0x20;
+const XLOG_PG_SEARCH_SEGMENTS_NAME: &CStr = c"SEGMENTS";
+
+/// Deleted segments were garbage collected, and their blocks returned to the FSM.
+const XLOG_PG_SEARCH_RECYCLE: u8 = 0x30;
+const XLOG_PG_SEARCH_RECYCLE_NAME: &CStr = c"RECYCLE";
+
+/// The metapage was changed, see [`MetaPageChange`].
+const XLOG_PG_SEARCH_METAPAGE: u8 = 0x40;
+const XLOG_PG_SEARCH_METAPAGE_NAME: &CStr = c"METAPAGE";
+
+/// Blocks were returned to the FSM, to be reused once a transaction id has passed.
+const XLOG_PG_SEARCH_FSM: u8 = 0x50;
+const XLOG_PG_SEARCH_FSM_NAME: &CStr = c"FSM";
+
+/// The index a record is about, as its tablespace, database, and relfilenumber.
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+#[repr(C)]
+struct RecordRelation {
+    spc_oid: u32,
+    db_oid: u32,
+    rel_number: u32,
+}
+
+impl RecordRelation {
+    fn new(indexrel: &PgSearchRelation) -> Self {
+        #[cfg(feature = "pg15")]
+        let locator = indexrel.rd_node;
+        #[cfg(feature = "pg15")]
+        let (spc_oid, db_oid, rel_number) = (locator.spcNode, locator.dbNode, locator.relNode);
+
+        #[cfg(any(feature = "pg16", feature = "pg17", feature = "pg18"))]
+        let locator = indexrel.rd_locator;
+        #[cfg(any(feature = "pg16", feature = "pg17", feature = "pg18"))]
+        let (spc_oid, db_oid, rel_number) = (locator.spcOid, locator.dbOid, locator.relNumber);
+
+        Self {
+            spc_oid: spc_oid.to_u32(),
+            db_oid: db_oid.to_u32(),
+            rel_number: rel_number.to_u32(),
+        }
+    }
+
+    unsafe fn read_buffer(&self, blockno: pg_sys::BlockNumber) -> pg_sys::Buffer {
+        #[cfg(feature = "pg15")]
+        let locator = pg_sys::RelFileNode {
+            spcNode: self.spc_oid.into(),
+            dbNode: self.db_oid.into(),
+            relNode: self.rel_number.into(),
+        };
+
+        #[cfg(any(feature = "pg16", feature = "pg17", feature = "pg18"))]
+        let locator = pg_sys::RelFileLocator {
+            spcOid: self.spc_oid.into(),
+            dbOid: self.db_oid.into(),
+            relNumber: self.rel_number.into(),
+        };
+
+        pg_sys::XLogReadBufferExtended(
+            locator,
+            pg_sys::ForkNumber::MAIN_FORKNUM,
+            blockno,
+            pg_sys::ReadBufferMode::RBM_NORMAL,
+            pg_sys::InvalidBuffer as _,
+        )
+    }
+}
+
+impl std::fmt::Display for RecordRelation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "rel {}/{}/{}",
+            self.spc_oid, self.db_oid, self.rel_number
+        )
+    }
+}
+
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+#[repr(C)]
+struct SegmentsHeader {
+    relation: RecordRelation,
+    ncreated: u32,
+    ndeleted: u32,
+    nmodified: u32,
+}
+
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+#[repr(C)]
+struct RecycleHeader {
+    relation: RecordRelation,
+    nblocks: u32,
+}
+
+/// What a [`XLOG_PG_SEARCH_METAPAGE`] record changed, and the block numbers or counters it
+/// changed them to.
+#[derive(Copy, Clone, Debug)]
+pub enum MetaPageChange {
+    /// Lists that indexes built by older versions don't have were created
+    InitLists {
+        vacuum_list: pg_sys::BlockNumber,
+        ambulkdelete_sentinel: pg_sys::BlockNumber,
+        merge_lock: pg_sys::BlockNumber,
+        fsm: pg_sys::BlockNumber,
+    },
+    /// `pdb.add_field()` or `pdb.drop_field()` pointed the metapage at new schema lists
+    SetSchema {
+        schema: pg_sys::BlockNumber,
+        schema_changes: pg_sys::BlockNumber,
+        xid: pg_sys::TransactionId,
+    },
+    /// The outcome of the last schema change was resolved, leaving these lists in use
+    ResolveSchema {
+        schema: pg_sys::BlockNumber,
+        schema_changes: pg_sys::BlockNumber,
+        committed: bool,
+    },
+    /// `ambulkdelete()` bumped its epoch
+    AmbulkdeleteEpoch { epoch: u32 },
+}
+
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+#[repr(C)]
+struct MetaPageRecord {
+    relation: RecordRelation,
+    change: u32,
+    values: [u32; 4],
+}
+
+impl MetaPageChange {
+    const INIT_LISTS: u32 = 1;
+    const SET_SCHEMA: u32 = 2;
+    const RESOLVE_SCHEMA: u32 = 3;
+    const AMBULKDELETE_EPOCH: u32 = 4;
+
+    fn encode(self) -> (u32, [u32; 4]) {
+        match self {
+            MetaPageChange::InitLists {
+                vacuum_list,
+                ambulkdelete_sentinel,
+                merge_lock,
+                fsm,
+            } => (
+                Self::INIT_LISTS,
+                [vacuum_list, ambulkdelete_sentinel, merge_lock, fsm],
+            ),
+            MetaPageChange::SetSchema {
+                schema,
+                schema_changes,
+                xid,
+            } => (
+                Self::SET_SCHEMA,
+                [schema, schema_changes, xid.into_inner(), 0],
+            ),
+            MetaPageChange::ResolveSchema {
+                schema,
+                schema_changes,
+                committed,
+            } => (
+                Self::RESOLVE_SCHEMA,
+                [schema, schema_changes, committed as u32, 0],
+            ),
+            MetaPageChange::AmbulkdeleteEpoch { epoch } => {
+                (Self::AMBULKDELETE_EPOCH, [epoch, 0, 0, 0])
+            }
+        }
+    }
+
+    fn describe(change: u32, values: [u32; 4]) -> Option<String> {
+        let [a, b, c, d] = values;
+        match change {
+            Self::INIT_LISTS => Some(format!(
+                "init lists; vacuum list {a}, ambulkdelete sentinel {b}, merge lock {c}, fsm {d}"
+            )),
+            Self::SET_SCHEMA => Some(format!(
+                "set schema; schema {a}, schema changes {b}, by xid {c}"
+            )),
+            Self::RESOLVE_SCHEMA => Some(format!(
+                "resolve schema; {}, schema {a}, schema changes {b}",
+                if c != 0 { "committed" } else { "aborted" }
+            )),
+            Self::AMBULKDELETE_EPOCH => Some(format!("ambulkdelete epoch {a}")),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+#[repr(C)]
+struct FsmRecord {
+    when_recyclable: u64,
+    relation: RecordRelation,
+    nblocks: u32,
+}
+
+/// Splits a record's payload into its header and the array that follows it.
+fn split_payload<H: bytemuck::Pod, T: bytemuck::Pod>(payload: &[u8]) -> Option<(H, Vec<T>)> {
+    let header_len = size_of::<H>();
+    if payload.len() < header_len {
+        return None;
+    }
+    let header = bytemuck::pod_read_unaligned::<H>(&payload[..header_len]);
+    let items = payload[header_len..]
+        .chunks_exact(size_of::<T>())
+        .map(bytemuck::pod_read_unaligned::<T>)
+        .collect();
+    Some((header, items))
+}
+
+unsafe fn record_payload<'a>(record: *mut pg_sys::XLogReaderState) -> &'a [u8] {
+    let decoded = (*record).record;
+    if (*decoded).main_data.is_null() {
+        return &[];
+    }
+    std::slice::from_raw_parts(
+        (*decoded).main_data.cast::<u8>(),
+        (*decoded).main_data_len as usize,
+    )
+}
+
 #[pg_guard]
 unsafe extern "C-unwind" fn rm_redo(record: *mut pg_sys::XLogReaderState) {
     let info = (*(*record).record).header.xl_info & !(pg_sys::XLR_INFO_MASK as u8);
-    if info == XLOG_PG_SEARCH_INIT_INDEX && pg_sys::StandbyMode {
-        pgrx::error!(
-            "replicas are not supported on community and require paradedb enterprise, \
-             which guarantees physical replication safety on standbys."
-        );
+    match info {
+        XLOG_PG_SEARCH_RECYCLE => {
+            // only a hot standby has readers to wait for
+            if !pg_sys::HotStandbyActive() {
+                return;
+            }
+            let Some((header, blocks)) =
+                split_payload::<RecycleHeader, pg_sys::BlockNumber>(record_payload(record))
+            else {
+                return;
+            };
+
+            // the primary only recycles a segment once it can get a cleanup lock on the segment's
+            // pintest block, so wait for the same on the standby.  If a reader holds its pin for
+            // too long, Postgres cancels it, like for any other buffer pin conflict
+            for blockno in blocks {
+                let buffer = header.relation.read_buffer(blockno);
+                if buffer != pg_sys::InvalidBuffer as pg_sys::Buffer {
+                    pg_sys::LockBufferForCleanup(buffer);
+                    pg_sys::UnlockReleaseBuffer(buffer);
+                }
+            }
+        }
+
+        // the pages these describe are restored by their generic WAL records
+        XLOG_PG_SEARCH_INIT_INDEX
+        | XLOG_PG_SEARCH_SEGMENTS
+        | XLOG_PG_SEARCH_METAPAGE
+        | XLOG_PG_SEARCH_FSM => {}
+
+        _ => pgrx::error!("pg_search: unknown WAL record info {info:#x}"),
     }
 }
 
 #[pg_guard]
-unsafe extern "C-unwind" fn rm_desc(
-    _buf: pg_sys::StringInfo,
-    _record: *mut pg_sys::XLogReaderState,
-) {
+unsafe extern "C-unwind" fn rm_desc(buf: pg_sys::StringInfo, record: *mut pg_sys::XLogReaderState) {
+    let info = (*(*record).record).header.xl_info & !(pg_sys::XLR_INFO_MASK as u8);
+    let payload = record_payload(record);
+    let desc = match info {
+        // records written before the relation was part of the payload have only a single byte
+        XLOG_PG_SEARCH_INIT_INDEX => {
+            split_payload::<RecordRelation, u8>(payload).map(|(relation, _)| relation.to_string())
+        }
+        XLOG_PG_SEARCH_SEGMENTS => {
+            split_payload::<SegmentsHeader, [u8; 16]>(payload).map(|(header, ids)| {
+                let (created, rest) = ids.split_at((header.ncreated as usize).min(ids.len()));
+                let (deleted, modified) = rest.split_at((header.ndeleted as usize).min(rest.len()));
+                let short_ids = |ids: &[[u8; 16]]| {
+                    ids.iter()
+                        .map(|id| SegmentId::from_bytes(*id).short_uuid_string())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                };
+                // only a merge deletes segments, and only a vacuum changes them in place
+                let cause = if header.ndeleted > 0 {
+                    "merge"
+                } else if header.ncreated > 0 {
+                    "commit"
+                } else {
+                    "vacuum"
+                };
+                format!(
+                    "{}; {cause}; created {}: [{}]; deleted {}: [{}]; modified {}: [{}]",
+                    header.relation,
+                    header.ncreated,
+                    short_ids(created),
+                    header.ndeleted,
+                    short_ids(deleted),
+                    header.nmodified,
+                    short_ids(modified)
+                )
+            })
+        }
+        XLOG_PG_SEARCH_METAPAGE => {
+            split_payload::<MetaPageRecord, u8>(payload).and_then(|(record, _)| {
+                MetaPageChange::describe(record.change, record.values)
+                    .map(|desc| format!("{}; {desc}", record.relation))
+            })
+        }
+        XLOG_PG_SEARCH_FSM => split_payload::<FsmRecord, u8>(payload).map(|(record, _)| {
+            format!(
+                "{}; {} blocks recyclable after xid {}:{}",
+                record.relation,
+                record.nblocks,
+                record.when_recyclable >> 32,
+                record.when_recyclable as u32
+            )
+        }),
+        XLOG_PG_SEARCH_RECYCLE => {
+            split_payload::<RecycleHeader, pg_sys::BlockNumber>(payload).map(|(header, blocks)| {
+                let blocks = blocks
+                    .iter()
+                    .map(|blockno| blockno.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!(
+                    "{}; segments {}; pintest blocks: [{blocks}]",
+                    header.relation, header.nblocks
+                )
+            })
+        }
+        _ => None,
+    };
+
+    if let Some(desc) = desc.and_then(|desc| CString::new(desc).ok()) {
+        pg_sys::appendStringInfoString(buf, desc.as_ptr());
+    }
 }
 
 #[pg_guard]
 unsafe extern "C-unwind" fn rm_identify(info: u8) -> *const ::core::ffi::c_char {
     match info & !(pg_sys::XLR_INFO_MASK as u8) {
         XLOG_PG_SEARCH_INIT_INDEX => XLOG_PG_SEARCH_INIT_INDEX_NAME.as_ptr(),
+        XLOG_PG_SEARCH_SEGMENTS => XLOG_PG_SEARCH_SEGMENTS_NAME.as_ptr(),
+        XLOG_PG_SEARCH_RECYCLE => XLOG_PG_SEARCH_RECYCLE_NAME.as_ptr(),
+        XLOG_PG_SEARCH_METAPAGE => XLOG_PG_SEARCH_METAPAGE_NAME.as_ptr(),
+        XLOG_PG_SEARCH_FSM => XLOG_PG_SEARCH_FSM_NAME.as_ptr(),
         _ => std::ptr::null(),
     }
 }
@@ -64,18 +396,103 @@ unsafe extern "C-unwind" fn rm_mask(
 ) {
 }
 
-pub fn emit_init_record() {
+fn insert_record(info: u8, mut payload: Vec<u8>) {
     // XLogRegisterData's signature varies across Postgres versions
     // (data is `*mut c_char` on pg15-17 vs `*const c_void` on pg18, and len is `c_int`
     // on pg15 vs `uint32` later). `as _` lets each build infer the right types.
-    let mut payload: u8 = 0;
     unsafe {
         pg_sys::XLogBeginInsert();
-        pg_sys::XLogRegisterData(&mut payload as *mut u8 as _, std::mem::size_of::<u8>() as _);
-        pg_sys::XLogInsert(RMGR_ID, XLOG_PG_SEARCH_INIT_INDEX);
+        pg_sys::XLogRegisterData(payload.as_mut_ptr() as _, payload.len() as _);
+        pg_sys::XLogInsert(RMGR_ID, info);
     }
 }
 
+pub fn emit_init_record(indexrel: &PgSearchRelation) {
+    let relation = RecordRelation::new(indexrel);
+    insert_record(
+        XLOG_PG_SEARCH_INIT_INDEX,
+        bytemuck::bytes_of(&relation).to_vec(),
+    );
+}
+
+/// Describes a change to the index's list of segments.  Called after the change is WAL-logged.
+pub fn emit_segments_record(
+    indexrel: &PgSearchRelation,
+    created: &[SegmentId],
+    deleted: &[SegmentId],
+    modified: &[SegmentId],
+) {
+    if !indexrel.need_wal() || (created.is_empty() && deleted.is_empty() && modified.is_empty()) {
+        return;
+    }
+
+    let header = SegmentsHeader {
+        relation: RecordRelation::new(indexrel),
+        ncreated: created.len() as u32,
+        ndeleted: deleted.len() as u32,
+        nmodified: modified.len() as u32,
+    };
+    let mut payload = bytemuck::bytes_of(&header).to_vec();
+    for id in created.iter().chain(deleted).chain(modified) {
+        payload.extend_from_slice(id.uuid_bytes());
+    }
+    insert_record(XLOG_PG_SEARCH_SEGMENTS, payload);
+}
+
+/// Describes a change to the metapage.  Called after the change is WAL-logged.
+pub fn emit_metapage_record(indexrel: &PgSearchRelation, change: MetaPageChange) {
+    if !indexrel.need_wal() {
+        return;
+    }
+
+    let (change, values) = change.encode();
+    let record = MetaPageRecord {
+        relation: RecordRelation::new(indexrel),
+        change,
+        values,
+    };
+    insert_record(
+        XLOG_PG_SEARCH_METAPAGE,
+        bytemuck::bytes_of(&record).to_vec(),
+    );
+}
+
+/// Describes `nblocks` blocks being returned to the FSM, to be reused once `when_recyclable` has
+/// passed.  Called after the change is WAL-logged.
+pub fn emit_fsm_record(
+    indexrel: &PgSearchRelation,
+    when_recyclable: pg_sys::FullTransactionId,
+    nblocks: u32,
+) {
+    if !indexrel.need_wal() || nblocks == 0 {
+        return;
+    }
+
+    let record = FsmRecord {
+        when_recyclable: when_recyclable.value,
+        relation: RecordRelation::new(indexrel),
+        nblocks,
+    };
+    insert_record(XLOG_PG_SEARCH_FSM, bytemuck::bytes_of(&record).to_vec());
+}
+
+/// Tells standbys that the segments with the given pintest blocks were removed from the index's
+/// list of segments, and are about to be returned to the FSM.  Called after their removal is
+/// WAL-logged, so that no new reader on the standby can find them.
+pub fn emit_recycle_record(indexrel: &PgSearchRelation, pintest_blocks: &[pg_sys::BlockNumber]) {
+    if !indexrel.need_wal() || pintest_blocks.is_empty() {
+        return;
+    }
+
+    let header = RecycleHeader {
+        relation: RecordRelation::new(indexrel),
+        nblocks: pintest_blocks.len() as u32,
+    };
+    let mut payload = bytemuck::bytes_of(&header).to_vec();
+    payload.extend_from_slice(bytemuck::cast_slice(pintest_blocks));
+    insert_record(XLOG_PG_SEARCH_RECYCLE, payload);
+}
+
 pub fn register() {
     // `mut` because RegisterCustomRmgr takes `*mut RmgrData` on pg15 (vs `*const` later).
     // The function only reads the struct, but the C signature was tightened post-15.
@@ -641,6 +641,7 @@ pub mod v2 {
     use crate::postgres::storage::buffer::{
         BufferManager, BufferMut, Page, PageMut, init_new_buffer,
     };
+    use crate::postgres::storage::custom_rmgr;
     use crate::postgres::storage::fsm::{FSMBlockHeader, FSMBlockKind, FreeSpaceManager};
     use pgrx::pg_sys;
     use std::iter::Peekable;
@@ -1001,7 +1002,8 @@ pub mod v2 {
             when_recyclable: pg_sys::FullTransactionId,
             extend_with: impl Iterator<Item = pg_sys::BlockNumber>,
         ) {
-            let mut extend_with = extend_with.peekable();
+            let mut nblocks = 0;
+            let mut extend_with = extend_with.inspect(|_| nblocks += 1).peekable();
             if extend_with.peek().is_none() {
                 // caller didn't give us anything to do
                 return;
@@ -1055,7 +1057,12 @@ pub mod v2 {
                 }
             };
 
-            Self::extend_freelist(bman, start_block, extend_with);
+            drop(Self::extend_freelist(bman, start_block, extend_with));
+
+            // an index being built is logged in full once it's done
+            if !bman.is_create_index() {
+                custom_rmgr::emit_fsm_record(bman.buffer_access().rel(), when_recyclable, nblocks);
+            }
         }
     }
 
@@ -21,14 +21,11 @@ use crate::postgres::storage::block::{SegmentMetaEntry, block_number_is_valid};
 use crate::postgres::storage::buffer::{
     Buffer, BufferManager, BufferMut, PinnedBuffer, init_new_buffer,
 };
+use crate::postgres::storage::custom_rmgr::{self, MetaPageChange};
 use crate::postgres::storage::fsm::FreeSpaceManager;
 use crate::postgres::storage::merge::{MergeLock, VacuumList, VacuumSentinel};
 use crate::postgres::storage::{LinkedBytesList, LinkedItemList};
-use pgrx::pg_sys::panic::ErrorReport;
-use pgrx::{
-    PgLogLevel, PgRelation, PgSqlErrorCode, function_name, iter::TableIterator, name, pg_extern,
-    pg_sys,
-};
+use pgrx::{PgRelation, iter::TableIterator, name, pg_extern, pg_sys};
 
 /// The metadata stored on the `Metadata` page
 #[derive(Debug, Copy, Clone)]
@@ -65,8 +62,11 @@ pub struct MetaPageData {
     /// The block where our old v1 FSM starts
     v1_fsm: pg_sys::BlockNumber,
 
-    /// The header block for a [`LinkedItemsList<SegmentMergeEntry>]`
-    segment_meta_garbage: pg_sys::BlockNumber,
+    /// This used to be the header block of a list of segments waiting for physical replicas to
+    /// stop reading them, which replicas now learn about from the WAL instead
+    #[allow(dead_code)]
+    #[doc(hidden)]
+    _dead_space_5: pg_sys::BlockNumber,
     ambulkdelete_epoch: u32,
 
     /// The block where our current, v2, FSM starts
@@ -85,11 +85,33 @@ pub struct MetaPageData {
     created_by_version_patch: u16,
 
     created_at: pg_sys::TimestampTz,
+
+    /// The header block of a [`LinkedBytesList`] holding the index's [`SchemaChanges`], or zero
+    /// if its schema hasn't changed since it was built
+    ///
+    /// [`SchemaChanges`]: crate::schema::SchemaChanges
+    schema_changes: pg_sys::BlockNumber,
+
+    /// The transaction that last replaced the schema lists, or zero once its outcome has been
+    /// resolved.  Until then the lists it replaced are kept in `previous_schema_start` and
+    /// `previous_schema_changes`, and whoever can't see the change reads those instead.  Inserts
+    /// resolve it when they consider merging, and so does every `VACUUM` that cleans up the index,
+    /// before the table's `relfrozenxid` can pass it
+    schema_xid: pg_sys::TransactionId,
+    previous_schema_start: pg_sys::BlockNumber,
+    previous_schema_changes: pg_sys::BlockNumber,
+
+    /// The header block of a [`LinkedBytesList`] holding the [`TokenizerTables`] the index was
+    /// built with, or zero if it was built before they were kept
+    ///
+    /// [`TokenizerTables`]: crate::api::tokenizers::TokenizerTables
+    tokenizer_tables: pg_sys::BlockNumber,
 }
 
 /// Provides read access to the metadata page
 /// Because the metadata page does not change after it's initialized in MetaPage::open(),
-// (with the exception of the `ambulkdelete_epoch` field, see comment below)
+// (with the exception of the `ambulkdelete_epoch` field, see comment below, and the schema
+// fields, which only change under an `AccessExclusiveLock` on the index)
 /// we do not need to hold a share lock for the lifetime of this struct.
 pub struct MetaPage {
     data: MetaPageData,
@@ -114,12 +136,11 @@ impl MetaPage {
             metadata.ambulkdelete_sentinel = init_new_buffer(indexrel).number();
             metadata.merge_lock = init_new_buffer(indexrel).number();
             metadata.v2_fsm = crate::postgres::storage::fsm::v2::V2FSM::create(indexrel);
-            metadata.segment_meta_garbage =
-                LinkedItemList::<SegmentMetaEntry>::create_without_fsm(indexrel);
 
             metadata.cleanup_lock = init_new_buffer(indexrel).number();
             metadata.schema_start = LinkedBytesList::create_without_fsm(indexrel);
             metadata.settings_start = LinkedBytesList::create_without_fsm(indexrel);
+            metadata.tokenizer_tables = LinkedBytesList::create_without_fsm(indexrel);
             metadata.segment_metas_start =
                 LinkedItemList::<SegmentMetaEntry>::create_without_fsm(indexrel);
 
@@ -135,16 +156,6 @@ impl MetaPage {
     }
 
     pub fn open(indexrel: &PgSearchRelation) -> Self {
-        if unsafe { pgrx::pg_sys::HotStandbyActive() } && unsafe { !pg_sys::XLogInsertAllowed() } {
-            ErrorReport::new(
-                PgSqlErrorCode::ERRCODE_FEATURE_NOT_SUPPORTED,
-                "Serving reads from a standby requires write-ahead log (WAL) integration, which is supported on ParadeDB Enterprise, not ParadeDB Community",
-                function_name!(),
-            )
-            .set_detail("Please contact ParadeDB for access to ParadeDB Enterprise")
-            .report(PgLogLevel::ERROR);
-        }
-
         let mut bman = BufferManager::new(indexrel);
         let buffer = bman.get_buffer(METAPAGE);
         let page = buffer.page();
@@ -160,11 +171,14 @@ impl MetaPage {
         //
         // These will have either been initialized in `MetaPage::init()` or known to be
         // our old hardcoded values
-        let may_need_init = !block_number_is_valid(metadata.active_vacuum_list)
-            || !block_number_is_valid(metadata.ambulkdelete_sentinel)
-            || !block_number_is_valid(metadata.merge_lock)
-            || !block_number_is_valid(metadata.v2_fsm)
-            || !block_number_is_valid(metadata.segment_meta_garbage);
+        //
+        // A hot standby can't write, and only reads the index anyway.  It gets the fields once the
+        // primary initializes them
+        let may_need_init = !unsafe { pg_sys::RecoveryInProgress() }
+            && (!block_number_is_valid(metadata.active_vacuum_list)
+                || !block_number_is_valid(metadata.ambulkdelete_sentinel)
+                || !block_number_is_valid(metadata.merge_lock)
+                || !block_number_is_valid(metadata.v2_fsm));
 
         drop(buffer);
 
@@ -204,17 +218,21 @@ impl MetaPage {
                         metadata.v1_fsm = pg_sys::InvalidBlockNumber;
                     }
                 }
-
-                if !block_number_is_valid(metadata.segment_meta_garbage) {
-                    metadata.segment_meta_garbage =
-                        LinkedItemList::<SegmentMetaEntry>::create_without_fsm(indexrel);
-                }
             }
 
-            Self {
-                data: *metadata,
-                bman,
-            }
+            let data = *metadata;
+            drop(buffer);
+            custom_rmgr::emit_metapage_record(
+                indexrel,
+                MetaPageChange::InitLists {
+                    vacuum_list: data.active_vacuum_list,
+                    ambulkdelete_sentinel: data.ambulkdelete_sentinel,
+                    merge_lock: data.merge_lock,
+                    fsm: data.v2_fsm,
+                },
+            );
+
+            Self { data, bman }
         } else {
             Self {
                 data: metadata,
@@ -278,26 +296,6 @@ impl MetaPage {
             Some(created_at)
         }
     }
-
-    ///
-    /// A `LinkedItemList<SegmentMetaEntry>` containing segments which are no longer visible from the
-    /// live `segment_metas()` list, and which will be recyclable when no transactions might still
-    /// be reading them on physical replicas.
-    ///
-    /// Deferring recycling avoids readers needing to hold a lock all the way from when
-    /// `segment_metas()` is first opened for reading until when they finish consuming the files
-    /// for the segments it references.
-    ///
-    pub fn segment_metas_garbage(&self) -> Option<LinkedItemList<SegmentMetaEntry>> {
-        if !block_number_is_valid(self.data.segment_meta_garbage) {
-            return None;
-        }
-
-        Some(LinkedItemList::<SegmentMetaEntry>::open(
-            self.bman.buffer_access().rel(),
-            self.data.segment_meta_garbage,
-        ))
-    }
 }
 
 // legacy hardcoded page support for various index objects
@@ -344,14 +342,152 @@ impl MetaPage {
     }
 
     pub fn schema_bytes(&self) -> LinkedBytesList {
-        let blockno = if self.data.schema_start == 0 {
+        let (schema_start, _) = self.visible_schema();
+        let blockno = if schema_start == 0 {
             Self::LEGACY_SCHEMA_START
         } else {
-            self.data.schema_start
+            schema_start
         };
         LinkedBytesList::open(self.bman.buffer_access().rel(), blockno)
     }
 
+    /// The list holding the index's [`SchemaChanges`](crate::schema::SchemaChanges), if its schema
+    /// has changed since it was built.
+    pub fn schema_changes_bytes(&self) -> Option<LinkedBytesList> {
+        let (_, blockno) = self.visible_schema();
+        if blockno == 0 {
+            return None;
+        }
+        Some(LinkedBytesList::open(
+            self.bman.buffer_access().rel(),
+            blockno,
+        ))
+    }
+
+    /// The schema list and schema changes list this transaction sees: the ones the last change
+    /// wrote if it was made by this transaction or has committed, otherwise the ones it replaced.
+    ///
+    /// Only a change that hasn't been resolved yet is looked up in the commit log, which still
+    /// has it, as `amvacuumcleanup()` resolves it before the xid can be frozen away.
+    fn visible_schema(&self) -> (pg_sys::BlockNumber, pg_sys::BlockNumber) {
+        let xid = self.data.schema_xid;
+        let visible = xid == pg_sys::InvalidTransactionId
+            || unsafe {
+                pg_sys::TransactionIdIsCurrentTransactionId(xid)
+                    || pg_sys::TransactionIdDidCommit(xid)
+            };
+        if visible {
+            (self.data.schema_start, self.data.schema_changes)
+        } else {
+            (
+                self.data.previous_schema_start,
+                self.data.previous_schema_changes,
+            )
+        }
+    }
+
+    /// Points the metapage at a new schema list and schema changes list, on behalf of the current
+    /// transaction.  Both are swapped under the metapage's buffer lock, so a reader never sees one
+    /// without the other, and other transactions keep reading the lists they replace until this
+    /// one commits.  If it aborts, the swap is undone by [`Self::resolve_schema_change`].
+    ///
+    /// The caller must have resolved the previous change first.
+    pub fn set_schema(
+        &mut self,
+        schema_start: pg_sys::BlockNumber,
+        schema_changes: pg_sys::BlockNumber,
+    ) {
+        assert_eq!(
+            { self.data.schema_xid },
+            pg_sys::InvalidTransactionId,
+            "the previous schema change must be resolved before the schema is changed again"
+        );
+        let mut buffer = self.bman.get_buffer_mut(METAPAGE);
+        let mut page = buffer.page_mut();
+        let metadata = page.contents_mut::<MetaPageData>();
+        metadata.previous_schema_start = metadata.schema_start;
+        metadata.previous_schema_changes = metadata.schema_changes;
+        metadata.schema_start = schema_start;
+        metadata.schema_changes = schema_changes;
+        metadata.schema_xid = unsafe { pg_sys::GetCurrentTransactionId() };
+        self.data = *metadata;
+        drop(buffer);
+
+        custom_rmgr::emit_metapage_record(
+            self.bman.buffer_access().rel(),
+            MetaPageChange::SetSchema {
+                schema: schema_start,
+                schema_changes,
+                xid: self.data.schema_xid,
+            },
+        );
+    }
+
+    /// Once the transaction that last changed the schema has committed or aborted, frees the
+    /// lists that its outcome left unused: the ones it replaced if it committed, or the ones it
+    /// wrote if it aborted, in which case the metapage is pointed back at the former.
+    ///
+    /// Does nothing while that transaction is still running, or if there's nothing to resolve.
+    ///
+    /// # Safety
+    ///
+    /// Nobody can still be reading the lists being freed.  That's the case once the change is
+    /// resolved, as it was made under an `AccessExclusiveLock` on the index.
+    pub unsafe fn resolve_schema_change(&mut self) {
+        let xid = self.data.schema_xid;
+        if xid == pg_sys::InvalidTransactionId
+            || pg_sys::TransactionIdIsCurrentTransactionId(xid)
+            || pg_sys::TransactionIdIsInProgress(xid)
+        {
+            return;
+        }
+        let committed = pg_sys::TransactionIdDidCommit(xid);
+
+        let mut buffer = self.bman.get_buffer_mut(METAPAGE);
+        let mut page = buffer.page_mut();
+        let metadata = page.contents_mut::<MetaPageData>();
+        if metadata.schema_xid != xid {
+            // someone else resolved it while we waited for the lock
+            self.data = *metadata;
+            return;
+        }
+        let (unused_schema_start, unused_schema_changes) = if committed {
+            (
+                metadata.previous_schema_start,
+                metadata.previous_schema_changes,
+            )
+        } else {
+            let unused = (metadata.schema_start, metadata.schema_changes);
+            metadata.schema_start = metadata.previous_schema_start;
+            metadata.schema_changes = metadata.previous_schema_changes;
+            unused
+        };
+        metadata.schema_xid = pg_sys::InvalidTransactionId;
+        metadata.previous_schema_start = 0;
+        metadata.previous_schema_changes = 0;
+        self.data = *metadata;
+        drop(buffer);
+
+        let rel = self.bman.buffer_access().rel();
+        custom_rmgr::emit_metapage_record(
+            rel,
+            MetaPageChange::ResolveSchema {
+                schema: self.data.schema_start,
+                schema_changes: self.data.schema_changes,
+                committed,
+            },
+        );
+        let unused_schema_start = if unused_schema_start == 0 {
+            Self::LEGACY_SCHEMA_START
+        } else {
+            unused_schema_start
+        };
+        LinkedBytesList::open(rel, unused_schema_start).return_to_fsm();
+        if unused_schema_changes != 0 {
+            LinkedBytesList::open(rel, unused_schema_changes).return_to_fsm();
+        }
+    }
+
     pub fn settings_bytes(&self) -> LinkedBytesList {
         let blockno = if self.data.settings_start == 0 {
             Self::LEGACY_SETTINGS_START
@@ -361,6 +497,18 @@ impl MetaPage {
         LinkedBytesList::open(self.bman.buffer_access().rel(), blockno)
     }
 
+    /// The list holding the contents of the tables the index's tokenizers read, or `None` if the
+    /// index was built before they were kept.
+    pub fn tokenizer_tables_bytes(&self) -> Option<LinkedBytesList> {
+        if self.data.tokenizer_tables == 0 {
+            return None;
+        }
+        Some(LinkedBytesList::open(
+            self.bman.buffer_access().rel(),
+            self.data.tokenizer_tables,
+        ))
+    }
+
     pub fn segment_metas(&self) -> LinkedItemList<SegmentMetaEntry> {
         let blockno = if self.data.segment_metas_start == 0 {
             Self::LEGACY_SEGMENT_METAS_START
@@ -381,6 +529,13 @@ impl MetaPage {
         let mut page = buffer.page_mut();
         let metadata = page.contents_mut::<MetaPageData>();
         metadata.ambulkdelete_epoch = metadata.ambulkdelete_epoch.wrapping_add(1);
+        let epoch = metadata.ambulkdelete_epoch;
+        drop(buffer);
+
+        custom_rmgr::emit_metapage_record(
+            self.bman.buffer_access().rel(),
+            MetaPageChange::AmbulkdeleteEpoch { epoch },
+        );
     }
 }
 
@@ -403,6 +558,8 @@ unsafe fn bgmerger_state(
 #[pgrx::pg_schema]
 mod tests {
     use super::*;
+    use crate::index::directory::utils::{load_index_schema, load_schema_changes, replace_schema};
+    use crate::schema::SchemaChanges;
     use pgrx::datum::TimestampWithTimeZone;
     use pgrx::prelude::*;
 
@@ -479,4 +636,48 @@ mod tests {
                 .unwrap();
         assert_eq!(via_udf, created_at);
     }
+
+    #[pg_test]
+    fn aborted_schema_change_is_undone() {
+        Spi::run("CREATE TABLE t (id SERIAL, data TEXT);").unwrap();
+        Spi::run("INSERT INTO t (data) VALUES ('hello');").unwrap();
+        Spi::run("CREATE INDEX t_idx ON t USING paradedb (id, data) WITH (key_field = 'id');")
+            .unwrap();
+
+        let index_oid: pg_sys::Oid =
+            Spi::get_one("SELECT oid FROM pg_class WHERE relname = 't_idx' AND relkind = 'i';")
+                .expect("spi should succeed")
+                .unwrap();
+        let indexrel = PgSearchRelation::open(index_oid);
+        let schema = load_index_schema(&indexrel).unwrap().unwrap();
+        let changes = SchemaChanges {
+            version: 1,
+            ..Default::default()
+        };
+
+        // change the schema in a subtransaction, which sees its own change, then abort it
+        unsafe {
+            let memcxt = pg_sys::CurrentMemoryContext;
+            let owner = pg_sys::CurrentResourceOwner;
+            pg_sys::BeginInternalSubTransaction(std::ptr::null());
+            replace_schema(&indexrel, &schema, &changes).unwrap();
+            assert_eq!(load_schema_changes(&indexrel).unwrap().version, 1);
+            pg_sys::RollbackAndReleaseCurrentSubTransaction();
+            pg_sys::MemoryContextSwitchTo(memcxt);
+            pg_sys::CurrentResourceOwner = owner;
+        }
+
+        // the aborted change is invisible before it's resolved...
+        let mut metapage = MetaPage::open(&indexrel);
+        assert_ne!({ metapage.data.schema_xid }, pg_sys::InvalidTransactionId);
+        assert_eq!(load_schema_changes(&indexrel).unwrap().version, 0);
+
+        // ...and once it is, the metapage no longer needs the commit log to tell
+        unsafe {
+            metapage.resolve_schema_change();
+        }
+        assert_eq!({ metapage.data.schema_xid }, pg_sys::InvalidTransactionId);
+        assert!(metapage.schema_changes_bytes().is_none());
+        assert_eq!(load_schema_changes(&indexrel).unwrap().version, 0);
+    }
 }
@@ -33,8 +33,8 @@ use crate::postgres::rel::PgSearchRelation;
 use crate::postgres::types::{TantivyValue, TantivyValueError};
 use crate::postgres::var::find_vars;
 use crate::schema::{CategorizedFieldData, SearchField, SearchFieldType};
-use crate::vector::PgVector;
 use crate::vector::metric::VectorMetric;
+use crate::vector::{PgVector, multi};
 use anyhow::Result;
 use pgrx::itemptr::{item_pointer_get_both, item_pointer_set_all};
 use pgrx::*;
@@ -740,9 +740,9 @@ pub unsafe fn extract_field_attributes(
         )
         .unwrap_or_else(|e| panic!("{e}"));
 
-        if let SearchFieldType::Vector(oid, dims, _) = tantivy_type {
+        if let SearchFieldType::Vector(oid, dims, default_metric) = tantivy_type {
             let metric =
-                VectorMetric::from_index_attr(indexrel, attno as usize).unwrap_or_default();
+                VectorMetric::from_index_attr(indexrel, attno as usize).unwrap_or(default_metric);
             tantivy_type = SearchFieldType::Vector(oid, dims, metric);
         }
 
@@ -808,7 +808,18 @@ pub unsafe fn row_to_search_document<'a>(
         // For pdb.alias/tokenizer types, get the underlying type if it's not a text type.
         let actual_datum = unsafe { unwrap_alias_datum(datum, *pg_type) };
 
-        if *is_array {
+        if let SearchFieldType::MultiVector(typoid, dims) = search_field.field_type() {
+            let vectors = unsafe { multi::vectors_from_array(actual_datum, typoid) };
+            if !vectors.is_empty() {
+                let packed = multi::pack(&vectors, dims).unwrap_or_else(|e| {
+                    panic!("could not parse field `{}`: {e}", search_field.field_name())
+                });
+                document.add_field_value(
+                    search_field.field(),
+                    &tantivy::schema::OwnedValue::Bytes(packed),
+                );
+            }
+        } else if *is_array {
             let converted_array = match search_field.field_type() {
                 SearchFieldType::Numeric64(_, scale) => {
                     TantivyValue::try_from_numeric_array_i64(actual_datum, scale)
@@ -842,13 +853,20 @@ pub unsafe fn row_to_search_document<'a>(
                     &value.into_tantivy_value(created_by_version),
                 );
             }
-        } else if matches!(search_field.field_type(), SearchFieldType::Vector(..)) {
+        } else if let SearchFieldType::Vector(typoid, ..) = search_field.field_type() {
             let vec = unsafe {
-                PgVector::from_datum(actual_datum, false)
+                PgVector::from_polymorphic_datum(actual_datum, false, typoid)
                     .expect("vector field datum should not be NULL")
                     .0
             };
-            document.add_vector(search_field.field(), &vec);
+            if let Some(quantization) = search_field.vector_quantization() {
+                document.add_field_value(
+                    search_field.field(),
+                    &tantivy::schema::OwnedValue::Bytes(quantization.encode(&vec)),
+                );
+            } else {
+                document.add_vector(search_field.field(), &vec);
+            }
         } else {
             let tv = unsafe {
                 scalar_datum_to_tantivy_value(actual_datum, search_field.field_type(), *base_oid)
@@ -19,6 +19,7 @@ use pgrx::*;
 
 use crate::postgres::merge::{MergeStyle, do_merge};
 use crate::postgres::rel::PgSearchRelation;
+use crate::postgres::storage::metadata::MetaPage;
 
 #[pg_guard]
 pub unsafe extern "C-unwind" fn amvacuumcleanup(
@@ -26,6 +27,11 @@ pub unsafe extern "C-unwind" fn amvacuumcleanup(
     stats: *mut pg_sys::IndexBulkDeleteResult,
 ) -> *mut pg_sys::IndexBulkDeleteResult {
     let index = PgSearchRelation::open((*(*info).index).rd_id);
+
+    // resolve the last `pdb.add_field()` or `pdb.drop_field()` even if the index doesn't merge, so
+    // its xid is gone from the metapage before the table's `relfrozenxid` can pass it, and readers
+    // never ask the commit log about an xid it has truncated
+    MetaPage::open(&index).resolve_schema_change();
     do_merge(&index, MergeStyle::Vacuum, None, None).expect("should be able to merge");
     stats
 }
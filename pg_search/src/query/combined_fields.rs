@@ -0,0 +1,424 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! BM25F: scores several text fields as if they were one field.
+//!
+//! Each token's term frequency and each document's length are the weighted sums of the fields',
+//! and the token's document frequency is the largest of its fields', so a token that's common in
+//! any of them isn't rewarded for being rare in another, as it would be by a disjunction of
+//! per-field BM25 queries.
+
+use tantivy::fieldnorm::FieldNormReader;
+use tantivy::postings::{Postings, SegmentPostings};
+use tantivy::query::{EmptyScorer, EnableScoring, Explanation, Query, QueryClone, Scorer, Weight};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{DocId, DocSet, Score, SegmentReader, TERMINATED, TantivyError, Term};
+
+use crate::api::HashSet;
+
+/// The BM25 score of a token, given its weighted term frequency `tf` and the document's weighted
+/// length `len`.
+fn bm25f(idf: Score, tf: Score, len: Score, avg_len: Score, k1: Score, b: Score) -> Score {
+    let norm = if avg_len > 0.0 {
+        k1 * (1.0 - b + b * len / avg_len)
+    } else {
+        k1
+    };
+    idf * (k1 + 1.0) * tf / (tf + norm)
+}
+
+fn idf(doc_freq: u64, num_docs: u64) -> Score {
+    let doc_freq = doc_freq as Score;
+    let num_docs = num_docs as Score;
+    (1.0 + (num_docs - doc_freq + 0.5) / (doc_freq + 0.5)).ln()
+}
+
+#[derive(Debug)]
+pub struct CombinedFieldsQuery {
+    fields: Vec<(Field, Score)>,
+    tokens: Vec<String>,
+    conjunction: bool,
+    k1: Score,
+    b: Score,
+}
+
+impl QueryClone for CombinedFieldsQuery {
+    fn box_clone(&self) -> Box<dyn Query> {
+        Box::new(Self {
+            fields: self.fields.clone(),
+            tokens: self.tokens.clone(),
+            conjunction: self.conjunction,
+            k1: self.k1,
+            b: self.b,
+        })
+    }
+}
+
+impl CombinedFieldsQuery {
+    /// Matches the documents with any of `tokens` in any of `fields`, or all of them if
+    /// `conjunction` is set. Each field is paired with the weight its frequencies are scaled by.
+    pub fn new(
+        fields: Vec<(Field, Score)>,
+        mut tokens: Vec<String>,
+        conjunction: bool,
+        k1: Score,
+        b: Score,
+    ) -> Self {
+        let mut seen = HashSet::default();
+        tokens.retain(|token| seen.insert(token.clone()));
+        Self {
+            fields,
+            tokens,
+            conjunction,
+            k1,
+            b,
+        }
+    }
+
+    /// `token`'s term in each of the fields, in the fields' order.
+    fn terms(&self, token: &str) -> Vec<Term> {
+        self.fields
+            .iter()
+            .map(|(field, _)| Term::from_field_text(*field, token))
+            .collect()
+    }
+}
+
+impl Query for CombinedFieldsQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let statistics_provider = match enable_scoring {
+            EnableScoring::Enabled {
+                statistics_provider,
+                ..
+            } => Some(statistics_provider),
+            EnableScoring::Disabled { .. } => None,
+        };
+
+        let mut avg_len = 0.0;
+        let mut tokens = Vec::with_capacity(self.tokens.len());
+        if let Some(statistics_provider) = statistics_provider {
+            let num_docs = statistics_provider.total_num_docs()?;
+            for (field, weight) in &self.fields {
+                if num_docs > 0 {
+                    let total_tokens = statistics_provider.total_num_tokens(*field)?;
+                    avg_len += weight * total_tokens as Score / num_docs as Score;
+                }
+            }
+            for token in &self.tokens {
+                let terms = self.terms(token);
+                let mut doc_freq = 0;
+                for term in &terms {
+                    doc_freq = doc_freq.max(statistics_provider.doc_freq(term)?);
+                }
+                tokens.push((terms, idf(doc_freq, num_docs)));
+            }
+        } else {
+            tokens.extend(self.tokens.iter().map(|token| (self.terms(token), 1.0)));
+        }
+
+        Ok(Box::new(CombinedFieldsWeight {
+            fields: self.fields.clone(),
+            tokens,
+            conjunction: self.conjunction,
+            avg_len,
+            k1: self.k1,
+            b: self.b,
+        }))
+    }
+
+    fn query_terms(
+        &self,
+        field: Field,
+        _segment_reader: &SegmentReader,
+        visitor: &mut dyn FnMut(&Term, bool),
+    ) {
+        if self.fields.iter().any(|(combined, _)| *combined == field) {
+            for token in &self.tokens {
+                visitor(&Term::from_field_text(field, token), false);
+            }
+        }
+    }
+}
+
+struct CombinedFieldsWeight {
+    fields: Vec<(Field, Score)>,
+    /// Each token's terms, one per field, and its idf.
+    tokens: Vec<(Vec<Term>, Score)>,
+    conjunction: bool,
+    avg_len: Score,
+    k1: Score,
+    b: Score,
+}
+
+impl Weight for CombinedFieldsWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        let mut tokens = Vec::with_capacity(self.tokens.len());
+        for (terms, idf) in &self.tokens {
+            let mut postings = Vec::new();
+            for (field_ord, term) in terms.iter().enumerate() {
+                let inverted_index = reader.inverted_index(term.field())?;
+                if let Some(segment_postings) =
+                    inverted_index.read_postings(term, IndexRecordOption::WithFreqs)?
+                {
+                    postings.push((field_ord, segment_postings));
+                }
+            }
+            if postings.is_empty() {
+                if self.conjunction {
+                    return Ok(Box::new(EmptyScorer));
+                }
+                continue;
+            }
+            tokens.push(TokenPostings::new(postings, *idf));
+        }
+        if tokens.is_empty() {
+            return Ok(Box::new(EmptyScorer));
+        }
+
+        let mut fieldnorm_readers = Vec::with_capacity(self.fields.len());
+        for (field, weight) in &self.fields {
+            let fieldnorm_reader = match reader.fieldnorms_readers().get_field(*field)? {
+                Some(fieldnorm_reader) => fieldnorm_reader,
+                None => FieldNormReader::constant(reader.max_doc(), 1),
+            };
+            fieldnorm_readers.push((fieldnorm_reader, *weight));
+        }
+
+        let mut scorer = CombinedFieldsScorer {
+            tokens,
+            fieldnorm_readers,
+            conjunction: self.conjunction,
+            avg_len: self.avg_len,
+            k1: self.k1,
+            b: self.b,
+            boost,
+            doc: TERMINATED,
+        };
+        scorer.doc = scorer.position();
+        Ok(Box::new(scorer))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )));
+        }
+        Ok(Explanation::new("BM25F combined fields", scorer.score()))
+    }
+}
+
+/// The postings of one token in each of the fields it occurs in. It's on the smallest document
+/// any of them are on.
+struct TokenPostings {
+    postings: Vec<(usize, SegmentPostings)>,
+    idf: Score,
+    doc: DocId,
+}
+
+impl TokenPostings {
+    fn new(postings: Vec<(usize, SegmentPostings)>, idf: Score) -> Self {
+        let mut token = Self {
+            postings,
+            idf,
+            doc: TERMINATED,
+        };
+        token.doc = token.min_doc();
+        token
+    }
+
+    fn min_doc(&self) -> DocId {
+        self.postings
+            .iter()
+            .map(|(_, postings)| postings.doc())
+            .min()
+            .unwrap_or(TERMINATED)
+    }
+
+    fn advance(&mut self) -> DocId {
+        if self.doc != TERMINATED {
+            for (_, postings) in &mut self.postings {
+                if postings.doc() == self.doc {
+                    postings.advance();
+                }
+            }
+            self.doc = self.min_doc();
+        }
+        self.doc
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        for (_, postings) in &mut self.postings {
+            if postings.doc() < target {
+                postings.seek(target);
+            }
+        }
+        self.doc = self.min_doc();
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.postings
+            .iter()
+            .map(|(_, postings)| postings.size_hint())
+            .sum()
+    }
+}
+
+struct CombinedFieldsScorer {
+    tokens: Vec<TokenPostings>,
+    /// Each field's fieldnorms and weight, in the fields' order.
+    fieldnorm_readers: Vec<(FieldNormReader, Score)>,
+    conjunction: bool,
+    avg_len: Score,
+    k1: Score,
+    b: Score,
+    boost: Score,
+    doc: DocId,
+}
+
+impl CombinedFieldsScorer {
+    /// The first document at or after the tokens' current ones that matches: the smallest any
+    /// token is on for a disjunction, and the first all of them are on for a conjunction.
+    fn position(&mut self) -> DocId {
+        if !self.conjunction {
+            return self
+                .tokens
+                .iter()
+                .map(|token| token.doc)
+                .min()
+                .unwrap_or(TERMINATED);
+        }
+
+        let mut target = self
+            .tokens
+            .iter()
+            .map(|token| token.doc)
+            .max()
+            .unwrap_or(TERMINATED);
+        'align: while target != TERMINATED {
+            for token in &mut self.tokens {
+                if token.doc < target {
+                    let doc = token.seek(target);
+                    if doc > target {
+                        target = doc;
+                        continue 'align;
+                    }
+                }
+            }
+            break;
+        }
+        target
+    }
+}
+
+impl Scorer for CombinedFieldsScorer {
+    fn score(&mut self) -> Score {
+        let doc = self.doc;
+        let len = self
+            .fieldnorm_readers
+            .iter()
+            .map(|(fieldnorm_reader, weight)| weight * fieldnorm_reader.fieldnorm(doc) as Score)
+            .sum();
+        let mut score = 0.0;
+        for token in &self.tokens {
+            if token.doc != doc {
+                continue;
+            }
+            let tf = token
+                .postings
+                .iter()
+                .filter(|(_, postings)| postings.doc() == doc)
+                .map(|(field_ord, postings)| {
+                    self.fieldnorm_readers[*field_ord].1 * postings.term_freq() as Score
+                })
+                .sum();
+            score += bm25f(token.idf, tf, len, self.avg_len, self.k1, self.b);
+        }
+        score * self.boost
+    }
+}
+
+impl DocSet for CombinedFieldsScorer {
+    fn advance(&mut self) -> DocId {
+        if self.doc == TERMINATED {
+            return TERMINATED;
+        }
+        if self.conjunction {
+            self.tokens[0].advance();
+        } else {
+            let doc = self.doc;
+            for token in &mut self.tokens {
+                if token.doc == doc {
+                    token.advance();
+                }
+            }
+        }
+        self.doc = self.position();
+        self.doc
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        for token in &mut self.tokens {
+            if token.doc < target {
+                token.seek(target);
+            }
+        }
+        self.doc = self.position();
+        self.doc
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        let size_hints = self.tokens.iter().map(TokenPostings::size_hint);
+        if self.conjunction {
+            size_hints.min().unwrap_or(0)
+        } else {
+            size_hints.sum()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_field_is_bm25() {
+        // tantivy's BM25 with k1 = 1.2 and b = 0.75
+        let idf = idf(3, 10);
+        let norm = 1.2 * (1.0 - 0.75 + 0.75 * 8.0 / 4.0);
+        assert_eq!(
+            bm25f(idf, 2.0, 8.0, 4.0, 1.2, 0.75),
+            idf * 2.2 * 2.0 / (2.0 + norm)
+        );
+    }
+
+    #[test]
+    fn frequencies_and_rarity_raise_scores() {
+        assert!(idf(1, 10) > idf(5, 10));
+        // a token in a heavier-weighted field has a larger weighted frequency
+        assert!(
+            bm25f(idf(3, 10), 2.0, 5.0, 4.0, 1.2, 0.75)
+                > bm25f(idf(3, 10), 1.0, 5.0, 4.0, 1.2, 0.75)
+        );
+    }
+}
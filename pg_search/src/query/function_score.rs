@@ -0,0 +1,531 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Function scoring: adjusts the score of every document a query matches with functions of the
+//! document's fast field values, like a decay away from a date or a factor of a popularity count.
+//!
+//! The functions are evaluated by [`FunctionScoreQuery`]'s scorer as the query matches each
+//! document, so the adjusted score is what a Top K by `pdb.score()` orders by.
+
+use serde::{Deserialize, Serialize};
+use tantivy::columnar::Column;
+use tantivy::query::{EnableScoring, Explanation, Query, QueryClone, Scorer, Weight};
+use tantivy::schema::Field;
+use tantivy::{DateTime, DocId, DocSet, Score, SegmentReader, TantivyError, Term};
+
+use crate::api::FieldName;
+use crate::postgres::datetime::unix_micros_to_pg_micros;
+use crate::query::SearchQueryInput;
+
+/// One function of a function score query.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreFunction {
+    /// The function only applies to documents that match this query.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Box<SearchQueryInput>>,
+    /// Multiplies the function's value.
+    pub weight: f32,
+    pub kind: ScoreFunctionKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreFunctionKind {
+    /// Just the function's weight.
+    Weight,
+    /// Decays from `1` as a field's value moves more than `offset` away from `origin`, down to
+    /// `decay` at `scale` past the offset. Date values, and so `origin`, `scale` and `offset`
+    /// when `dates` is set, are in microseconds.
+    Decay {
+        function: DecayFunction,
+        field: FieldName,
+        origin: f64,
+        scale: f64,
+        offset: f64,
+        decay: f64,
+        dates: bool,
+    },
+    /// `modifier(factor * value)` of a field's value, using `missing` for documents without one.
+    FieldValueFactor {
+        field: FieldName,
+        factor: f64,
+        modifier: Modifier,
+        missing: Option<f64>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecayFunction {
+    Gauss,
+    Exp,
+    Linear,
+}
+
+impl DecayFunction {
+    fn apply(self, value: f64, origin: f64, scale: f64, offset: f64, decay: f64) -> f64 {
+        let distance = ((value - origin).abs() - offset).max(0.0);
+        match self {
+            DecayFunction::Gauss => decay.powf((distance / scale).powi(2)),
+            DecayFunction::Exp => decay.powf(distance / scale),
+            DecayFunction::Linear => (1.0 - distance * (1.0 - decay) / scale).max(0.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Modifier {
+    None,
+    Log,
+    Log1p,
+    Log2p,
+    Ln,
+    Ln1p,
+    Ln2p,
+    Square,
+    Sqrt,
+    Reciprocal,
+}
+
+impl Modifier {
+    fn apply(self, value: f64) -> f64 {
+        let modified = match self {
+            Modifier::None => value,
+            Modifier::Log => value.log10(),
+            Modifier::Log1p => (value + 1.0).log10(),
+            Modifier::Log2p => (value + 2.0).log10(),
+            Modifier::Ln => value.ln(),
+            Modifier::Ln1p => value.ln_1p(),
+            Modifier::Ln2p => (value + 2.0).ln(),
+            Modifier::Square => value * value,
+            Modifier::Sqrt => value.sqrt(),
+            Modifier::Reciprocal => 1.0 / value,
+        };
+        // the log of a negative value, say, can't rank a document
+        if modified.is_finite() { modified } else { 0.0 }
+    }
+}
+
+/// How the values of the functions that apply to a document are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreMode {
+    Multiply,
+    Sum,
+    /// The weighted average: the sum of the values over the sum of their weights.
+    Avg,
+    First,
+    Max,
+    Min,
+}
+
+/// How the combined function value is applied to the query's score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoostMode {
+    Multiply,
+    Replace,
+    Sum,
+    Avg,
+    Max,
+    Min,
+}
+
+impl ScoreMode {
+    /// Combines `(weighted value, weight)` pairs, or returns `None` if there are none.
+    fn combine(self, values: impl Iterator<Item = (f64, f64)>) -> Option<f64> {
+        let mut combined: Option<(f64, f64)> = None;
+        for (value, weight) in values {
+            combined = Some(match combined {
+                None => (value, weight),
+                Some((acc, weights)) => match self {
+                    ScoreMode::Multiply => (acc * value, weights),
+                    ScoreMode::Sum | ScoreMode::Avg => (acc + value, weights + weight),
+                    ScoreMode::First => return Some(acc),
+                    ScoreMode::Max => (acc.max(value), weights),
+                    ScoreMode::Min => (acc.min(value), weights),
+                },
+            });
+        }
+        combined.map(|(acc, weights)| match self {
+            ScoreMode::Avg if weights > 0.0 => acc / weights,
+            ScoreMode::Avg => 0.0,
+            _ => acc,
+        })
+    }
+}
+
+impl BoostMode {
+    fn apply(self, query_score: f64, function_score: f64) -> f64 {
+        match self {
+            BoostMode::Multiply => query_score * function_score,
+            BoostMode::Replace => function_score,
+            BoostMode::Sum => query_score + function_score,
+            BoostMode::Avg => (query_score + function_score) / 2.0,
+            BoostMode::Max => query_score.max(function_score),
+            BoostMode::Min => query_score.min(function_score),
+        }
+    }
+}
+
+/// The fast field a function reads, resolved against the index's schema.
+#[derive(Debug, Clone)]
+pub struct ValueSource {
+    pub field_name: String,
+    /// Divides the column's values, for `NUMERIC` fields stored as scaled integers.
+    pub divisor: f64,
+}
+
+/// A [`ScoreFunction`] whose filter is a tantivy query and whose field is resolved.
+#[derive(Debug)]
+pub struct PreparedFunction {
+    pub filter: Option<Box<dyn Query>>,
+    pub weight: f32,
+    pub kind: ScoreFunctionKind,
+    pub source: Option<ValueSource>,
+}
+
+impl Clone for PreparedFunction {
+    fn clone(&self) -> Self {
+        Self {
+            filter: self.filter.as_ref().map(|filter| filter.box_clone()),
+            weight: self.weight,
+            kind: self.kind.clone(),
+            source: self.source.clone(),
+        }
+    }
+}
+
+impl PreparedFunction {
+    fn value(&self, field_value: Option<f64>) -> f64 {
+        match &self.kind {
+            ScoreFunctionKind::Weight => 1.0,
+            ScoreFunctionKind::Decay {
+                function,
+                origin,
+                scale,
+                offset,
+                decay,
+                ..
+            } => field_value.map_or(1.0, |value| {
+                function.apply(value, *origin, *scale, *offset, *decay)
+            }),
+            ScoreFunctionKind::FieldValueFactor {
+                factor,
+                modifier,
+                missing,
+                ..
+            } => field_value
+                .or(*missing)
+                .map_or(1.0, |value| modifier.apply(factor * value)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FunctionScoreQuery {
+    query: Box<dyn Query>,
+    functions: Vec<PreparedFunction>,
+    score_mode: ScoreMode,
+    boost_mode: BoostMode,
+}
+
+impl QueryClone for FunctionScoreQuery {
+    fn box_clone(&self) -> Box<dyn Query> {
+        Box::new(Self {
+            query: self.query.box_clone(),
+            functions: self.functions.clone(),
+            score_mode: self.score_mode,
+            boost_mode: self.boost_mode,
+        })
+    }
+}
+
+impl FunctionScoreQuery {
+    pub fn new(
+        query: Box<dyn Query>,
+        functions: Vec<PreparedFunction>,
+        score_mode: ScoreMode,
+        boost_mode: BoostMode,
+    ) -> Self {
+        Self {
+            query,
+            functions,
+            score_mode,
+            boost_mode,
+        }
+    }
+}
+
+impl Query for FunctionScoreQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let functions = self
+            .functions
+            .iter()
+            .map(|function| {
+                Ok(FunctionWeight {
+                    filter: function
+                        .filter
+                        .as_ref()
+                        .map(|filter| filter.weight(enable_scoring))
+                        .transpose()?,
+                    function: function.clone(),
+                })
+            })
+            .collect::<tantivy::Result<_>>()?;
+        Ok(Box::new(FunctionScoreWeight {
+            weight: self.query.weight(enable_scoring)?,
+            functions,
+            score_mode: self.score_mode,
+            boost_mode: self.boost_mode,
+        }))
+    }
+
+    fn query_terms(
+        &self,
+        field: Field,
+        segment_reader: &SegmentReader,
+        visitor: &mut dyn FnMut(&Term, bool),
+    ) {
+        self.query.query_terms(field, segment_reader, visitor)
+    }
+}
+
+struct FunctionWeight {
+    filter: Option<Box<dyn Weight>>,
+    function: PreparedFunction,
+}
+
+struct FunctionScoreWeight {
+    weight: Box<dyn Weight>,
+    functions: Vec<FunctionWeight>,
+    score_mode: ScoreMode,
+    boost_mode: BoostMode,
+}
+
+impl Weight for FunctionScoreWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        let functions = self
+            .functions
+            .iter()
+            .map(|function| {
+                Ok(SegmentFunction {
+                    filter: function
+                        .filter
+                        .as_ref()
+                        .map(|filter| filter.scorer(reader, 1.0))
+                        .transpose()?,
+                    column: function
+                        .function
+                        .source
+                        .as_ref()
+                        .map(|source| ValueColumn::open(reader, source))
+                        .transpose()?
+                        .flatten(),
+                    function: function.function.clone(),
+                })
+            })
+            .collect::<tantivy::Result<_>>()?;
+        Ok(Box::new(FunctionScorer {
+            scorer: self.weight.scorer(reader, boost)?,
+            functions,
+            score_mode: self.score_mode,
+            boost_mode: self.boost_mode,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )));
+        }
+        let mut explanation = Explanation::new("FunctionScore", scorer.score());
+        explanation.add_detail(self.weight.explain(reader, doc)?);
+        Ok(explanation)
+    }
+}
+
+/// A fast field column, read as `f64`. Dates are read as Postgres microseconds whether they're
+/// stored as `I64` or, by indexes that predate that, as `DateTime`.
+enum ValueColumn {
+    I64(Column<i64>),
+    U64(Column<u64>),
+    F64(Column<f64>),
+    Date(Column<DateTime>),
+}
+
+impl ValueColumn {
+    /// The segment's column for `source`, or `None` if none of its documents have a value.
+    fn open(reader: &SegmentReader, source: &ValueSource) -> tantivy::Result<Option<(Self, f64)>> {
+        let fast_fields = reader.fast_fields();
+        let name = source.field_name.as_str();
+        let column = if let Some(column) = fast_fields.column_opt::<i64>(name)? {
+            Self::I64(column)
+        } else if let Some(column) = fast_fields.column_opt::<u64>(name)? {
+            Self::U64(column)
+        } else if let Some(column) = fast_fields.column_opt::<f64>(name)? {
+            Self::F64(column)
+        } else if let Some(column) = fast_fields.column_opt::<DateTime>(name)? {
+            Self::Date(column)
+        } else {
+            return Ok(None);
+        };
+        Ok(Some((column, source.divisor)))
+    }
+
+    fn value(&self, doc: DocId) -> Option<f64> {
+        match self {
+            ValueColumn::I64(column) => column.first(doc).map(|value| value as f64),
+            ValueColumn::U64(column) => column.first(doc).map(|value| value as f64),
+            ValueColumn::F64(column) => column.first(doc),
+            ValueColumn::Date(column) => column
+                .first(doc)
+                .map(|value| unix_micros_to_pg_micros(value.into_timestamp_micros()) as f64),
+        }
+    }
+}
+
+struct SegmentFunction {
+    filter: Option<Box<dyn Scorer>>,
+    column: Option<(ValueColumn, f64)>,
+    function: PreparedFunction,
+}
+
+impl SegmentFunction {
+    /// The function's weighted value for `doc`, or `None` if its filter doesn't match `doc`.
+    fn value(&mut self, doc: DocId) -> Option<(f64, f64)> {
+        if let Some(filter) = &mut self.filter {
+            if filter.doc() < doc {
+                filter.seek(doc);
+            }
+            if filter.doc() != doc {
+                return None;
+            }
+        }
+        let field_value = self
+            .column
+            .as_ref()
+            .and_then(|(column, divisor)| Some(column.value(doc)? / divisor));
+        let weight = self.function.weight as f64;
+        Some((self.function.value(field_value) * weight, weight))
+    }
+}
+
+struct FunctionScorer {
+    scorer: Box<dyn Scorer>,
+    functions: Vec<SegmentFunction>,
+    score_mode: ScoreMode,
+    boost_mode: BoostMode,
+}
+
+impl Scorer for FunctionScorer {
+    fn score(&mut self) -> Score {
+        let doc = self.scorer.doc();
+        let query_score = self.scorer.score();
+        // documents none of the functions apply to keep the query's score
+        match self
+            .score_mode
+            .combine(self.functions.iter_mut().filter_map(|f| f.value(doc)))
+        {
+            Some(function_score) => {
+                self.boost_mode.apply(query_score as f64, function_score) as Score
+            }
+            None => query_score,
+        }
+    }
+}
+
+impl DocSet for FunctionScorer {
+    fn advance(&mut self) -> DocId {
+        self.scorer.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.scorer.seek(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+}
+
+/// Parses a duration like `7d` or `1.5h` into microseconds. The units are `ms`, `s`, `m`, `h`,
+/// `d` and `w`.
+pub fn parse_duration_micros(duration: &str) -> Option<f64> {
+    let duration = duration.trim();
+    let split = duration.find(|c: char| c.is_ascii_alphabetic())?;
+    let (amount, unit) = duration.split_at(split);
+    let amount: f64 = amount.trim().parse().ok()?;
+    let micros_per_unit = match unit {
+        "ms" => 1_000.0,
+        "s" => 1_000_000.0,
+        "m" => 60_000_000.0,
+        "h" => 3_600_000_000.0,
+        "d" => 86_400_000_000.0,
+        "w" => 604_800_000_000.0,
+        _ => return None,
+    };
+    Some(amount * micros_per_unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decay_functions_reach_decay_at_scale_past_offset() {
+        for function in [
+            DecayFunction::Gauss,
+            DecayFunction::Exp,
+            DecayFunction::Linear,
+        ] {
+            assert_eq!(function.apply(105.0, 100.0, 10.0, 5.0, 0.5), 1.0);
+            assert!((function.apply(85.0, 100.0, 10.0, 5.0, 0.5) - 0.5).abs() < 1e-12);
+        }
+        assert_eq!(
+            DecayFunction::Linear.apply(130.0, 100.0, 10.0, 0.0, 0.5),
+            0.0
+        );
+    }
+
+    #[test]
+    fn score_modes_combine_weighted_values() {
+        let values = || [(2.0, 1.0), (6.0, 3.0)].into_iter();
+        assert_eq!(ScoreMode::Multiply.combine(values()), Some(12.0));
+        assert_eq!(ScoreMode::Sum.combine(values()), Some(8.0));
+        assert_eq!(ScoreMode::Avg.combine(values()), Some(2.0));
+        assert_eq!(ScoreMode::First.combine(values()), Some(2.0));
+        assert_eq!(ScoreMode::Max.combine(values()), Some(6.0));
+        assert_eq!(ScoreMode::Min.combine(values()), Some(2.0));
+        assert_eq!(ScoreMode::Sum.combine(std::iter::empty()), None);
+    }
+
+    #[test]
+    fn durations_parse_with_units() {
+        assert_eq!(parse_duration_micros("7d"), Some(604_800_000_000.0));
+        assert_eq!(parse_duration_micros("1.5h"), Some(5_400_000_000.0));
+        assert_eq!(parse_duration_micros("10"), None);
+        assert_eq!(parse_duration_micros("3y"), None);
+    }
+}
@@ -0,0 +1,329 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Hybrid search: one query that fuses the ranked results of a text query with the nearest
+//! neighbours of a query vector.
+//!
+//! Each segment is fused on its own, when its scorer is created: the text query and the vector
+//! search each keep their top `window_size` documents of the segment, and those two lists are
+//! fused into a score per document. [`FusedQuery`] then matches exactly those documents, scored
+//! with their fused score, which is what `pdb.score()` returns. Nothing is searched until the
+//! scan reaches a segment, so planning and parallel workers only pay for the segments they read.
+//!
+//! Both searches only consider the documents that the rest of the query's required clauses
+//! match, so that documents which are filtered out anyway don't take up their windows.
+
+use serde::{Deserialize, Serialize};
+use tantivy::collector::sort_key::SortBySimilarityScore;
+use tantivy::collector::{Collector, TopDocs};
+use tantivy::query::{
+    AllQuery, BooleanQuery, ConstScoreQuery, EnableScoring, Explanation, Occur, Query, Scorer,
+    Weight,
+};
+use tantivy::schema::Field;
+use tantivy::{DocAddress, DocId, DocSet, Score, SegmentReader, TERMINATED, TantivyError};
+
+use crate::api::HashMap;
+
+/// How the text and vector result lists are combined into one score.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fusion {
+    /// Reciprocal rank fusion: each list contributes `weight / (k + rank)`, where `rank` is the
+    /// document's 1-based position in that list.
+    Rrf {
+        k: f32,
+        text_weight: f32,
+        vector_weight: f32,
+    },
+    /// Each list's scores are min-max normalized to `[0, 1]`, its best document scoring `1`, and
+    /// blended as `text_weight * text + vector_weight * vector`.
+    ScoreBlend {
+        text_weight: f32,
+        vector_weight: f32,
+    },
+}
+
+impl Fusion {
+    /// The `(text_weight, vector_weight)` of either kind of fusion.
+    pub fn weights(&self) -> (f32, f32) {
+        match *self {
+            Fusion::Rrf {
+                text_weight,
+                vector_weight,
+                ..
+            }
+            | Fusion::ScoreBlend {
+                text_weight,
+                vector_weight,
+            } => (text_weight, vector_weight),
+        }
+    }
+
+    /// Fuses two result lists, each ordered best first, into a score per document. A document
+    /// missing from a list gets nothing from it.
+    fn fuse(
+        &self,
+        text: &[(Score, DocAddress)],
+        vector: &[(Score, DocAddress)],
+    ) -> HashMap<DocAddress, Score> {
+        let (text_weight, vector_weight) = self.weights();
+        let mut fused = HashMap::default();
+        for (hits, weight) in [(text, text_weight), (vector, vector_weight)] {
+            // a vector search's score may be a distance, so the best and worst scores are taken
+            // from the ends of the list rather than from their values
+            let (best, worst) = match (hits.first(), hits.last()) {
+                (Some((best, _)), Some((worst, _))) => (*best, *worst),
+                _ => continue,
+            };
+            for (rank, (score, doc)) in hits.iter().enumerate() {
+                let contribution = match self {
+                    Fusion::Rrf { k, .. } => weight / (k + (rank + 1) as f32),
+                    Fusion::ScoreBlend { .. } if best == worst => weight,
+                    Fusion::ScoreBlend { .. } => weight * (score - worst) / (best - worst),
+                };
+                *fused.entry(*doc).or_insert(0.0) += contribution;
+            }
+        }
+        fused
+    }
+}
+
+/// Matches the documents of a fused text and vector search, scored with their fused score.
+#[derive(Clone, Debug)]
+pub struct FusedQuery {
+    text_query: Box<dyn Query>,
+    filter: Option<Box<dyn Query>>,
+    field: Field,
+    vector: Vec<f32>,
+    fusion: Fusion,
+    window_size: usize,
+}
+
+impl FusedQuery {
+    /// Fuses the top `window_size` documents of `text_query` with the `window_size` nearest
+    /// neighbours of `vector` in `field` with `fusion`, both among the documents that `filter`
+    /// matches.
+    pub fn new(
+        text_query: Box<dyn Query>,
+        filter: Option<Box<dyn Query>>,
+        field: Field,
+        vector: Vec<f32>,
+        fusion: Fusion,
+        window_size: usize,
+    ) -> Self {
+        Self {
+            text_query,
+            filter,
+            field,
+            vector,
+            fusion,
+            window_size,
+        }
+    }
+}
+
+impl Query for FusedQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        // the text query is ranked by its score even when the fused scores aren't needed, as its
+        // ranks decide which documents match
+        let (searcher, scoring) = match enable_scoring {
+            EnableScoring::Enabled { searcher, .. } => (searcher, enable_scoring),
+            EnableScoring::Disabled {
+                searcher_opt: Some(searcher),
+                ..
+            } => (searcher, EnableScoring::enabled_from_searcher(searcher)),
+            EnableScoring::Disabled {
+                searcher_opt: None, ..
+            } => {
+                return Err(TantivyError::InvalidArgument(
+                    "a hybrid query needs a searcher to rank its text query".to_string(),
+                ));
+            }
+        };
+
+        let filter: Box<dyn Query> = match &self.filter {
+            Some(filter) => filter.clone(),
+            None => Box::new(AllQuery),
+        };
+        let text_query: Box<dyn Query> = match &self.filter {
+            // the filter mustn't change the text query's scores
+            Some(filter) => Box::new(BooleanQuery::new(vec![
+                (Occur::Must, self.text_query.clone()),
+                (
+                    Occur::Must,
+                    Box::new(ConstScoreQuery::new(filter.clone(), 0.0)),
+                ),
+            ])),
+            None => self.text_query.clone(),
+        };
+
+        Ok(Box::new(FusedWeight {
+            text_weight: text_query.weight(scoring)?,
+            filter_weight: filter.weight(EnableScoring::disabled_from_searcher(searcher))?,
+            field: self.field,
+            vector: self.vector.clone(),
+            fusion: self.fusion,
+            window_size: self.window_size,
+        }))
+    }
+}
+
+struct FusedWeight {
+    text_weight: Box<dyn Weight>,
+    filter_weight: Box<dyn Weight>,
+    field: Field,
+    vector: Vec<f32>,
+    fusion: Fusion,
+    window_size: usize,
+}
+
+impl FusedWeight {
+    /// Runs both searches over the segment and fuses them, returning the fused documents in doc
+    /// id order.
+    fn fuse_segment(&self, reader: &SegmentReader) -> tantivy::Result<Vec<(DocId, Score)>> {
+        // only the doc ids are kept, so the segment's ordinal doesn't matter
+        let text =
+            TopDocs::with_limit(self.window_size).order_by::<Score>(SortBySimilarityScore::new());
+        let text_hits = text.merge_fruits(vec![text.collect_segment(
+            self.text_weight.as_ref(),
+            0,
+            reader,
+        )?])?;
+
+        // exact search scores every vector of the segment, see `vector_exact_search`
+        let exact = crate::gucs::vector_exact_search();
+        let limit = if exact {
+            (reader.max_doc() as usize).max(1)
+        } else {
+            self.window_size
+        };
+        let vector = TopDocs::with_limit(limit)
+            .order_by_similarity(self.field, self.vector.clone())
+            .with_adaptive_params(crate::vector::probe_params());
+        let mut vector_hits = vector
+            .merge_fruits(vec![vector.collect_segment(
+                self.filter_weight.as_ref(),
+                0,
+                reader,
+            )?])?
+            .results;
+        vector_hits.truncate(self.window_size);
+
+        let mut docs = self
+            .fusion
+            .fuse(&text_hits, &vector_hits)
+            .into_iter()
+            .map(|(doc, score)| (doc.doc_id, score))
+            .collect::<Vec<_>>();
+        docs.sort_unstable_by_key(|(doc, _)| *doc);
+        Ok(docs)
+    }
+}
+
+impl Weight for FusedWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        Ok(Box::new(FusedScorer {
+            docs: self.fuse_segment(reader)?,
+            cursor: 0,
+            boost,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) is not in the fused results"
+            )));
+        }
+        Ok(Explanation::new("Hybrid fusion", scorer.score()))
+    }
+}
+
+struct FusedScorer {
+    docs: Vec<(DocId, Score)>,
+    cursor: usize,
+    boost: Score,
+}
+
+impl Scorer for FusedScorer {
+    fn score(&mut self) -> Score {
+        self.docs[self.cursor].1 * self.boost
+    }
+}
+
+impl DocSet for FusedScorer {
+    fn advance(&mut self) -> DocId {
+        if self.cursor < self.docs.len() {
+            self.cursor += 1;
+        }
+        self.doc()
+    }
+
+    fn doc(&self) -> DocId {
+        self.docs
+            .get(self.cursor)
+            .map_or(TERMINATED, |(doc, _)| *doc)
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.docs.len() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hits(docs: &[(Score, DocId)]) -> Vec<(Score, DocAddress)> {
+        docs.iter()
+            .map(|(score, doc)| (*score, DocAddress::new(0, *doc)))
+            .collect()
+    }
+
+    #[test]
+    fn rrf_adds_weighted_reciprocal_ranks() {
+        let fusion = Fusion::Rrf {
+            k: 60.0,
+            text_weight: 1.0,
+            vector_weight: 0.5,
+        };
+        let fused = fusion.fuse(&hits(&[(9.0, 1), (4.0, 2)]), &hits(&[(0.1, 2), (0.3, 3)]));
+
+        assert_eq!(fused[&DocAddress::new(0, 1)], 1.0 / 61.0);
+        assert_eq!(fused[&DocAddress::new(0, 2)], 1.0 / 62.0 + 0.5 / 61.0);
+        assert_eq!(fused[&DocAddress::new(0, 3)], 0.5 / 62.0);
+    }
+
+    #[test]
+    fn score_blend_normalizes_each_list_best_first() {
+        let fusion = Fusion::ScoreBlend {
+            text_weight: 0.5,
+            vector_weight: 0.5,
+        };
+        // the vector list is ordered by ascending distance
+        let fused = fusion.fuse(
+            &hits(&[(10.0, 1), (6.0, 2), (2.0, 3)]),
+            &hits(&[(0.2, 3), (0.6, 1)]),
+        );
+
+        assert_eq!(fused[&DocAddress::new(0, 1)], 0.5);
+        assert_eq!(fused[&DocAddress::new(0, 2)], 0.25);
+        assert_eq!(fused[&DocAddress::new(0, 3)], 0.5);
+    }
+}
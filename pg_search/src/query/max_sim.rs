@@ -0,0 +1,133 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Late-interaction search of a multi-vector field: matches every document with vectors in
+//! the field, scored by sum-of-MaxSim against a set of query vectors.
+//!
+//! Each segment is scored when its scorer is created.  The field's bytes fast column keeps
+//! each distinct packed value once, in its dictionary, so the dictionary is streamed and each
+//! value scored once, and every document then takes the score of its value.  The field's
+//! vectors aren't clustered, so every document with vectors is scored.
+
+use tantivy::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use tantivy::schema::Field;
+use tantivy::{DocId, DocSet, Score, SegmentReader, TERMINATED, TantivyError};
+
+use crate::vector::multi::max_sim;
+
+/// Matches the documents with vectors in the multi-vector `field`, scored with [`max_sim`]
+/// against `vectors`.
+#[derive(Clone, Debug)]
+pub struct MaxSimQuery {
+    field: Field,
+    vectors: Vec<Vec<f32>>,
+}
+
+impl MaxSimQuery {
+    pub fn new(field: Field, vectors: Vec<Vec<f32>>) -> Self {
+        Self { field, vectors }
+    }
+}
+
+impl Query for MaxSimQuery {
+    fn weight(&self, _enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        Ok(Box::new(MaxSimWeight {
+            query: self.clone(),
+        }))
+    }
+}
+
+struct MaxSimWeight {
+    query: MaxSimQuery,
+}
+
+impl MaxSimWeight {
+    /// Scores the segment's live documents with vectors, in doc id order.
+    fn score_segment(&self, reader: &SegmentReader) -> tantivy::Result<Vec<(DocId, Score)>> {
+        let field_name = reader.schema().get_field_name(self.query.field);
+        let Some(column) = reader.fast_fields().bytes(field_name)? else {
+            return Ok(Vec::new());
+        };
+
+        // the dictionary streams in term ordinal order, so a value's score is at its ordinal
+        let mut value_scores = Vec::new();
+        let mut values = column.dictionary().stream()?;
+        while values.advance() {
+            value_scores.push(max_sim(&self.query.vectors, values.key()));
+        }
+
+        let alive_bitset = reader.alive_bitset();
+        Ok((0..reader.max_doc())
+            .filter(|doc| alive_bitset.is_none_or(|alive_bitset| alive_bitset.is_alive(*doc)))
+            .filter_map(|doc| {
+                let ord = column.term_ords(doc).next()?;
+                Some((doc, value_scores[ord as usize]))
+            })
+            .collect())
+    }
+}
+
+impl Weight for MaxSimWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        Ok(Box::new(MaxSimScorer {
+            docs: self.score_segment(reader)?,
+            cursor: 0,
+            boost,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) has no vectors in the field"
+            )));
+        }
+        Ok(Explanation::new("MaxSim", scorer.score()))
+    }
+}
+
+struct MaxSimScorer {
+    docs: Vec<(DocId, Score)>,
+    cursor: usize,
+    boost: Score,
+}
+
+impl Scorer for MaxSimScorer {
+    fn score(&mut self) -> Score {
+        self.docs[self.cursor].1 * self.boost
+    }
+}
+
+impl DocSet for MaxSimScorer {
+    fn advance(&mut self) -> DocId {
+        if self.cursor < self.docs.len() {
+            self.cursor += 1;
+        }
+        self.doc()
+    }
+
+    fn doc(&self) -> DocId {
+        self.docs
+            .get(self.cursor)
+            .map_or(TERMINATED, |(doc, _)| *doc)
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.docs.len() as u32
+    }
+}
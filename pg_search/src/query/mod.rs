@@ -16,18 +16,33 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 pub mod builder;
+mod combined_fields;
 pub mod estimate_tree;
+pub mod function_score;
 pub mod heap_field_filter;
+pub mod hybrid;
+mod max_sim;
 mod more_like_this;
 pub mod numeric;
 pub mod pdb_query;
 pub(crate) mod proximity;
 mod range;
 mod score;
+pub mod similarity;
+pub mod skip_segments;
+mod vector_within;
 
 use builder::{QueryBuilder, QueryOnlyBuilder, QueryTreeBuilder};
+use combined_fields::CombinedFieldsQuery;
 use estimate_tree::QueryWithEstimates;
+use function_score::{
+    BoostMode, FunctionScoreQuery, PreparedFunction, ScoreFunction, ScoreFunctionKind, ScoreMode,
+    ValueSource,
+};
 use heap_field_filter::HeapFieldFilter;
+use hybrid::{FusedQuery, Fusion};
+use max_sim::MaxSimQuery;
+use vector_within::VectorWithinQuery;
 
 use crate::api::FieldName;
 use crate::api::HashMap;
@@ -37,9 +52,9 @@ use crate::postgres::customscan::explain::{ExplainFormat, format_for_explain};
 use crate::postgres::datetime::PostgresDateTime;
 use crate::postgres::pdb_owned_value::PdbOwnedValue;
 use crate::query::more_like_this::MoreLikeThisQuery;
-use crate::query::pdb_query::pdb;
+use crate::query::pdb_query::{pdb, resolve_search_tokenizer};
 use crate::query::score::ScoreFilter;
-use crate::schema::SearchIndexSchema;
+use crate::schema::{SearchFieldConfig, SearchFieldType, SearchIndexSchema};
 use anyhow::Result;
 use core::panic;
 use pgrx::{
@@ -133,6 +148,46 @@ pub enum SearchQueryInput {
         indexed_query: Box<SearchQueryInput>,
         field_filters: Vec<HeapFieldFilter>,
     },
+    /// Fuses the top `window_size` results of `query` with the `window_size` nearest
+    /// neighbours of `vector` in the vector field `field`
+    Hybrid {
+        query: Box<SearchQueryInput>,
+        field: FieldName,
+        vector: Vec<f32>,
+        fusion: Fusion,
+        window_size: usize,
+        /// The other required clauses of the query this one is part of, which both searches are
+        /// restricted to.  Filled in when the query is built
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        filter: Option<Box<SearchQueryInput>>,
+    },
+    /// Matches the documents whose vector field `field` is within `max_distance` of `vector`
+    VectorWithin {
+        field: FieldName,
+        vector: Vec<f32>,
+        max_distance: f64,
+    },
+    /// Matches the documents with vectors in the multi-vector field `field`, scored by the sum,
+    /// over `vectors`, of each one's greatest inner product with any of the document's
+    MaxSim {
+        field: FieldName,
+        vectors: Vec<Vec<f32>>,
+    },
+    /// Adjusts the scores of `query`'s documents with functions of their fast field values
+    FunctionScore {
+        query: Box<SearchQueryInput>,
+        functions: Vec<ScoreFunction>,
+        score_mode: ScoreMode,
+        boost_mode: BoostMode,
+    },
+    /// Scores `value` in the text `fields` with BM25F, as if they were one field. Each field is
+    /// paired with the weight its term frequencies and lengths are scaled by
+    CombinedFields {
+        fields: Vec<(FieldName, f32)>,
+        value: String,
+        conjunction_mode: bool,
+    },
 
     #[serde(serialize_with = "serialize_fielded_query")]
     #[serde(deserialize_with = "deserialize_fielded_query")]
@@ -167,7 +222,8 @@ macro_rules! visit_search_query_input {
             }
             SearchQueryInput::Boost { query, .. }
             | SearchQueryInput::ConstScore { query, .. }
-            | SearchQueryInput::WithIndex { query, .. } => query.$visit_method($visitor),
+            | SearchQueryInput::WithIndex { query, .. }
+            | SearchQueryInput::Hybrid { query, .. } => query.$visit_method($visitor),
             SearchQueryInput::ScoreFilter { query, .. } => query
                 .$option_access()
                 .expect("ScoreFilter's query should have been set")
@@ -177,6 +233,16 @@ macro_rules! visit_search_query_input {
                     query.$visit_method($visitor);
                 }
             }
+            SearchQueryInput::FunctionScore {
+                query, functions, ..
+            } => {
+                query.$visit_method($visitor);
+                for function in functions {
+                    if let Some(filter) = function.filter.$option_access() {
+                        filter.$visit_method($visitor);
+                    }
+                }
+            }
             SearchQueryInput::HeapFilter { indexed_query, .. } => {
                 indexed_query.$visit_method($visitor);
             }
@@ -186,6 +252,9 @@ macro_rules! visit_search_query_input {
             | SearchQueryInput::MoreLikeThis { .. }
             | SearchQueryInput::Parse { .. }
             | SearchQueryInput::TermSet { .. }
+            | SearchQueryInput::CombinedFields { .. }
+            | SearchQueryInput::VectorWithin { .. }
+            | SearchQueryInput::MaxSim { .. }
             | SearchQueryInput::PostgresExpression { .. }
             | SearchQueryInput::FieldedQuery { .. } => {}
         }
@@ -324,6 +393,8 @@ impl SearchQueryInput {
             SearchQueryInput::HeapFilter { indexed_query, .. } => Self::need_scores(indexed_query),
             SearchQueryInput::MoreLikeThis { .. } => true,
             SearchQueryInput::ScoreFilter { .. } => true,
+            SearchQueryInput::Hybrid { .. } => true,
+            SearchQueryInput::FunctionScore { .. } => true,
             _ => false,
         }
     }
@@ -429,6 +500,7 @@ impl SearchQueryInput {
             SearchQueryInput::HeapFilter { indexed_query, .. } => {
                 Self::is_full_scan_query(indexed_query)
             }
+            SearchQueryInput::FunctionScore { query, .. } => Self::is_full_scan_query(query),
 
             // All other variants are not full scans
             _ => false,
@@ -441,9 +513,13 @@ impl SearchQueryInput {
             | SearchQueryInput::All
             | SearchQueryInput::Empty
             | SearchQueryInput::TermSet { .. }
+            | SearchQueryInput::VectorWithin { .. }
+            | SearchQueryInput::MaxSim { .. }
             | SearchQueryInput::PostgresExpression { .. } => false,
 
-            SearchQueryInput::Parse { .. } | SearchQueryInput::MoreLikeThis { .. } => true,
+            SearchQueryInput::Parse { .. }
+            | SearchQueryInput::MoreLikeThis { .. }
+            | SearchQueryInput::CombinedFields { .. } => true,
 
             SearchQueryInput::FieldedQuery { query, .. } => query.needs_tokenizer(),
             SearchQueryInput::Boolean {
@@ -459,6 +535,7 @@ impl SearchQueryInput {
             SearchQueryInput::Boost { query, .. }
             | SearchQueryInput::ConstScore { query, .. }
             | SearchQueryInput::WithIndex { query, .. }
+            | SearchQueryInput::Hybrid { query, .. }
             | SearchQueryInput::HeapFilter {
                 indexed_query: query,
                 ..
@@ -470,6 +547,15 @@ impl SearchQueryInput {
                 query: Some(query), ..
             } => query.needs_tokenizer(),
             SearchQueryInput::ScoreFilter { query: None, .. } => false,
+            SearchQueryInput::FunctionScore {
+                query, functions, ..
+            } => {
+                query.needs_tokenizer()
+                    || functions
+                        .iter()
+                        .filter_map(|f| f.filter.as_deref())
+                        .any(Self::needs_tokenizer)
+            }
         }
     }
 
@@ -499,8 +585,15 @@ impl SearchQueryInput {
             SearchQueryInput::ScoreFilter {
                 query: Some(query), ..
             } => Self::is_expensive_to_estimate(query),
+            SearchQueryInput::FunctionScore { query, .. } => Self::is_expensive_to_estimate(query),
 
-            SearchQueryInput::MoreLikeThis { .. } => true,
+            // a hybrid query runs both of its searches over every segment it scores, a vector
+            // range query scores every vector of the clusters it probes, and a MaxSim query
+            // scores every document with vectors
+            SearchQueryInput::MoreLikeThis { .. }
+            | SearchQueryInput::Hybrid { .. }
+            | SearchQueryInput::VectorWithin { .. }
+            | SearchQueryInput::MaxSim { .. } => true,
 
             SearchQueryInput::FieldedQuery { query, .. } => query.is_expensive_to_estimate(),
 
@@ -546,6 +639,7 @@ impl SearchQueryInput {
             SearchQueryInput::ScoreFilter {
                 query: Some(query), ..
             } => Self::selectivity_heuristic(query),
+            SearchQueryInput::FunctionScore { query, .. } => Self::selectivity_heuristic(query),
 
             SearchQueryInput::MoreLikeThis { .. } => MORE_LIKE_THIS_SELECTIVITY,
 
@@ -674,6 +768,36 @@ impl SearchQueryInput {
             SearchQueryInput::HeapFilter { indexed_query, .. } => {
                 indexed_query.extract_field_names(field_names);
             }
+            SearchQueryInput::Hybrid { query, field, .. } => {
+                query.extract_field_names(field_names);
+                field_names.insert(field.root());
+            }
+            SearchQueryInput::FunctionScore {
+                query, functions, ..
+            } => {
+                query.extract_field_names(field_names);
+                for function in functions {
+                    if let Some(filter) = &function.filter {
+                        filter.extract_field_names(field_names);
+                    }
+                    match &function.kind {
+                        ScoreFunctionKind::Weight => {}
+                        ScoreFunctionKind::Decay { field, .. }
+                        | ScoreFunctionKind::FieldValueFactor { field, .. } => {
+                            field_names.insert(field.root());
+                        }
+                    }
+                }
+            }
+            SearchQueryInput::CombinedFields { fields, .. } => {
+                for (field, _) in fields {
+                    field_names.insert(field.root());
+                }
+            }
+            SearchQueryInput::VectorWithin { field, .. }
+            | SearchQueryInput::MaxSim { field, .. } => {
+                field_names.insert(field.root());
+            }
             SearchQueryInput::FieldedQuery { field, .. } => {
                 field_names.insert(field.root());
             }
@@ -1115,11 +1239,13 @@ impl SearchQueryInput {
                 Ok(builder.build_leaf(query, || "All Query".to_string(), cloned_for_estimate))
             }
             SearchQueryInput::Boolean {
-                must,
+                mut must,
                 should,
                 must_not,
                 minimum_should_match,
             } => {
+                push_filters_into_hybrid(&mut must, &must_not);
+
                 // ---------------------------------
                 // We use B::split_for_parent() to avoid cloning for QueryOnlyBuilder.
                 //
@@ -1493,6 +1619,188 @@ impl SearchQueryInput {
                     cloned_for_estimate,
                 ))
             }
+            SearchQueryInput::Hybrid {
+                query: inner_query,
+                field,
+                vector,
+                fusion,
+                window_size,
+                filter,
+            } => {
+                let search_field = schema
+                    .search_field(field.root())
+                    .ok_or_else(|| QueryError::NonIndexedField(field.clone()))?;
+                let SearchFieldType::Vector(_, dims, metric) = search_field.field_type() else {
+                    return Err(QueryError::WrongFieldType(field).into());
+                };
+                if metric == crate::vector::metric::VectorMetric::Jaccard {
+                    anyhow::bail!(
+                        "field `{field}` is indexed with bit_jaccard_ops, which hybrid search doesn't support"
+                    );
+                }
+                if search_field.vector_quantization().is_some() {
+                    anyhow::bail!(
+                        "field `{field}` is quantized, which hybrid search doesn't support"
+                    );
+                }
+                if vector.len() != dims {
+                    anyhow::bail!(
+                        "field `{field}` has {dims} dimensions, but the query vector has {}",
+                        vector.len()
+                    );
+                }
+
+                let inner_output = recurse(*inner_query)?;
+                // Use split_for_parent: zero-cost for QueryOnlyBuilder
+                let (inner_tantivy, opt_output) = B::split_for_parent(inner_output);
+                let filter = filter
+                    .map(|filter| recurse(*filter).map(B::take_query))
+                    .transpose()?;
+                let query = Box::new(FusedQuery::new(
+                    inner_tantivy,
+                    filter,
+                    search_field.field(),
+                    vector,
+                    fusion,
+                    window_size,
+                ));
+                Ok(builder.build_with_children(
+                    query,
+                    || format!("Hybrid Query (field: {field})"),
+                    |_| opt_output.into_iter().collect(),
+                    cloned_for_estimate,
+                ))
+            }
+            SearchQueryInput::VectorWithin {
+                field,
+                vector,
+                max_distance,
+            } => {
+                let search_field = schema
+                    .search_field(field.root())
+                    .ok_or_else(|| QueryError::NonIndexedField(field.clone()))?;
+                let SearchFieldType::Vector(_, dims, metric) = search_field.field_type() else {
+                    return Err(QueryError::WrongFieldType(field).into());
+                };
+                if metric == crate::vector::metric::VectorMetric::Jaccard {
+                    anyhow::bail!(
+                        "field `{field}` is indexed with bit_jaccard_ops, which vector range search doesn't support"
+                    );
+                }
+                if search_field.vector_quantization().is_some() {
+                    anyhow::bail!(
+                        "field `{field}` is quantized, which vector range search doesn't support"
+                    );
+                }
+                if vector.len() != dims {
+                    anyhow::bail!(
+                        "field `{field}` has {dims} dimensions, but the query vector has {}",
+                        vector.len()
+                    );
+                }
+
+                let query =
+                    VectorWithinQuery::new(search_field.field(), vector, metric, max_distance);
+                Ok(builder.build_leaf(
+                    Box::new(query),
+                    || format!("VectorWithin Query (field: {field}, max_distance: {max_distance})"),
+                    cloned_for_estimate,
+                ))
+            }
+            SearchQueryInput::MaxSim { field, vectors } => {
+                let search_field = schema
+                    .search_field(field.root())
+                    .ok_or_else(|| QueryError::NonIndexedField(field.clone()))?;
+                let SearchFieldType::MultiVector(_, dims) = search_field.field_type() else {
+                    return Err(QueryError::WrongFieldType(field).into());
+                };
+                if let Some(vector) = vectors.iter().find(|vector| vector.len() != dims) {
+                    anyhow::bail!(
+                        "field `{field}` has vectors of {dims} dimensions, but a query vector has {}",
+                        vector.len()
+                    );
+                }
+
+                let query = MaxSimQuery::new(search_field.field(), vectors);
+                Ok(builder.build_leaf(
+                    Box::new(query),
+                    || format!("MaxSim Query (field: {field})"),
+                    cloned_for_estimate,
+                ))
+            }
+            SearchQueryInput::FunctionScore {
+                query: inner_query,
+                functions,
+                score_mode,
+                boost_mode,
+            } => {
+                let functions = functions
+                    .into_iter()
+                    .map(|function| {
+                        let source = match &function.kind {
+                            ScoreFunctionKind::Weight => None,
+                            ScoreFunctionKind::Decay { field, dates, .. } => {
+                                Some(function_score_source(schema, field, Some(*dates))?)
+                            }
+                            ScoreFunctionKind::FieldValueFactor { field, .. } => {
+                                Some(function_score_source(schema, field, None)?)
+                            }
+                        };
+                        let filter = function
+                            .filter
+                            .map(|filter| {
+                                filter.into_tantivy_query(
+                                    schema,
+                                    index_created_by_version,
+                                    parser,
+                                    searcher,
+                                    index_oid,
+                                    relation_oid,
+                                    expr_context,
+                                    planstate,
+                                )
+                            })
+                            .transpose()?;
+                        Ok(PreparedFunction {
+                            filter,
+                            weight: function.weight,
+                            kind: function.kind,
+                            source,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                let inner_output = recurse(*inner_query)?;
+                // Use split_for_parent: zero-cost for QueryOnlyBuilder
+                let (inner_tantivy, opt_output) = B::split_for_parent(inner_output);
+                let num_functions = functions.len();
+                let query = Box::new(FunctionScoreQuery::new(
+                    inner_tantivy,
+                    functions,
+                    score_mode,
+                    boost_mode,
+                ));
+                Ok(builder.build_with_children(
+                    query,
+                    || format!("FunctionScore Query (functions: {num_functions})"),
+                    |_| opt_output.into_iter().collect(),
+                    cloned_for_estimate,
+                ))
+            }
+            SearchQueryInput::CombinedFields {
+                fields,
+                value,
+                conjunction_mode,
+            } => {
+                let num_fields = fields.len();
+                let query =
+                    combined_fields_query(schema, searcher, &fields, &value, conjunction_mode)?;
+                Ok(builder.build_leaf(
+                    Box::new(query),
+                    || format!("CombinedFields Query (fields: {num_fields})"),
+                    cloned_for_estimate,
+                ))
+            }
             SearchQueryInput::PostgresExpression { .. } => {
                 panic!("postgres expressions have not been solved")
             }
@@ -1545,6 +1853,135 @@ impl SearchQueryInput {
     }
 }
 
+/// Restricts the searches of a `Hybrid` query among `must` to the documents that the other
+/// required clauses match, so that documents filtered out anyway don't take up its windows.
+fn push_filters_into_hybrid(must: &mut [SearchQueryInput], must_not: &[SearchQueryInput]) {
+    let is_hybrid = |query: &SearchQueryInput| matches!(query, SearchQueryInput::Hybrid { .. });
+    if must.iter().filter(|query| is_hybrid(query)).count() != 1 {
+        return;
+    }
+    let mut filters = must
+        .iter()
+        .filter(|query| !is_hybrid(query))
+        .cloned()
+        .collect::<Vec<_>>();
+    if filters.is_empty() && must_not.is_empty() {
+        return;
+    }
+
+    let Some(SearchQueryInput::Hybrid { filter, .. }) = must.iter_mut().find(|q| is_hybrid(q))
+    else {
+        unreachable!("there should be exactly one hybrid query");
+    };
+    filters.extend(filter.take().map(|filter| *filter));
+    *filter = Some(Box::new(SearchQueryInput::Boolean {
+        must: filters,
+        should: vec![],
+        must_not: must_not.to_vec(),
+        minimum_should_match: None,
+    }));
+}
+
+/// The fast field a function score `field` reads. `dates` is whether the function was given dates,
+/// or `None` if it works with either.
+fn function_score_source(
+    schema: &SearchIndexSchema,
+    field: &FieldName,
+    dates: Option<bool>,
+) -> Result<ValueSource> {
+    let search_field = schema
+        .search_field(field.root())
+        .ok_or_else(|| QueryError::NonIndexedField(field.clone()))?;
+    if field.path().is_some() || !search_field.is_fast() {
+        anyhow::bail!("function score field `{field}` must be a fast numeric or date field");
+    }
+    let field_type = search_field.field_type();
+    let is_date = match field_type {
+        SearchFieldType::I64(_)
+        | SearchFieldType::U64(_)
+        | SearchFieldType::F64(_)
+        | SearchFieldType::Numeric64(..) => false,
+        SearchFieldType::Date(_) => true,
+        _ => anyhow::bail!("function score field `{field}` must be a fast numeric or date field"),
+    };
+    match dates {
+        Some(true) if !is_date => {
+            anyhow::bail!("field `{field}` is not a date, so its origin must be a number")
+        }
+        Some(false) if is_date => {
+            anyhow::bail!("field `{field}` is a date, so its origin must be a timestamp")
+        }
+        _ => {}
+    }
+    Ok(ValueSource {
+        field_name: field.root(),
+        divisor: field_type
+            .numeric_scale()
+            .map_or(1.0, |scale| 10f64.powi(scale as i32)),
+    })
+}
+
+/// The BM25F query for `value` in `fields`, which must be indexed text fields that are scored
+/// with the same BM25 parameters and that tokenize `value` the same way.
+fn combined_fields_query(
+    schema: &SearchIndexSchema,
+    searcher: &Searcher,
+    fields: &[(FieldName, f32)],
+    value: &str,
+    conjunction_mode: bool,
+) -> Result<CombinedFieldsQuery> {
+    let mut combined = Vec::with_capacity(fields.len());
+    let mut params = None;
+    let mut tokens = None;
+    for (field, weight) in fields {
+        let search_field = schema
+            .search_field(field.root())
+            .ok_or_else(|| QueryError::NonIndexedField(field.clone()))?;
+        let config = search_field.field_config();
+        if field.path().is_some()
+            || !matches!(config, SearchFieldConfig::Text { indexed: true, .. })
+        {
+            anyhow::bail!("combined fields field `{field}` must be an indexed text field");
+        }
+        if !config.similarity().is_bm25() {
+            anyhow::bail!("combined fields field `{field}` must use the bm25 similarity");
+        }
+        let field_params = config.bm25_params();
+        let field_params = (field_params.k1(), field_params.b());
+        match params {
+            None => params = Some(field_params),
+            Some(params) if params != field_params => {
+                anyhow::bail!("combined fields must all have the same k1 and b")
+            }
+            Some(_) => {}
+        }
+
+        let mut tokenizer = resolve_search_tokenizer(&search_field, schema, searcher)?;
+        let mut stream = tokenizer.token_stream(value);
+        let mut field_tokens = Vec::new();
+        while let Some(token) = stream.next() {
+            field_tokens.push(token.text.clone());
+        }
+        match &tokens {
+            None => tokens = Some(field_tokens),
+            Some(tokens) if *tokens != field_tokens => anyhow::bail!(
+                "combined fields must tokenize their query the same way, but `{field}` doesn't"
+            ),
+            Some(_) => {}
+        }
+        combined.push((search_field.field(), *weight));
+    }
+
+    let (k1, b) = params.ok_or_else(|| anyhow::anyhow!("combined fields needs a field"))?;
+    Ok(CombinedFieldsQuery::new(
+        combined,
+        tokens.unwrap_or_default(),
+        conjunction_mode,
+        k1,
+        b,
+    ))
+}
+
 /// Convert a string-encoded numeric value to the appropriate type based on field type.
 /// Used for JSON field comparisons where NUMERIC constants need to match stored JSON numbers.
 fn convert_for_field_type(value: &PdbOwnedValue, field_type: &FieldType) -> PdbOwnedValue {
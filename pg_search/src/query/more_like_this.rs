@@ -196,8 +196,10 @@ impl MoreLikeThisQueryBuilder {
                         continue;
                     }
 
-                    let is_vector =
-                        matches!(search_field.field_type(), SearchFieldType::Vector(..));
+                    let is_vector = matches!(
+                        search_field.field_type(),
+                        SearchFieldType::Vector(..) | SearchFieldType::MultiVector(..)
+                    );
 
                     if let Some(ref fields) = fields {
                         if !fields.contains(&search_field.field_name().clone().into_inner()) {
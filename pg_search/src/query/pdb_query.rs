@@ -16,6 +16,7 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use crate::api::FieldName;
+use crate::api::tokenizers::TokenizerTables;
 use crate::api::version::{Version, VersionInfo};
 use crate::postgres::datetime::PostgresDateTime;
 use crate::postgres::pdb_owned_value::PdbOwnedValue;
@@ -28,6 +29,7 @@ use crate::query::pdb_query::pdb::{FuzzyData, ScoreAdjustStyle, SlopData};
 use crate::query::proximity::query::ProximityQuery;
 use crate::query::proximity::{ProximityClause, ProximityDistance};
 use crate::query::range::{Comparison, RangeField};
+use crate::query::similarity::SimilarityQuery;
 use crate::query::{
     QueryError, SearchQueryInput, check_range_bounds, coerce_bound_to_field_type, value_to_term,
 };
@@ -463,6 +465,23 @@ impl pdb::Query {
         parser: &QueryParserCtor,
         searcher: &Searcher,
     ) -> anyhow::Result<Box<dyn TantivyQuery>> {
+        // queries on a field with a non-BM25 similarity are rescored by that similarity.  the
+        // reader rescores the terms of any other query, see `with_field_similarities`, but not
+        // those under a score adjustment, so the queries of a field are rescored here
+        let similarity = if self.uses_field_similarity() {
+            schema
+                .search_field(field.root())
+                .map(|search_field| {
+                    (
+                        search_field.field(),
+                        search_field.field_config().similarity(),
+                    )
+                })
+                .filter(|(_, similarity)| !similarity.is_bm25())
+        } else {
+            None
+        };
+
         let query: Box<dyn TantivyQuery> = match self {
             pdb::Query::All => Box::new(AllQuery),
             pdb::Query::Empty => Box::new(EmptyQuery),
@@ -662,7 +681,28 @@ impl pdb::Query {
             }
         };
 
-        Ok(query)
+        match similarity {
+            Some((field, similarity)) => {
+                Ok(Box::new(SimilarityQuery::new(field, similarity, query)))
+            }
+            None => Ok(query),
+        }
+    }
+
+    /// Whether this query scores the terms of its field, and should be rescored by the
+    /// field's similarity. Score adjustments wrap an inner query that's rescored itself.
+    fn uses_field_similarity(&self) -> bool {
+        matches!(
+            self,
+            pdb::Query::Term { .. }
+                | pdb::Query::Match { .. }
+                | pdb::Query::MatchArray { .. }
+                | pdb::Query::Phrase { .. }
+                | pdb::Query::PhraseArray { .. }
+                | pdb::Query::TokenizedPhrase { .. }
+                | pdb::Query::ParseWithField { .. }
+                | pdb::Query::Proximity { .. }
+        )
     }
 
     pub fn needs_tokenizer(&self) -> bool {
@@ -1632,20 +1672,24 @@ fn range(
     Ok(Box::new(RangeQuery::new(lower_bound, upper_bound)))
 }
 
-fn resolve_search_tokenizer(
+pub(crate) fn resolve_search_tokenizer(
     search_field: &SearchField,
     schema: &SearchIndexSchema,
     searcher: &Searcher,
 ) -> anyhow::Result<tantivy::tokenizer::TextAnalyzer> {
+    // search tokenizers are registered with the index, which fills in the tables they read
+    let registered = |st: &SearchTokenizer| {
+        searcher
+            .index()
+            .tokenizers()
+            .get(&st.name())
+            .ok_or_else(|| anyhow::anyhow!("search_tokenizer `{}` is not registered", st.name()))
+    };
     if let Some(st) = search_field.field_config().search_tokenizer() {
-        return Ok(st
-            .to_tantivy_tokenizer()
-            .expect("field-level search_tokenizer should be a valid tantivy tokenizer"));
+        return registered(st);
     }
     if let Some(ref st) = schema.index_search_tokenizer() {
-        return Ok(st
-            .to_tantivy_tokenizer()
-            .expect("index-level search_tokenizer should be a valid tantivy tokenizer"));
+        return registered(st);
     }
     Ok(searcher.index().tokenizer_for_field(search_field.field())?)
 }
@@ -1973,9 +2017,11 @@ fn match_query(
         .ok_or(QueryError::NonIndexedField(field.clone()))?;
     let field_type = search_field.field_entry().field_type();
     let mut analyzer = match tokenizer {
-        Some(ref tokenizer) => SearchTokenizer::from_json_value(tokenizer)?
-            .to_tantivy_tokenizer()
-            .expect("tantivy should support tokenizer {tokenizer:?}"),
+        Some(ref tokenizer) => {
+            let mut tokenizer = SearchTokenizer::from_json_value(tokenizer)?;
+            TokenizerTables::resolve(&mut tokenizer)?;
+            tokenizer.to_tantivy_tokenizer()?
+        }
         None => resolve_search_tokenizer(&search_field, schema, searcher)?,
     };
     let mut stream = analyzer.token_stream(value);
@@ -0,0 +1,418 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-field similarities: how a text field scores the terms a query matches in it.
+//!
+//! BM25 fields are scored by tantivy. Queries on a field configured with any other similarity
+//! are wrapped in a [`SimilarityQuery`], which matches the same documents and scores each of
+//! them as the sum of its matched terms' scores under that similarity.
+
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use tantivy::fieldnorm::FieldNormReader;
+use tantivy::index::SegmentId;
+use tantivy::postings::{Postings, SegmentPostings};
+use tantivy::query::{EnableScoring, Explanation, Query, QueryClone, Scorer, Weight};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{DocId, DocSet, Score, Searcher, SegmentReader, TERMINATED, TantivyError, Term};
+
+use crate::api::{HashMap, HashSet};
+
+fn default_dirichlet_mu() -> f32 {
+    2000.0
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Similarity {
+    /// Okapi BM25, tuned by the field's `k1` and `b`.
+    #[default]
+    Bm25,
+    /// Classic TF-IDF: `sqrt(tf) * idf² / sqrt(length)`.
+    Tfidf,
+    /// Divergence from randomness, with the `I(n)` basic model, Laplace after-effect and `H2`
+    /// length normalization.
+    Dfr,
+    /// A language model with Dirichlet smoothing of strength `mu`.
+    Dirichlet {
+        #[serde(default = "default_dirichlet_mu")]
+        mu: f32,
+    },
+    /// Every matched term scores `1`.
+    Constant,
+}
+
+/// The corpus statistics of one query term.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TermStats {
+    pub num_docs: u64,
+    pub doc_freq: u64,
+    pub avg_len: f32,
+    /// Only gathered for the similarities that use them, see [`Similarity::needs_term_freqs`].
+    pub total_term_freq: u64,
+    pub total_tokens: u64,
+}
+
+impl Similarity {
+    /// Parses a `similarity=` type modifier, whose `mu=` only applies to `dirichlet`.
+    pub fn from_name(name: &str, mu: Option<f32>) -> anyhow::Result<Self> {
+        let similarity = match name {
+            "bm25" => Similarity::Bm25,
+            "tfidf" => Similarity::Tfidf,
+            "dfr" => Similarity::Dfr,
+            "dirichlet" => Similarity::Dirichlet {
+                mu: mu.unwrap_or_else(default_dirichlet_mu),
+            },
+            "constant" => Similarity::Constant,
+            _ => anyhow::bail!("unknown similarity `{name}`"),
+        };
+        if mu.is_some() && !matches!(similarity, Similarity::Dirichlet { .. }) {
+            anyhow::bail!("mu only applies to the dirichlet similarity");
+        }
+        Ok(similarity)
+    }
+
+    pub fn is_bm25(&self) -> bool {
+        matches!(self, Similarity::Bm25)
+    }
+
+    /// Whether scoring needs each term's total frequency across the corpus, which tantivy
+    /// doesn't store and has to be counted from the postings, once per segment.
+    fn needs_term_freqs(&self) -> bool {
+        matches!(self, Similarity::Dirichlet { .. })
+    }
+
+    /// The score of a term that occurs `tf` times in a field of `len` tokens.
+    pub fn score(&self, tf: u32, len: u32, stats: &TermStats) -> Score {
+        let tf = tf as f32;
+        let len = len.max(1) as f32;
+        let num_docs = stats.num_docs as f32;
+        let doc_freq = stats.doc_freq as f32;
+        match *self {
+            Similarity::Bm25 => unreachable!("BM25 fields are scored by tantivy"),
+            Similarity::Tfidf => {
+                let idf = 1.0 + ((num_docs + 1.0) / (doc_freq + 1.0)).ln();
+                tf.sqrt() * idf * idf / len.sqrt()
+            }
+            Similarity::Dfr => {
+                let tfn = tf * (1.0 + stats.avg_len / len).log2();
+                let information = ((num_docs + 1.0) / (doc_freq + 0.5)).log2();
+                tfn * information / (tfn + 1.0)
+            }
+            Similarity::Dirichlet { mu } => {
+                let collection_probability =
+                    (stats.total_term_freq as f32 + 1.0) / (stats.total_tokens as f32 + 1.0);
+                let score =
+                    (1.0 + tf / (mu * collection_probability)).ln() + (mu / (len + mu)).ln();
+                // documents the smoothing says are less likely than the collection score nothing
+                score.max(0.0)
+            }
+            Similarity::Constant => 1.0,
+        }
+    }
+}
+
+/// Scores the documents `query` matches with `similarity`, from the terms it matches in `field`.
+#[derive(Debug)]
+pub struct SimilarityQuery {
+    field: Field,
+    similarity: Similarity,
+    query: Box<dyn Query>,
+}
+
+impl QueryClone for SimilarityQuery {
+    fn box_clone(&self) -> Box<dyn Query> {
+        Box::new(Self {
+            field: self.field,
+            similarity: self.similarity,
+            query: self.query.box_clone(),
+        })
+    }
+}
+
+impl SimilarityQuery {
+    pub fn new(field: Field, similarity: Similarity, query: Box<dyn Query>) -> Self {
+        Self {
+            field,
+            similarity,
+            query,
+        }
+    }
+}
+
+impl Query for SimilarityQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let EnableScoring::Enabled {
+            searcher,
+            statistics_provider,
+        } = enable_scoring
+        else {
+            return self.query.weight(enable_scoring);
+        };
+
+        let mut terms = HashSet::default();
+        for segment_reader in searcher.segment_readers() {
+            self.query
+                .query_terms(self.field, segment_reader, &mut |term, _| {
+                    terms.insert(term.clone());
+                });
+        }
+        if terms.is_empty() {
+            // the query's terms are only known as it matches, like a fuzzy or regex query's
+            // expansions, so there's nothing to rescore it from
+            return self.query.weight(enable_scoring);
+        }
+
+        let num_docs = statistics_provider.total_num_docs()?;
+        let total_tokens = statistics_provider.total_num_tokens(self.field)?;
+        let avg_len = if num_docs > 0 {
+            total_tokens as f32 / num_docs as f32
+        } else {
+            0.0
+        };
+        let terms = terms
+            .into_iter()
+            .map(|term| {
+                let stats = TermStats {
+                    num_docs,
+                    doc_freq: statistics_provider.doc_freq(&term)?,
+                    avg_len,
+                    total_term_freq: if self.similarity.needs_term_freqs() {
+                        total_term_freq(searcher, &term)?
+                    } else {
+                        0
+                    },
+                    total_tokens,
+                };
+                Ok((term, stats))
+            })
+            .collect::<tantivy::Result<_>>()?;
+
+        Ok(Box::new(SimilarityWeight {
+            weight: self
+                .query
+                .weight(EnableScoring::disabled_from_searcher(searcher))?,
+            field: self.field,
+            similarity: self.similarity,
+            terms,
+        }))
+    }
+
+    fn query_terms(
+        &self,
+        field: Field,
+        segment_reader: &SegmentReader,
+        visitor: &mut dyn FnMut(&Term, bool),
+    ) {
+        self.query.query_terms(field, segment_reader, visitor)
+    }
+}
+
+/// How many segment term frequencies [`SEGMENT_TERM_FREQS`] keeps before it starts over.
+const MAX_SEGMENT_TERM_FREQS: usize = 100_000;
+
+thread_local! {
+    /// The number of times each term occurs in each segment, which tantivy doesn't store.  A
+    /// segment's postings never change once it's written, so each is only counted once.
+    static SEGMENT_TERM_FREQS: RefCell<HashMap<(SegmentId, Term), u64>> = RefCell::default();
+}
+
+/// The number of times `term` occurs in all of `searcher`'s documents.
+fn total_term_freq(searcher: &Searcher, term: &Term) -> tantivy::Result<u64> {
+    let mut total = 0;
+    for segment_reader in searcher.segment_readers() {
+        total += segment_term_freq(segment_reader, term)?;
+    }
+    Ok(total)
+}
+
+/// The number of times `term` occurs in `segment_reader`'s segment.
+fn segment_term_freq(segment_reader: &SegmentReader, term: &Term) -> tantivy::Result<u64> {
+    let key = (segment_reader.segment_id(), term.clone());
+    if let Some(freq) = SEGMENT_TERM_FREQS.with_borrow(|freqs| freqs.get(&key).copied()) {
+        return Ok(freq);
+    }
+
+    let mut freq = 0;
+    let inverted_index = segment_reader.inverted_index(term.field())?;
+    if let Some(mut postings) = inverted_index.read_postings(term, IndexRecordOption::WithFreqs)? {
+        while postings.doc() != TERMINATED {
+            freq += postings.term_freq() as u64;
+            postings.advance();
+        }
+    }
+
+    SEGMENT_TERM_FREQS.with_borrow_mut(|freqs| {
+        if freqs.len() >= MAX_SEGMENT_TERM_FREQS {
+            freqs.clear();
+        }
+        freqs.insert(key, freq);
+    });
+    Ok(freq)
+}
+
+struct SimilarityWeight {
+    weight: Box<dyn Weight>,
+    field: Field,
+    similarity: Similarity,
+    terms: Vec<(Term, TermStats)>,
+}
+
+impl Weight for SimilarityWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        let inverted_index = reader.inverted_index(self.field)?;
+        let mut postings = Vec::with_capacity(self.terms.len());
+        for (term, stats) in &self.terms {
+            if let Some(segment_postings) =
+                inverted_index.read_postings(term, IndexRecordOption::WithFreqs)?
+            {
+                postings.push((segment_postings, *stats));
+            }
+        }
+        let fieldnorm_reader = match reader.fieldnorms_readers().get_field(self.field)? {
+            Some(fieldnorm_reader) => fieldnorm_reader,
+            None => FieldNormReader::constant(reader.max_doc(), 1),
+        };
+        Ok(Box::new(SimilarityScorer {
+            scorer: self.weight.scorer(reader, 1.0)?,
+            postings,
+            fieldnorm_reader,
+            similarity: self.similarity,
+            boost,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )));
+        }
+        Ok(Explanation::new(
+            format!("{:?} similarity", self.similarity),
+            scorer.score(),
+        ))
+    }
+}
+
+struct SimilarityScorer {
+    scorer: Box<dyn Scorer>,
+    postings: Vec<(SegmentPostings, TermStats)>,
+    fieldnorm_reader: FieldNormReader,
+    similarity: Similarity,
+    boost: Score,
+}
+
+impl Scorer for SimilarityScorer {
+    fn score(&mut self) -> Score {
+        let doc = self.scorer.doc();
+        let len = self.fieldnorm_reader.fieldnorm(doc);
+        let mut score = 0.0;
+        for (postings, stats) in &mut self.postings {
+            if postings.doc() < doc {
+                postings.seek(doc);
+            }
+            if postings.doc() == doc {
+                score += self.similarity.score(postings.term_freq(), len, stats);
+            }
+        }
+        score * self.boost
+    }
+}
+
+impl DocSet for SimilarityScorer {
+    fn advance(&mut self) -> DocId {
+        self.scorer.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.scorer.seek(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(doc_freq: u64) -> TermStats {
+        TermStats {
+            num_docs: 9,
+            doc_freq,
+            avg_len: 4.0,
+            total_term_freq: 19,
+            total_tokens: 3900,
+        }
+    }
+
+    #[test]
+    fn rarer_terms_score_higher() {
+        for similarity in [
+            Similarity::Tfidf,
+            Similarity::Dfr,
+            Similarity::Dirichlet { mu: 10.0 },
+        ] {
+            let rare = similarity.score(1, 4, &stats(1));
+            let common = similarity.score(
+                1,
+                4,
+                &TermStats {
+                    total_term_freq: 29,
+                    ..stats(8)
+                },
+            );
+            assert!(rare > common, "{similarity:?}: {rare} <= {common}");
+        }
+    }
+
+    #[test]
+    fn tfidf_and_constant_scores() {
+        // idf = 1 + ln(10 / 2)
+        let idf = 1.0 + 5.0f32.ln();
+        assert_eq!(
+            Similarity::Tfidf.score(4, 4, &stats(1)),
+            2.0 * idf * idf / 2.0
+        );
+        assert_eq!(Similarity::Constant.score(7, 100, &stats(1)), 1.0);
+    }
+
+    #[test]
+    fn similarity_names_parse() {
+        assert_eq!(
+            Similarity::from_name("dirichlet", None).unwrap(),
+            Similarity::Dirichlet { mu: 2000.0 }
+        );
+        assert_eq!(
+            Similarity::from_name("dirichlet", Some(500.0)).unwrap(),
+            Similarity::Dirichlet { mu: 500.0 }
+        );
+        assert_eq!(
+            Similarity::from_name("tfidf", None).unwrap(),
+            Similarity::Tfidf
+        );
+        assert!(Similarity::from_name("lucene", None).is_err());
+        assert!(Similarity::from_name("tfidf", Some(500.0)).is_err());
+    }
+}
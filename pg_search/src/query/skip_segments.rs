@@ -0,0 +1,93 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::api::HashSet;
+use std::sync::Arc;
+use tantivy::index::SegmentId;
+use tantivy::query::{EmptyScorer, EnableScoring, Explanation, Query, QueryClone, Scorer, Weight};
+use tantivy::schema::Field;
+use tantivy::{DocId, Score, SegmentReader, TantivyError, Term};
+
+/// Matches nothing in the given segments, and whatever `query` matches in the others.
+///
+/// Used to hide the segments that haven't been backfilled with a field added by
+/// `pdb.add_field()` from queries on that field, so they don't answer as if none of their
+/// documents had a value for it.
+#[derive(Debug)]
+pub struct SkipSegments {
+    segment_ids: Arc<HashSet<SegmentId>>,
+    query: Box<dyn Query>,
+}
+
+impl QueryClone for SkipSegments {
+    fn box_clone(&self) -> Box<dyn Query> {
+        Box::new(Self {
+            segment_ids: self.segment_ids.clone(),
+            query: self.query.box_clone(),
+        })
+    }
+}
+
+impl SkipSegments {
+    pub fn new(segment_ids: HashSet<SegmentId>, query: Box<dyn Query>) -> Self {
+        Self {
+            segment_ids: Arc::new(segment_ids),
+            query,
+        }
+    }
+}
+
+struct SkipSegmentsWeight {
+    segment_ids: Arc<HashSet<SegmentId>>,
+    weight: Box<dyn Weight>,
+}
+
+impl Weight for SkipSegmentsWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        if self.segment_ids.contains(&reader.segment_id()) {
+            return Ok(Box::new(EmptyScorer));
+        }
+        self.weight.scorer(reader, boost)
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        if self.segment_ids.contains(&reader.segment_id()) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )));
+        }
+        self.weight.explain(reader, doc)
+    }
+}
+
+impl Query for SkipSegments {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        Ok(Box::new(SkipSegmentsWeight {
+            segment_ids: self.segment_ids.clone(),
+            weight: self.query.weight(enable_scoring)?,
+        }))
+    }
+
+    fn query_terms(
+        &self,
+        field: Field,
+        segment_reader: &SegmentReader,
+        visitor: &mut dyn FnMut(&Term, bool),
+    ) {
+        self.query.query_terms(field, segment_reader, visitor)
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Vector range search: matches the documents whose vector is within a distance of a query
+//! vector.
+//!
+//! Each segment is searched when its scorer is created, like a hybrid query's.  The search
+//! probes up to `paradedb.vector_cluster_max_probe` of the segment's clusters, nearest first,
+//! with a top-K that can hold every document of the segment, so every vector of a probed cluster
+//! is scored and the ones further than `max_distance` are filtered out.  With
+//! `paradedb.vector_exact_search` on, every cluster is probed and the result is exact.
+
+use tantivy::collector::{Collector, TopDocs};
+use tantivy::query::{AllQuery, EnableScoring, Explanation, Query, Scorer, Weight};
+use tantivy::schema::Field;
+use tantivy::{DocAddress, DocId, DocSet, Score, SegmentReader, TERMINATED, TantivyError};
+
+use crate::vector::metric::VectorMetric;
+
+/// Matches the documents whose `field` is within `max_distance` of `vector`, scored with
+/// `1 / (1 + distance)` so that nearer documents score higher.
+#[derive(Clone, Debug)]
+pub struct VectorWithinQuery {
+    field: Field,
+    vector: Vec<f32>,
+    metric: VectorMetric,
+    max_distance: f64,
+}
+
+impl VectorWithinQuery {
+    /// `metric` is the field's, which gives the distances `max_distance` is compared to.
+    pub fn new(field: Field, vector: Vec<f32>, metric: VectorMetric, max_distance: f64) -> Self {
+        Self {
+            field,
+            vector,
+            metric,
+            max_distance,
+        }
+    }
+}
+
+impl Query for VectorWithinQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let Some(searcher) = enable_scoring.searcher() else {
+            return Err(TantivyError::InvalidArgument(
+                "a vector range query needs a searcher to probe its clusters".to_string(),
+            ));
+        };
+        Ok(Box::new(VectorWithinWeight {
+            all_weight: AllQuery.weight(EnableScoring::disabled_from_searcher(searcher))?,
+            query: self.clone(),
+        }))
+    }
+}
+
+struct VectorWithinWeight {
+    all_weight: Box<dyn Weight>,
+    query: VectorWithinQuery,
+}
+
+impl VectorWithinWeight {
+    /// Searches the segment and returns the documents within range in doc id order.
+    fn search_segment(&self, reader: &SegmentReader) -> tantivy::Result<Vec<(DocId, Score)>> {
+        // only the doc ids are kept, so the segment's ordinal doesn't matter
+        let collector = TopDocs::with_limit((reader.max_doc() as usize).max(1))
+            .order_by_similarity(self.query.field, self.query.vector.clone())
+            .with_adaptive_params(crate::vector::probe_params());
+        let hits = collector
+            .merge_fruits(vec![collector.collect_segment(
+                self.all_weight.as_ref(),
+                0,
+                reader,
+            )?])?
+            .results;
+        Ok(within(&hits, self.query.metric, self.query.max_distance))
+    }
+}
+
+/// The hits of a vector search within `max_distance`, in doc id order and scored with
+/// `1 / (1 + distance)`.
+fn within(
+    hits: &[(Score, DocAddress)],
+    metric: VectorMetric,
+    max_distance: f64,
+) -> Vec<(DocId, Score)> {
+    let mut docs = hits
+        .iter()
+        .filter_map(|(score, doc)| {
+            let distance = metric.distance_from_score(*score);
+            (distance <= max_distance).then(|| (doc.doc_id, (1.0 / (1.0 + distance)) as Score))
+        })
+        .collect::<Vec<_>>();
+    docs.sort_unstable_by_key(|(doc, _)| *doc);
+    docs
+}
+
+impl Weight for VectorWithinWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        Ok(Box::new(VectorWithinScorer {
+            docs: self.search_segment(reader)?,
+            cursor: 0,
+            boost,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) is not within range of the query vector"
+            )));
+        }
+        Ok(Explanation::new("Vector within range", scorer.score()))
+    }
+}
+
+struct VectorWithinScorer {
+    docs: Vec<(DocId, Score)>,
+    cursor: usize,
+    boost: Score,
+}
+
+impl Scorer for VectorWithinScorer {
+    fn score(&mut self) -> Score {
+        self.docs[self.cursor].1 * self.boost
+    }
+}
+
+impl DocSet for VectorWithinScorer {
+    fn advance(&mut self) -> DocId {
+        if self.cursor < self.docs.len() {
+            self.cursor += 1;
+        }
+        self.doc()
+    }
+
+    fn doc(&self) -> DocId {
+        self.docs
+            .get(self.cursor)
+            .map_or(TERMINATED, |(doc, _)| *doc)
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.docs.len() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn within_filters_by_the_metric_distance() {
+        // L2 scores are squared distances
+        let hits = [
+            (1.0, DocAddress::new(0, 7)),
+            (4.0, DocAddress::new(0, 2)),
+            (9.0, DocAddress::new(0, 5)),
+        ];
+
+        assert_eq!(
+            within(&hits, VectorMetric::L2, 2.0),
+            vec![(2, 1.0 / 3.0), (7, 0.5)]
+        );
+        assert_eq!(
+            within(&hits, VectorMetric::Cosine, 4.0),
+            vec![(2, 0.2), (7, 0.5)]
+        );
+        assert!(within(&hits, VectorMetric::L2, 0.5).is_empty());
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::api::{FieldName, HashSet};
+use serde::{Deserialize, Serialize};
+use tantivy::index::SegmentId;
+
+/// The fields added to and dropped from an index since it was built, by `pdb.add_field()` and
+/// `pdb.drop_field()`.
+///
+/// The segments that existed when a field was added don't index it.  A background merge rebuilds
+/// each of them from the heap under a new [`SegmentId`], so a segment is missing a field exactly
+/// when its id is in that field's `unfilled` list, and the list never needs to shrink as segments
+/// are backfilled.  Queries on an added field skip the segments that are still missing it.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SchemaChanges {
+    /// Bumped by every change
+    pub version: u32,
+    pub added: Vec<AddedField>,
+    pub dropped: Vec<DroppedField>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddedField {
+    pub name: FieldName,
+    pub version: u32,
+    /// The segments that existed when the field was added
+    pub unfilled: Vec<SegmentId>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DroppedField {
+    pub name: FieldName,
+    pub version: u32,
+}
+
+impl SchemaChanges {
+    pub fn is_dropped(&self, field_name: &str) -> bool {
+        self.dropped
+            .iter()
+            .any(|dropped| dropped.name.as_ref() == field_name)
+    }
+
+    /// The segments that are missing any of `field_names`.
+    pub fn unfilled_segments<S: AsRef<str>>(
+        &self,
+        field_names: impl IntoIterator<Item = S>,
+    ) -> HashSet<SegmentId> {
+        let mut unfilled = HashSet::default();
+        for field_name in field_names {
+            for added in &self.added {
+                if added.name.as_ref() == field_name.as_ref() {
+                    unfilled.extend(added.unfilled.iter().copied());
+                }
+            }
+        }
+        unfilled
+    }
+
+    /// The segments that are missing any added field.
+    pub fn all_unfilled_segments(&self) -> HashSet<SegmentId> {
+        self.added
+            .iter()
+            .flat_map(|added| added.unfilled.iter().copied())
+            .collect()
+    }
+
+    pub fn add_field(&mut self, name: FieldName, unfilled: Vec<SegmentId>) {
+        self.version += 1;
+        self.added.push(AddedField {
+            name,
+            version: self.version,
+            unfilled,
+        });
+    }
+
+    pub fn drop_field(&mut self, name: FieldName) {
+        self.version += 1;
+        self.dropped.push(DroppedField {
+            name,
+            version: self.version,
+        });
+    }
+
+    /// Forgets the unfilled segments that are no longer part of the index, as they've since been
+    /// backfilled or merged away.
+    pub fn retain_segments(&mut self, segment_ids: &HashSet<SegmentId>) {
+        for added in &mut self.added {
+            added
+                .unfilled
+                .retain(|segment_id| segment_ids.contains(segment_id));
+        }
+    }
+}
@@ -26,6 +26,8 @@ use tantivy::schema::{
 };
 use tokenizers::{SearchNormalizer, SearchTokenizer};
 
+use crate::query::similarity::Similarity;
+
 // Eq intentionally omitted: f32 (k1/b) does not implement Eq.
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 // TODO: re-enable this once we are okay with a breaking change
@@ -52,6 +54,8 @@ pub enum SearchFieldConfig {
         k1: Option<f32>,
         #[serde(default)]
         b: Option<f32>,
+        #[serde(default)]
+        similarity: Similarity,
     },
     Inet {
         #[serde(default = "default_as_true")]
@@ -82,6 +86,8 @@ pub enum SearchFieldConfig {
         k1: Option<f32>,
         #[serde(default)]
         b: Option<f32>,
+        #[serde(default)]
+        similarity: Similarity,
     },
     Range {
         #[serde(default = "default_as_true")]
@@ -226,6 +232,26 @@ impl SearchFieldConfig {
         }
     }
 
+    /// How the field scores the terms a query matches in it: BM25 for any field without a
+    /// similarity of its own.
+    pub fn similarity(&self) -> Similarity {
+        match self {
+            Self::Text { similarity, .. } | Self::Json { similarity, .. } => *similarity,
+            _ => Similarity::Bm25,
+        }
+    }
+
+    /// The field's BM25 parameters, tantivy's defaults unless it sets `k1` or `b`.
+    pub fn bm25_params(&self) -> Bm25Params {
+        let d = Bm25Params::default();
+        match self {
+            Self::Text { k1, b, .. } | Self::Json { k1, b, .. } => {
+                Bm25Params::new(k1.unwrap_or(d.k1()), b.unwrap_or(d.b()))
+            }
+            _ => d,
+        }
+    }
+
     pub fn search_tokenizer(&self) -> Option<&SearchTokenizer> {
         match self {
             Self::Text {
@@ -322,10 +348,16 @@ fn apply_bm25(mut idx: TextFieldIndexing, k1: Option<f32>, b: Option<f32>) -> Te
     idx
 }
 
-fn validate_bm25_indexed(indexed: bool, k1: Option<f32>, b: Option<f32>) {
+fn validate_bm25_indexed(indexed: bool, k1: Option<f32>, b: Option<f32>, similarity: Similarity) {
     if !indexed && (k1.is_some() || b.is_some()) {
         panic!("BM25 parameters k1/b require an indexed field");
     }
+    if !indexed && !similarity.is_bm25() {
+        panic!("a similarity requires an indexed field");
+    }
+    if !similarity.is_bm25() && (k1.is_some() || b.is_some()) {
+        panic!("BM25 parameters k1/b only apply to the bm25 similarity");
+    }
 }
 
 impl From<SearchFieldConfig> for TextOptions {
@@ -341,9 +373,10 @@ impl From<SearchFieldConfig> for TextOptions {
                 normalizer,
                 k1,
                 b,
+                similarity,
                 ..
             } => {
-                validate_bm25_indexed(indexed, k1, b);
+                validate_bm25_indexed(indexed, k1, b, similarity);
                 if fast {
                     text_options = text_options.set_fast(normalizer.name());
                 }
@@ -443,9 +476,10 @@ impl From<SearchFieldConfig> for JsonObjectOptions {
                 normalizer,
                 k1,
                 b,
+                similarity,
                 ..
             } => {
-                validate_bm25_indexed(indexed, k1, b);
+                validate_bm25_indexed(indexed, k1, b, similarity);
                 if fast {
                     json_options = json_options.set_fast(normalizer.name());
                 }
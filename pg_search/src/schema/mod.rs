@@ -16,13 +16,14 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 mod anyenum;
+mod changes;
 mod config;
 pub mod range;
 
 use crate::api::FieldName;
 use crate::api::HashMap;
 use crate::api::version::{Version, VersionInfo};
-use crate::postgres::catalog::{is_citext_oid, is_pgvector_oid};
+use crate::postgres::catalog::is_citext_oid;
 use crate::postgres::datetime::PostgresDateTime;
 use crate::postgres::options::{BM25IndexOptions, SortByDirection, SortByField};
 use crate::postgres::pdb_owned_value::PdbOwnedValue;
@@ -30,8 +31,11 @@ use crate::postgres::types::{is_datetime_type, is_pgoid_datetime_type};
 use crate::postgres::utils::{ExtractedFieldAttribute, resolve_base_type};
 pub use crate::postgres::utils::{FieldSource, convert_pg_date_string};
 use crate::vector::metric::VectorMetric;
+use crate::vector::quantize::VectorQuantization;
+use crate::vector::{MAX_VECTOR_DIMENSIONS, VectorType};
 pub use anyenum::AnyEnum;
 use anyhow::bail;
+pub use changes::*;
 pub use config::*;
 use std::cell::{Ref, RefCell};
 use std::hash::{Hash, Hasher};
@@ -39,7 +43,7 @@ use std::rc::Rc;
 use tantivy::index::{IndexSortByField, Order};
 
 use crate::api::tokenizers::{Typmod, type_is_alias, type_is_tokenizer};
-use crate::index::utils::load_index_schema;
+use crate::index::utils::{load_index_schema, load_schema_changes};
 use crate::postgres::catalog::is_ltree_oid;
 use crate::postgres::rel::PgSearchRelation;
 use crate::postgres::utils::extract_numeric_precision_scale;
@@ -76,9 +80,13 @@ pub enum SearchFieldType {
     /// NUMERIC with precision > 18 or unlimited: stored as lexicographically sortable bytes.
     /// The `Option<i16>` is the scale (number of decimal places), or None for unlimited precision.
     NumericBytes(pg_sys::Oid, Option<i16>),
-    /// Dense vector field (pgvector type). The usize is the number of dimensions,
-    /// and `VectorMetric` is the distance metric (default L2).
+    /// Vector field: a pgvector `vector`, `halfvec` or `sparsevec`, or a `bit`
+    /// (see [`VectorType`]). The usize is the number of dimensions, and
+    /// `VectorMetric` is the distance metric (default L2, Hamming for `bit`).
     Vector(pg_sys::Oid, usize, VectorMetric),
+    /// Multi-vector field: an array of vectors of the element type, packed into a bytes fast
+    /// field (see [`crate::vector::multi`]).  The usize is the dimensions of each vector.
+    MultiVector(pg_sys::Oid, usize),
 }
 
 impl SearchFieldType {
@@ -111,7 +119,9 @@ impl SearchFieldType {
             SearchFieldType::Json(_) => SearchFieldConfig::default_json(),
             SearchFieldType::Date(_) => SearchFieldConfig::default_date(),
             SearchFieldType::Range(_) => SearchFieldConfig::default_range(),
-            SearchFieldType::Vector(_, dims, _) => SearchFieldConfig::default_vector(*dims),
+            SearchFieldType::Vector(_, dims, _) | SearchFieldType::MultiVector(_, dims) => {
+                SearchFieldConfig::default_vector(*dims)
+            }
         }
     }
 
@@ -132,6 +142,7 @@ impl SearchFieldType {
             SearchFieldType::Numeric64(oid, _) => *oid,
             SearchFieldType::NumericBytes(oid, _) => *oid,
             SearchFieldType::Vector(oid, _, _) => *oid,
+            SearchFieldType::MultiVector(oid, _) => *oid,
         }
         .into()
     }
@@ -220,7 +231,9 @@ impl SearchFieldType {
             SearchFieldType::NumericBytes(..) => arrow_schema::DataType::BinaryView,
 
             // Vector is not stored in Arrow columnar format
-            SearchFieldType::Vector(..) => arrow_schema::DataType::BinaryView,
+            SearchFieldType::Vector(..) | SearchFieldType::MultiVector(..) => {
+                arrow_schema::DataType::BinaryView
+            }
         }
     }
 }
@@ -267,6 +280,21 @@ fn derive_field_type_from_schema(
     }
 }
 
+/// The field type of an array of `vector_type`, whose vectors are packed one after the other
+/// and so must all have the `dims` the column declares.
+fn multi_vector_type(
+    oid: pg_sys::Oid,
+    vector_type: VectorType,
+    dims: usize,
+) -> Result<SearchFieldType, SearchIndexSchemaError> {
+    if dims == 0 || dims > MAX_VECTOR_DIMENSIONS {
+        return Err(SearchIndexSchemaError::MultiVectorDimensions(
+            vector_type.type_name(),
+        ));
+    }
+    Ok(SearchFieldType::MultiVector(oid, dims))
+}
+
 impl SearchFieldType {
     pub fn try_from_type_info(
         pg_oid: PgOid,
@@ -281,7 +309,7 @@ impl SearchFieldType {
             return Err(SearchIndexSchemaError::JsonArraysNotYetSupported);
         }
 
-        let (mut base_oid, _) = resolve_base_type(pg_oid)
+        let (mut base_oid, is_array) = resolve_base_type(pg_oid)
             .unwrap_or_else(|| pgrx::error!("Failed to resolve base type for type {:?}", pg_oid));
 
         if matches!(base_oid, PgOid::Custom(alias_oid) if type_is_alias(alias_oid)) {
@@ -357,6 +385,24 @@ impl SearchFieldType {
                         Ok(SearchFieldType::Date((*builtin).into()))
                     }
                 }
+                // Dimensions and metric as for the pgvector types below; `bit(n)`'s typmod
+                // is its length.
+                PgBuiltInOids::BITOID => {
+                    let dims = if typmod > 0 { typmod as usize } else { 0 };
+                    if is_array {
+                        return multi_vector_type((*builtin).into(), VectorType::Bit, dims);
+                    }
+                    if dims > MAX_VECTOR_DIMENSIONS {
+                        return Err(SearchIndexSchemaError::VectorDimensions(
+                            VectorType::Bit.type_name(),
+                        ));
+                    }
+                    Ok(SearchFieldType::Vector(
+                        (*builtin).into(),
+                        dims,
+                        VectorMetric::default_for(VectorType::Bit),
+                    ))
+                }
                 _ => Err(SearchIndexSchemaError::InvalidPgOid(pg_oid)),
             },
             PgOid::Custom(custom) if unsafe { pgrx::pg_sys::type_is_enum(*custom) } => {
@@ -367,17 +413,29 @@ impl SearchFieldType {
                 SearchFieldType::Tokenized(*tokenizer_oid, typmod, inner_typoid),
             ),
 
-            PgOid::Custom(custom) if is_pgvector_oid(*custom) => {
-                // Metric defaults to L2 here; the real value comes from
-                // the index attribute's opclass and is patched in by
-                // `extract_field_attributes` once we know which index
+            PgOid::Custom(custom) if let Some(vector_type) = VectorType::from_oid(*custom) => {
+                // Metric defaults to the type's default here; the real value
+                // comes from the index attribute's opclass and is patched in
+                // by `extract_field_attributes` once we know which index
                 // column owns the field. Callers that build a
-                // SearchFieldType outside an index (rare) get L2.
+                // SearchFieldType outside an index (rare) get the default.
                 let dims = if typmod > 0 { typmod as usize } else { 0 };
+                if is_array {
+                    return multi_vector_type(*custom, vector_type, dims);
+                }
+                // vectors are indexed with all of their dimensions, so a sparsevec must
+                // declare them, see `VectorType`
+                if dims > MAX_VECTOR_DIMENSIONS
+                    || (vector_type == VectorType::SparseVec && dims == 0)
+                {
+                    return Err(SearchIndexSchemaError::VectorDimensions(
+                        vector_type.type_name(),
+                    ));
+                }
                 Ok(SearchFieldType::Vector(
                     *custom,
                     dims,
-                    VectorMetric::default(),
+                    VectorMetric::default_for(vector_type),
                 ))
             }
 
@@ -412,6 +470,7 @@ pub struct SearchIndexSchema {
     schema: Schema,
     bm25_options: BM25IndexOptions,
     categorized: Rc<RefCell<Vec<(SearchField, CategorizedFieldData)>>>,
+    changes: Rc<SchemaChanges>,
 }
 
 impl From<SearchIndexSchema> for Schema {
@@ -422,19 +481,27 @@ impl From<SearchIndexSchema> for Schema {
 
 impl SearchIndexSchema {
     pub fn open(indexrel: &PgSearchRelation) -> tantivy::Result<Self> {
+        let changes = Rc::new(load_schema_changes(indexrel)?);
         Ok(load_index_schema(indexrel)?
             .map(|schema| Self {
                 schema,
                 bm25_options: indexrel.options().clone(),
                 categorized: Default::default(),
+                changes: changes.clone(),
             })
             .unwrap_or_else(|| Self {
                 schema: Schema::builder().build(),
                 bm25_options: indexrel.options().clone(),
                 categorized: Default::default(),
+                changes,
             }))
     }
 
+    /// The fields added to and dropped from the index since it was built.
+    pub fn changes(&self) -> &SchemaChanges {
+        &self.changes
+    }
+
     pub fn tantivy_schema(&self) -> &Schema {
         &self.schema
     }
@@ -530,6 +597,11 @@ impl SearchIndexSchema {
 
     pub fn search_field(&self, name: impl AsRef<str>) -> Option<SearchField> {
         let field_name = FieldName::from(name.as_ref());
+        // a dropped field's data stays in the segments until the index is rebuilt, but it's no
+        // longer part of the index's configuration
+        if self.changes.is_dropped(&field_name.root()) {
+            return None;
+        }
         match self.schema.get_field(&field_name.root()) {
             Ok(field) => Some(SearchField::new(field, &self.bm25_options, &self.schema)),
             Err(_) => None,
@@ -549,16 +621,24 @@ impl SearchIndexSchema {
     }
 
     pub fn fields(&self) -> impl Iterator<Item = (Field, &FieldEntry)> {
-        self.schema.fields()
+        self.schema
+            .fields()
+            .filter(|(_, field_entry)| !self.changes.is_dropped(field_entry.name()))
     }
 
     pub fn has_vector_field(&self) -> bool {
-        self.fields().any(|(_, field_entry)| {
+        self.vector_fields().next().is_some()
+    }
+
+    /// The vector fields kept in the index's vector storage, which excludes quantized ones.
+    pub fn vector_fields(&self) -> impl Iterator<Item = Field> + '_ {
+        self.fields().filter_map(|(field, field_entry)| {
             let field_name: FieldName = field_entry.name().into();
-            matches!(
+            (matches!(
                 self.bm25_options.get_field_type(&field_name),
                 Some(SearchFieldType::Vector(..))
-            )
+            ) && !matches!(field_entry.field_type(), FieldType::Bytes(_)))
+            .then_some(field)
         })
     }
 
@@ -667,6 +747,7 @@ pub struct SearchField {
     field_entry: FieldEntry,
     field_type: SearchFieldType,
     field_config: SearchFieldConfig,
+    quantization: Option<VectorQuantization>,
 }
 
 impl Hash for SearchField {
@@ -693,12 +774,23 @@ impl SearchField {
         // This ensures backwards compatibility with legacy indexes.
         let field_type = derive_field_type_from_schema(&field_entry, options, &field_name);
 
+        // a quantized vector field's codes are stored in a bytes fast field, and describe their
+        // own encoding, so the option only picks how new vectors are encoded
+        let quantization = (matches!(field_type, SearchFieldType::Vector(..))
+            && matches!(field_entry.field_type(), FieldType::Bytes(_)))
+        .then(|| {
+            options
+                .vector_quantization()
+                .unwrap_or(VectorQuantization::Int8)
+        });
+
         Self {
             field,
             field_name,
             field_entry,
             field_type,
             field_config,
+            quantization,
         }
     }
 
@@ -722,6 +814,12 @@ impl SearchField {
         &self.field_config
     }
 
+    /// How this vector field's vectors are encoded, if the index was built with
+    /// `vector_quantization`, see [`crate::vector::quantize`].
+    pub fn vector_quantization(&self) -> Option<VectorQuantization> {
+        self.quantization
+    }
+
     pub fn is_raw_sortable(&self) -> bool {
         self.is_sortable(SearchNormalizer::Raw)
     }
@@ -934,6 +1032,16 @@ pub enum SearchIndexSchemaError {
 
     #[error("json(b) arrays are not yet supported")]
     JsonArraysNotYetSupported,
+
+    #[error(
+        "a {0} column must declare at most {MAX_VECTOR_DIMENSIONS} dimensions to be indexed, e.g. {0}(1000)"
+    )]
+    VectorDimensions(&'static str),
+
+    #[error(
+        "a {0}[] column must declare the dimensions of its vectors, at most {MAX_VECTOR_DIMENSIONS}, to be indexed, e.g. {0}(128)[]"
+    )]
+    MultiVectorDimensions(&'static str),
 }
 
 #[cfg(test)]
@@ -25,6 +25,7 @@ use tantivy::vector::{
 use tantivy::{Index, TantivyError};
 
 use crate::postgres::options::BM25IndexOptions;
+use crate::postgres::progress::TrainingCentroids;
 
 const DEFAULT_ASSIGN_BATCH_SIZE: usize = 40_960;
 
@@ -183,6 +184,7 @@ impl IvfClusterer for SuperKMeansIvfClusterer {
             )));
         }
 
+        let _progress = TrainingCentroids::start();
         let mut config = self.config.clone();
         if matches!(options.metric(), Metric::Cosine | Metric::Dot) {
             config.base.angular = true;
@@ -20,16 +20,28 @@
 //! The metric travels via the index attribute's operator class — pgvector
 //! convention. CREATE INDEX uses one of:
 //!
-//!   embedding vector_l2_ops      -- <-> L2
-//!   embedding vector_cosine_ops  -- <=> Cosine
-//!   embedding vector_ip_ops      -- <#> InnerProduct
+//!   embedding vector_l2_ops        -- <-> L2
+//!   embedding vector_cosine_ops    -- <=> Cosine
+//!   embedding vector_ip_ops        -- <#> InnerProduct
+//!   embedding bit_hamming_ops      -- <~> Hamming
+//!   embedding bit_jaccard_ops      -- <%> Jaccard
+//!
+//! with `halfvec_*_ops` and `sparsevec_*_ops` for the L2, cosine and inner
+//! product metrics of those types.
 //!
 //! At build time we read `pg_index.indclass[i]` for each vector attribute
 //! and map the opclass name to a `VectorMetric` via
 //! `metric_from_opclass_name`. The metric is then persisted in the
 //! tantivy schema's `VectorOptions::metric`.
+//!
+//! Bits are indexed as `0.0`/`1.0` elements, so Hamming distance is the
+//! squared L2 distance and ranks identically. No tantivy metric ranks like
+//! Jaccard distance, so `bit_jaccard_ops` fields are indexed under the inner
+//! product, which counts the bits two bit strings share, and a `<%>` ORDER BY
+//! scores every bit string with it (see `SearchIndexReader::jaccard_distances`).
 
 use crate::postgres::catalog::lookup_opfamily_name;
+use crate::vector::VectorType;
 use pgrx::{IntoDatum, direct_function_call, pg_sys};
 use serde::{Deserialize, Serialize};
 use tantivy::vector::Metric as TantivyMetric;
@@ -40,65 +52,176 @@ pub enum VectorMetric {
     L2,
     Cosine,
     InnerProduct,
+    Hamming,
+    Jaccard,
 }
 
 impl VectorMetric {
+    /// The metric of a `vector_type` attribute built without an explicit
+    /// opclass: Hamming for `bit`, L2 for everything else.
+    pub fn default_for(vector_type: VectorType) -> Self {
+        match vector_type {
+            VectorType::Bit => VectorMetric::Hamming,
+            VectorType::Vector | VectorType::HalfVec | VectorType::SparseVec => VectorMetric::L2,
+        }
+    }
+
     /// Map the pgvector-facing metric to Tantivy's vector metric.
     pub fn runtime_metric(self) -> TantivyMetric {
-        match self {
-            VectorMetric::L2 => TantivyMetric::L2,
-            VectorMetric::Cosine => TantivyMetric::Cosine,
-            VectorMetric::InnerProduct => TantivyMetric::Dot,
-        }
+        self.into()
     }
 
-    /// pgvector opclass name (`vector_l2_ops`, `vector_cosine_ops`,
-    /// `vector_ip_ops`). Inverse of [`Self::from_opfamily_name`].
-    pub fn opclass_name(self) -> &'static str {
-        match self {
-            VectorMetric::L2 => "vector_l2_ops",
-            VectorMetric::Cosine => "vector_cosine_ops",
-            VectorMetric::InnerProduct => "vector_ip_ops",
-        }
+    /// pgvector opclass name for this metric on `vector_type`, e.g.
+    /// `vector_l2_ops` or `bit_hamming_ops`. Inverse of
+    /// [`Self::from_opfamily_name`].
+    pub fn opclass_name(self, vector_type: VectorType) -> String {
+        let metric = match self {
+            VectorMetric::L2 => "l2",
+            VectorMetric::Cosine => "cosine",
+            VectorMetric::InnerProduct => "ip",
+            VectorMetric::Hamming => "hamming",
+            VectorMetric::Jaccard => "jaccard",
+        };
+        format!("{}_{metric}_ops", vector_type.type_name())
     }
 
-    /// pgvector distance operator symbol (`<->`, `<=>`, `<#>`).
+    /// pgvector distance operator symbol (`<->`, `<=>`, `<#>`, `<~>`, `<%>`).
     pub fn operator(self) -> &'static str {
         match self {
             VectorMetric::L2 => "<->",
             VectorMetric::Cosine => "<=>",
             VectorMetric::InnerProduct => "<#>",
+            VectorMetric::Hamming => "<~>",
+            VectorMetric::Jaccard => "<%>",
         }
     }
 
-    /// Map a pgvector distance operator OID (`<->`, `<=>`, `<#>`) to its
-    /// implied metric. Returns `None` when `opoid` is not one of them —
-    /// which includes the case where pgvector is not installed at all.
+    /// The distance this metric's operator returns, given the score the
+    /// index ranked a row by. Tantivy scores L2 as the squared distance, so
+    /// it's the only metric that needs converting; the others already score
+    /// with the operator's distance (`1 - cos`, the negated inner product and
+    /// the Jaccard distance an ORDER BY computes).
+    /// Scores are single precision, so the result can differ from the
+    /// operator's in the last digits.
+    pub fn distance_from_score(self, score: f32) -> f64 {
+        match self {
+            VectorMetric::L2 => (score as f64).sqrt(),
+            VectorMetric::Cosine
+            | VectorMetric::InnerProduct
+            | VectorMetric::Hamming
+            | VectorMetric::Jaccard => score as f64,
+        }
+    }
+
+    /// The score the index would rank `vector` by for `query` under this metric, for vectors
+    /// compared outside the index's vector storage, such as those of a quantized field: the
+    /// squared L2 distance, `1 - cos`, the negated inner product, or the Jaccard distance of bit
+    /// strings indexed as `0.0`/`1.0` elements.  [`Self::distance_from_score`] applies to it.
+    pub fn score(self, query: &[f32], vector: &[f32]) -> f32 {
+        let dot = || query.iter().zip(vector).map(|(a, b)| a * b).sum::<f32>();
+        match self {
+            VectorMetric::L2 | VectorMetric::Hamming => query
+                .iter()
+                .zip(vector)
+                .map(|(a, b)| (a - b) * (a - b))
+                .sum(),
+            VectorMetric::Cosine => {
+                let norms = query.iter().map(|a| a * a).sum::<f32>().sqrt()
+                    * vector.iter().map(|b| b * b).sum::<f32>().sqrt();
+                if norms > 0.0 {
+                    1.0 - dot() / norms
+                } else {
+                    1.0
+                }
+            }
+            VectorMetric::InnerProduct => -dot(),
+            VectorMetric::Jaccard => {
+                let shared = dot();
+                let union = query.iter().sum::<f32>() + vector.iter().sum::<f32>() - shared;
+                if shared > 0.0 {
+                    1.0 - shared / union
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+
+    /// Map a pgvector distance operator OID (`<->`, `<=>`, `<#>` on
+    /// `vector`, `halfvec` and `sparsevec`, `<~>` and `<%>` on `bit`) to its implied
+    /// metric. Returns `None` when `opoid` is not one of them — which
+    /// includes the case where pgvector is not installed at all.
     ///
     /// The operator OIDs are resolved once and cached. Resolving them via
     /// `regoperatorin` raises `type "vector" does not exist` when pgvector is
     /// absent (a Postgres ereport, not catchable by `unwrap_or`), and this runs
-    /// on every custom-scan plan — so gate the lookups on the `vector` type
-    /// existing (`to_regtype` returns NULL rather than erroring); absent it, all
+    /// on every custom-scan plan — so gate the lookups on the operand type
+    /// existing (`to_regtype` returns NULL rather than erroring); absent it, its
     /// OIDs stay INVALID and no metric ever matches.
     pub(crate) fn from_opoid(opoid: pg_sys::Oid) -> Option<Self> {
         use std::sync::OnceLock;
-        static OP_METRICS: OnceLock<[(pg_sys::Oid, VectorMetric); 3]> = OnceLock::new();
+        static OP_METRICS: OnceLock<[(pg_sys::Oid, VectorMetric); 11]> = OnceLock::new();
         let cached = OP_METRICS.get_or_init(|| unsafe {
-            let vector_type_exists =
-                direct_function_call::<pg_sys::Oid>(pg_sys::to_regtype, &["vector".into_datum()])
-                    .is_some();
-            let lookup = |sig: &std::ffi::CStr| -> pg_sys::Oid {
-                if !vector_type_exists {
+            let type_exists = |name: &str| {
+                direct_function_call::<pg_sys::Oid>(pg_sys::to_regtype, &[name.into_datum()])
+                    .is_some()
+            };
+            let vector_exists = type_exists("vector");
+            let halfvec_exists = type_exists("halfvec");
+            let sparsevec_exists = type_exists("sparsevec");
+            let lookup = |exists: bool, sig: &std::ffi::CStr| -> pg_sys::Oid {
+                if !exists {
                     return pg_sys::Oid::INVALID;
                 }
                 direct_function_call::<pg_sys::Oid>(pg_sys::regoperatorin, &[sig.into_datum()])
                     .unwrap_or(pg_sys::Oid::INVALID)
             };
             [
-                (lookup(c"<->(vector,vector)"), VectorMetric::L2),
-                (lookup(c"<=>(vector,vector)"), VectorMetric::Cosine),
-                (lookup(c"<#>(vector,vector)"), VectorMetric::InnerProduct),
+                (
+                    lookup(vector_exists, c"<->(vector,vector)"),
+                    VectorMetric::L2,
+                ),
+                (
+                    lookup(vector_exists, c"<=>(vector,vector)"),
+                    VectorMetric::Cosine,
+                ),
+                (
+                    lookup(vector_exists, c"<#>(vector,vector)"),
+                    VectorMetric::InnerProduct,
+                ),
+                (
+                    lookup(halfvec_exists, c"<->(halfvec,halfvec)"),
+                    VectorMetric::L2,
+                ),
+                (
+                    lookup(halfvec_exists, c"<=>(halfvec,halfvec)"),
+                    VectorMetric::Cosine,
+                ),
+                (
+                    lookup(halfvec_exists, c"<#>(halfvec,halfvec)"),
+                    VectorMetric::InnerProduct,
+                ),
+                (
+                    lookup(sparsevec_exists, c"<->(sparsevec,sparsevec)"),
+                    VectorMetric::L2,
+                ),
+                (
+                    lookup(sparsevec_exists, c"<=>(sparsevec,sparsevec)"),
+                    VectorMetric::Cosine,
+                ),
+                (
+                    lookup(sparsevec_exists, c"<#>(sparsevec,sparsevec)"),
+                    VectorMetric::InnerProduct,
+                ),
+                // `bit` is a built-in type, but its distance operators come with pgvector
+                (
+                    lookup(vector_exists, c"<~>(bit,bit)"),
+                    VectorMetric::Hamming,
+                ),
+                (
+                    lookup(vector_exists, c"<%>(bit,bit)"),
+                    VectorMetric::Jaccard,
+                ),
             ]
         });
         cached
@@ -109,16 +232,20 @@ impl VectorMetric {
 
     /// Map a pg_opfamily name (e.g. `vector_cosine_ops`) to its implied
     /// metric. Returns `None` for any name we don't recognise — callers
-    /// then fall back to `VectorMetric::default()` (L2).
+    /// then fall back to [`Self::default_for`] the attribute's type.
     ///
-    /// Opfamily and opclass names match for the three vector opclasses
-    /// we declare (CREATE OPERATOR CLASS without an explicit FAMILY
-    /// clause makes Postgres invent a same-named family).
+    /// Opfamily and opclass names match for the vector opclasses we declare
+    /// (CREATE OPERATOR CLASS without an explicit FAMILY clause makes
+    /// Postgres invent a same-named family).
     fn from_opfamily_name(name: &str) -> Option<Self> {
         match name {
-            "vector_l2_ops" => Some(Self::L2),
-            "vector_cosine_ops" => Some(Self::Cosine),
-            "vector_ip_ops" => Some(Self::InnerProduct),
+            "vector_l2_ops" | "halfvec_l2_ops" | "sparsevec_l2_ops" => Some(Self::L2),
+            "vector_cosine_ops" | "halfvec_cosine_ops" | "sparsevec_cosine_ops" => {
+                Some(Self::Cosine)
+            }
+            "vector_ip_ops" | "halfvec_ip_ops" | "sparsevec_ip_ops" => Some(Self::InnerProduct),
+            "bit_hamming_ops" => Some(Self::Hamming),
+            "bit_jaccard_ops" => Some(Self::Jaccard),
             _ => None,
         }
     }
@@ -129,7 +256,7 @@ impl VectorMetric {
     ///
     /// `attno` is the **0-based** index attribute position (matches
     /// `IndexInfo.ii_IndexAttrNumbers[attno]`). Returns `None` if the
-    /// opfamily isn't one of our three vector opfamilies (e.g. the
+    /// opfamily isn't one of our vector opfamilies (e.g. the
     /// user fell back to the default `anyelement_bm25_ops`).
     pub unsafe fn from_index_attr(indexrel: pg_sys::Relation, attno: usize) -> Option<Self> {
         if indexrel.is_null() {
@@ -169,6 +296,10 @@ impl From<VectorMetric> for TantivyMetric {
             VectorMetric::L2 => TantivyMetric::L2,
             VectorMetric::Cosine => TantivyMetric::Cosine,
             VectorMetric::InnerProduct => TantivyMetric::Dot,
+            // squared L2 distance between 0/1 elements is the Hamming distance
+            VectorMetric::Hamming => TantivyMetric::L2,
+            // only used to count shared bits, see the module docs
+            VectorMetric::Jaccard => TantivyMetric::Dot,
         }
     }
 }
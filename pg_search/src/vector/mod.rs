@@ -17,14 +17,157 @@
 
 pub mod clusterer;
 pub mod metric;
+pub mod multi;
+pub mod quantize;
+pub mod rebalance;
 
+use crate::postgres::catalog::{
+    is_pgvector_halfvec_oid, is_pgvector_oid, is_pgvector_sparsevec_oid,
+};
 use pgrx::{FromDatum, pg_sys};
+use serde::{Deserialize, Serialize};
+use tantivy::vector::ivf::AdaptiveProbeParams;
 
-/// Owned `Vec<f32>` extracted from a pgvector datum. pgvector's on-disk
-/// layout (after the varlena header) is `[int16 dim][int16 unused]
-/// [float4; dim]`. `from_datum` detoasts the datum, copies the floats
-/// out, and frees the detoasted copy if it differs from the original
-/// pointer.
+/// The IVF probe parameters of a vector search: at most
+/// `paradedb.vector_cluster_max_probe` of each segment's clusters, or all of
+/// them when `paradedb.vector_exact_search` is on.
+pub fn probe_params() -> AdaptiveProbeParams {
+    if crate::gucs::vector_exact_search() {
+        exact_probe_params()
+    } else {
+        AdaptiveProbeParams {
+            max_probe_fraction: crate::gucs::vector_cluster_max_probe(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Probe parameters that may probe every cluster.  An exact search pairs them
+/// with a top-K that can hold every document of the segments it searches, which
+/// never fills, so every cluster is probed and every vector scored.
+pub fn exact_probe_params() -> AdaptiveProbeParams {
+    AdaptiveProbeParams {
+        max_probe_fraction: 1.0,
+        ..Default::default()
+    }
+}
+
+/// The most dimensions a vector field can have.  pgvector's `vector` and
+/// `halfvec` stop at 16,000, but a `sparsevec` or `bit` can go further, and
+/// this leaves room for learned sparse vocabularies like SPLADE's 30,522
+/// terms.
+pub const MAX_VECTOR_DIMENSIONS: usize = 100_000;
+
+/// The column types that can be indexed as vectors: pgvector's `vector`,
+/// `halfvec` and `sparsevec`, and Postgres' own `bit`, which pgvector gives
+/// Hamming and Jaccard distance operators.
+///
+/// Every type is indexed as a dense `f32` vector, the only element type the
+/// index's vector storage has: `halfvec` elements are widened, so they only
+/// save space in the heap, `sparsevec`s are expanded to their full dimension,
+/// and each bit of a `bit` becomes a `0.0` or `1.0` element.  That's why
+/// neither can have more than [`MAX_VECTOR_DIMENSIONS`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VectorType {
+    Vector,
+    HalfVec,
+    SparseVec,
+    Bit,
+}
+
+impl VectorType {
+    pub fn from_oid(oid: pg_sys::Oid) -> Option<Self> {
+        if is_pgvector_oid(oid) {
+            Some(Self::Vector)
+        } else if is_pgvector_halfvec_oid(oid) {
+            Some(Self::HalfVec)
+        } else if is_pgvector_sparsevec_oid(oid) {
+            Some(Self::SparseVec)
+        } else if oid == pg_sys::BITOID {
+            Some(Self::Bit)
+        } else {
+            None
+        }
+    }
+
+    /// The SQL name of the type, which prefixes the names of its opclasses.
+    pub fn type_name(self) -> &'static str {
+        match self {
+            VectorType::Vector => "vector",
+            VectorType::HalfVec => "halfvec",
+            VectorType::SparseVec => "sparsevec",
+            VectorType::Bit => "bit",
+        }
+    }
+
+    /// Raises an error for a vector too long to be expanded to dense `f32`s,
+    /// such as a query vector longer than any indexed one can be.
+    fn check_dimensions(self, dim: usize) {
+        if dim > MAX_VECTOR_DIMENSIONS {
+            pgrx::error!(
+                "{} has {dim} dimensions, but a vector field can have at most {MAX_VECTOR_DIMENSIONS}",
+                self.type_name()
+            );
+        }
+    }
+
+    /// Decodes the body of a detoasted datum of this type (after the varlena
+    /// header) into dense `f32`s.
+    ///
+    /// * `vector`: `[int16 dim][int16 unused][float4; dim]`
+    /// * `halfvec`: `[int16 dim][int16 unused][float2; dim]`
+    /// * `sparsevec`: `[int32 dim][int32 nnz][int32 unused][int32; nnz]
+    ///   [float4; nnz]`, with 0-based indices
+    /// * `bit`: `[int32 bit_len][uint8; (bit_len + 7) / 8]`, most significant
+    ///   bit first
+    unsafe fn decode(self, data: *const u8) -> Vec<f32> {
+        match self {
+            VectorType::Vector => {
+                let dim = (data as *const i16).read_unaligned() as usize;
+                std::slice::from_raw_parts(data.add(4) as *const f32, dim).to_vec()
+            }
+            VectorType::HalfVec => {
+                let dim = (data as *const i16).read_unaligned() as usize;
+                std::slice::from_raw_parts(data.add(4) as *const u16, dim)
+                    .iter()
+                    .map(|bits| half::f16::from_bits(*bits).to_f32())
+                    .collect()
+            }
+            VectorType::SparseVec => {
+                let header = std::slice::from_raw_parts(data as *const i32, 3);
+                let (dim, nnz) = (header[0] as usize, header[1] as usize);
+                self.check_dimensions(dim);
+                let indices = std::slice::from_raw_parts(data.add(12) as *const i32, nnz);
+                let values = std::slice::from_raw_parts(data.add(12 + 4 * nnz) as *const f32, nnz);
+                let mut floats = vec![0.0; dim];
+                for (index, value) in indices.iter().zip(values) {
+                    floats[*index as usize] = *value;
+                }
+                floats
+            }
+            VectorType::Bit => {
+                let bit_len = (data as *const i32).read_unaligned() as usize;
+                self.check_dimensions(bit_len);
+                let bytes = std::slice::from_raw_parts(data.add(4), bit_len.div_ceil(8));
+                (0..bit_len)
+                    .map(|i| {
+                        if bytes[i / 8] & (0x80 >> (i % 8)) != 0 {
+                            1.0
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Owned `Vec<f32>` extracted from a vector datum. The layout is chosen by
+/// the datum's type (see [`VectorType::decode`]), and is pgvector's `vector`
+/// layout when the type isn't given. `from_polymorphic_datum` detoasts the
+/// datum, copies the elements out, and frees the detoasted copy if it
+/// differs from the original pointer.
 #[derive(Debug, Clone, PartialEq)]
 pub struct PgVector(pub Vec<f32>);
 
@@ -32,17 +175,16 @@ impl FromDatum for PgVector {
     unsafe fn from_polymorphic_datum(
         datum: pg_sys::Datum,
         is_null: bool,
-        _typoid: pg_sys::Oid,
+        typoid: pg_sys::Oid,
     ) -> Option<Self> {
         if is_null {
             return None;
         }
+        let vector_type = VectorType::from_oid(typoid).unwrap_or(VectorType::Vector);
         let ptr = datum.cast_mut_ptr::<pg_sys::varlena>();
         let detoasted = pg_sys::pg_detoast_datum(ptr);
         let data = pgrx::varlena::vardata_any(detoasted);
-        let dim = *(data as *const i16) as usize;
-        let floats_ptr = data.add(4) as *const f32;
-        let floats = std::slice::from_raw_parts(floats_ptr, dim).to_vec();
+        let floats = vector_type.decode(data as *const u8);
         if detoasted != ptr {
             pg_sys::pfree(detoasted as *mut std::ffi::c_void);
         }
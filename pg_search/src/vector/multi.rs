@@ -0,0 +1,110 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Multi-vector fields, for late-interaction retrieval like ColBERT's: a `vector[]` column
+//! whose rows each hold many vectors, such as an embedding per token of a passage.
+//!
+//! The index's vector storage holds one vector per document, so a multi-vector field isn't
+//! clustered.  A row's vectors are packed one after the other, as little-endian `f32`s, into a
+//! bytes fast field, and a query's vectors are compared to every row's with [`max_sim`].
+
+use super::PgVector;
+use pgrx::{Array, FromDatum, pg_sys};
+
+/// Decodes a non-NULL array of vectors of `element_type`, skipping its NULL elements.
+pub unsafe fn vectors_from_array(datum: pg_sys::Datum, element_type: pg_sys::Oid) -> Vec<Vec<f32>> {
+    Array::<pg_sys::Datum>::from_datum(datum, false)
+        .expect("vector array should not be NULL")
+        .iter()
+        .flatten()
+        .map(|datum| {
+            PgVector::from_polymorphic_datum(datum, false, element_type)
+                .expect("array element should not be NULL")
+                .0
+        })
+        .collect()
+}
+
+/// Packs `vectors` into the value of a multi-vector field whose vectors have `dims`
+/// dimensions.
+pub fn pack(vectors: &[Vec<f32>], dims: usize) -> anyhow::Result<Vec<u8>> {
+    let mut packed = Vec::with_capacity(vectors.len() * dims * size_of::<f32>());
+    for vector in vectors {
+        if vector.len() != dims {
+            anyhow::bail!(
+                "expected vectors of {dims} dimensions, but one has {}",
+                vector.len()
+            );
+        }
+        packed.extend(vector.iter().flat_map(|element| element.to_le_bytes()));
+    }
+    Ok(packed)
+}
+
+/// The late-interaction score of the vectors `packed` by [`pack`] for `query`: the sum, over
+/// the query's vectors, of each one's greatest inner product with any of the packed vectors.
+/// Nothing packed scores `0`.
+pub fn max_sim(query: &[Vec<f32>], packed: &[u8]) -> f32 {
+    let Some(dims) = query.first().map(Vec::len).filter(|dims| *dims > 0) else {
+        return 0.0;
+    };
+    let vectors = packed
+        .chunks_exact(size_of::<f32>())
+        .map(|bytes| f32::from_le_bytes(bytes.try_into().expect("chunk should be 4 bytes")))
+        .collect::<Vec<_>>();
+    if vectors.len() < dims {
+        return 0.0;
+    }
+
+    query
+        .iter()
+        .map(|query_vector| {
+            vectors
+                .chunks_exact(dims)
+                .map(|vector| {
+                    query_vector
+                        .iter()
+                        .zip(vector)
+                        .map(|(a, b)| a * b)
+                        .sum::<f32>()
+                })
+                .fold(f32::NEG_INFINITY, f32::max)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_sim_sums_each_query_vectors_best_match() {
+        let packed = pack(&[vec![1.0, 0.0], vec![0.0, 2.0], vec![0.5, 0.5]], 2).unwrap();
+
+        // [1, 0] best matches [1, 0] (1.0), [0, 1] best matches [0, 2] (2.0)
+        assert_eq!(max_sim(&[vec![1.0, 0.0], vec![0.0, 1.0]], &packed), 3.0);
+        // [-1, -1] matches nothing well, so its best is the least negative product
+        assert_eq!(max_sim(&[vec![-1.0, -1.0]], &packed), -1.0);
+        assert_eq!(max_sim(&[vec![1.0, 0.0]], &[]), 0.0);
+    }
+
+    #[test]
+    fn pack_rejects_vectors_of_other_dimensions() {
+        assert!(pack(&[vec![1.0, 2.0], vec![3.0]], 2).is_err());
+        assert_eq!(pack(&[], 2).unwrap(), Vec::<u8>::new());
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Quantized vector fields, for indexes whose vectors are too large to keep at full precision.
+//!
+//! An index built `WITH (vector_quantization = 'int8')` or `'binary'` doesn't keep its vector
+//! fields in the index's vector storage.  Each vector is encoded into a code, which is stored in
+//! a bytes fast field: `int8` keeps a byte per dimension, a quarter of the `f32` it replaces, and
+//! `binary` keeps a bit.  A vector ORDER BY ranks every code by its approximate distance, then
+//! re-ranks the nearest by the exact distance of their full-precision vectors, which are read
+//! from the table (see `SearchIndexReader::top_by_quantized_distance`).
+//!
+//! The index's vector storage only holds `f32`s, so a quantized field isn't clustered, and every
+//! code of every segment is scanned.  Codes start with a tag naming their encoding, so an index
+//! whose `vector_quantization` was altered can hold both kinds until it's reindexed.
+
+const INT8_TAG: u8 = 1;
+const BINARY_TAG: u8 = 2;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VectorQuantization {
+    /// Each element is rounded to one of 256 steps between the vector's smallest and largest
+    /// elements.
+    Int8,
+    /// Each element keeps only its sign, and the vector its length.
+    Binary,
+}
+
+impl VectorQuantization {
+    /// The encoding named by a `vector_quantization` option, or `None` for `'none'`.
+    pub fn from_option(value: &str) -> Result<Option<Self>, String> {
+        match value {
+            "" | "none" => Ok(None),
+            "int8" => Ok(Some(Self::Int8)),
+            "binary" => Ok(Some(Self::Binary)),
+            other => Err(format!(
+                "invalid `vector_quantization`: {other:?}; expected 'none', 'int8' or 'binary'"
+            )),
+        }
+    }
+
+    /// Encodes `vector` into a code that [`decode`] approximately reverses.
+    pub fn encode(self, vector: &[f32]) -> Vec<u8> {
+        match self {
+            Self::Int8 => {
+                let min = vector.iter().copied().fold(f32::INFINITY, f32::min);
+                let max = vector.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                let (min, step) = if vector.is_empty() {
+                    (0.0, 0.0)
+                } else {
+                    (min, (max - min) / 255.0)
+                };
+                let mut code = Vec::with_capacity(9 + vector.len());
+                code.push(INT8_TAG);
+                code.extend(min.to_le_bytes());
+                code.extend(step.to_le_bytes());
+                code.extend(vector.iter().map(|element| {
+                    if step > 0.0 {
+                        ((element - min) / step).round() as u8
+                    } else {
+                        0
+                    }
+                }));
+                code
+            }
+            Self::Binary => {
+                let norm = vector
+                    .iter()
+                    .map(|element| element * element)
+                    .sum::<f32>()
+                    .sqrt();
+                let mut code = Vec::with_capacity(9 + vector.len().div_ceil(8));
+                code.push(BINARY_TAG);
+                code.extend(norm.to_le_bytes());
+                code.extend((vector.len() as u32).to_le_bytes());
+                code.extend(vector.chunks(8).map(|elements| {
+                    elements
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, element)| **element > 0.0)
+                        .fold(0u8, |byte, (i, _)| byte | (0x80 >> i))
+                }));
+                code
+            }
+        }
+    }
+}
+
+/// Decodes a code [`VectorQuantization::encode`] produced into the vector it approximates.
+///
+/// A binary code decodes to a vector of its original's length whose elements all have the same
+/// magnitude, and the signs of the original's.
+pub fn decode(code: &[u8]) -> Vec<f32> {
+    let f32_at = |at: usize| {
+        f32::from_le_bytes(
+            code[at..at + 4]
+                .try_into()
+                .expect("slice should be 4 bytes"),
+        )
+    };
+    match code.first() {
+        Some(&INT8_TAG) => {
+            let (min, step) = (f32_at(1), f32_at(5));
+            code[9..]
+                .iter()
+                .map(|step_count| min + *step_count as f32 * step)
+                .collect()
+        }
+        Some(&BINARY_TAG) => {
+            let norm = f32_at(1);
+            let dims = u32::from_le_bytes(code[5..9].try_into().expect("slice should be 4 bytes"))
+                as usize;
+            let magnitude = if dims > 0 {
+                norm / (dims as f32).sqrt()
+            } else {
+                0.0
+            };
+            (0..dims)
+                .map(|i| {
+                    if code[9 + i / 8] & (0x80 >> (i % 8)) != 0 {
+                        magnitude
+                    } else {
+                        -magnitude
+                    }
+                })
+                .collect()
+        }
+        tag => panic!("unknown quantized vector encoding: {tag:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn int8_decodes_within_half_a_step() {
+        let vector = [-1.0, -0.25, 0.0, 0.3, 2.0];
+        let decoded = decode(&VectorQuantization::Int8.encode(&vector));
+        let half_step = 3.0 / 255.0 / 2.0 + 1e-5;
+
+        assert_eq!(decoded.len(), vector.len());
+        for (a, b) in vector.iter().zip(&decoded) {
+            assert!((a - b).abs() <= half_step, "{a} decoded as {b}");
+        }
+        assert_eq!(
+            decode(&VectorQuantization::Int8.encode(&[0.5, 0.5])),
+            [0.5, 0.5]
+        );
+    }
+
+    #[test]
+    fn binary_keeps_signs_and_length() {
+        let vector = [3.0, -4.0, 0.0, 1.0, -1.0, 2.0, -2.0, 5.0, -0.5];
+        let code = VectorQuantization::Binary.encode(&vector);
+        let decoded = decode(&code);
+
+        assert_eq!(code.len(), 9 + 2);
+        assert_eq!(decoded.len(), vector.len());
+        for (a, b) in vector.iter().zip(&decoded) {
+            assert_eq!(*a > 0.0, *b > 0.0, "{a} decoded as {b}");
+        }
+        let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((norm(&vector) - norm(&decoded)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn from_option_names_every_encoding() {
+        assert_eq!(VectorQuantization::from_option(""), Ok(None));
+        assert_eq!(VectorQuantization::from_option("none"), Ok(None));
+        assert_eq!(
+            VectorQuantization::from_option("int8"),
+            Ok(Some(VectorQuantization::Int8))
+        );
+        assert_eq!(
+            VectorQuantization::from_option("binary"),
+            Ok(Some(VectorQuantization::Binary))
+        );
+        assert!(VectorQuantization::from_option("int4").is_err());
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Detects clustered segments whose IVF clusters have drifted out of balance.
+//!
+//! A segment's centroids are trained when the segment is written and never
+//! move afterwards, so under update-heavy workloads its clusters drift: some
+//! empty out while others grow. Merging such a segment on its own rewrites it
+//! with freshly trained centroids; the merge policy does that for the
+//! segments [`SkewThresholds`] flags, and `pdb.rebalance_vectors` for all of
+//! them.
+
+use tantivy::SegmentReader;
+use tantivy::schema::Field;
+
+/// How far a segment's clusters may drift before it is rebalanced, from
+/// `paradedb.vector_rebalance_size_ratio` and
+/// `paradedb.vector_rebalance_empty_fraction`. A threshold of `0` disables
+/// its check.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SkewThresholds {
+    /// Largest allowed ratio of the largest cluster's size to the average.
+    pub max_size_ratio: f64,
+    /// Largest allowed fraction of empty clusters.
+    pub max_empty_fraction: f64,
+    /// The fraction of a segment's rows that must be deleted before its
+    /// clusters are checked at all, from
+    /// `paradedb.vector_rebalance_min_deleted_fraction`.
+    pub min_deleted_fraction: f64,
+}
+
+impl SkewThresholds {
+    /// The configured thresholds, or `None` when both checks are disabled.
+    pub fn from_gucs() -> Option<Self> {
+        let thresholds = Self {
+            max_size_ratio: crate::gucs::vector_rebalance_size_ratio(),
+            max_empty_fraction: crate::gucs::vector_rebalance_empty_fraction(),
+            min_deleted_fraction: crate::gucs::vector_rebalance_min_deleted_fraction(),
+        };
+        (thresholds.max_size_ratio > 0.0 || thresholds.max_empty_fraction > 0.0)
+            .then_some(thresholds)
+    }
+
+    /// Could a segment of `max_doc` rows, `num_deleted` of them deleted, have
+    /// drifted enough to check?  Clusters only drift as rows are deleted, so
+    /// a segment with few deletes isn't worth opening.
+    pub fn may_have_drifted(&self, max_doc: usize, num_deleted: usize) -> bool {
+        num_deleted > 0 && num_deleted as f64 >= self.min_deleted_fraction * max_doc as f64
+    }
+
+    pub fn is_exceeded_by(&self, skew: &ClusterSkew) -> bool {
+        (self.max_size_ratio > 0.0 && skew.size_ratio > self.max_size_ratio)
+            || (self.max_empty_fraction > 0.0 && skew.empty_fraction > self.max_empty_fraction)
+    }
+}
+
+/// The balance of one vector field's clusters in one segment, as reported by
+/// `paradedb.vector_info`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ClusterSkew {
+    /// The largest cluster's size over the average cluster size.
+    pub size_ratio: f64,
+    /// The fraction of clusters that are empty.
+    pub empty_fraction: f64,
+}
+
+impl ClusterSkew {
+    pub fn new(
+        max_cluster_size: f64,
+        avg_cluster_size: f64,
+        empty_clusters: f64,
+        num_clusters: f64,
+    ) -> Self {
+        Self {
+            size_ratio: if avg_cluster_size > 0.0 {
+                max_cluster_size / avg_cluster_size
+            } else {
+                0.0
+            },
+            empty_fraction: if num_clusters > 0.0 {
+                empty_clusters / num_clusters
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// The skew of `field`'s clusters in `reader`'s segment, or `None` if the
+    /// segment doesn't store `field` clustered.
+    pub fn of(reader: &SegmentReader, field: Field) -> tantivy::Result<Option<Self>> {
+        let vector_index = reader.vector_index(field)?;
+        let Some(info) = vector_index.info() else {
+            return Ok(None);
+        };
+        let (Some(stats), Some(num_centroids)) = (info.cluster_stats.as_ref(), info.num_centroids)
+        else {
+            return Ok(None);
+        };
+        Ok(Some(Self::new(
+            stats.max_cluster_size as f64,
+            stats.avg_cluster_size,
+            stats.empty_clusters as f64,
+            num_centroids as f64,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thresholds_of_zero_are_disabled() {
+        let skew = ClusterSkew::new(40.0, 4.0, 3.0, 10.0);
+        assert_eq!(skew.size_ratio, 10.0);
+        assert_eq!(skew.empty_fraction, 0.3);
+
+        let size_only = SkewThresholds {
+            max_size_ratio: 8.0,
+            max_empty_fraction: 0.0,
+            min_deleted_fraction: 0.1,
+        };
+        assert!(size_only.is_exceeded_by(&skew));
+
+        let empty_only = SkewThresholds {
+            max_size_ratio: 0.0,
+            max_empty_fraction: 0.5,
+            min_deleted_fraction: 0.1,
+        };
+        assert!(!empty_only.is_exceeded_by(&skew));
+    }
+
+    #[test]
+    fn few_deletes_are_not_checked() {
+        let thresholds = SkewThresholds {
+            max_size_ratio: 8.0,
+            max_empty_fraction: 0.25,
+            min_deleted_fraction: 0.1,
+        };
+        assert!(!thresholds.may_have_drifted(1000, 0));
+        assert!(!thresholds.may_have_drifted(1000, 99));
+        assert!(thresholds.may_have_drifted(1000, 100));
+    }
+}
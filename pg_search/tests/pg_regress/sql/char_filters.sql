@@ -0,0 +1,35 @@
+\i common/common_setup.sql
+
+-- html_strip removes tags and decodes entities before tokenizing
+SELECT '<p>Red <b>running</b> shoes &amp; socks</p>'::pdb.simple('html_strip=true')::text[];
+
+-- Block-level elements keep the words around them apart
+SELECT 'one<br>two<div>three</div>'::pdb.simple('html_strip=true')::text[];
+
+-- mapping replaces every occurrence of a key with its value
+SELECT 'salt&pepper'::pdb.simple('mapping=& => and')::text[];
+
+-- pattern_replace rewrites every match of a regex
+SELECT 'call 555-1234'::pdb.simple('pattern_replace=/([0-9]+)-([0-9]+)/', 'pattern_replacement=$1$2')::text[];
+
+-- Char filters run in the order they are given
+SELECT '<i>Fish</i>&amp;<i>Chips</i>'::pdb.simple('html_strip=true', 'mapping=& => and')::text[];
+
+-- Invalid mappings are rejected
+SELECT 'x'::pdb.simple('mapping=a')::text[];
+
+-- Offsets point at the original text
+CREATE TABLE html_products (id serial8 NOT NULL PRIMARY KEY, description text);
+INSERT INTO html_products (description) VALUES
+    ('<p>Red <b>running</b> shoes</p>'),
+    ('<div class="item">Blue &amp; white shoes</div>');
+CREATE INDEX html_products_idx ON html_products
+USING paradedb (id, (description::pdb.simple('html_strip=true')))
+WITH (key_field = 'id');
+SELECT id, pdb.snippet_positions(description) FROM html_products WHERE description ||| 'shoes' ORDER BY id;
+SELECT id, pdb.snippet_positions(description) FROM html_products WHERE description ||| 'white' ORDER BY id;
+
+-- Markup is not indexed
+SELECT id FROM html_products WHERE description ||| 'div' ORDER BY id;
+
+DROP TABLE html_products;
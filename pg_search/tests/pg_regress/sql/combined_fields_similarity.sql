@@ -0,0 +1,77 @@
+-- pdb.combined_fields scores a query across several text fields with BM25F,
+-- and a text field's `similarity` typmod picks how its terms are scored.
+CREATE EXTENSION IF NOT EXISTS pg_search;
+
+CREATE TABLE sim_docs (
+    id    int PRIMARY KEY,
+    title text,
+    body  text
+);
+
+INSERT INTO sim_docs VALUES
+    (1, 'search engine', 'fast search engine for postgres'),
+    (2, 'postgres',      'search inside postgres with bm25'),
+    (3, 'search search', 'nothing relevant here'),
+    (4, 'database',      'engine room');
+
+-- every matched term scores 1, however often it occurs
+CREATE INDEX sim_docs_idx ON sim_docs
+    USING paradedb (id, (title::pdb.simple('similarity=constant')), body)
+    WITH (key_field = id);
+
+SELECT id, round(pdb.score(id)::numeric, 4) AS score
+FROM sim_docs
+WHERE title @@@ 'search engine'
+ORDER BY pdb.score(id) DESC, id;
+
+-- a query string's terms are scored by their field's similarity too
+SELECT id, round(pdb.score(id)::numeric, 4) AS score
+FROM sim_docs
+WHERE id @@@ 'title:search OR title:engine'
+ORDER BY pdb.score(id) DESC, id;
+
+DROP INDEX sim_docs_idx;
+
+-- mu only applies to the dirichlet similarity
+CREATE INDEX sim_docs_idx ON sim_docs
+    USING paradedb (id, (title::pdb.simple('similarity=tfidf', 'mu=10')), body)
+    WITH (key_field = id);
+
+-- classic TF-IDF: sqrt(tf) * idf^2 / sqrt(length), with idf = 1 + ln(5 / 3)
+CREATE INDEX sim_docs_idx ON sim_docs
+    USING paradedb (id, (title::pdb.simple('similarity=tfidf')), body)
+    WITH (key_field = id);
+
+SELECT id, round(pdb.score(id)::numeric, 4) AS score
+FROM sim_docs
+WHERE title @@@ 'search'
+ORDER BY pdb.score(id) DESC, id;
+
+-- combined fields need the fields' shared BM25 statistics
+SELECT id
+FROM sim_docs
+WHERE id @@@ pdb.combined_fields('search', ARRAY['title', 'body']);
+
+DROP INDEX sim_docs_idx;
+
+CREATE INDEX sim_docs_idx ON sim_docs
+    USING paradedb (id, title, body)
+    WITH (key_field = id);
+
+-- title's frequencies and lengths count twice. Both terms are in two documents'
+-- fields, so they share an idf of ln(2)
+SELECT id, round(pdb.score(id)::numeric, 4) AS score
+FROM sim_docs
+WHERE id @@@ pdb.combined_fields('search engine', ARRAY['title^2', 'body'])
+ORDER BY pdb.score(id) DESC, id;
+
+SELECT id, round(pdb.score(id)::numeric, 4) AS score
+FROM sim_docs
+WHERE id @@@ pdb.combined_fields('search engine', ARRAY['title^2', 'body'], conjunction_mode => true)
+ORDER BY pdb.score(id) DESC, id;
+
+SELECT id
+FROM sim_docs
+WHERE id @@@ pdb.combined_fields('search', ARRAY['title^0', 'body']);
+
+DROP TABLE sim_docs;
@@ -0,0 +1,37 @@
+\i common/common_setup.sql
+
+-- Sub-words are emitted alongside the compound
+SELECT 'Krankenversicherungsbeitrag'::pdb.simple('decompounder_words=kranken,versicherung,beitrag')::text[];
+
+-- Every sub-word is emitted by default, or only the longest one starting at each character
+SELECT 'Donaudampfschiff'::pdb.simple('decompounder_words=donau,dampf,schiff,dampfschiff')::text[];
+SELECT 'Donaudampfschiff'::pdb.simple('decompounder_words=donau,dampf,schiff,dampfschiff', 'only_longest_match=true')::text[];
+
+-- Invalid sizes are rejected
+SELECT 'Krankenversicherungsbeitrag'::pdb.simple('decompounder_words=kranken', 'min_subword_size=4', 'max_subword_size=3')::text[];
+
+-- Words can be loaded from a table
+CREATE TABLE decompounder_words (word text);
+INSERT INTO decompounder_words VALUES ('kranken'), ('versicherung'), ('beitrag');
+SELECT 'Krankenversicherungsbeitrag'::pdb.simple('decompounder_table=decompounder_words')::text[];
+
+-- Sub-words can be matched on their own
+CREATE TABLE decompounder_docs (id serial8 NOT NULL PRIMARY KEY, body text);
+INSERT INTO decompounder_docs (body) VALUES
+    ('Krankenversicherungsbeitrag steigt'),
+    ('Versicherung für Autos'),
+    ('Beitragsbemessungsgrenze');
+CREATE INDEX decompounder_docs_idx ON decompounder_docs
+USING paradedb (id, (body::pdb.simple('decompounder_table=decompounder_words')))
+WITH (key_field = 'id');
+SELECT id, body FROM decompounder_docs WHERE body === 'versicherung' ORDER BY id;
+SELECT id, body FROM decompounder_docs WHERE body === 'beitrag' ORDER BY id;
+
+-- The index keeps the words it was built with, so edits to the table only apply after a REINDEX
+INSERT INTO decompounder_words VALUES ('grenze');
+SELECT id, body FROM decompounder_docs WHERE body === 'grenze' ORDER BY id;
+REINDEX INDEX decompounder_docs_idx;
+SELECT id, body FROM decompounder_docs WHERE body === 'grenze' ORDER BY id;
+
+DROP TABLE decompounder_docs;
+DROP TABLE decompounder_words;
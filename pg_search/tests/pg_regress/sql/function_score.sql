@@ -0,0 +1,85 @@
+-- pdb.function_score adjusts a query's scores with functions of each document's
+-- fast field values, evaluated as the query matches it, so a Top K by
+-- pdb.score() ranks by the adjusted score.
+CREATE EXTENSION IF NOT EXISTS pg_search;
+
+CREATE TABLE fs_posts (
+    id        int PRIMARY KEY,
+    title     text,
+    likes     int,
+    published timestamptz
+);
+
+INSERT INTO fs_posts VALUES
+    (1, 'postgres search',      9,   '2026-01-10 00:00:00+00'),
+    (2, 'postgres tuning',      99,  '2026-01-01 00:00:00+00'),
+    (3, 'postgres search tips', 0,   '2025-12-01 00:00:00+00'),
+    (4, 'cooking',              999, '2026-01-10 00:00:00+00');
+
+CREATE INDEX fs_posts_idx ON fs_posts
+    USING paradedb (id, title, likes, published)
+    WITH (key_field = id);
+
+-- popularity: log10(1 + likes) replaces the BM25 score
+SELECT id, round(pdb.score(id)::numeric, 4) AS score
+FROM fs_posts
+WHERE id @@@ pdb.function_score(
+    pdb.match('title', 'postgres'),
+    '[{"field_value_factor": {"field": "likes", "modifier": "log1p"}}]',
+    boost_mode => 'replace')
+ORDER BY pdb.score(id) DESC, id
+LIMIT 3;
+
+-- the default multiplies the BM25 score, so the more popular of the two
+-- equally relevant posts wins
+SELECT id
+FROM fs_posts
+WHERE id @@@ pdb.function_score(
+    pdb.match('title', 'postgres'),
+    '[{"field_value_factor": {"field": "likes", "modifier": "log1p"}}]')
+ORDER BY pdb.score(id) DESC, id
+LIMIT 3;
+
+-- recency: halves 10 days from the origin, 0.5 ^ (9 / 10) ^ 2 for post 2
+SELECT id, round(pdb.score(id)::numeric, 4) AS score
+FROM fs_posts
+WHERE id @@@ pdb.function_score(
+    pdb.match('title', 'postgres'),
+    '[{"gauss": {"field": "published", "origin": "2026-01-10T00:00:00Z", "scale": "10d"}}]',
+    boost_mode => 'replace')
+ORDER BY pdb.score(id) DESC, id
+LIMIT 3;
+
+-- weights for the documents matching a filter, added up
+SELECT id, round(pdb.score(id)::numeric, 4) AS score
+FROM fs_posts
+WHERE id @@@ pdb.function_score(
+    pdb.match('title', 'postgres'),
+    '[{"filter": "title:tips", "weight": 5}, {"weight": 1}]',
+    score_mode => 'sum', boost_mode => 'replace')
+ORDER BY pdb.score(id) DESC, id
+LIMIT 3;
+
+-- a linear decay on a number reaches zero at twice the scale
+SELECT id, round(pdb.score(id)::numeric, 4) AS score
+FROM fs_posts
+WHERE id @@@ pdb.function_score(
+    pdb.match('title', 'postgres'),
+    '[{"linear": {"field": "likes", "origin": 99, "scale": 90}}]',
+    boost_mode => 'replace')
+ORDER BY pdb.score(id) DESC, id
+LIMIT 3;
+
+SELECT id FROM fs_posts
+WHERE id @@@ pdb.function_score(paradedb.all(),
+    '[{"gauss": {"field": "likes", "origin": 10, "scale": 5, "decay": 1.5}}]');
+SELECT id FROM fs_posts
+WHERE id @@@ pdb.function_score(paradedb.all(),
+    '[{"gauss": {"field": "likes", "origin": "2026-01-10", "scale": "1d"}}]');
+SELECT id FROM fs_posts
+WHERE id @@@ pdb.function_score(paradedb.all(),
+    '[{"field_value_factor": {"field": "title"}}]');
+SELECT id FROM fs_posts
+WHERE id @@@ pdb.function_score(paradedb.all(), '[{"weight": 2}]', score_mode => 'median');
+
+DROP TABLE fs_posts;
@@ -0,0 +1,40 @@
+\i common/common_setup.sql
+
+-- Dictionaries can be stored in a table, with their .aff and .dic files as bytea
+CREATE TABLE hunspell_dictionaries (name text, aff bytea, dic bytea);
+INSERT INTO hunspell_dictionaries VALUES (
+    'en_TEST',
+    convert_to(E'SET UTF-8\nSFX S Y 2\nSFX S y ies [^aeiou]y\nSFX S 0 s [^y]\nSFX D Y 2\nSFX D 0 ed [^ey]\nSFX D 0 d e\n', 'UTF8'),
+    convert_to(E'3\ncity/S\nwalk/DS\nbake/D\n', 'UTF8')
+);
+
+-- Words are replaced by their dictionary stems, and unknown words are kept as-is
+SELECT 'The cities walked and baked'::pdb.simple('hunspell=en_TEST', 'hunspell_table=hunspell_dictionaries')::text[];
+
+-- Dictionary names can't leave the dictionary directory
+SELECT 'cities'::pdb.simple('hunspell=../en_TEST')::text[];
+
+-- Stems can be matched as single terms
+CREATE TABLE hunspell_docs (id serial8 NOT NULL PRIMARY KEY, body text);
+INSERT INTO hunspell_docs (body) VALUES ('Two cities'), ('One city'), ('A walk');
+CREATE INDEX hunspell_docs_idx ON hunspell_docs
+USING paradedb (id, (body::pdb.simple('hunspell=en_TEST', 'hunspell_table=hunspell_dictionaries')))
+WITH (key_field = 'id');
+SELECT id, body FROM hunspell_docs WHERE body === 'city' ORDER BY id;
+
+-- The index keeps using the version of the dictionary it was built with until it's rebuilt
+UPDATE hunspell_dictionaries SET dic = convert_to(E'4\ncity/S\nwalk/DS\nbake/D\ntown/S\n', 'UTF8');
+INSERT INTO hunspell_docs (body) VALUES ('Two towns');
+SELECT id, body FROM hunspell_docs WHERE body === 'town' ORDER BY id;
+
+-- including in a new session, which reads the dictionary from the index
+\set QUIET 1
+\c
+\set QUIET 0
+INSERT INTO hunspell_docs (body) VALUES ('Three towns');
+SELECT id, body FROM hunspell_docs WHERE body === 'town' ORDER BY id;
+REINDEX INDEX hunspell_docs_idx;
+SELECT id, body FROM hunspell_docs WHERE body === 'town' ORDER BY id;
+
+DROP TABLE hunspell_docs;
+DROP TABLE hunspell_dictionaries;
@@ -0,0 +1,71 @@
+-- pdb.rrf and pdb.score_blend fuse a text query with a vector search in a single
+-- query, scored through pdb.score().
+--
+-- Text ranks for 'shoes' (shorter bodies score higher): 1, 2, 4
+-- Vector ranks for [1,0,0] under L2:                    1, 3, 5, 2, 4
+CREATE EXTENSION IF NOT EXISTS vector;
+CREATE EXTENSION IF NOT EXISTS pg_search;
+
+CREATE TABLE hybrid_items (
+    id   int PRIMARY KEY,
+    body text,
+    vec  vector(3)
+);
+
+INSERT INTO hybrid_items VALUES
+    (1, 'running shoes',             '[1,   0,   0]'),
+    (2, 'blue running shoes',        '[0,   1,   0]'),
+    (3, 'green hat',                 '[0.9, 0.1, 0]'),
+    (4, 'bright yellow trail shoes', '[0,   0,   2]'),
+    (5, 'black hat',                 '[0.5, 0.5, 0]');
+
+CREATE INDEX hybrid_items_idx ON hybrid_items
+    USING paradedb (id, body, vec vector_l2_ops)
+    WITH (key_field = id);
+
+-- each branch keeps its top 3: 1/(60 + rank) per branch a row appears in
+SELECT id, round(pdb.score(id)::numeric, 4) AS score
+FROM hybrid_items
+WHERE id @@@ pdb.rrf(pdb.match('body', 'shoes'), 'vec', '[1,0,0]'::vector, window_size => 3)
+ORDER BY pdb.score(id) DESC, id
+LIMIT 5;
+
+-- weighted branches
+SELECT id, round(pdb.score(id)::numeric, 4) AS score
+FROM hybrid_items
+WHERE id @@@ pdb.rrf(pdb.match('body', 'shoes'), 'vec', '[1,0,0]'::vector,
+                     text_weight => 1.0, vector_weight => 0.5, window_size => 3)
+ORDER BY pdb.score(id) DESC, id
+LIMIT 5;
+
+-- min-max normalized blend: 1 scores 1 in both branches, 4 and 5 are the
+-- worst of their branch and score 0
+SELECT id
+FROM hybrid_items
+WHERE id @@@ pdb.score_blend(pdb.match('body', 'shoes'), 'vec', '[1,0,0]'::vector, window_size => 3)
+ORDER BY pdb.score(id) DESC, id
+LIMIT 5;
+
+-- the fused rows can be filtered like any other search
+SELECT id
+FROM hybrid_items
+WHERE id @@@ pdb.rrf(pdb.match('body', 'shoes'), 'vec', '[1,0,0]'::vector, window_size => 3)
+  AND body @@@ 'running'
+ORDER BY pdb.score(id) DESC, id
+LIMIT 5;
+
+-- both branches only consider the rows the filters match: 2 is outside the top 1
+-- of either branch over every row, but first in both among the blue ones
+SELECT id
+FROM hybrid_items
+WHERE id @@@ pdb.rrf(pdb.match('body', 'shoes'), 'vec', '[1,0,0]'::vector, window_size => 1)
+  AND body @@@ 'blue'
+ORDER BY pdb.score(id) DESC, id
+LIMIT 5;
+
+SELECT id FROM hybrid_items
+WHERE id @@@ pdb.rrf(pdb.match('body', 'shoes'), 'vec', '[1,0,0]'::vector, window_size => 0);
+SELECT id FROM hybrid_items
+WHERE id @@@ pdb.rrf(pdb.match('body', 'shoes'), 'vec', '[1,0]'::vector);
+
+DROP TABLE hybrid_items;
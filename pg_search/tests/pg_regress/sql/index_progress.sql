@@ -0,0 +1,10 @@
+-- Tests the progress reporting of index builds and background merges
+
+CREATE EXTENSION IF NOT EXISTS pg_search;
+
+-- the phases of a build, shown by pg_stat_progress_create_index as subphases of `building index`
+SELECT phase, pg_indexam_progress_phasename((SELECT oid FROM pg_am WHERE amname = 'paradedb'), phase) AS name
+FROM generate_series(1, 6) AS phase;
+
+-- no build or merge is running
+SELECT count(*) FROM pdb.index_progress;
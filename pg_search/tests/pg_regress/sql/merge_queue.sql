@@ -0,0 +1,17 @@
+-- Tests the settings and queue of the merge scheduler
+
+CREATE EXTENSION IF NOT EXISTS pg_search;
+
+SELECT name, setting, unit, context
+FROM pg_settings
+WHERE name IN (
+    'paradedb.enable_merge_scheduler',
+    'paradedb.max_concurrent_merges',
+    'paradedb.merge_io_rate_limit',
+    'paradedb.merge_quiet_windows',
+    'paradedb.merge_scheduler_naptime'
+)
+ORDER BY name;
+
+-- without the scheduler, nothing is ever queued
+SELECT count(*) FROM pdb.merge_queue();
@@ -0,0 +1,65 @@
+\i common/common_setup.sql
+
+-- An analyzer bundles a tokenizer and its filters under a name
+SELECT pdb.create_analyzer('english_std', 'simple', '{"stemmer": "english", "stopwords_language": "english"}');
+SELECT * FROM pdb.tokenize('english_std', 'The runners are running');
+
+-- Tokenizer parameters are written the same way as in `search_tokenizer`
+SELECT pdb.create_analyzer('trigrams', 'ngram(3,3)');
+SELECT token FROM pdb.tokenize('trigrams', 'Search');
+
+-- Analyzers are validated when they're created
+SELECT pdb.create_analyzer('english_std', 'simple');
+SELECT pdb.create_analyzer('bad', 'nope');
+SELECT pdb.create_analyzer('bad', 'simple', '{"alias": "title"}');
+SELECT * FROM pdb.tokenize('nope', 'Search');
+
+-- Casts reference an analyzer by name, with the type of its tokenizer
+SELECT 'Running shoes'::pdb.simple('analyzer=english_std')::text[];
+SELECT 'Search'::pdb.ngram('analyzer=trigrams')::text[];
+SELECT 'Search'::pdb.simple('analyzer=trigrams')::text[];
+SELECT 'Search'::pdb.simple('analyzer=english_std', 'lowercase=false')::text[];
+
+-- So do indexes
+CREATE TABLE analyzer_docs (id serial8 NOT NULL PRIMARY KEY, title text, body text);
+INSERT INTO analyzer_docs (title, body) VALUES
+    ('Running shoes', 'The runner runs'),
+    ('Walking boots', 'A long walk'),
+    ('Run club', 'Runners meet on Sundays');
+CREATE INDEX analyzer_docs_idx ON analyzer_docs
+USING paradedb (id, (title::pdb.simple('analyzer=english_std')), (body::pdb.simple('analyzer=english_std', 'fieldnorms=false')))
+WITH (key_field = 'id');
+SELECT id, title FROM analyzer_docs WHERE title === 'run' ORDER BY id;
+SELECT id, body FROM analyzer_docs WHERE body === 'runner' ORDER BY id;
+
+-- Indexes keep the definition they were created with after the analyzer is dropped
+SELECT pdb.drop_analyzer('english_std');
+INSERT INTO analyzer_docs (title, body) VALUES ('Runs daily', 'Every morning');
+SELECT id, title FROM analyzer_docs WHERE title === 'run' ORDER BY id;
+
+-- ...until they're rebuilt, which looks the analyzer up again
+REINDEX INDEX analyzer_docs_idx;
+
+SELECT pdb.drop_analyzer('english_std');
+SELECT pdb.drop_analyzer('english_std', if_exists => true);
+
+DROP TABLE analyzer_docs;
+
+-- Search tokenizers can name an analyzer too
+SELECT pdb.create_analyzer('stemmed', 'simple', '{"stemmer": "english"}');
+CREATE TABLE analyzer_search_docs (id serial8 NOT NULL PRIMARY KEY, body text);
+INSERT INTO analyzer_search_docs (body) VALUES ('run'), ('running');
+CREATE INDEX analyzer_search_docs_idx ON analyzer_search_docs
+USING paradedb (id, body)
+WITH (key_field = 'id', search_tokenizer = 'simple(analyzer=stemmed)');
+SELECT id, body FROM analyzer_search_docs WHERE body ||| 'running' ORDER BY id;
+-- but ALTER INDEX can't switch to one, as analyzers are only read when an index is built
+ALTER INDEX analyzer_search_docs_idx SET (search_tokenizer = 'simple(analyzer=trigrams)');
+ALTER INDEX analyzer_search_docs_idx SET (text_fields = '{"body": {"tokenizer": {"type": "default", "analyzer": "trigrams"}}}');
+DROP TABLE analyzer_search_docs;
+
+-- Tokenizers given as JSON can name an analyzer too
+SELECT * FROM paradedb.tokenize('{"type": "default", "analyzer": "stemmed"}'::jsonb, 'Running shoes');
+
+SELECT pdb.drop_analyzer('stemmed');
+SELECT pdb.drop_analyzer('trigrams');
@@ -0,0 +1,62 @@
+-- pdb.percolate runs the queries stored in an index's table against a document,
+-- indexed on its own with the index's fields and tokenizers.
+CREATE EXTENSION IF NOT EXISTS pg_search;
+
+CREATE TABLE saved_searches (
+    id          int PRIMARY KEY,
+    description text,
+    category    text,
+    query       paradedb.searchqueryinput
+);
+
+CREATE INDEX saved_searches_idx ON saved_searches
+    USING paradedb (id, description, (category::pdb.literal))
+    WITH (key_field = id);
+
+INSERT INTO saved_searches (id, query) VALUES
+    (1, pdb.match('description', 'running shoes')),
+    (2, paradedb.term('category', 'Electronics')),
+    (3, paradedb.boolean(must => ARRAY[
+        pdb.match('description', 'keyboard'),
+        paradedb.term('category', 'Electronics')])),
+    (4, paradedb.all()),
+    (5, NULL);
+
+SELECT key FROM pdb.percolate('saved_searches_idx',
+    '{"description": "Ergonomic metal keyboard", "category": "Electronics"}')
+ORDER BY key;
+
+SELECT key FROM pdb.percolate('saved_searches_idx',
+    '{"description": "Sleek running shoes", "category": "Footwear"}')
+ORDER BY key;
+
+-- the category is indexed with the literal tokenizer, so its case matters
+SELECT key FROM pdb.percolate('saved_searches_idx',
+    '{"description": "Ergonomic metal keyboard", "category": "electronics"}')
+ORDER BY key;
+
+-- keys that aren't columns of the table are ignored
+SELECT key FROM pdb.percolate('saved_searches_idx', '{"title": "running shoes"}')
+ORDER BY key;
+
+-- percolation only runs the stored queries that have one of the document's
+-- terms, and a GIN index over their terms finds those
+SELECT id, pdb.percolate_terms('saved_searches_idx', query) = '{*}' AS any_document
+FROM saved_searches
+WHERE query IS NOT NULL
+ORDER BY id;
+
+CREATE INDEX saved_searches_terms_idx ON saved_searches
+    USING gin (pdb.percolate_terms('saved_searches_idx'::regclass, query));
+
+SELECT key FROM pdb.percolate('saved_searches_idx',
+    '{"description": "Ergonomic metal keyboard", "category": "Electronics"}')
+ORDER BY key;
+
+CREATE TABLE no_queries (id int PRIMARY KEY, description text);
+CREATE INDEX no_queries_idx ON no_queries USING paradedb (id, description)
+    WITH (key_field = id);
+SELECT key FROM pdb.percolate('no_queries_idx', '{"description": "shoes"}');
+
+DROP TABLE no_queries;
+DROP TABLE saved_searches;
@@ -0,0 +1,24 @@
+\i common/common_setup.sql
+
+-- Tokens are replaced by their phonetic codes
+SELECT 'Robert Rupert'::pdb.simple('phonetic=soundex')::text[];
+
+-- Double Metaphone emits a primary and an alternate code
+SELECT 'Smith Schmidt'::pdb.simple('phonetic=double_metaphone')::text[];
+
+-- The original tokens can be kept alongside their codes
+SELECT 'Robert 42'::pdb.simple('phonetic=soundex', 'phonetic_replace=false')::text[];
+
+-- Unknown algorithms are rejected
+SELECT 'Robert'::pdb.simple('phonetic=caverphone')::text[];
+
+-- Names that sound alike match each other
+CREATE TABLE phonetic_people (id serial8 NOT NULL PRIMARY KEY, name text);
+INSERT INTO phonetic_people (name) VALUES ('John Smith'), ('Anna Schmidt'), ('Mary Jones');
+CREATE INDEX phonetic_people_idx ON phonetic_people
+USING paradedb (id, (name::pdb.simple('phonetic=double_metaphone')))
+WITH (key_field = 'id');
+SELECT id, name FROM phonetic_people WHERE name ||| 'Smyth' ORDER BY id;
+SELECT id, name FROM phonetic_people WHERE name ||| 'Jonez' ORDER BY id;
+
+DROP TABLE phonetic_people;
@@ -0,0 +1,65 @@
+-- pdb.add_field and pdb.drop_field change the fields of an index without a REINDEX.
+CREATE EXTENSION IF NOT EXISTS pg_search;
+
+CREATE TABLE evolving (
+    id          int PRIMARY KEY,
+    description text,
+    rating      int
+);
+
+CREATE INDEX evolving_idx ON evolving
+    USING paradedb (id, description, rating)
+    WITH (key_field = id);
+
+-- index the description column a second time, with an ngram tokenizer
+SELECT pdb.add_field('evolving_idx', 'description_ngram',
+    '{"column": "description", "tokenizer": {"type": "ngram", "min_gram": 3, "max_gram": 3, "prefix_only": false}}');
+
+INSERT INTO evolving (id, description, rating) VALUES
+    (1, 'Ergonomic metal keyboard', 4),
+    (2, 'Plastic keyboard', 3),
+    (3, 'Sleek running shoes', 5);
+
+SELECT id FROM evolving WHERE id @@@ pdb.match('description_ngram', 'boa') ORDER BY id;
+SELECT id FROM evolving WHERE id @@@ pdb.match('description', 'boa') ORDER BY id;
+
+SELECT * FROM pdb.schema_changes('evolving_idx');
+
+-- invalid fields
+SELECT pdb.add_field('evolving_idx', 'description_ngram', '{"column": "description"}');
+SELECT pdb.add_field('evolving_idx', 'ctid', '{"column": "description"}');
+SELECT pdb.add_field('evolving_idx', 'rating_text', '{"column": "rating"}');
+SELECT pdb.add_field('evolving_idx', 'description_raw', '{"tokenizer": {"type": "keyword"}}');
+SELECT pdb.add_field('evolving_idx', 'description_raw',
+    '{"column": "description", "tokenizer": {"type": "keyword"}}');
+
+-- not inside a transaction block
+BEGIN;
+SELECT pdb.add_field('evolving_idx', 'description_simple', '{"column": "description"}');
+ROLLBACK;
+
+-- an error later in the same statement rolls the whole change back
+SELECT pdb.add_field('evolving_idx', 'description_simple', '{"column": "description"}'),
+    1 / (SELECT count(*)::int FROM evolving WHERE id < 0);
+SELECT * FROM pdb.schema_changes('evolving_idx');
+SELECT pdb.drop_field('evolving_idx', 'description_simple');
+
+-- VACUUM resolves the aborted change for good, and the index keeps the fields from before it
+VACUUM evolving;
+SELECT * FROM pdb.schema_changes('evolving_idx');
+SELECT id FROM evolving WHERE id @@@ pdb.match('description_ngram', 'boa') ORDER BY id;
+
+-- only fields with a `column` key can be dropped
+SELECT pdb.drop_field('evolving_idx', 'description');
+
+SELECT pdb.drop_field('evolving_idx', 'description_ngram');
+SELECT pdb.drop_field('evolving_idx', 'description_ngram');
+SELECT pdb.add_field('evolving_idx', 'description_ngram', '{"column": "description"}');
+
+SELECT * FROM pdb.schema_changes('evolving_idx');
+
+-- the rest of the index is unaffected
+INSERT INTO evolving (id, description, rating) VALUES (4, 'Wireless keyboard', 2);
+SELECT id FROM evolving WHERE id @@@ pdb.match('description', 'keyboard') ORDER BY id;
+
+DROP TABLE evolving;
@@ -0,0 +1,26 @@
+\i common/common_setup.sql
+
+-- Shingles are emitted alongside the original tokens by default
+SELECT 'quick brown fox'::pdb.simple('min_shingle_size=2')::text[];
+
+-- Longer shingles, without the original tokens
+SELECT 'quick brown fox'::pdb.simple('max_shingle_size=3', 'output_unigrams=false')::text[];
+
+-- Removed stopwords are replaced by the filler token
+SELECT 'the quick fox'::pdb.simple('stopwords_language=english', 'output_unigrams=false')::text[];
+SELECT 'the quick fox'::pdb.simple('stopwords_language=english', 'output_unigrams=false', 'filler_token=*')::text[];
+
+-- Invalid sizes are rejected
+SELECT 'quick brown fox'::pdb.simple('min_shingle_size=1')::text[];
+SELECT 'quick brown fox'::pdb.simple('min_shingle_size=3', 'max_shingle_size=2')::text[];
+
+-- Shingles can be matched as single terms
+CREATE TABLE shingle_docs (id serial8 NOT NULL PRIMARY KEY, body text);
+INSERT INTO shingle_docs (body) VALUES ('the quick brown fox'), ('a brown quick fox'), ('brown bears');
+CREATE INDEX shingle_docs_idx ON shingle_docs
+USING paradedb (id, (body::pdb.simple('min_shingle_size=2')))
+WITH (key_field = 'id');
+SELECT id, body FROM shingle_docs WHERE body === 'quick brown' ORDER BY id;
+SELECT id, body FROM shingle_docs WHERE body === 'brown' ORDER BY id;
+
+DROP TABLE shingle_docs;
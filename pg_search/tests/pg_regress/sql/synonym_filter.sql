@@ -0,0 +1,52 @@
+\i common/common_setup.sql
+
+-- Equivalence rules keep the original token and add its synonyms at the same position
+SELECT 'Cheap TV deals'::pdb.simple('synonyms=tv,television')::text[];
+
+-- One-way rules replace the original token
+SELECT 'a telly show'::pdb.simple('synonyms=telly => television')::text[];
+
+-- Rules are separated by semicolons, and terms may span several words
+SELECT 'the united states of america'::pdb.simple('synonyms=united states => usa; america, us')::text[];
+
+-- Synonyms can be combined with other filters
+SELECT 'Running Shoes'::pdb.simple('synonyms=shoes, sneakers', 'stemmer=english')::text[];
+
+-- Invalid rules are rejected
+SELECT 'tv'::pdb.simple('synonyms=tv')::text[];
+
+-- Rules can be loaded from a table
+CREATE TABLE synonym_rules (term text, synonyms text[], equivalent boolean);
+INSERT INTO synonym_rules VALUES
+    ('tv', ARRAY['television'], true),
+    ('couch', ARRAY['sofa'], false);
+SELECT 'tv couch'::pdb.simple('synonyms_table=synonym_rules')::text[];
+
+-- Synonyms applied only at search time
+CREATE TABLE synonym_products (id serial8 NOT NULL PRIMARY KEY, title text);
+INSERT INTO synonym_products (title) VALUES ('Television stand'), ('TV remote'), ('Garden hose');
+CREATE INDEX synonym_products_idx ON synonym_products
+USING paradedb (id, title)
+WITH (key_field = 'id', search_tokenizer = 'unicode_words(synonyms=tv,television)');
+SELECT id, title FROM synonym_products WHERE title ||| 'tv' ORDER BY id;
+DROP INDEX synonym_products_idx;
+
+-- Synonyms applied at index time
+CREATE INDEX synonym_products_index_time_idx ON synonym_products
+USING paradedb (id, (title::pdb.simple('synonyms_table=synonym_rules')))
+WITH (key_field = 'id');
+SELECT id, title FROM synonym_products WHERE title &&& 'television' ORDER BY id;
+
+-- The index keeps the rules it was built with, so edits to the table only apply after a REINDEX
+INSERT INTO synonym_rules VALUES ('hose', ARRAY['pipe'], true);
+INSERT INTO synonym_products (title) VALUES ('Water pipe');
+SELECT id, title FROM synonym_products WHERE title &&& 'hose' ORDER BY id;
+REINDEX INDEX synonym_products_index_time_idx;
+SELECT id, title FROM synonym_products WHERE title &&& 'hose' ORDER BY id;
+
+-- ALTER INDEX can't switch to a tokenizer that reads a table, which is only read when an index is built
+ALTER INDEX synonym_products_index_time_idx SET (search_tokenizer = 'simple(synonyms_table=synonym_rules)');
+SELECT id, title FROM synonym_products WHERE title &&& 'hose' ORDER BY id;
+
+DROP TABLE synonym_products;
+DROP TABLE synonym_rules;
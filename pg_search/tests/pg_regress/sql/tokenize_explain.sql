@@ -0,0 +1,13 @@
+\i common/common_setup.sql
+
+-- Every stage of the analyzer, in the order it's applied
+SELECT * FROM paradedb.tokenize_explain('{"type": "whitespace", "word_delimiter": {"preserve_original": true}}', 'PowerShot SD500');
+
+-- Filters that aren't enabled aren't stages
+SELECT DISTINCT stage FROM paradedb.tokenize_explain('{"type": "default", "lowercase": false}', 'Hello World');
+
+-- Named analyzers
+SELECT pdb.create_analyzer('explain_english', 'simple', '{"stemmer": "english", "stopwords": "the"}');
+SELECT * FROM pdb.tokenize_explain('explain_english', 'The Runners ran');
+SELECT * FROM pdb.tokenize_explain('nope', 'The Runners ran');
+SELECT pdb.drop_analyzer('explain_english');
@@ -0,0 +1,86 @@
+-- pdb.vector_distance projects the distance a vector ORDER BY ... LIMIT scan
+-- ordered each row by, so it can be selected, filtered on and fed to window
+-- functions without recomputing it from the heap vector.
+CREATE EXTENSION IF NOT EXISTS vector;
+CREATE EXTENSION IF NOT EXISTS pg_search;
+
+CREATE TABLE vd (
+    id    int PRIMARY KEY,
+    label text,
+    vec   vector(3)
+);
+
+INSERT INTO vd VALUES
+    (1, 'east wind',  '[1,    0,   0]'),
+    (2, 'east gate',  '[0.9,  0,   0.1]'),
+    (3, 'north wind', '[0,    1,   0]'),
+    (4, 'up draft',   '[0,    0,   1]'),
+    (5, 'mid point',  '[0.7,  0.7, 0]');
+
+CREATE INDEX vd_idx ON vd
+    USING paradedb (id, label, vec vector_l2_ops)
+    WITH (key_field = id);
+
+-- the projected distance matches the operator's
+SELECT id,
+       round(pdb.vector_distance(vd)::numeric, 4) AS distance,
+       round((vec <-> '[1,0,0]')::numeric, 4) AS exact
+FROM vd WHERE id @@@ pdb.all()
+ORDER BY vec <-> '[1,0,0]' LIMIT 3;
+
+-- post-filter on the distance
+SELECT id FROM (
+    SELECT id, pdb.vector_distance(vd) AS distance
+    FROM vd WHERE id @@@ pdb.all()
+    ORDER BY vec <-> '[1,0,0]' LIMIT 3
+) nearest
+WHERE distance < 0.5
+ORDER BY id;
+
+-- window functions over the distance
+SELECT id,
+       round((distance - min(distance) OVER ())::numeric, 4) AS gap,
+       round((avg(distance) OVER ())::numeric, 4) AS avg_distance
+FROM (
+    SELECT id, pdb.vector_distance(vd) AS distance
+    FROM vd WHERE id @@@ pdb.all()
+    ORDER BY vec <-> '[1,0,0]' LIMIT 3
+) nearest
+ORDER BY distance;
+
+DROP INDEX vd_idx;
+CREATE INDEX vd_idx ON vd
+    USING paradedb (id, label, vec vector_cosine_ops)
+    WITH (key_field = id);
+
+SELECT id,
+       round(pdb.vector_distance(vd)::numeric, 4) AS distance,
+       round((vec <=> '[1,0,0]')::numeric, 4) AS exact
+FROM vd WHERE id @@@ pdb.all()
+ORDER BY vec <=> '[1,0,0]' LIMIT 3;
+
+-- filters on the distance compute it from the heap vector
+SELECT id, round(pdb.vector_distance(vd)::numeric, 4) AS distance
+FROM vd WHERE id @@@ pdb.all() AND pdb.vector_distance(vd) < 0.1
+ORDER BY vec <=> '[1,0,0]' LIMIT 3;
+
+-- and so does a query without a LIMIT
+SELECT id, round(pdb.vector_distance(vd)::numeric, 4) AS distance
+FROM vd WHERE label @@@ 'wind'
+ORDER BY vec <=> '[1,0,0]';
+
+-- without a vector ORDER BY there is no distance to project
+SELECT id, pdb.vector_distance(vd) FROM vd WHERE label @@@ 'wind' ORDER BY id;
+
+DROP INDEX vd_idx;
+CREATE INDEX vd_idx ON vd
+    USING paradedb (id, label, vec vector_ip_ops)
+    WITH (key_field = id);
+
+SELECT id,
+       round(pdb.vector_distance(vd)::numeric, 4) AS distance,
+       round((vec <#> '[1,0,0]')::numeric, 4) AS exact
+FROM vd WHERE id @@@ pdb.all()
+ORDER BY vec <#> '[1,0,0]' LIMIT 3;
+
+DROP TABLE vd;
@@ -0,0 +1,62 @@
+-- A vector[] column is indexed as a multi-vector field, and pdb.max_sim scores each
+-- row by the sum, over the query vectors, of each one's greatest inner product with
+-- any of the row's vectors.
+--
+-- For the query vectors [1,0] and [0,1]: 1 -> 1 + 1, 2 -> 0.5 + 0.5, 3 -> 2 + 0.
+-- Rows 4 and 5 have no vectors.
+CREATE EXTENSION IF NOT EXISTS vector;
+CREATE EXTENSION IF NOT EXISTS pg_search;
+
+CREATE TABLE max_sim_items (
+    id     int PRIMARY KEY,
+    body   text,
+    tokens vector(2)[]
+);
+
+INSERT INTO max_sim_items VALUES
+    (1, 'running shoes',      ARRAY['[1, 0]', '[0, 1]']::vector(2)[]),
+    (2, 'blue running shoes', ARRAY['[0.5, 0.5]']::vector(2)[]),
+    (3, 'green hat',          ARRAY['[2, 0]']::vector(2)[]),
+    (4, 'black hat',          NULL),
+    (5, 'trail shoes',        '{}');
+
+CREATE INDEX max_sim_items_idx ON max_sim_items
+    USING paradedb (id, body, tokens)
+    WITH (key_field = id);
+
+SELECT id, round(pdb.score(id)::numeric, 4) AS score
+FROM max_sim_items
+WHERE id @@@ pdb.max_sim('tokens', ARRAY['[1, 0]', '[0, 1]']::vector[])
+ORDER BY score DESC, id;
+
+SELECT id
+FROM max_sim_items
+WHERE id @@@ pdb.max_sim('tokens', ARRAY['[0, 1]']::vector[])
+ORDER BY pdb.score(id) DESC
+LIMIT 2;
+
+-- combined with a text query
+SELECT id
+FROM max_sim_items
+WHERE id @@@ paradedb.boolean(
+    must => ARRAY[pdb.max_sim('tokens', ARRAY['[1, 0]']::vector[]), paradedb.term('body', 'shoes')]
+)
+ORDER BY id;
+
+-- rows inserted after the index is built are packed the same way
+INSERT INTO max_sim_items VALUES (6, 'red hat', ARRAY['[0, 3]', '[1, 1]']::vector(2)[]);
+SELECT id, round(pdb.score(id)::numeric, 4) AS score
+FROM max_sim_items
+WHERE id @@@ pdb.max_sim('tokens', ARRAY['[1, 0]', '[0, 1]']::vector[])
+ORDER BY score DESC, id;
+
+SELECT id FROM max_sim_items WHERE id @@@ pdb.max_sim('tokens', ARRAY['[1, 0, 0]']::vector[]);
+SELECT id FROM max_sim_items WHERE id @@@ pdb.max_sim('body', ARRAY['[1, 0]']::vector[]);
+SELECT id FROM max_sim_items WHERE id @@@ pdb.max_sim('tokens', '[1, 0]'::vector);
+
+-- the vectors of a multi-vector column must declare their dimensions
+CREATE TABLE max_sim_untyped (id int PRIMARY KEY, tokens vector[]);
+CREATE INDEX max_sim_untyped_idx ON max_sim_untyped USING paradedb (id, tokens) WITH (key_field = id);
+
+DROP TABLE max_sim_untyped;
+DROP TABLE max_sim_items;
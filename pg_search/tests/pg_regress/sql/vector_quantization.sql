@@ -0,0 +1,69 @@
+-- An index built WITH (vector_quantization = 'int8' | 'binary') stores its vectors
+-- as quantized codes.  A vector ORDER BY ranks the codes, then re-ranks
+-- paradedb.vector_rerank_oversample times its LIMIT plus OFFSET candidates by the
+-- full-precision vectors of the table, so the distances it returns are exact.
+CREATE EXTENSION IF NOT EXISTS vector;
+CREATE EXTENSION IF NOT EXISTS pg_search;
+
+CREATE TABLE qv (
+    id    int PRIMARY KEY,
+    label text,
+    vec   vector(3)
+);
+
+INSERT INTO qv VALUES
+    (1, 'east wind',  '[1,    0,   0]'),
+    (2, 'east gate',  '[0.9,  0,   0.1]'),
+    (3, 'north wind', '[0,    1,   0]'),
+    (4, 'up draft',   '[0,    0,   1]'),
+    (5, 'mid point',  '[0.7,  0.7, 0]'),
+    (6, 'no vector',  NULL);
+
+CREATE INDEX qv_idx ON qv
+    USING paradedb (id, label, vec vector_l2_ops)
+    WITH (key_field = id, vector_quantization = 'int8');
+
+SELECT id,
+       round(pdb.vector_distance(qv)::numeric, 4) AS distance,
+       round((vec <-> '[1,0,0]')::numeric, 4) AS exact
+FROM qv WHERE id @@@ pdb.all()
+ORDER BY vec <-> '[1,0,0]' LIMIT 3;
+
+-- with a text query
+SELECT id FROM qv WHERE label @@@ 'wind'
+ORDER BY vec <-> '[1,0,0]' LIMIT 2;
+
+-- only (LIMIT + OFFSET) candidates are re-ranked
+SET paradedb.vector_rerank_oversample = 1;
+SELECT id FROM qv WHERE id @@@ pdb.all()
+ORDER BY vec <-> '[1,0,0]' LIMIT 2 OFFSET 1;
+RESET paradedb.vector_rerank_oversample;
+
+-- rows inserted after the build are quantized too
+INSERT INTO qv VALUES (7, 'west wind', '[-1, 0, 0]');
+SELECT id, round(pdb.vector_distance(qv)::numeric, 4) AS distance
+FROM qv WHERE id @@@ pdb.all()
+ORDER BY vec <-> '[-1,0,0]' LIMIT 1;
+
+-- quantized fields aren't clustered, so they can't be searched any other way
+SELECT id FROM qv WHERE id @@@ pdb.vector_within('vec', '[1,0,0]'::vector, 1.0);
+SELECT id FROM qv WHERE id @@@ pdb.rrf(pdb.match('label', 'wind'), 'vec', '[1,0,0]'::vector);
+SELECT * FROM pdb.vector_recall('qv_idx', 'vec', ARRAY['[1,0,0]']::vector[], k => 1);
+
+DROP INDEX qv_idx;
+CREATE INDEX qv_idx ON qv
+    USING paradedb (id, label, vec vector_cosine_ops)
+    WITH (key_field = id, vector_quantization = 'binary');
+
+SELECT id,
+       round(pdb.vector_distance(qv)::numeric, 4) AS distance,
+       round((vec <=> '[1,0,0]')::numeric, 4) AS exact
+FROM qv WHERE id @@@ pdb.all()
+ORDER BY vec <=> '[1,0,0]' LIMIT 3;
+
+DROP INDEX qv_idx;
+CREATE INDEX qv_idx ON qv
+    USING paradedb (id, label, vec vector_l2_ops)
+    WITH (key_field = id, vector_quantization = 'int4');
+
+DROP TABLE qv;
@@ -0,0 +1,37 @@
+-- pdb.rebalance_vectors merges each clustered segment on its own, retraining
+-- its centroids, without changing what the index holds.
+--
+-- client_min_messages: the IVF merge emits a paradedb::ivf_build timings
+-- NOTICE with nondeterministic millisecond values.
+SET client_min_messages = WARNING;
+CREATE EXTENSION IF NOT EXISTS vector;
+CREATE EXTENSION IF NOT EXISTS pg_search;
+
+CREATE TABLE rebalance_items (
+    id  int PRIMARY KEY,
+    vec vector(3)
+);
+
+INSERT INTO rebalance_items
+SELECT g, ('[' || g % 7 || ',' || g % 11 || ',' || g % 13 || ']')::vector
+FROM generate_series(1, 200) g;
+
+SET paradedb.vector_clustering_threshold = 50;
+CREATE INDEX rebalance_items_idx ON rebalance_items
+    USING paradedb (id, vec vector_l2_ops)
+    WITH (key_field = id);
+RESET paradedb.vector_clustering_threshold;
+
+SELECT pdb.rebalance_vectors('rebalance_items_idx');
+
+SELECT sum(vector_num_vectors) AS num_vectors
+FROM paradedb.vector_info('rebalance_items_idx', 'vec');
+
+SET paradedb.vector_exact_search = true;
+SELECT id FROM rebalance_items
+WHERE id @@@ pdb.all()
+ORDER BY vec <-> '[1,2,3]', id
+LIMIT 4;
+RESET paradedb.vector_exact_search;
+
+DROP TABLE rebalance_items;
@@ -0,0 +1,85 @@
+-- pdb.vector_recall compares each segment's vector search against a
+-- brute-force search that scores every live vector, and
+-- paradedb.vector_exact_search makes vector ORDER BY queries score them all too.
+CREATE EXTENSION IF NOT EXISTS vector;
+CREATE EXTENSION IF NOT EXISTS pg_search;
+
+CREATE TABLE recall_items (
+    id  int PRIMARY KEY,
+    vec vector(3)
+);
+
+INSERT INTO recall_items
+SELECT g, ('[' || g % 7 || ',' || g % 11 || ',' || g % 13 || ']')::vector
+FROM generate_series(1, 200) g;
+
+CREATE INDEX recall_items_idx ON recall_items
+    USING paradedb (id, vec vector_l2_ops)
+    WITH (key_field = id);
+
+-- one row per segment that stores vectors, each measured with every query
+SELECT count(*) > 0 AS has_segments,
+       bool_and(num_queries = 3) AS all_queries,
+       bool_and(recall BETWEEN 0 AND 1) AS recall_in_range
+FROM pdb.vector_recall(
+    'recall_items_idx', 'vec',
+    ARRAY['[1,2,3]', '[0,0,0]', '[6,10,12]']::vector[],
+    k => 5
+);
+
+SET paradedb.vector_exact_search = true;
+
+-- both searches now score every vector
+SELECT bool_and(recall = 1) AS exact
+FROM pdb.vector_recall(
+    'recall_items_idx', 'vec',
+    ARRAY['[1,2,3]', '[0,0,0]', '[6,10,12]']::vector[],
+    k => 5
+);
+
+-- 2 and 133 are both at distance sqrt(2)
+SELECT id FROM recall_items
+WHERE id @@@ pdb.all()
+ORDER BY vec <-> '[1,2,3]', id
+LIMIT 4;
+
+-- exact search returns what a brute-force sort of every row does, also when
+-- the index's segment is clustered
+SET client_min_messages = WARNING;
+SET paradedb.vector_clustering_threshold = 50;
+DROP INDEX recall_items_idx;
+CREATE INDEX recall_items_idx ON recall_items
+    USING paradedb (id, vec vector_l2_ops)
+    WITH (key_field = id);
+RESET paradedb.vector_clustering_threshold;
+RESET client_min_messages;
+
+PREPARE matches_brute_force(vector) AS
+SELECT (SELECT array_agg(id) FROM (
+            SELECT id FROM recall_items
+            WHERE id @@@ pdb.all()
+            ORDER BY vec <-> $1, id
+            LIMIT 10) exact)
+     = (SELECT array_agg(id) FROM (
+            SELECT id FROM (SELECT id, vec FROM recall_items OFFSET 0) all_rows
+            ORDER BY vec <-> $1, id
+            LIMIT 10) brute_force) AS matches_brute_force;
+
+EXECUTE matches_brute_force('[1,2,3]');
+EXECUTE matches_brute_force('[0,0,0]');
+EXECUTE matches_brute_force('[6,10,12]');
+EXECUTE matches_brute_force('[3.5,5.5,6.5]');
+
+-- and only the rows that are still live
+DELETE FROM recall_items WHERE id % 3 = 0;
+EXECUTE matches_brute_force('[1,2,3]');
+EXECUTE matches_brute_force('[6,10,12]');
+DEALLOCATE matches_brute_force;
+
+RESET paradedb.vector_exact_search;
+
+SELECT * FROM pdb.vector_recall('recall_items_idx', 'vec', ARRAY['[1,2,3]']::vector[], k => 0);
+SELECT * FROM pdb.vector_recall('recall_items_idx', 'vec', ARRAY[1, 2]);
+SELECT * FROM pdb.vector_recall('recall_items_idx', 'vec', ARRAY['[1,2]']::vector[]);
+
+DROP TABLE recall_items;
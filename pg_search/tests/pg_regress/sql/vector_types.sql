@@ -0,0 +1,73 @@
+-- halfvec, sparsevec and bit columns are indexed as dense vectors, with the
+-- opclasses halfvec_*_ops, sparsevec_*_ops, bit_hamming_ops and bit_jaccard_ops.
+-- The opclass's operator pushes down through TopK like it does for `vector`.
+CREATE EXTENSION IF NOT EXISTS vector;
+CREATE EXTENSION IF NOT EXISTS pg_search;
+
+CREATE TABLE vec_types (
+    id int PRIMARY KEY,
+    hv halfvec(3),
+    sv sparsevec(5),
+    bv bit(4)
+);
+
+INSERT INTO vec_types VALUES
+    (1, '[1,   0,   0]', '{1:1}/5',     '1100'),
+    (2, '[0.5, 0.5, 0]', '{1:1,5:1}/5', '1110'),
+    (3, '[0,   1,   0]', '{3:1}/5',     '0011'),
+    (4, '[0,   0,   1]', '{2:1,4:1}/5', '0001');
+
+-- the default opclasses are halfvec_l2_ops, sparsevec_l2_ops and bit_hamming_ops
+CREATE INDEX vec_types_idx ON vec_types
+    USING paradedb (id, hv, sv, bv)
+    WITH (key_field = id);
+
+EXPLAIN (FORMAT TEXT, COSTS OFF, TIMING OFF)
+SELECT id FROM vec_types WHERE id @@@ pdb.all() ORDER BY hv <-> '[1,0,0]' LIMIT 2;
+SELECT id FROM vec_types WHERE id @@@ pdb.all() ORDER BY hv <-> '[1,0,0]' LIMIT 2;
+
+EXPLAIN (FORMAT TEXT, COSTS OFF, TIMING OFF)
+SELECT id FROM vec_types WHERE id @@@ pdb.all() ORDER BY sv <-> '{1:1}/5' LIMIT 2;
+SELECT id FROM vec_types WHERE id @@@ pdb.all() ORDER BY sv <-> '{1:1}/5' LIMIT 2;
+
+EXPLAIN (FORMAT TEXT, COSTS OFF, TIMING OFF)
+SELECT id FROM vec_types WHERE id @@@ pdb.all() ORDER BY bv <~> '1100' LIMIT 2;
+SELECT id FROM vec_types WHERE id @@@ pdb.all() ORDER BY bv <~> '1100' LIMIT 2;
+
+-- mismatch: <=> on a halfvec_l2_ops column falls back, planner warns
+SELECT id FROM vec_types WHERE id @@@ pdb.all() ORDER BY hv <=> '[1,0,0]' LIMIT 2;
+
+DROP INDEX vec_types_idx;
+
+-- bit_jaccard_ops: every bit string is scored by its Jaccard distance to the query
+CREATE INDEX vec_types_idx ON vec_types
+    USING paradedb (id, bv bit_jaccard_ops)
+    WITH (key_field = id);
+
+EXPLAIN (FORMAT TEXT, COSTS OFF, TIMING OFF)
+SELECT id FROM vec_types WHERE id @@@ pdb.all() ORDER BY bv <%> '1100' LIMIT 2;
+SELECT id FROM vec_types WHERE id @@@ pdb.all() ORDER BY bv <%> '1100' LIMIT 2;
+-- 3 and 4 share no bits with the query, so they tie at distance 1
+SELECT id FROM vec_types WHERE id @@@ pdb.all() ORDER BY bv <%> '1100', id LIMIT 3;
+SELECT id FROM vec_types WHERE id @@@ pdb.all() ORDER BY bv <%> '1100', id LIMIT 2 OFFSET 2;
+
+DROP TABLE vec_types;
+
+-- a sparsevec as wide as SPLADE's vocabulary
+CREATE TABLE splade_docs (
+    id int PRIMARY KEY,
+    terms sparsevec(30522)
+);
+
+INSERT INTO splade_docs VALUES
+    (1, '{1:0.5,30522:2}/30522'),
+    (2, '{1:1,2:1}/30522'),
+    (3, '{15000:3}/30522');
+
+CREATE INDEX splade_docs_idx ON splade_docs
+    USING paradedb (id, terms sparsevec_ip_ops)
+    WITH (key_field = id);
+
+SELECT id FROM splade_docs WHERE id @@@ pdb.all() ORDER BY terms <#> '{1:1,30522:1}/30522' LIMIT 2;
+
+DROP TABLE splade_docs;
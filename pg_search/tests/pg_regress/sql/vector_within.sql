@@ -0,0 +1,57 @@
+-- pdb.vector_within matches the rows whose vector is within a distance of a query
+-- vector, by the distance of the field's operator class, and scores them with
+-- 1 / (1 + distance).
+--
+-- L2 distances from [1,0,0]: 1 -> 0, 3 -> 0.14, 5 -> 0.71, 2 -> 1.41, 4 -> 2.24
+CREATE EXTENSION IF NOT EXISTS vector;
+CREATE EXTENSION IF NOT EXISTS pg_search;
+SET paradedb.vector_exact_search = on;
+
+CREATE TABLE within_items (
+    id   int PRIMARY KEY,
+    body text,
+    vec  vector(3)
+);
+
+INSERT INTO within_items VALUES
+    (1, 'running shoes',             '[1,   0,   0]'),
+    (2, 'blue running shoes',        '[0,   1,   0]'),
+    (3, 'green hat',                 '[0.9, 0.1, 0]'),
+    (4, 'bright yellow trail shoes', '[0,   0,   2]'),
+    (5, 'black hat',                 '[0.5, 0.5, 0]');
+
+CREATE INDEX within_items_idx ON within_items
+    USING paradedb (id, body, vec vector_l2_ops)
+    WITH (key_field = id);
+
+SELECT id, round(pdb.score(id)::numeric, 4) AS score
+FROM within_items
+WHERE id @@@ pdb.vector_within('vec', '[1,0,0]'::vector, 1.0)
+ORDER BY id;
+
+-- combined with a text query
+SELECT id
+FROM within_items
+WHERE id @@@ paradedb.boolean(
+    must => ARRAY[pdb.vector_within('vec', '[1,0,0]'::vector, 1.5), paradedb.term('body', 'shoes')]
+)
+ORDER BY id;
+
+SELECT id
+FROM within_items
+WHERE id @@@ pdb.vector_within('vec', '[1,0,0]'::vector, 1.5) AND body @@@ 'shoes'
+ORDER BY id;
+
+-- and aggregated
+SELECT pdb.agg('{"avg": {"field": "id"}}'::jsonb)
+FROM within_items
+WHERE id @@@ pdb.vector_within('vec', '[1,0,0]'::vector, 1.0);
+
+-- nothing is within range
+SELECT id FROM within_items WHERE id @@@ pdb.vector_within('vec', '[10,10,10]'::vector, 1.0);
+
+SELECT id FROM within_items WHERE id @@@ pdb.vector_within('vec', '[1,0]'::vector, 1.0);
+SELECT id FROM within_items WHERE id @@@ pdb.vector_within('body', '[1,0,0]'::vector, 1.0);
+
+RESET paradedb.vector_exact_search;
+DROP TABLE within_items;
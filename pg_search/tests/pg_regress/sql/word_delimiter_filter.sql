@@ -0,0 +1,26 @@
+\i common/common_setup.sql
+
+-- Tokens are split on punctuation, case changes and letter/digit transitions
+SELECT 'WiFi-6E PowerShot SD500'::pdb.whitespace('word_delimiter=true')::text[];
+
+-- The original token and catenated forms can be kept alongside the parts
+SELECT 'WiFi-6E'::pdb.whitespace('preserve_original=true', 'catenate_all=true')::text[];
+SELECT 'wi-fi-555-1234'::pdb.whitespace('catenate_words=true', 'catenate_numbers=true')::text[];
+
+-- Splitting on case changes and digits can be turned off
+SELECT 'PowerShot SD500 wi-fi'::pdb.whitespace('split_on_case_change=false', 'split_on_numerics=false')::text[];
+
+-- Parts take positions of their own, so phrase queries still match
+CREATE TABLE word_delimiter_docs (id serial8 NOT NULL PRIMARY KEY, body text);
+INSERT INTO word_delimiter_docs (body) VALUES
+    ('Canon PowerShot SD500 camera'),
+    ('Canon Power Shot SD 500'),
+    ('Shot by a PowerBook');
+CREATE INDEX word_delimiter_docs_idx ON word_delimiter_docs
+USING paradedb (id, (body::pdb.whitespace('word_delimiter=true')))
+WITH (key_field = 'id');
+SELECT id, body FROM word_delimiter_docs WHERE body ### 'PowerShot SD500' ORDER BY id;
+SELECT id, body FROM word_delimiter_docs WHERE body ### 'shot sd' ORDER BY id;
+SELECT id, body FROM word_delimiter_docs WHERE body ### 'sd500 camera' ORDER BY id;
+
+DROP TABLE word_delimiter_docs;
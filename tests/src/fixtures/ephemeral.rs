@@ -0,0 +1,195 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Postgres instances that a test initializes, configures, and starts on its own, for settings
+//! that can't be changed on the shared test database.
+
+use anyhow::Result;
+use cmd_lib::{run_cmd, run_fun};
+use dotenvy::dotenv;
+use sqlx::{Connection, PgConnection};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tempfile::TempDir;
+
+// Static variables for initializing port assignment and ensuring one-time setup
+static INIT: Once = Once::new();
+static LAST_PORT: AtomicUsize = AtomicUsize::new(49152);
+
+// Function to check if a port can be bound (i.e., is available)
+fn can_bind(port: u16) -> bool {
+    std::net::TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
+
+// Function to get a free port in the dynamic port range
+pub fn get_free_port() -> u16 {
+    let port_upper_bound = 65535;
+    let port_lower_bound = 49152;
+
+    INIT.call_once(|| {
+        LAST_PORT.store(port_lower_bound, Ordering::SeqCst);
+    });
+
+    loop {
+        let port = LAST_PORT.fetch_add(1, Ordering::SeqCst);
+        if port > port_upper_bound {
+            LAST_PORT.store(port_lower_bound, Ordering::SeqCst);
+            continue;
+        }
+
+        if can_bind(port as u16) {
+            return port as u16;
+        }
+    }
+}
+
+// Superuser created by `initdb` for every ephemeral instance.
+//
+// This is pinned rather than left to `initdb`'s default (the OS user) because sqlx
+// resolves an omitted username via `whoami`, which reports `anonymous` when neither
+// `USER` nor `LOGNAME` is set — as is the case in our CI containers. Naming the role on
+// both sides keeps the tests independent of the ambient environment.
+pub const SUPERUSER: &str = "postgres";
+
+// Struct to manage an ephemeral PostgreSQL instance
+pub struct EphemeralPostgres {
+    pub tempdir_path: String,
+    pub host: String,
+    pub port: u16,
+    pub dbname: String,
+    pub pg_ctl_path: PathBuf,
+    pub log_path: PathBuf,
+}
+
+// Implement Drop trait to ensure the PostgreSQL instance is properly stopped
+impl Drop for EphemeralPostgres {
+    fn drop(&mut self) {
+        let path = &self.tempdir_path;
+        let pg_ctl_path = &self.pg_ctl_path;
+        run_cmd!($pg_ctl_path -D $path stop &> /dev/null)
+            .unwrap_or_else(|_| println!("postgres instance at {} already shut down", self.port));
+        std::fs::remove_dir_all(self.tempdir_path.clone()).unwrap();
+    }
+}
+
+// Implementation of EphemeralPostgres
+impl EphemeralPostgres {
+    pub fn pg_bin_path() -> PathBuf {
+        let pg_config_path = std::env::var("PG_CONFIG").expect(
+            "PG_CONFIG variable must be set to enable creating ephemeral Postgres instances",
+        );
+        if !PathBuf::from(&pg_config_path).exists() {
+            panic!(
+                "PG_CONFIG variable must be a valid path to enable creating ephemeral Postgres instances, received {pg_config_path}"
+            );
+        }
+        match run_fun!($pg_config_path --bindir) {
+            Ok(path) => PathBuf::from(path.trim().to_string()),
+            Err(err) => panic!("could run pg_config --bindir to get Postgres bin folder: {err}"),
+        }
+    }
+
+    pub fn pg_basebackup_path() -> PathBuf {
+        Self::pg_bin_path().join("pg_basebackup")
+    }
+
+    pub fn initdb_path() -> PathBuf {
+        Self::pg_bin_path().join("initdb")
+    }
+
+    pub fn pg_ctl_path() -> PathBuf {
+        Self::pg_bin_path().join("pg_ctl")
+    }
+
+    pub fn new_from_initialized(
+        tempdir_path: &Path,
+        postgresql_conf: Option<&str>,
+        pg_hba_conf: Option<&str>,
+    ) -> Self {
+        let tempdir_path = tempdir_path.to_str().unwrap().to_string();
+        let port = get_free_port();
+        let pg_ctl_path = Self::pg_ctl_path();
+
+        // Write to postgresql.conf
+        let config_content = match postgresql_conf {
+            Some(config) => format!("port = {}\n{}", port, config.trim()),
+            None => format!("port = {port}"),
+        };
+        let config_path = format!("{tempdir_path}/postgresql.conf");
+        std::fs::write(config_path, config_content).expect("Failed to write to postgresql.conf");
+
+        // Write to pg_hba.conf
+        if let Some(config_content) = pg_hba_conf {
+            let config_path = format!("{tempdir_path}/pg_hba.conf");
+
+            let mut file = std::fs::OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(config_path)
+                .expect("Failed to open pg_hba.conf");
+
+            writeln!(file, "{config_content}").expect("Failed to append to pg_hba.conf");
+        }
+
+        // Create log directory
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        let logfile = format!("/tmp/ephemeral_postgres_logs/{timestamp}.log");
+        std::fs::create_dir_all(Path::new(&logfile).parent().unwrap())
+            .expect("Failed to create log directory");
+
+        // Start PostgreSQL
+        run_cmd!($pg_ctl_path -D $tempdir_path -l $logfile start)
+            .expect("Failed to start Postgres");
+
+        Self {
+            // TempDir needs to be stored on the struct to avoid being dropped, otherwise the
+            // temp folder will be deleted before the test finishes.
+            tempdir_path,
+            host: "localhost".to_string(),
+            port,
+            dbname: "postgres".to_string(),
+            pg_ctl_path,
+            log_path: PathBuf::from(logfile),
+        }
+    }
+
+    pub fn new(postgresql_conf: Option<&str>, pg_hba_conf: Option<&str>) -> Self {
+        // Make sure .env files are loaded before reading env vars.
+        dotenv().ok();
+
+        let init_db_path = Self::initdb_path();
+        let tempdir = TempDir::new().expect("Failed to create temp dir");
+        let tempdir_path = tempdir.keep();
+
+        // Initialize PostgreSQL data directory
+        run_cmd!($init_db_path -D $tempdir_path --username $SUPERUSER &> /dev/null)
+            .expect("Failed to initialize Postgres data directory");
+
+        Self::new_from_initialized(tempdir_path.as_path(), postgresql_conf, pg_hba_conf)
+    }
+
+    // Method to establish a connection to the PostgreSQL instance
+    pub async fn connection(&self) -> Result<PgConnection> {
+        Ok(PgConnection::connect(&format!(
+            "postgresql://{}@{}:{}/{}",
+            SUPERUSER, self.host, self.port, self.dbname
+        ))
+        .await?)
+    }
+}
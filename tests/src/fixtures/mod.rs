@@ -19,6 +19,7 @@
 #![allow(unused_imports)]
 
 pub mod db;
+pub mod ephemeral;
 pub mod fault_grace;
 pub mod querygen;
 pub mod tables;
@@ -0,0 +1,138 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use anyhow::Result;
+use rstest::*;
+use std::time::{Duration, Instant};
+use tests::fixtures::db::Query;
+use tests::fixtures::ephemeral::EphemeralPostgres;
+
+// `paradedb.enable_merge_scheduler` can only be set at server start, so these tests run against
+// their own instance.  The quiet windows cover the whole day, so that merges stay queued until a
+// test lifts them.
+const CONFIG: &str = "
+    shared_preload_libraries = 'pg_search'
+    paradedb.enable_merge_scheduler = on
+    paradedb.max_concurrent_merges = 1
+    paradedb.merge_scheduler_naptime = '100ms'
+    paradedb.merge_quiet_windows = '00:00-12:00, 12:00-00:00'
+";
+
+const TIMEOUT: Duration = Duration::from_secs(120);
+
+type QueueRow = (String, String, String, String, Option<i32>);
+
+fn create_index(table: &str, conn: &mut sqlx::PgConnection) {
+    format!(
+        "CREATE TABLE {table} (id SERIAL PRIMARY KEY, description TEXT);
+         CREATE INDEX {table}_idx ON {table} USING paradedb (id, description)
+         WITH (
+             key_field = 'id',
+             layer_sizes = '0',
+             background_layer_sizes = '1kb',
+             mutable_segment_rows = 0,
+             target_segment_count = 1
+         );"
+    )
+    .execute(conn);
+
+    // every insert writes a segment of its own, and finds them worth merging in the background
+    for _ in 0..8 {
+        format!("INSERT INTO {table} (description) SELECT repeat('merge me ', 200)").execute(conn);
+    }
+}
+
+#[rstest]
+#[async_std::test]
+async fn test_merge_queue() -> Result<()> {
+    let postgres = EphemeralPostgres::new(Some(CONFIG), None);
+    let mut conn = postgres.connection().await?;
+    "CREATE EXTENSION pg_search CASCADE".execute(&mut conn);
+
+    create_index("scheduled_a", &mut conn);
+    create_index("scheduled_b", &mut conn);
+
+    // during a quiet window both merges are queued, once each however many inserts found them
+    let queue: Vec<QueueRow> = "
+        SELECT database_name, index_name, layer, state, pid
+        FROM pdb.merge_queue()
+        ORDER BY index_name"
+        .fetch(&mut conn);
+    assert_eq!(
+        queue,
+        vec![
+            (
+                "postgres".into(),
+                "public.scheduled_a_idx".into(),
+                "small".into(),
+                "queued".into(),
+                None
+            ),
+            (
+                "postgres".into(),
+                "public.scheduled_b_idx".into(),
+                "small".into(),
+                "queued".into(),
+                None
+            ),
+        ]
+    );
+    let (same_oid,): (bool,) = "
+        SELECT index_oid = 'scheduled_a_idx'::regclass::oid
+        FROM pdb.merge_queue()
+        WHERE index_name = 'public.scheduled_a_idx'"
+        .fetch_one(&mut conn);
+    assert!(same_oid);
+
+    // lift the quiet windows, and slow the merges down enough to see one wait for the other
+    "ALTER SYSTEM SET paradedb.merge_quiet_windows = ''".execute(&mut conn);
+    "ALTER SYSTEM SET paradedb.merge_io_rate_limit = '1kB'".execute(&mut conn);
+    "SELECT pg_reload_conf()".execute(&mut conn);
+
+    let started = Instant::now();
+    let mut saw_one_waiting = false;
+    loop {
+        let (running, queued): (i64, i64) = "
+            SELECT count(*) FILTER (WHERE state = 'running'),
+                   count(*) FILTER (WHERE state = 'queued')
+            FROM pdb.merge_queue()"
+            .fetch_one(&mut conn);
+        assert!(
+            running <= 1,
+            "paradedb.max_concurrent_merges is 1, but {running} merges are running"
+        );
+        saw_one_waiting |= running == 1 && queued == 1;
+        if running == 0 && queued == 0 {
+            break;
+        }
+        assert!(started.elapsed() < TIMEOUT, "the merge queue never drained");
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    assert!(
+        saw_one_waiting,
+        "never saw a merge wait for the other to finish"
+    );
+
+    // and both indexes were merged down
+    for table in ["scheduled_a", "scheduled_b"] {
+        let (segments,): (i64,) =
+            format!("SELECT count(*) FROM pdb.index_segments('{table}_idx')").fetch_one(&mut conn);
+        assert!(segments < 8, "{table}_idx still has {segments} segments");
+    }
+
+    Ok(())
+}
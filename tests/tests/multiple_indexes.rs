@@ -0,0 +1,137 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use pretty_assertions::assert_eq;
+use rstest::*;
+use serde_json::Value;
+use sqlx::PgConnection;
+use tests::fixtures::*;
+
+fn chosen_index(conn: &mut PgConnection, query: &str) -> String {
+    let (plan,) = format!("EXPLAIN (FORMAT JSON) {query}").fetch_one::<(Value,)>(conn);
+    let plan = plan.to_string();
+    ["index_description", "index_category"]
+        .into_iter()
+        .find(|index| plan.contains(&format!(r#""Index":"{index}""#)))
+        .unwrap_or_else(|| panic!("no ParadeDB index in plan: {plan}"))
+        .to_string()
+}
+
+#[rstest]
+fn queries_use_the_index_with_their_fields(mut conn: PgConnection) {
+    r#"
+    CALL paradedb.create_paradedb_test_table(
+      schema_name => 'public',
+      table_name => 'mock_items'
+    )
+    "#
+    .execute(&mut conn);
+
+    r#"
+    CREATE INDEX index_description ON public.mock_items
+    USING paradedb (id, description)
+    WITH (key_field = 'id');
+    "#
+    .execute(&mut conn);
+
+    r#"
+    CREATE INDEX index_category ON public.mock_items
+    USING paradedb (id, category)
+    WITH (key_field = 'id');
+    "#
+    .execute(&mut conn);
+
+    let query = "SELECT id FROM mock_items WHERE description @@@ 'keyboard' ORDER BY id";
+    assert_eq!(chosen_index(&mut conn, query), "index_description");
+    let expected: Vec<(i32,)> =
+        "SELECT id FROM mock_items WHERE description ILIKE '%keyboard%' ORDER BY id"
+            .fetch(&mut conn);
+    assert_eq!(query.fetch::<(i32,)>(&mut conn), expected);
+
+    let query = "SELECT id FROM mock_items WHERE category @@@ 'electronics' ORDER BY id";
+    assert_eq!(chosen_index(&mut conn, query), "index_category");
+    let expected: Vec<(i32,)> =
+        "SELECT id FROM mock_items WHERE category ILIKE 'electronics' ORDER BY id".fetch(&mut conn);
+    assert_eq!(query.fetch::<(i32,)>(&mut conn), expected);
+
+    let query = "SELECT id FROM mock_items WHERE id @@@ paradedb.term('description', 'keyboard')";
+    assert_eq!(chosen_index(&mut conn, query), "index_description");
+
+    // aggregates choose among the indexes the same way
+    let query = "SELECT COUNT(*) FROM mock_items WHERE category @@@ 'electronics'";
+    assert_eq!(chosen_index(&mut conn, query), "index_category");
+    let (expected,): (i64,) =
+        "SELECT COUNT(*) FROM mock_items WHERE category ILIKE 'electronics'".fetch_one(&mut conn);
+    assert_eq!(query.fetch_one::<(i64,)>(&mut conn), (expected,));
+}
+
+#[rstest]
+fn search_conditions_on_one_table_use_one_index(mut conn: PgConnection) {
+    r#"
+    CALL paradedb.create_paradedb_test_table(
+      schema_name => 'public',
+      table_name => 'mock_items'
+    )
+    "#
+    .execute(&mut conn);
+
+    r#"
+    CREATE INDEX index_description ON public.mock_items
+    USING paradedb (id, description)
+    WITH (key_field = 'id');
+    "#
+    .execute(&mut conn);
+
+    r#"
+    CREATE INDEX index_category ON public.mock_items
+    USING paradedb (id, category)
+    WITH (key_field = 'id');
+    "#
+    .execute(&mut conn);
+
+    // neither index has both fields, so the conditions can't be answered by one scan
+    let query = "SELECT id FROM mock_items \
+                 WHERE description @@@ 'keyboard' AND category @@@ 'electronics' ORDER BY id";
+    match query.fetch_result::<(i32,)>(&mut conn) {
+        Ok(_) => panic!("conditions on fields of different indexes should be rejected"),
+        Err(err) => assert!(
+            err.to_string().contains("a scan can only use one index"),
+            "unexpected error: {err}"
+        ),
+    }
+
+    r#"
+    CREATE INDEX index_description_category ON public.mock_items
+    USING paradedb (id, description, category)
+    WITH (key_field = 'id');
+    "#
+    .execute(&mut conn);
+
+    // every condition, and the scan, use the index that has both fields
+    let (plan,) = format!("EXPLAIN (FORMAT JSON) {query}").fetch_one::<(Value,)>(&mut conn);
+    assert!(
+        plan.to_string()
+            .contains(r#""Index":"index_description_category""#),
+        "unexpected plan: {plan}"
+    );
+    let expected: Vec<(i32,)> = "SELECT id FROM mock_items \
+                                 WHERE description ILIKE '%keyboard%' \
+                                 AND category ILIKE 'electronics' ORDER BY id"
+        .fetch(&mut conn);
+    assert!(!expected.is_empty());
+    assert_eq!(query.fetch::<(i32,)>(&mut conn), expected);
+}
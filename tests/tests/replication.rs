@@ -16,189 +16,18 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use anyhow::Result;
-use cmd_lib::{run_cmd, run_fun};
-use dotenvy::dotenv;
+use cmd_lib::run_cmd;
 use rstest::*;
-use sqlx::{Connection, PgConnection};
-use std::io::Write;
 use std::os::unix::fs::PermissionsExt;
-use std::path::{Path, PathBuf};
-use std::sync::Once;
-use std::sync::atomic::{AtomicUsize, Ordering};
 use std::thread;
 use std::time::Duration;
 use tempfile::TempDir;
 use tests::fixtures::db::Query;
-
-// Static variables for initializing port assignment and ensuring one-time setup
-static INIT: Once = Once::new();
-static LAST_PORT: AtomicUsize = AtomicUsize::new(49152);
+use tests::fixtures::ephemeral::{EphemeralPostgres, SUPERUSER};
 
 const RETRIES: u32 = 60;
 const RETRY_DELAY: u64 = 1000; // measured in milliseconds
 
-// Function to check if a port can be bound (i.e., is available)
-fn can_bind(port: u16) -> bool {
-    std::net::TcpListener::bind(("127.0.0.1", port)).is_ok()
-}
-
-// Function to get a free port in the dynamic port range
-fn get_free_port() -> u16 {
-    let port_upper_bound = 65535;
-    let port_lower_bound = 49152;
-
-    INIT.call_once(|| {
-        LAST_PORT.store(port_lower_bound, Ordering::SeqCst);
-    });
-
-    loop {
-        let port = LAST_PORT.fetch_add(1, Ordering::SeqCst);
-        if port > port_upper_bound {
-            LAST_PORT.store(port_lower_bound, Ordering::SeqCst);
-            continue;
-        }
-
-        if can_bind(port as u16) {
-            return port as u16;
-        }
-    }
-}
-
-// Superuser created by `initdb` for every ephemeral instance.
-//
-// This is pinned rather than left to `initdb`'s default (the OS user) because sqlx
-// resolves an omitted username via `whoami`, which reports `anonymous` when neither
-// `USER` nor `LOGNAME` is set — as is the case in our CI containers. Naming the role on
-// both sides keeps the tests independent of the ambient environment.
-const SUPERUSER: &str = "postgres";
-
-// Struct to manage an ephemeral PostgreSQL instance
-struct EphemeralPostgres {
-    pub tempdir_path: String,
-    pub host: String,
-    pub port: u16,
-    pub dbname: String,
-    pub pg_ctl_path: PathBuf,
-    pub log_path: PathBuf,
-}
-
-// Implement Drop trait to ensure the PostgreSQL instance is properly stopped
-impl Drop for EphemeralPostgres {
-    fn drop(&mut self) {
-        let path = &self.tempdir_path;
-        let pg_ctl_path = &self.pg_ctl_path;
-        run_cmd!($pg_ctl_path -D $path stop &> /dev/null)
-            .unwrap_or_else(|_| println!("postgres instance at {} already shut down", self.port));
-        std::fs::remove_dir_all(self.tempdir_path.clone()).unwrap();
-    }
-}
-
-// Implementation of EphemeralPostgres
-impl EphemeralPostgres {
-    fn pg_bin_path() -> PathBuf {
-        let pg_config_path = std::env::var("PG_CONFIG").expect(
-            "PG_CONFIG variable must be set to enable creating ephemeral Postgres instances",
-        );
-        if !PathBuf::from(&pg_config_path).exists() {
-            panic!(
-                "PG_CONFIG variable must be a valid path to enable creating ephemeral Postgres instances, received {pg_config_path}"
-            );
-        }
-        match run_fun!($pg_config_path --bindir) {
-            Ok(path) => PathBuf::from(path.trim().to_string()),
-            Err(err) => panic!("could run pg_config --bindir to get Postgres bin folder: {err}"),
-        }
-    }
-
-    fn pg_basebackup_path() -> PathBuf {
-        Self::pg_bin_path().join("pg_basebackup")
-    }
-
-    fn initdb_path() -> PathBuf {
-        Self::pg_bin_path().join("initdb")
-    }
-
-    fn pg_ctl_path() -> PathBuf {
-        Self::pg_bin_path().join("pg_ctl")
-    }
-
-    fn new_from_initialized(
-        tempdir_path: &Path,
-        postgresql_conf: Option<&str>,
-        pg_hba_conf: Option<&str>,
-    ) -> Self {
-        let tempdir_path = tempdir_path.to_str().unwrap().to_string();
-        let port = get_free_port();
-        let pg_ctl_path = Self::pg_ctl_path();
-
-        // Write to postgresql.conf
-        let config_content = match postgresql_conf {
-            Some(config) => format!("port = {}\n{}", port, config.trim()),
-            None => format!("port = {port}"),
-        };
-        let config_path = format!("{tempdir_path}/postgresql.conf");
-        std::fs::write(config_path, config_content).expect("Failed to write to postgresql.conf");
-
-        // Write to pg_hba.conf
-        if let Some(config_content) = pg_hba_conf {
-            let config_path = format!("{tempdir_path}/pg_hba.conf");
-
-            let mut file = std::fs::OpenOptions::new()
-                .append(true)
-                .create(true)
-                .open(config_path)
-                .expect("Failed to open pg_hba.conf");
-
-            writeln!(file, "{config_content}").expect("Failed to append to pg_hba.conf");
-        }
-
-        // Create log directory
-        let timestamp = chrono::Utc::now().timestamp_millis();
-        let logfile = format!("/tmp/ephemeral_postgres_logs/{timestamp}.log");
-        std::fs::create_dir_all(Path::new(&logfile).parent().unwrap())
-            .expect("Failed to create log directory");
-
-        // Start PostgreSQL
-        run_cmd!($pg_ctl_path -D $tempdir_path -l $logfile start)
-            .expect("Failed to start Postgres");
-
-        Self {
-            // TempDir needs to be stored on the struct to avoid being dropped, otherwise the
-            // temp folder will be deleted before the test finishes.
-            tempdir_path,
-            host: "localhost".to_string(),
-            port,
-            dbname: "postgres".to_string(),
-            pg_ctl_path,
-            log_path: PathBuf::from(logfile),
-        }
-    }
-
-    fn new(postgresql_conf: Option<&str>, pg_hba_conf: Option<&str>) -> Self {
-        // Make sure .env files are loaded before reading env vars.
-        dotenv().ok();
-
-        let init_db_path = Self::initdb_path();
-        let tempdir = TempDir::new().expect("Failed to create temp dir");
-        let tempdir_path = tempdir.keep();
-
-        // Initialize PostgreSQL data directory
-        run_cmd!($init_db_path -D $tempdir_path --username $SUPERUSER &> /dev/null)
-            .expect("Failed to initialize Postgres data directory");
-
-        Self::new_from_initialized(tempdir_path.as_path(), postgresql_conf, pg_hba_conf)
-    }
-
-    // Method to establish a connection to the PostgreSQL instance
-    async fn connection(&self) -> Result<PgConnection> {
-        Ok(PgConnection::connect(&format!(
-            "postgresql://{}@{}:{}/{}",
-            SUPERUSER, self.host, self.port, self.dbname
-        ))
-        .await?)
-    }
-}
-
 // Test function to test the ephemeral PostgreSQL setup
 #[rstest]
 #[async_std::test]
@@ -735,8 +564,7 @@ async fn test_wal_streaming_replication_with_pg_search() -> Result<()> {
     );
     assert_eq!(standby_rows.len(), 4);
 
-    // Now build a bm25 index on the primary. ambuild emits the rmgr 137 marker; when the
-    // standby replays it, rm_redo aborts recovery with our "not supported" error.
+    // Now build a bm25 index on the primary, and search it on the standby.
     "
     CREATE INDEX items_search_idx ON items
     USING paradedb (id, description, category)
@@ -744,35 +572,127 @@ async fn test_wal_streaming_replication_with_pg_search() -> Result<()> {
     "
     .execute(&mut source_conn);
 
-    // Wait for the marker to stream to the standby and FATAL its recovery process. Poll the
-    // log instead of sleeping for a fixed duration so the test self-paces.
-    let expected = "replicas are not supported on community and require paradedb enterprise";
-    let log_path = standby_postgres.log_path.clone();
-    let deadline = std::time::Instant::now() + Duration::from_secs(30);
-    let mut tripwire_found = false;
-    while std::time::Instant::now() < deadline {
-        if let Ok(log) = std::fs::read_to_string(&log_path)
-            && log.contains(expected)
-        {
-            tripwire_found = true;
-            break;
-        }
-        thread::sleep(Duration::from_millis(200));
+    let standby_rows: Vec<(String,)> =
+        "SELECT description FROM items WHERE description @@@ 'shoes' ORDER BY id".fetch_retry(
+            &mut standby_conn,
+            RETRIES,
+            RETRY_DELAY,
+            |result| result.len() == 2,
+        );
+    assert_eq!(
+        standby_rows,
+        vec![("Red running shoes".into(),), ("Blue sports shoes".into(),)]
+    );
+
+    // Each insert creates a new segment, and the VACUUM merges them and recycles the old ones,
+    // which the standby has to replay while keeping its own readers correct.
+    for description in [
+        "Green hiking shoes",
+        "Wireless earbuds",
+        "White tennis shoes",
+    ] {
+        format!(
+            "INSERT INTO items (description, category, created_at) VALUES ('{description}', 'Misc', NOW())"
+        )
+        .execute(&mut source_conn);
     }
+    "DELETE FROM items WHERE description = 'Red running shoes'".execute(&mut source_conn);
+    "VACUUM items".execute(&mut source_conn);
+    "VACUUM items".execute(&mut source_conn);
+
+    let standby_rows: Vec<(String,)> =
+        "SELECT description FROM items WHERE description @@@ 'shoes' ORDER BY id".fetch_retry(
+            &mut standby_conn,
+            RETRIES,
+            RETRY_DELAY,
+            |result| result.len() == 3,
+        );
+    assert_eq!(
+        standby_rows,
+        vec![
+            ("Blue sports shoes".into(),),
+            ("Green hiking shoes".into(),),
+            ("White tennis shoes".into(),)
+        ]
+    );
+
+    // The standby's copy of the index matches the primary's.
+    let count_query = "SELECT COUNT(*) FROM items WHERE id @@@ pdb.all()";
+    let (primary_count,) = count_query.fetch_one::<(i64,)>(&mut source_conn);
+    let (standby_count,) = count_query.fetch_one::<(i64,)>(&mut standby_conn);
+    assert_eq!(primary_count, standby_count);
+
+    Ok(())
+}
+
+#[rstest]
+#[async_std::test]
+async fn test_wal_records_describe_index_changes() -> Result<()> {
+    let config = "
+        shared_preload_libraries = 'pg_search'
+    ";
+    let postgres = EphemeralPostgres::new(Some(config), None);
+    let mut conn = postgres.connection().await?;
+
+    "CREATE EXTENSION IF NOT EXISTS pg_search".execute(&mut conn);
+    "CREATE EXTENSION IF NOT EXISTS pg_walinspect".execute(&mut conn);
+    "CREATE TABLE items (id SERIAL PRIMARY KEY, description TEXT)".execute(&mut conn);
+    "CREATE INDEX items_idx ON items USING paradedb (id, description)
+     WITH (key_field = 'id', mutable_segment_rows = 0, layer_sizes = '1mb')"
+        .execute(&mut conn);
+    let (start_lsn,): (String,) = "SELECT pg_current_wal_insert_lsn()::text".fetch_one(&mut conn);
+
+    // every insert writes a segment of its own, and the second is merged with the first
+    "INSERT INTO items (description) VALUES ('Red running shoes')".execute(&mut conn);
+    "INSERT INTO items (description) VALUES ('Blue sports shoes')".execute(&mut conn);
+
+    // a schema change, and the VACUUM that resolves it and returns the lists it replaced to the FSM
+    r#"SELECT pdb.add_field('items_idx', 'description_raw',
+        '{"column": "description", "tokenizer": {"type": "keyword"}}')"#
+        .execute(&mut conn);
+    "VACUUM items".execute(&mut conn);
+
+    let records: Vec<(String, String)> = format!(
+        "SELECT record_type, description
+         FROM pg_get_wal_records_info('{start_lsn}', pg_current_wal_flush_lsn())
+         WHERE resource_manager = 'pg_search'
+         ORDER BY start_lsn"
+    )
+    .fetch(&mut conn);
+    let described = |record_type: &str, pattern: &str| {
+        records
+            .iter()
+            .any(|(kind, desc)| kind == record_type && desc.contains(pattern))
+    };
+    assert!(
+        described("SEGMENTS", "; commit; created 1: ["),
+        "{records:#?}"
+    );
+    assert!(
+        described("SEGMENTS", "; merge; created 1: ["),
+        "{records:#?}"
+    );
+    assert!(
+        described("METAPAGE", "; set schema; schema "),
+        "{records:#?}"
+    );
+    assert!(
+        described("METAPAGE", "; resolve schema; committed, schema "),
+        "{records:#?}"
+    );
     assert!(
-        tripwire_found,
-        "expected standby log {log_path:?} to contain tripwire message {expected:?}"
+        described("FSM", " blocks recyclable after xid "),
+        "{records:#?}"
     );
 
-    // The FATAL during recovery should also have brought the standby down: reconnects fail.
-    let reconnect = PgConnection::connect(&format!(
-        "postgresql://{}@{}:{}/{}",
-        SUPERUSER, standby_postgres.host, standby_postgres.port, standby_postgres.dbname
-    ))
-    .await;
+    // and each of them names the index it's about
+    let (filenode,): (i64,) =
+        "SELECT pg_relation_filenode('items_idx')::bigint".fetch_one(&mut conn);
     assert!(
-        reconnect.is_err(),
-        "expected reconnect to dead standby to fail, got {reconnect:?}"
+        records
+            .iter()
+            .all(|(_, desc)| desc.starts_with("rel ") && desc.contains(&format!("/{filenode};"))),
+        "{records:#?}"
     );
 
     Ok(())
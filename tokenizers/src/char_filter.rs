@@ -0,0 +1,666 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Character filters rewrite the text before it reaches the tokenizer.
+//!
+//! Three filters are supported, mirroring Elasticsearch's character filters:
+//!
+//! - `HtmlStrip` removes HTML tags, comments and the contents of `<script>`/`<style>` elements,
+//!   and decodes character entities. Block-level elements are replaced by a newline so that the
+//!   words on either side of them are not glued together.
+//! - `Mapping` replaces every occurrence of a key by its value, preferring the longest key.
+//! - `PatternReplace` replaces every match of a regular expression, with `$1`-style references
+//!   to capture groups in the replacement.
+//!
+//! Every rewrite is recorded, so that the offsets of the tokens produced from the rewritten text
+//! can be mapped back to the original text. This keeps highlighting and `snippet_positions`
+//! pointing at the right spans of the source document.
+
+use std::ops::Range;
+
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tantivy::tokenizer::{Token, TokenStream, Tokenizer};
+
+/// HTML elements that are replaced by a newline rather than removed outright.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+/// HTML elements whose contents are removed along with their tags.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// The longest entity name we try to decode, not counting the `&` and `;`.
+const MAX_ENTITY_LEN: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum CharFilter {
+    HtmlStrip,
+    Mapping {
+        mappings: Vec<(String, String)>,
+    },
+    PatternReplace {
+        pattern: String,
+        replacement: String,
+    },
+}
+
+impl CharFilter {
+    /// Parses `;`-separated `key => value` mappings, e.g. `& => and; ä => ae`.
+    pub fn parse_mappings(value: &str) -> Result<Vec<(String, String)>> {
+        value
+            .split(';')
+            .filter(|mapping| !mapping.trim().is_empty())
+            .map(|mapping| {
+                let (from, to) = mapping.split_once("=>").ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "mapping `{}` must be of the form `key => value`",
+                        mapping.trim()
+                    )
+                })?;
+                Ok((from.trim().to_string(), to.trim().to_string()))
+            })
+            .collect()
+    }
+
+    /// Checks that the filter can be built, without building it.
+    pub fn validate(&self) -> Result<()> {
+        ActiveCharFilter::new(self).map(|_| ())
+    }
+
+    fn name(&self) -> String {
+        match self {
+            CharFilter::HtmlStrip => "html_strip".into(),
+            CharFilter::Mapping { mappings } => format!("mapping={mappings:?}"),
+            CharFilter::PatternReplace {
+                pattern,
+                replacement,
+            } => format!("pattern_replace={pattern:?}=>{replacement:?}"),
+        }
+    }
+
+    /// The suffix that char filters contribute to a tokenizer's name.
+    pub fn name_suffix(filters: &[CharFilter]) -> String {
+        let names = filters.iter().map(CharFilter::name).collect::<Vec<_>>();
+        format!("char_filters=[{}]", names.join(","))
+    }
+}
+
+/// A [`CharFilter`] that has been compiled into something that can rewrite text.
+#[derive(Clone)]
+enum ActiveCharFilter {
+    HtmlStrip,
+    /// Sorted by descending key length, so the first matching key is the longest one.
+    Mapping(Vec<(String, String)>),
+    PatternReplace(Regex, String),
+}
+
+impl ActiveCharFilter {
+    fn new(filter: &CharFilter) -> Result<Self> {
+        Ok(match filter {
+            CharFilter::HtmlStrip => ActiveCharFilter::HtmlStrip,
+            CharFilter::Mapping { mappings } => {
+                if mappings.is_empty() {
+                    anyhow::bail!("a mapping char filter requires at least one mapping");
+                }
+                if mappings.iter().any(|(from, _)| from.is_empty()) {
+                    anyhow::bail!("a mapping char filter cannot map an empty string");
+                }
+                let mut mappings = mappings.clone();
+                mappings.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+                ActiveCharFilter::Mapping(mappings)
+            }
+            CharFilter::PatternReplace {
+                pattern,
+                replacement,
+            } => ActiveCharFilter::PatternReplace(
+                Regex::new(pattern)
+                    .map_err(|e| anyhow::anyhow!("invalid pattern_replace pattern: {e}"))?,
+                replacement.clone(),
+            ),
+        })
+    }
+
+    fn apply(&self, text: &str) -> Rewrite {
+        match self {
+            ActiveCharFilter::HtmlStrip => strip_html(text),
+            ActiveCharFilter::Mapping(mappings) => {
+                let mut rewrite = Rewriter::new(text);
+                let mut pos = 0;
+                while pos < text.len() {
+                    let rest = &text[pos..];
+                    if let Some((from, to)) =
+                        mappings.iter().find(|(from, _)| rest.starts_with(from))
+                    {
+                        rewrite.replace(pos..pos + from.len(), to);
+                        pos += from.len();
+                    } else {
+                        pos += rest.chars().next().map_or(1, char::len_utf8);
+                    }
+                }
+                rewrite.finish()
+            }
+            ActiveCharFilter::PatternReplace(regex, replacement) => {
+                let mut rewrite = Rewriter::new(text);
+                let mut expanded = String::new();
+                for captures in regex.captures_iter(text) {
+                    let matched = captures.get(0).expect("capture group 0 always matches");
+                    expanded.clear();
+                    captures.expand(replacement, &mut expanded);
+                    rewrite.replace(matched.range(), &expanded);
+                }
+                rewrite.finish()
+            }
+        }
+    }
+}
+
+/// Replaces `source` in the input text with `filtered` in the output text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Edit {
+    filtered: Range<usize>,
+    source: Range<usize>,
+}
+
+/// The output of a single char filter, and the edits that produced it.
+struct Rewrite {
+    text: String,
+    edits: Vec<Edit>,
+}
+
+/// Builds a [`Rewrite`] by copying the input text, except for the ranges that get replaced.
+struct Rewriter<'a> {
+    source: &'a str,
+    copied_to: usize,
+    rewrite: Rewrite,
+}
+
+impl<'a> Rewriter<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            copied_to: 0,
+            rewrite: Rewrite {
+                text: String::with_capacity(source.len()),
+                edits: Vec::new(),
+            },
+        }
+    }
+
+    /// Replaces `range` of the source with `with`.  Ranges must be replaced in order.
+    fn replace(&mut self, range: Range<usize>, with: &str) {
+        debug_assert!(range.start >= self.copied_to);
+        self.rewrite
+            .text
+            .push_str(&self.source[self.copied_to..range.start]);
+        let start = self.rewrite.text.len();
+        self.rewrite.text.push_str(with);
+        self.rewrite.edits.push(Edit {
+            filtered: start..self.rewrite.text.len(),
+            source: range.clone(),
+        });
+        self.copied_to = range.end;
+    }
+
+    fn finish(mut self) -> Rewrite {
+        self.rewrite.text.push_str(&self.source[self.copied_to..]);
+        self.rewrite
+    }
+}
+
+/// Maps an offset in the output of a char filter back to its input.
+///
+/// A token starting inside an edit starts where the edit's source does, and a token ending inside
+/// an edit ends where the edit's source ends.  Offsets outside of every edit are shifted by the
+/// difference in length that the preceding edits introduced.
+fn map_offset(edits: &[Edit], offset: usize, is_end: bool) -> usize {
+    let idx = edits.partition_point(|edit| {
+        if is_end {
+            edit.filtered.start < offset
+        } else {
+            edit.filtered.start <= offset
+        }
+    });
+    let Some(edit) = idx.checked_sub(1).map(|idx| &edits[idx]) else {
+        return offset;
+    };
+
+    if offset < edit.filtered.end {
+        if is_end {
+            edit.source.end
+        } else {
+            edit.source.start
+        }
+    } else if is_end && offset == edit.filtered.end {
+        edit.source.end
+    } else {
+        edit.source.end + (offset - edit.filtered.end)
+    }
+}
+
+fn strip_html(text: &str) -> Rewrite {
+    let mut rewrite = Rewriter::new(text);
+    let bytes = text.as_bytes();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'<' => match html_tag_end(text, pos) {
+                Some((end, replacement)) => {
+                    rewrite.replace(pos..end, replacement);
+                    pos = end;
+                }
+                None => pos += 1,
+            },
+            b'&' => match html_entity_end(text, pos) {
+                Some((end, decoded)) => {
+                    let mut buf = [0u8; 4];
+                    rewrite.replace(pos..end, decoded.encode_utf8(&mut buf));
+                    pos = end;
+                }
+                None => pos += 1,
+            },
+            _ => pos += 1,
+        }
+    }
+
+    rewrite.finish()
+}
+
+/// If a tag, comment or declaration starts at `start`, returns where it ends and what it should
+/// be replaced with.
+fn html_tag_end(text: &str, start: usize) -> Option<(usize, &'static str)> {
+    let rest = &text[start..];
+
+    if let Some(comment) = rest.strip_prefix("<!--") {
+        let end = comment
+            .find("-->")
+            .map_or(text.len(), |idx| start + 4 + idx + 3);
+        return Some((end, ""));
+    }
+
+    let inner = rest[1..].strip_prefix('/').unwrap_or(&rest[1..]);
+    let is_closing = inner.len() < rest.len() - 1;
+    match inner.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => {}
+        Some('!' | '?') if !is_closing => {}
+        _ => return None,
+    }
+
+    let end = start + rest.find('>')? + 1;
+    let name = inner
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    if !is_closing && RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+        // drop everything up to, and including, the matching closing tag
+        let closing = format!("</{name}");
+        let end = text[end..]
+            .to_ascii_lowercase()
+            .find(&closing)
+            .and_then(|idx| text[end + idx..].find('>').map(|gt| end + idx + gt + 1))
+            .unwrap_or(text.len());
+        return Some((end, ""));
+    }
+
+    if BLOCK_ELEMENTS.contains(&name.as_str()) {
+        Some((end, "\n"))
+    } else {
+        Some((end, ""))
+    }
+}
+
+/// If a character entity starts at `start`, returns where it ends and the character it decodes
+/// to.
+fn html_entity_end(text: &str, start: usize) -> Option<(usize, char)> {
+    let rest = &text[start + 1..];
+    let semicolon = rest
+        .char_indices()
+        .take(MAX_ENTITY_LEN + 1)
+        .find(|(_, c)| *c == ';')?
+        .0;
+    let entity = &rest[..semicolon];
+
+    let decoded = if let Some(numeric) = entity.strip_prefix('#') {
+        let code = match numeric.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => numeric.parse::<u32>().ok()?,
+        };
+        char::from_u32(code)?
+    } else {
+        match entity {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            "nbsp" => '\u{a0}',
+            "copy" => '©',
+            "reg" => '®',
+            "trade" => '™',
+            "hellip" => '…',
+            "mdash" => '—',
+            "ndash" => '–',
+            "lsquo" => '‘',
+            "rsquo" => '’',
+            "ldquo" => '“',
+            "rdquo" => '”',
+            "eacute" => 'é',
+            "egrave" => 'è',
+            "agrave" => 'à',
+            "auml" => 'ä',
+            "ouml" => 'ö',
+            "uuml" => 'ü',
+            "szlig" => 'ß',
+            _ => return None,
+        }
+    };
+
+    Some((start + 1 + semicolon + 1, decoded))
+}
+
+/// Runs the text through a list of [`CharFilter`]s before handing it to the inner tokenizer, and
+/// maps the offsets of the resulting tokens back to the original text.
+#[derive(Clone)]
+pub struct CharFilterTokenizer<T: Tokenizer> {
+    inner: T,
+    filters: Vec<ActiveCharFilter>,
+    buffer: String, // Buffer to store the filtered text
+    edits: Vec<Vec<Edit>>,
+}
+
+impl<T: Tokenizer> CharFilterTokenizer<T> {
+    pub fn new(inner: T, filters: &[CharFilter]) -> Result<Self> {
+        Ok(Self {
+            inner,
+            filters: filters
+                .iter()
+                .map(ActiveCharFilter::new)
+                .collect::<Result<_>>()?,
+            buffer: String::new(),
+            edits: Vec::new(),
+        })
+    }
+}
+
+impl<T: Tokenizer> Tokenizer for CharFilterTokenizer<T> {
+    type TokenStream<'a> = CharFilterTokenStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        if self.filters.is_empty() {
+            return CharFilterTokenStream {
+                tail: self.inner.token_stream(text),
+                edits: &[],
+            };
+        }
+
+        self.edits.clear();
+        let mut filtered = text.to_string();
+        for filter in &self.filters {
+            let rewrite = filter.apply(&filtered);
+            filtered = rewrite.text;
+            self.edits.push(rewrite.edits);
+        }
+        self.buffer = filtered;
+
+        CharFilterTokenStream {
+            tail: self.inner.token_stream(&self.buffer),
+            edits: &self.edits,
+        }
+    }
+}
+
+pub struct CharFilterTokenStream<'a, T> {
+    tail: T,
+    /// The edits made by each char filter, in the order the filters ran.
+    edits: &'a [Vec<Edit>],
+}
+
+impl<T: TokenStream> TokenStream for CharFilterTokenStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+
+        let token = self.tail.token_mut();
+        for edits in self.edits.iter().rev() {
+            token.offset_from = map_offset(edits, token.offset_from, false);
+            token.offset_to = map_offset(edits, token.offset_to, true);
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::tokenizer::SimpleTokenizer;
+
+    fn tokens(filters: &[CharFilter], text: &str) -> Vec<(String, usize, usize)> {
+        let mut tokenizer = CharFilterTokenizer::new(SimpleTokenizer::default(), filters).unwrap();
+        let mut stream = tokenizer.token_stream(text);
+        let mut tokens = Vec::new();
+        while stream.advance() {
+            let token = stream.token();
+            tokens.push((token.text.clone(), token.offset_from, token.offset_to));
+        }
+        tokens
+    }
+
+    fn assert_offsets_point_at_source(tokens: &[(String, usize, usize)], text: &str) {
+        for (_, from, to) in tokens {
+            assert!(text.is_char_boundary(*from) && text.is_char_boundary(*to));
+            assert!(from <= to);
+        }
+    }
+
+    #[test]
+    fn test_no_filters() {
+        assert_eq!(
+            tokens(&[], "hello world"),
+            vec![("hello".into(), 0, 5), ("world".into(), 6, 11)]
+        );
+    }
+
+    #[test]
+    fn test_html_strip() {
+        let text = "<p>Red <b>shoes</b></p><p>Blue&nbsp;hat &amp; scarf</p>";
+        let tokens = tokens(&[CharFilter::HtmlStrip], text);
+        assert_offsets_point_at_source(&tokens, text);
+        let texts = tokens.iter().map(|(t, ..)| t.as_str()).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["Red", "shoes", "Blue", "hat", "scarf"]);
+
+        // offsets point at the original text
+        let (_, from, to) = &tokens[1];
+        assert_eq!(&text[*from..*to], "shoes");
+        let (_, from, to) = &tokens[4];
+        assert_eq!(&text[*from..*to], "scarf");
+    }
+
+    #[test]
+    fn test_html_strip_block_elements_separate_words() {
+        let text = "one<br>two<div>three</div>four<span>five</span>";
+        let texts = tokens(&[CharFilter::HtmlStrip], text)
+            .into_iter()
+            .map(|(t, ..)| t)
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["one", "two", "three", "fourfive"]);
+    }
+
+    #[test]
+    fn test_html_strip_script_and_comments() {
+        let text =
+            "before<script>var hidden = 1;</script><!-- comment -->after <style>p {}</style>end";
+        let texts = tokens(&[CharFilter::HtmlStrip], text)
+            .into_iter()
+            .map(|(t, ..)| t)
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["beforeafter", "end"]);
+    }
+
+    #[test]
+    fn test_html_strip_leaves_non_tags_alone() {
+        let text = "1 < 2 && 3 > 2 &unknown;";
+        let tokens = tokens(&[CharFilter::HtmlStrip], text);
+        let texts = tokens.iter().map(|(t, ..)| t.as_str()).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["1", "2", "3", "2", "unknown"]);
+    }
+
+    #[test]
+    fn test_numeric_entities() {
+        let text = "caf&#233; &#x43;afe";
+        let tokens = tokens(&[CharFilter::HtmlStrip], text);
+        assert_eq!(tokens, vec![("café".into(), 0, 9), ("Cafe".into(), 10, 19)]);
+    }
+
+    #[test]
+    fn test_mapping() {
+        let filter = CharFilter::Mapping {
+            mappings: CharFilter::parse_mappings(
+                "& => and; ä => ae; :) => happy; :)) => veryhappy",
+            )
+            .unwrap(),
+        };
+        let text = "salt&pepper, Bär :)) x";
+        let tokens = tokens(&[filter], text);
+        assert_offsets_point_at_source(&tokens, text);
+        assert_eq!(
+            tokens,
+            vec![
+                ("saltandpepper".into(), 0, 11),
+                ("Baer".into(), 13, 17),
+                ("veryhappy".into(), 18, 21),
+                ("x".into(), 22, 23),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_mappings_requires_arrow() {
+        assert!(CharFilter::parse_mappings("a => b; c").is_err());
+        assert!(
+            CharFilter::Mapping {
+                mappings: vec![(String::new(), "x".into())]
+            }
+            .validate()
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_pattern_replace() {
+        let filter = CharFilter::PatternReplace {
+            pattern: r"(\d+)-(\d+)".into(),
+            replacement: "$1$2".into(),
+        };
+        let text = "call 555-1234 now";
+        assert_eq!(
+            tokens(&[filter], text),
+            vec![
+                ("call".into(), 0, 4),
+                ("5551234".into(), 5, 13),
+                ("now".into(), 14, 17),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let filter = CharFilter::PatternReplace {
+            pattern: "(".into(),
+            replacement: String::new(),
+        };
+        assert!(filter.validate().is_err());
+    }
+
+    #[test]
+    fn test_chained_filters_map_offsets_through_every_filter() {
+        let filters = [
+            CharFilter::HtmlStrip,
+            CharFilter::Mapping {
+                mappings: vec![("&".into(), " and ".into())],
+            },
+        ];
+        let text = "<i>rock</i>&amp;<i>roll</i>";
+        let tokens = tokens(&filters, text);
+        assert_eq!(
+            tokens,
+            vec![
+                ("rock".into(), 3, 7),
+                ("and".into(), 11, 16),
+                ("roll".into(), 19, 23),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_map_offset() {
+        // "a<b>c" -> "ac"
+        let edits = vec![Edit {
+            filtered: 1..1,
+            source: 1..4,
+        }];
+        assert_eq!(map_offset(&edits, 0, false), 0);
+        assert_eq!(map_offset(&edits, 1, true), 1);
+        assert_eq!(map_offset(&edits, 1, false), 4);
+        assert_eq!(map_offset(&edits, 2, true), 5);
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+
+use anyhow::Result;
+use tantivy::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// `DecompounderFilter` finds the dictionary words contained in compound words, like `kranken`,
+/// `versicherung` and `beitrag` in `Krankenversicherungsbeitrag`, and emits them alongside the
+/// compound. This matches the behavior of Elasticsearch's dictionary_decompounder token filter.
+///
+/// Sub-words are emitted at the position of their compound, so phrase queries keep working, and
+/// share its offsets. Dictionary words are matched regardless of case.
+#[derive(Clone)]
+pub struct DecompounderFilter {
+    words: Arc<HashSet<String>>,
+    min_word_size: usize,
+    min_subword_size: usize,
+    max_subword_size: usize,
+    only_longest_match: bool,
+}
+
+impl DecompounderFilter {
+    /// Creates a `DecompounderFilter`. Only tokens of at least `min_word_size` characters are
+    /// decompounded, into sub-words of `min_subword_size` to `max_subword_size` characters. If
+    /// `only_longest_match` is true, only the longest sub-word starting at any one character is
+    /// emitted.
+    pub fn new<S: AsRef<str>>(
+        words: impl IntoIterator<Item = S>,
+        min_word_size: usize,
+        min_subword_size: usize,
+        max_subword_size: usize,
+        only_longest_match: bool,
+    ) -> Result<DecompounderFilter> {
+        if min_subword_size == 0 {
+            anyhow::bail!("min_subword_size must be at least 1");
+        }
+        if max_subword_size < min_subword_size {
+            anyhow::bail!(
+                "max_subword_size must be at least min_subword_size ({min_subword_size}), got {max_subword_size}"
+            );
+        }
+        Ok(DecompounderFilter {
+            words: Arc::new(
+                words
+                    .into_iter()
+                    .map(|word| word.as_ref().trim().to_lowercase())
+                    .filter(|word| !word.is_empty())
+                    .collect(),
+            ),
+            min_word_size,
+            min_subword_size,
+            max_subword_size,
+            only_longest_match,
+        })
+    }
+
+    /// Returns the dictionary words found in `text`, in the order they start at.
+    fn decompound<'t>(&self, text: &'t str) -> Vec<&'t str> {
+        let boundaries = text
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .chain(std::iter::once(text.len()))
+            .collect::<Vec<_>>();
+        let len = boundaries.len() - 1;
+        if len < self.min_word_size {
+            return vec![];
+        }
+
+        let mut subwords: Vec<&str> = Vec::new();
+        for start in 0..(len + 1).saturating_sub(self.min_subword_size) {
+            let mut longest = None;
+            for size in self.min_subword_size..=self.max_subword_size.min(len - start) {
+                // the whole token is already emitted as the compound itself
+                if size == len {
+                    continue;
+                }
+                let subword = &text[boundaries[start]..boundaries[start + size]];
+                if self.words.contains(&subword.to_lowercase()) {
+                    if self.only_longest_match {
+                        longest = Some(subword);
+                    } else if !subwords.contains(&subword) {
+                        subwords.push(subword);
+                    }
+                }
+            }
+            if let Some(subword) = longest
+                && !subwords.contains(&subword)
+            {
+                subwords.push(subword);
+            }
+        }
+        subwords
+    }
+}
+
+impl TokenFilter for DecompounderFilter {
+    type Tokenizer<T: Tokenizer> = DecompounderFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> DecompounderFilterWrapper<T> {
+        DecompounderFilterWrapper {
+            filter: self,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DecompounderFilterWrapper<T: Tokenizer> {
+    filter: DecompounderFilter,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for DecompounderFilterWrapper<T> {
+    type TokenStream<'a> = DecompounderFilterStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        DecompounderFilterStream {
+            filter: &self.filter,
+            tail: self.inner.token_stream(text),
+            pending: VecDeque::new(),
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct DecompounderFilterStream<'a, T> {
+    filter: &'a DecompounderFilter,
+    tail: T,
+    /// Sub-words of the current token that haven't been returned yet.
+    pending: VecDeque<Token>,
+    token: Token,
+}
+
+impl<T: TokenStream> TokenStream for DecompounderFilterStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        if let Some(token) = self.pending.pop_front() {
+            self.token = token;
+            return true;
+        }
+        if !self.tail.advance() {
+            return false;
+        }
+
+        let original = self.tail.token();
+        for subword in self.filter.decompound(&original.text) {
+            self.pending.push_back(Token {
+                text: subword.to_string(),
+                ..original.clone()
+            });
+        }
+        self.token = original.clone();
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DecompounderFilter;
+    use tantivy::tokenizer::{SimpleTokenizer, TextAnalyzer};
+
+    fn tokens(filter: DecompounderFilter, text: &str) -> Vec<(String, usize)> {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(filter)
+            .build();
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        stream.process(&mut |token| tokens.push((token.text.clone(), token.position)));
+        tokens
+    }
+
+    fn decompounder(words: &[&str], only_longest_match: bool) -> DecompounderFilter {
+        DecompounderFilter::new(words, 5, 2, 15, only_longest_match).unwrap()
+    }
+
+    #[test]
+    fn test_subwords_at_the_compound_position() {
+        assert_eq!(
+            tokens(
+                decompounder(&["Kranken", "versicherung", "beitrag"], false),
+                "hoher Krankenversicherungsbeitrag"
+            ),
+            vec![
+                ("hoher".to_string(), 0),
+                ("Krankenversicherungsbeitrag".to_string(), 1),
+                ("Kranken".to_string(), 1),
+                ("versicherung".to_string(), 1),
+                ("beitrag".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_only_longest_match() {
+        let words = ["donau", "dampf", "schiff", "dampfschiff"];
+        let subwords = |only_longest_match| {
+            tokens(decompounder(&words, only_longest_match), "donaudampfschiff")
+                .into_iter()
+                .skip(1)
+                .map(|(text, _)| text)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            subwords(false),
+            vec!["donau", "dampf", "dampfschiff", "schiff"]
+        );
+        assert_eq!(subwords(true), vec!["donau", "dampfschiff", "schiff"]);
+    }
+
+    #[test]
+    fn test_word_sizes() {
+        assert_eq!(
+            tokens(decompounder(&["dampf", "damp"], false), "dampf"),
+            vec![("dampf".to_string(), 0), ("damp".to_string(), 0)]
+        );
+        assert_eq!(
+            tokens(decompounder(&["dam"], false), "damps"),
+            vec![("damps".to_string(), 0), ("dam".to_string(), 0)]
+        );
+        assert_eq!(
+            tokens(decompounder(&["dam"], false), "dams"),
+            vec![("dams".to_string(), 0)]
+        );
+    }
+
+    #[test]
+    fn test_invalid_sizes() {
+        assert!(DecompounderFilter::new(["a"], 5, 0, 15, false).is_err());
+        assert!(DecompounderFilter::new(["a"], 5, 4, 3, false).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Records the tokens an analyzer produces after its tokenizer and after each of its filters, to
+//! show which stage of the analyzer changed the token stream.
+//!
+//! The stages are recorded by the analyzer that's actually used for indexing and searching, built
+//! with a [`StageTokens`] recorder instead of `()`, so they can't drift from it.
+
+use std::sync::{Arc, Mutex};
+
+use tantivy::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// The tokens produced by one stage of an analyzer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnalyzerStage {
+    /// `tokenizer`, or the name of the option that enables the filter, like `stemmer`.
+    pub name: &'static str,
+    pub tokens: Vec<Token>,
+}
+
+/// Wraps each stage of an analyzer while it's built.
+pub trait StageRecorder {
+    type Filter<F: TokenFilter>: TokenFilter;
+
+    /// Wraps `filter`, the stage called `name`.  Filters that aren't enabled, which are `None`,
+    /// aren't stages.
+    fn stage<F: TokenFilter>(&self, name: &'static str, filter: Option<F>) -> Self::Filter<F>;
+}
+
+/// Builds analyzers without recording anything.
+impl StageRecorder for () {
+    type Filter<F: TokenFilter> = Option<F>;
+
+    fn stage<F: TokenFilter>(&self, _name: &'static str, filter: Option<F>) -> Option<F> {
+        filter
+    }
+}
+
+/// Records the tokens after every stage of the analyzers it builds, in the order the stages are
+/// applied.
+#[derive(Clone, Default)]
+pub struct StageTokens {
+    stages: Arc<Mutex<Vec<AnalyzerStage>>>,
+}
+
+impl StageTokens {
+    pub fn into_stages(self) -> Vec<AnalyzerStage> {
+        std::mem::take(&mut *self.stages.lock().expect("stage tokens lock is poisoned"))
+    }
+}
+
+impl StageRecorder for StageTokens {
+    type Filter<F: TokenFilter> = RecordingFilter<F>;
+
+    fn stage<F: TokenFilter>(&self, name: &'static str, filter: Option<F>) -> RecordingFilter<F> {
+        let tap = filter.is_some().then(|| {
+            let mut stages = self.stages.lock().expect("stage tokens lock is poisoned");
+            stages.push(AnalyzerStage {
+                name,
+                tokens: Vec::new(),
+            });
+            Tap {
+                stages: self.stages.clone(),
+                index: stages.len() - 1,
+            }
+        });
+        RecordingFilter { filter, tap }
+    }
+}
+
+/// A filter that passes tokens through unchanged, used to record the tokenizer's own output.
+#[derive(Clone)]
+pub struct NoFilter;
+
+impl TokenFilter for NoFilter {
+    type Tokenizer<T: Tokenizer> = T;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> T {
+        tokenizer
+    }
+}
+
+#[derive(Clone)]
+struct Tap {
+    stages: Arc<Mutex<Vec<AnalyzerStage>>>,
+    index: usize,
+}
+
+impl Tap {
+    fn record(&self, token: &Token) {
+        let mut stages = self.stages.lock().expect("stage tokens lock is poisoned");
+        stages[self.index].tokens.push(token.clone());
+    }
+}
+
+pub struct RecordingFilter<F> {
+    filter: Option<F>,
+    tap: Option<Tap>,
+}
+
+impl<F: TokenFilter> TokenFilter for RecordingFilter<F> {
+    type Tokenizer<T: Tokenizer> = RecordingTokenizer<<Option<F> as TokenFilter>::Tokenizer<T>>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> Self::Tokenizer<T> {
+        RecordingTokenizer {
+            inner: self.filter.transform(tokenizer),
+            tap: self.tap,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RecordingTokenizer<T> {
+    inner: T,
+    tap: Option<Tap>,
+}
+
+impl<T: Tokenizer> Tokenizer for RecordingTokenizer<T> {
+    type TokenStream<'a> = RecordingTokenStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        RecordingTokenStream {
+            tail: self.inner.token_stream(text),
+            tap: self.tap.clone(),
+        }
+    }
+}
+
+pub struct RecordingTokenStream<T> {
+    tail: T,
+    tap: Option<Tap>,
+}
+
+impl<T: TokenStream> TokenStream for RecordingTokenStream<T> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        if let Some(tap) = &self.tap {
+            tap.record(self.tail.token());
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
@@ -0,0 +1,793 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Dictionary-based stemming with Hunspell `.aff` and `.dic` files.
+//!
+//! Stems are found the way Lucene's Hunspell stemmer finds them: by stripping the affixes the
+//! `.aff` file describes from a word, and checking that what remains is a dictionary word that
+//! allows those affixes. A suffix, a prefix, both (if the affixes allow cross products) and two
+//! suffixes (if the inner one allows the outer one as a continuation class) are stripped.
+//! Compounding rules, conversion tables and morphological fields are not used.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use tantivy::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
+
+type Flag = u32;
+
+/// Reads the `.aff` and `.dic` files of a dictionary, given its name and, optionally, the table
+/// it is stored in.
+pub type DictionaryLoader = fn(name: &str, table: Option<&str>) -> Result<(Vec<u8>, Vec<u8>)>;
+
+static LOADER: OnceLock<DictionaryLoader> = OnceLock::new();
+
+/// Dictionaries are large, so each version of one is only parsed once and shared by every filter.
+static DICTIONARIES: Lazy<RwLock<HashMap<DictionaryKey, Arc<HunspellDictionary>>>> =
+    Lazy::new(Default::default);
+
+/// A dictionary's name, the table it is stored in, and the [`content_hash`] of its files.
+type DictionaryKey = (String, Option<String>, u64);
+
+/// Installs the function that reads dictionaries the first time they are used. This crate has no
+/// access to Postgres, so reading the files or tables is up to the caller.
+pub fn set_dictionary_loader(loader: DictionaryLoader) {
+    let _ = LOADER.set(loader);
+}
+
+/// Loads the dictionary called `name` as it is now, and returns the [`content_hash`] of its
+/// files, which [`dictionary`] is given to get this version of it.
+pub fn load(name: &str, table: Option<&str>) -> Result<u64> {
+    Ok(read(name, table)?.0)
+}
+
+/// Like [`load`], also returning the `.aff` and `.dic` files, so they can be kept and
+/// [`insert`]ed again after they've changed.
+pub fn read(name: &str, table: Option<&str>) -> Result<(u64, Vec<u8>, Vec<u8>)> {
+    let loader = LOADER
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("no hunspell dictionary loader is installed"))?;
+    let (aff, dic) = loader(name, table)?;
+    let version = content_hash(&aff, &dic);
+    insert(name, table, version, &aff, &dic)?;
+    Ok((version, aff, dic))
+}
+
+/// Makes `version` of the dictionary called `name`, whose files are `aff` and `dic`, available
+/// to [`dictionary`], whatever its files are now.
+pub fn insert(name: &str, table: Option<&str>, version: u64, aff: &[u8], dic: &[u8]) -> Result<()> {
+    let key = (name.to_string(), table.map(String::from), version);
+    let loaded = DICTIONARIES
+        .read()
+        .expect("hunspell dictionaries should not be poisoned")
+        .contains_key(&key);
+    if !loaded {
+        let dictionary = Arc::new(HunspellDictionary::parse(aff, dic)?);
+        DICTIONARIES
+            .write()
+            .expect("hunspell dictionaries should not be poisoned")
+            .insert(key, dictionary);
+    }
+    Ok(())
+}
+
+/// Returns the version of the dictionary called `name` whose files have the [`content_hash`]
+/// `version`, or the version it has now if that's `None`.
+pub fn dictionary(
+    name: &str,
+    table: Option<&str>,
+    version: Option<u64>,
+) -> Result<Arc<HunspellDictionary>> {
+    let version = match version {
+        Some(version) => version,
+        None => load(name, table)?,
+    };
+    let key = (name.to_string(), table.map(String::from), version);
+    let cached = || {
+        DICTIONARIES
+            .read()
+            .expect("hunspell dictionaries should not be poisoned")
+            .get(&key)
+            .cloned()
+    };
+    if let Some(dictionary) = cached() {
+        return Ok(dictionary);
+    }
+
+    // unless the files of this version were `insert`ed, it can only be loaded if it's current
+    if load(name, table)? != version {
+        anyhow::bail!(
+            "hunspell dictionary `{name}` has changed since the index was built, which doesn't keep a copy of it.  REINDEX the index to use its new version"
+        );
+    }
+    Ok(cached().expect("the dictionary should have just been loaded"))
+}
+
+/// A hash of a dictionary's files that tells its versions apart.  It's FNV-1a, which unlike the
+/// standard library's hasher is the same in every build, as indexes keep it.
+pub fn content_hash(aff: &[u8], dic: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    let aff_len = (aff.len() as u64).to_le_bytes();
+    for byte in aff_len.iter().chain(aff).chain(dic) {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+#[derive(Clone, Copy)]
+enum FlagMode {
+    /// Every character is a flag, which is Hunspell's default.
+    Char,
+    /// Every two characters are a flag.
+    Long,
+    /// Flags are comma-separated numbers.
+    Numeric,
+}
+
+impl FlagMode {
+    fn parse(self, flags: &str) -> Result<Vec<Flag>> {
+        let mut parsed = match self {
+            FlagMode::Char => flags.chars().map(Flag::from).collect::<Vec<_>>(),
+            FlagMode::Long => {
+                let chars = flags.chars().collect::<Vec<_>>();
+                if chars.len() % 2 != 0 {
+                    anyhow::bail!("long flags `{flags}` must come in pairs of characters");
+                }
+                chars
+                    .chunks(2)
+                    .map(|pair| (Flag::from(pair[0]) << 16) | Flag::from(pair[1]))
+                    .collect()
+            }
+            FlagMode::Numeric => flags
+                .split(',')
+                .map(|flag| {
+                    flag.trim()
+                        .parse::<Flag>()
+                        .with_context(|| format!("invalid numeric flag `{flag}`"))
+                })
+                .collect::<Result<_>>()?,
+        };
+        parsed.sort_unstable();
+        parsed.dedup();
+        Ok(parsed)
+    }
+}
+
+fn has_flag(flags: &[Flag], flag: Flag) -> bool {
+    flags.binary_search(&flag).is_ok()
+}
+
+/// One character of an affix condition.
+enum ConditionChar {
+    Any,
+    Set { chars: Vec<char>, negated: bool },
+}
+
+impl ConditionChar {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            ConditionChar::Any => true,
+            ConditionChar::Set { chars, negated } => chars.contains(&c) != *negated,
+        }
+    }
+}
+
+/// The condition the stem an affix is added to must meet, a regular expression made of
+/// characters, `.` and character classes like `[^aeiou]`.
+struct Condition(Vec<ConditionChar>);
+
+impl Condition {
+    fn parse(condition: &str) -> Result<Condition> {
+        if condition == "." {
+            return Ok(Condition(vec![]));
+        }
+
+        let mut parsed = Vec::new();
+        let mut chars = condition.chars();
+        while let Some(c) = chars.next() {
+            parsed.push(match c {
+                '.' => ConditionChar::Any,
+                '[' => {
+                    let mut set = Vec::new();
+                    let mut negated = false;
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some('^') if set.is_empty() && !negated => negated = true,
+                            Some(c) => set.push(c),
+                            None => anyhow::bail!("unterminated `[` in condition `{condition}`"),
+                        }
+                    }
+                    ConditionChar::Set {
+                        chars: set,
+                        negated,
+                    }
+                }
+                c => ConditionChar::Set {
+                    chars: vec![c],
+                    negated: false,
+                },
+            });
+        }
+        Ok(Condition(parsed))
+    }
+
+    fn matches_start(&self, stem: &str) -> bool {
+        let mut chars = stem.chars();
+        self.0
+            .iter()
+            .all(|condition| chars.next().is_some_and(|c| condition.matches(c)))
+    }
+
+    fn matches_end(&self, stem: &str) -> bool {
+        let mut chars = stem.chars().rev();
+        self.0
+            .iter()
+            .rev()
+            .all(|condition| chars.next().is_some_and(|c| condition.matches(c)))
+    }
+}
+
+/// A prefix or suffix rule: `strip` is removed from the stem, and the affix the rule is stored
+/// under is added in its place.
+struct Affix {
+    flag: Flag,
+    cross_product: bool,
+    strip: String,
+    continuation: Vec<Flag>,
+    condition: Condition,
+}
+
+/// Decodes the `.aff` and `.dic` files with the encoding the `.aff` file's `SET` line names.
+fn decode(bytes: &[u8], encoding: &str) -> Result<String> {
+    match encoding.to_ascii_uppercase().as_str() {
+        "UTF-8" | "UTF8" => {
+            let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+            String::from_utf8(bytes.to_vec()).context("the dictionary is not valid UTF-8")
+        }
+        "ISO8859-1" | "ISO-8859-1" | "LATIN1" => Ok(bytes.iter().map(|&b| char::from(b)).collect()),
+        "ISO8859-15" | "ISO-8859-15" | "LATIN9" => Ok(bytes
+            .iter()
+            .map(|&b| match b {
+                0xA4 => '€',
+                0xA6 => 'Š',
+                0xA8 => 'š',
+                0xB4 => 'Ž',
+                0xB8 => 'ž',
+                0xBC => 'Œ',
+                0xBD => 'œ',
+                0xBE => 'Ÿ',
+                b => char::from(b),
+            })
+            .collect()),
+        other => anyhow::bail!("unsupported dictionary encoding `{other}`"),
+    }
+}
+
+/// A Hunspell dictionary, with the affix rules of its `.aff` file and the words of its `.dic`
+/// file.
+pub struct HunspellDictionary {
+    words: HashMap<String, Vec<Vec<Flag>>>,
+    /// Prefix rules, by the text they add.
+    prefixes: HashMap<String, Vec<Affix>>,
+    /// Suffix rules, by the text they add.
+    suffixes: HashMap<String, Vec<Affix>>,
+    need_affix: Option<Flag>,
+    only_in_compound: Option<Flag>,
+    forbidden: Option<Flag>,
+}
+
+impl HunspellDictionary {
+    /// Parses the contents of a dictionary's `.aff` and `.dic` files.
+    pub fn parse(aff: &[u8], dic: &[u8]) -> Result<HunspellDictionary> {
+        // the `SET` line itself is ASCII, and Hunspell's default encoding is ISO8859-1
+        let encoding = aff
+            .split(|&b| b == b'\n')
+            .find_map(|line| line.strip_prefix(b"SET"))
+            .map(|encoding| String::from_utf8_lossy(encoding).trim().to_string())
+            .unwrap_or_else(|| "ISO8859-1".to_string());
+        let aff = decode(aff, &encoding).context("could not decode the .aff file")?;
+        let dic = decode(dic, &encoding).context("could not decode the .dic file")?;
+
+        let mut dictionary = HunspellDictionary {
+            words: HashMap::new(),
+            prefixes: HashMap::new(),
+            suffixes: HashMap::new(),
+            need_affix: None,
+            only_in_compound: None,
+            forbidden: None,
+        };
+        let mut flag_mode = FlagMode::Char;
+        // `AF` lines number sets of flags, which the other lines then refer to by number
+        let mut aliases: Option<Vec<Vec<Flag>>> = None;
+        let mut cross_products = HashMap::new();
+        let parse_flags =
+            |flags: &str, flag_mode: FlagMode, aliases: &Option<Vec<Vec<Flag>>>| match aliases {
+                Some(aliases) if flags.chars().all(|c| c.is_ascii_digit()) => flags
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|alias| aliases.get(alias.checked_sub(1)?))
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("unknown flag alias `{flags}`")),
+                _ => flag_mode.parse(flags),
+            };
+
+        for (line_number, line) in aff.lines().enumerate() {
+            let line_error = || format!("invalid .aff file line {}: `{line}`", line_number + 1);
+            let mut fields = line.split_whitespace();
+            let Some(kind) = fields.next() else {
+                continue;
+            };
+            match kind {
+                "FLAG" => {
+                    flag_mode = match fields.next() {
+                        Some("long") => FlagMode::Long,
+                        Some("num") => FlagMode::Numeric,
+                        Some("UTF-8") => FlagMode::Char,
+                        _ => anyhow::bail!(line_error()),
+                    }
+                }
+                "AF" => {
+                    let flags = fields.next().with_context(line_error)?;
+                    match aliases.as_mut() {
+                        // the first line only gives the number of aliases
+                        None => aliases = Some(Vec::new()),
+                        Some(aliases) => aliases.push(flag_mode.parse(flags)?),
+                    }
+                }
+                "NEEDAFFIX" | "ONLYINCOMPOUND" | "FORBIDDENWORD" => {
+                    let flags = flag_mode.parse(fields.next().with_context(line_error)?)?;
+                    let flag = flags.first().copied().with_context(line_error)?;
+                    match kind {
+                        "NEEDAFFIX" => dictionary.need_affix = Some(flag),
+                        "ONLYINCOMPOUND" => dictionary.only_in_compound = Some(flag),
+                        _ => dictionary.forbidden = Some(flag),
+                    }
+                }
+                "PFX" | "SFX" => {
+                    let flag = flag_mode
+                        .parse(fields.next().with_context(line_error)?)?
+                        .first()
+                        .copied()
+                        .with_context(line_error)?;
+                    // the first line of a rule only says whether it combines with other affixes
+                    let Some(&cross_product) = cross_products.get(&(kind, flag)) else {
+                        let cross_product = fields.next().with_context(line_error)? == "Y";
+                        cross_products.insert((kind, flag), cross_product);
+                        continue;
+                    };
+
+                    let strip = fields.next().with_context(line_error)?;
+                    let affix = fields.next().with_context(line_error)?;
+                    let (append, continuation) = match affix.split_once('/') {
+                        Some((append, flags)) => (append, parse_flags(flags, flag_mode, &aliases)?),
+                        None => (affix, vec![]),
+                    };
+                    let condition =
+                        Condition::parse(fields.next().unwrap_or(".")).with_context(line_error)?;
+                    let affixes = if kind == "PFX" {
+                        &mut dictionary.prefixes
+                    } else {
+                        &mut dictionary.suffixes
+                    };
+                    let empty_if_zero = |text: &str| {
+                        if text == "0" {
+                            String::new()
+                        } else {
+                            text.to_string()
+                        }
+                    };
+                    affixes
+                        .entry(empty_if_zero(append))
+                        .or_default()
+                        .push(Affix {
+                            flag,
+                            cross_product,
+                            strip: empty_if_zero(strip),
+                            continuation,
+                            condition,
+                        });
+                }
+                _ => {}
+            }
+        }
+
+        for (line_number, line) in dic.lines().enumerate() {
+            // the first line is the number of words, and morphological fields follow whitespace
+            let Some(entry) = line.split_whitespace().next() else {
+                continue;
+            };
+            if line_number == 0 && entry.parse::<usize>().is_ok() {
+                continue;
+            }
+
+            let (word, flags) = match entry.split_once('/') {
+                Some((word, flags)) => (word, parse_flags(flags, flag_mode, &aliases)?),
+                None => (entry, vec![]),
+            };
+            dictionary
+                .words
+                .entry(word.to_string())
+                .or_default()
+                .push(flags);
+        }
+
+        Ok(dictionary)
+    }
+
+    /// Returns the distinct stems of `word`, which is empty if the dictionary doesn't know it.
+    ///
+    /// The word is also looked up in lowercase and with its first letter in uppercase, since
+    /// filters like lowercasing change the case of the words the dictionary lists.
+    pub fn stem(&self, word: &str) -> Vec<String> {
+        let lowercase = word.to_lowercase();
+        let mut chars = lowercase.chars();
+        let titlecase = chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+            .unwrap_or_default();
+
+        let mut stems = Vec::new();
+        for variant in [word, &lowercase, &titlecase] {
+            self.stem_variant(variant, &mut stems);
+        }
+        stems
+    }
+
+    fn stem_variant(&self, word: &str, stems: &mut Vec<String>) {
+        if self
+            .words
+            .get(word)
+            .is_some_and(|entries| entries.iter().any(|flags| self.is_standalone(flags)))
+        {
+            push_stem(stems, word.to_string());
+        }
+
+        for (base, rules) in self.affix_rules(word, &self.suffixes, false) {
+            for rule in rules {
+                let Some(stem) = strip_suffix(base, rule) else {
+                    continue;
+                };
+                if self.has_root(&stem, &[rule.flag]) {
+                    push_stem(stems, stem.clone());
+                }
+                // the stem may itself end with a suffix that allows this one after it
+                for (inner_base, inner_rules) in self.affix_rules(&stem, &self.suffixes, false) {
+                    for inner_rule in inner_rules {
+                        if !has_flag(&inner_rule.continuation, rule.flag) {
+                            continue;
+                        }
+                        if let Some(root) = strip_suffix(inner_base, inner_rule)
+                            && self.has_root(&root, &[inner_rule.flag])
+                        {
+                            push_stem(stems, root);
+                        }
+                    }
+                }
+                if rule.cross_product {
+                    self.strip_prefixes(&stem, Some(rule.flag), stems);
+                }
+            }
+        }
+        self.strip_prefixes(word, None, stems);
+    }
+
+    fn strip_prefixes(&self, word: &str, suffix_flag: Option<Flag>, stems: &mut Vec<String>) {
+        for (rest, rules) in self.affix_rules(word, &self.prefixes, true) {
+            for rule in rules {
+                if suffix_flag.is_some() && !rule.cross_product {
+                    continue;
+                }
+                if rest.is_empty() && rule.strip.is_empty() {
+                    continue;
+                }
+                let root = format!("{}{rest}", rule.strip);
+                let required = std::iter::once(rule.flag)
+                    .chain(suffix_flag)
+                    .collect::<Vec<_>>();
+                if rule.condition.matches_start(&root) && self.has_root(&root, &required) {
+                    push_stem(stems, root);
+                }
+            }
+        }
+    }
+
+    /// The rules whose affix `word` has, with what remains of `word` without the affix.
+    fn affix_rules<'w, 's>(
+        &'s self,
+        word: &'w str,
+        affixes: &'s HashMap<String, Vec<Affix>>,
+        is_prefix: bool,
+    ) -> impl Iterator<Item = (&'w str, &'s Vec<Affix>)> {
+        word.char_indices()
+            .map(|(offset, _)| offset)
+            .chain(std::iter::once(word.len()))
+            .filter_map(move |offset| {
+                let (affix, rest) = if is_prefix {
+                    (&word[..offset], &word[offset..])
+                } else {
+                    (&word[offset..], &word[..offset])
+                };
+                affixes.get(affix).map(|rules| (rest, rules))
+            })
+    }
+
+    /// Whether a word with these flags may appear on its own.
+    fn is_standalone(&self, flags: &[Flag]) -> bool {
+        [self.need_affix, self.only_in_compound, self.forbidden]
+            .into_iter()
+            .flatten()
+            .all(|flag| !has_flag(flags, flag))
+    }
+
+    /// Whether `root` is a dictionary word that allows every affix in `required`.
+    fn has_root(&self, root: &str, required: &[Flag]) -> bool {
+        self.words.get(root).is_some_and(|entries| {
+            entries.iter().any(|flags| {
+                required.iter().all(|flag| has_flag(flags, *flag))
+                    && [self.only_in_compound, self.forbidden]
+                        .into_iter()
+                        .flatten()
+                        .all(|flag| !has_flag(flags, flag))
+            })
+        })
+    }
+}
+
+/// Undoes a suffix rule on `base`, the word without the rule's suffix.
+fn strip_suffix(base: &str, rule: &Affix) -> Option<String> {
+    if base.is_empty() && rule.strip.is_empty() {
+        return None;
+    }
+    let stem = format!("{base}{}", rule.strip);
+    rule.condition.matches_end(&stem).then_some(stem)
+}
+
+fn push_stem(stems: &mut Vec<String>, stem: String) {
+    if !stems.contains(&stem) {
+        stems.push(stem);
+    }
+}
+
+/// `HunspellFilter` replaces each token with its stems from a Hunspell dictionary.
+/// This matches the behavior of Elasticsearch's hunspell token filter.
+///
+/// A token with several stems is replaced by all of them, at its position, unless `longest_only`
+/// is set. Tokens the dictionary doesn't know are passed through as-is.
+#[derive(Clone)]
+pub struct HunspellFilter {
+    dictionary: Arc<HunspellDictionary>,
+    longest_only: bool,
+    lowercase: bool,
+}
+
+impl HunspellFilter {
+    /// Creates a `HunspellFilter`. If `lowercase` is true, stems are lowercased, as dictionaries
+    /// list some words, like German nouns, with an uppercase first letter.
+    pub fn new(
+        dictionary: Arc<HunspellDictionary>,
+        longest_only: bool,
+        lowercase: bool,
+    ) -> HunspellFilter {
+        HunspellFilter {
+            dictionary,
+            longest_only,
+            lowercase,
+        }
+    }
+
+    fn stems(&self, text: &str) -> Vec<String> {
+        let mut stems = self.dictionary.stem(text);
+        if self.lowercase {
+            let mut lowercased = Vec::with_capacity(stems.len());
+            for stem in stems {
+                push_stem(&mut lowercased, stem.to_lowercase());
+            }
+            stems = lowercased;
+        }
+        if self.longest_only
+            && let Some(longest) = stems
+                .iter()
+                .enumerate()
+                .max_by_key(|(i, stem)| (stem.chars().count(), std::cmp::Reverse(*i)))
+                .map(|(_, stem)| stem.clone())
+        {
+            stems = vec![longest];
+        }
+        stems
+    }
+}
+
+impl TokenFilter for HunspellFilter {
+    type Tokenizer<T: Tokenizer> = HunspellFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> HunspellFilterWrapper<T> {
+        HunspellFilterWrapper {
+            filter: self,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct HunspellFilterWrapper<T: Tokenizer> {
+    filter: HunspellFilter,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for HunspellFilterWrapper<T> {
+    type TokenStream<'a> = HunspellFilterStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        HunspellFilterStream {
+            filter: &self.filter,
+            tail: self.inner.token_stream(text),
+            pending: VecDeque::new(),
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct HunspellFilterStream<'a, T> {
+    filter: &'a HunspellFilter,
+    tail: T,
+    /// Stems of the current token that haven't been returned yet.
+    pending: VecDeque<Token>,
+    token: Token,
+}
+
+impl<T: TokenStream> TokenStream for HunspellFilterStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        if let Some(token) = self.pending.pop_front() {
+            self.token = token;
+            return true;
+        }
+        if !self.tail.advance() {
+            return false;
+        }
+
+        let original = self.tail.token();
+        let mut stems = self.filter.stems(&original.text).into_iter();
+        self.token = original.clone();
+        if let Some(stem) = stems.next() {
+            self.token.text = stem;
+        }
+        for stem in stems {
+            self.pending.push_back(Token {
+                text: stem,
+                ..original.clone()
+            });
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::tokenizer::{LowerCaser, SimpleTokenizer, TextAnalyzer};
+
+    const AFF: &str = "SET UTF-8
+FLAG long
+
+PFX Un Y 1
+PFX Un 0 un .
+
+SFX Ed Y 2
+SFX Ed 0 ed [^ey]
+SFX Ed 0 d e
+
+SFX Ss Y 2
+SFX Ss y ies [^aeiou]y
+SFX Ss 0 s [^y]
+
+SFX Ng N 1
+SFX Ng 0 ing/Ss .
+
+SFX Er N 1
+SFX Er 0 er .
+
+NEEDAFFIX Na
+";
+
+    const DIC: &str = "6
+lock/UnEd
+bake/Ed
+city/Ss
+walk/Ng
+Haus/Er
+happen/NaEd
+";
+
+    fn dictionary() -> HunspellDictionary {
+        HunspellDictionary::parse(AFF.as_bytes(), DIC.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_stems() {
+        let dictionary = dictionary();
+        assert_eq!(dictionary.stem("locked"), vec!["lock"]);
+        assert_eq!(dictionary.stem("baked"), vec!["bake"]);
+        assert_eq!(dictionary.stem("cities"), vec!["city"]);
+        assert_eq!(dictionary.stem("lock"), vec!["lock"]);
+        assert!(dictionary.stem("bakeed").is_empty());
+        assert!(dictionary.stem("unknown").is_empty());
+    }
+
+    #[test]
+    fn test_prefix_and_suffix() {
+        let dictionary = dictionary();
+        assert_eq!(dictionary.stem("unlock"), vec!["lock"]);
+        assert_eq!(dictionary.stem("unlocked"), vec!["lock"]);
+        assert!(dictionary.stem("unbaked").is_empty());
+    }
+
+    #[test]
+    fn test_twofold_suffix() {
+        assert_eq!(dictionary().stem("walkings"), vec!["walk"]);
+    }
+
+    #[test]
+    fn test_need_affix() {
+        let dictionary = dictionary();
+        assert!(dictionary.stem("happen").is_empty());
+        assert_eq!(dictionary.stem("happened"), vec!["happen"]);
+    }
+
+    #[test]
+    fn test_case_variants() {
+        assert_eq!(dictionary().stem("hauser"), vec!["Haus"]);
+    }
+
+    #[test]
+    fn test_filter() {
+        let filter = HunspellFilter::new(Arc::new(dictionary()), false, true);
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LowerCaser)
+            .filter(filter)
+            .build();
+        let mut stream = analyzer.token_stream("Unlocked Hauser cities today");
+        let mut tokens = vec![];
+        stream.process(&mut |token| tokens.push((token.text.clone(), token.position)));
+        assert_eq!(
+            tokens,
+            vec![
+                ("lock".to_string(), 0),
+                ("haus".to_string(), 1),
+                ("city".to_string(), 2),
+                ("today".to_string(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_iso8859_1() {
+        let dictionary = HunspellDictionary::parse(b"SET ISO8859-1\n", b"1\ncaf\xe9\n").unwrap();
+        assert_eq!(dictionary.stem("café"), vec!["café"]);
+    }
+}
@@ -15,39 +15,46 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+pub mod char_filter;
 pub mod chinese_convert;
 pub mod cjk;
 pub mod code;
+pub mod decompounder;
 pub mod edge_ngram;
+pub mod explain;
+pub mod hunspell;
 pub mod icu;
 pub mod lindera;
 pub mod manager;
 pub mod ngram;
+pub mod phonetic;
+pub mod shingle;
+pub mod synonym;
 pub mod token_length;
 pub mod token_trim;
 mod unicode_words;
+pub mod word_delimiter;
 
 use tantivy::tokenizer::{LowerCaser, RawTokenizer, TextAnalyzer, TokenizerManager};
 use tracing::debug;
 
 pub use manager::{SearchNormalizer, SearchTokenizer};
 
-pub fn create_tokenizer_manager(search_tokenizers: Vec<SearchTokenizer>) -> TokenizerManager {
+pub fn create_tokenizer_manager(
+    search_tokenizers: Vec<SearchTokenizer>,
+) -> anyhow::Result<TokenizerManager> {
     let tokenizer_manager = TokenizerManager::default();
 
     for search_tokenizer in search_tokenizers {
-        let tokenizer_option = search_tokenizer.to_tantivy_tokenizer();
-
-        if let Some(text_analyzer) = tokenizer_option {
-            debug!(
-                tokenizer_name = &search_tokenizer.name(),
-                "registering tokenizer",
-            );
-            tokenizer_manager.register(&search_tokenizer.name(), text_analyzer);
-        }
+        let text_analyzer = search_tokenizer.to_tantivy_tokenizer()?;
+        debug!(
+            tokenizer_name = &search_tokenizer.name(),
+            "registering tokenizer",
+        );
+        tokenizer_manager.register(&search_tokenizer.name(), text_analyzer);
     }
 
-    tokenizer_manager
+    Ok(tokenizer_manager)
 }
 
 pub fn create_normalizer_manager() -> TokenizerManager {
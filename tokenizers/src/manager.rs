@@ -18,13 +18,21 @@
 
 use std::fmt::Write;
 
+use crate::decompounder::DecompounderFilter;
 use crate::edge_ngram::{EdgeNgramTokenizer, TokenCharClass};
+use crate::explain::{AnalyzerStage, NoFilter, StageRecorder, StageTokens};
+use crate::hunspell::{self, HunspellFilter};
 use crate::icu::ICUTokenizer;
 use crate::ngram::NgramTokenizer;
+use crate::phonetic::{PhoneticAlgorithm, PhoneticFilter};
+use crate::shingle::ShingleFilter;
+use crate::word_delimiter::{WordDelimiterFilter, WordDelimiterOptions};
 use crate::{
+    char_filter::{CharFilter, CharFilterTokenizer},
     cjk::ChineseTokenizer,
     code::CodeTokenizer,
     lindera::{LinderaChineseTokenizer, LinderaJapaneseTokenizer, LinderaKoreanTokenizer},
+    synonym::{SynonymFilter, SynonymMap},
     token_length::TokenLengthFilter,
     token_trim::TokenTrimFilter,
     unicode_words::UnicodeWordsTokenizer,
@@ -39,7 +47,7 @@ use std::collections::HashMap;
 use strum::AsRefStr;
 use tantivy::tokenizer::{
     AlphaNumOnlyFilter, AsciiFoldingFilter, Language, LowerCaser, RawTokenizer, RegexTokenizer,
-    SimpleTokenizer, Stemmer, StopWordFilter, TextAnalyzer, WhitespaceTokenizer,
+    SimpleTokenizer, Stemmer, StopWordFilter, TextAnalyzer, Tokenizer, WhitespaceTokenizer,
 };
 
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
@@ -56,6 +64,168 @@ pub struct SearchTokenizerFilters {
     pub ascii_folding: Option<bool>,
     pub trim: Option<bool>,
     pub normalizer: Option<SearchNormalizer>,
+    pub synonyms: Option<SearchSynonyms>,
+    /// Rewrites the text before it is tokenized, in order.
+    pub char_filters: Option<Vec<CharFilter>>,
+    pub phonetic: Option<PhoneticAlgorithm>,
+    /// Whether phonetic codes replace the original tokens.  Defaults to true.
+    pub phonetic_replace: Option<bool>,
+    pub shingles: Option<SearchShingles>,
+    /// Splits tokens on punctuation, case changes and letter/digit transitions.
+    pub word_delimiter: Option<WordDelimiterOptions>,
+    pub decompounder: Option<SearchDecompounder>,
+    pub hunspell: Option<SearchHunspell>,
+    /// The name of an analyzer, whose tokenizer and filters replace these when an index is built.
+    /// Resolving it is up to the caller, as this crate has no access to Postgres.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub analyzer: Option<String>,
+}
+
+/// Options for the [`ShingleFilter`], named after their Elasticsearch counterparts.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SearchShingles {
+    pub min_shingle_size: usize,
+    pub max_shingle_size: usize,
+    pub output_unigrams: bool,
+    /// Stands in for positions left empty by an earlier filter, like a removed stopword.
+    pub filler_token: String,
+}
+
+impl SearchShingles {
+    /// Checks that the options describe a valid [`ShingleFilter`].
+    pub fn validate(&self) -> Result<()> {
+        self.filter().map(|_| ())
+    }
+
+    fn filter(&self) -> Result<ShingleFilter> {
+        ShingleFilter::new(
+            self.min_shingle_size,
+            self.max_shingle_size,
+            self.output_unigrams,
+            self.filler_token.clone(),
+        )
+    }
+}
+
+impl Default for SearchShingles {
+    fn default() -> Self {
+        Self {
+            min_shingle_size: 2,
+            max_shingle_size: 2,
+            output_unigrams: true,
+            filler_token: "_".to_string(),
+        }
+    }
+}
+
+/// Options for the [`DecompounderFilter`], named after their Elasticsearch counterparts.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SearchDecompounder {
+    /// Dictionary words given inline in the tokenizer configuration.
+    pub words: Vec<String>,
+    /// The table the remaining words were loaded from, if any.
+    ///
+    /// Only the table's name takes part in the tokenizer name.  An index reads the table once,
+    /// when it's built, and keeps its own copy of the words, so they can't change under its
+    /// segments.
+    pub table: Option<String>,
+    /// Words loaded from `table`. Resolving the table is up to the caller, as this crate has no
+    /// access to Postgres.
+    #[serde(default)]
+    pub table_words: Vec<String>,
+    pub min_word_size: usize,
+    pub min_subword_size: usize,
+    pub max_subword_size: usize,
+    pub only_longest_match: bool,
+}
+
+impl SearchDecompounder {
+    /// Splits a list of inline words, separated by commas or whitespace.
+    pub fn parse_words(value: &str) -> Vec<String> {
+        value
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|word| !word.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    /// Checks that the options describe a valid [`DecompounderFilter`].
+    pub fn validate(&self) -> Result<()> {
+        self.filter().map(|_| ())
+    }
+
+    fn filter(&self) -> Result<DecompounderFilter> {
+        DecompounderFilter::new(
+            self.words.iter().chain(self.table_words.iter()),
+            self.min_word_size,
+            self.min_subword_size,
+            self.max_subword_size,
+            self.only_longest_match,
+        )
+    }
+}
+
+impl Default for SearchDecompounder {
+    fn default() -> Self {
+        Self {
+            words: vec![],
+            table: None,
+            table_words: vec![],
+            min_word_size: 5,
+            min_subword_size: 2,
+            max_subword_size: 15,
+            only_longest_match: false,
+        }
+    }
+}
+
+/// A Hunspell dictionary for the [`HunspellFilter`].
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct SearchHunspell {
+    /// The dictionary's name, like `en_US`, which is also the name of its `.aff` and `.dic` files.
+    pub dictionary: String,
+    /// The table the dictionary is stored in, if it isn't read from files.
+    pub table: Option<String>,
+    /// Whether to keep only the longest stem of a word that has several.
+    pub longest_only: bool,
+    /// The [`content_hash`](crate::hunspell::content_hash) of the version of the dictionary to
+    /// use, or `None` for the version it has now.  An index keeps the version it was built with,
+    /// which is filled in by the caller, as this crate has no access to Postgres.
+    #[serde(default)]
+    pub version: Option<u64>,
+}
+
+/// Synonym rules for the [`SynonymFilter`], in the Solr format understood by [`SynonymMap`].
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct SearchSynonyms {
+    /// Rules given inline in the tokenizer configuration.
+    pub rules: Vec<String>,
+    /// The table the remaining rules were loaded from, if any.
+    ///
+    /// Only the table's name takes part in the tokenizer name.  An index reads the table once,
+    /// when it's built, and keeps its own copy of the rules, so they can't change under its
+    /// segments.
+    pub table: Option<String>,
+    /// Rules loaded from `table`. Resolving the table is up to the caller, as this crate has no
+    /// access to Postgres.
+    #[serde(default)]
+    pub table_rules: Vec<String>,
+}
+
+impl SearchSynonyms {
+    /// Splits a `;`-separated list of inline rules.
+    pub fn parse_rules(value: &str) -> Vec<String> {
+        value
+            .split(';')
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    fn all_rules(&self) -> Vec<&String> {
+        self.rules.iter().chain(self.table_rules.iter()).collect()
+    }
 }
 
 impl SearchTokenizerFilters {
@@ -76,6 +246,15 @@ impl SearchTokenizerFilters {
             alpha_num_only: None,
             trim: None,
             normalizer: Some(SearchNormalizer::Raw),
+            synonyms: None,
+            char_filters: None,
+            phonetic: None,
+            phonetic_replace: None,
+            shingles: None,
+            word_delimiter: None,
+            decompounder: None,
+            hunspell: None,
+            analyzer: None,
         }
     }
 
@@ -91,6 +270,15 @@ impl SearchTokenizerFilters {
             alpha_num_only: None,
             trim: None,
             normalizer: Some(SearchNormalizer::Raw),
+            synonyms: None,
+            char_filters: None,
+            phonetic: None,
+            phonetic_replace: None,
+            shingles: None,
+            word_delimiter: None,
+            decompounder: None,
+            hunspell: None,
+            analyzer: None,
         }
     }
 
@@ -188,10 +376,278 @@ impl SearchTokenizerFilters {
                 )
             })?);
         }
+        if let Some(phonetic) = value.get("phonetic") {
+            filters.phonetic = Some(serde_json::from_value(phonetic.clone()).map_err(|_| {
+                anyhow::anyhow!("phonetic tokenizer requires a valid 'phonetic' field")
+            })?);
+        }
+        if let Some(phonetic_replace) = value.get("phonetic_replace") {
+            filters.phonetic_replace = Some(phonetic_replace.as_bool().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "a 'phonetic_replace' value passed to the pg_search tokenizer configuration \
+                     must be of type bool, found: {phonetic_replace:#?}"
+                )
+            })?);
+        }
+        if [
+            "min_shingle_size",
+            "max_shingle_size",
+            "output_unigrams",
+            "filler_token",
+        ]
+        .iter()
+        .any(|key| value.get(key).is_some())
+        {
+            filters.shingles = Some(Self::parse_shingles(value)?);
+        }
+        if let Some(word_delimiter) = value.get("word_delimiter") {
+            filters.word_delimiter = Self::parse_word_delimiter(word_delimiter)?;
+        }
+        if let Some(dictionary) = value.get("hunspell") {
+            filters.hunspell = Some(Self::parse_hunspell(dictionary, value)?);
+        }
+        if value.get("decompounder_words").is_some() {
+            filters.decompounder = Some(Self::parse_decompounder(value)?);
+        }
+        if let Some(char_filters) = value.get("char_filters") {
+            filters.char_filters = Some(Self::parse_char_filters(char_filters)?);
+        }
+        if let Some(analyzer) = value.get("analyzer") {
+            filters.analyzer = Some(
+                analyzer
+                    .as_str()
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "an 'analyzer' value passed to the pg_search tokenizer configuration \
+                             must be of type string, found: {analyzer:#?}"
+                        )
+                    })?
+                    .to_string(),
+            );
+        }
+        if let Some(synonyms) = value.get("synonyms") {
+            let rules = match synonyms {
+                serde_json::Value::String(s) => SearchSynonyms::parse_rules(s),
+                serde_json::Value::Array(_) => serde_json::from_value(synonyms.clone())
+                    .map_err(|_| anyhow::anyhow!("synonyms must be an array of strings"))?,
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "synonyms must be a string or array of strings"
+                    ));
+                }
+            };
+            filters.synonyms = Some(SearchSynonyms {
+                rules,
+                ..Default::default()
+            });
+        }
 
+        filters.validate()?;
         Ok(filters)
     }
 
+    fn parse_hunspell(
+        dictionary: &serde_json::Value,
+        value: &serde_json::Value,
+    ) -> Result<SearchHunspell, anyhow::Error> {
+        let mut hunspell = SearchHunspell {
+            dictionary: dictionary
+                .as_str()
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "a 'hunspell' value passed to the pg_search tokenizer configuration \
+                         must be of type string, found: {dictionary:#?}"
+                    )
+                })?
+                .to_string(),
+            ..Default::default()
+        };
+        if let Some(table) = value.get("hunspell_table") {
+            hunspell.table = Some(
+                table
+                    .as_str()
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "a 'hunspell_table' value passed to the pg_search tokenizer \
+                             configuration must be of type string, found: {table:#?}"
+                        )
+                    })?
+                    .to_string(),
+            );
+        }
+        if let Some(longest_only) = value.get("hunspell_longest_only") {
+            hunspell.longest_only = longest_only.as_bool().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "a 'hunspell_longest_only' value passed to the pg_search tokenizer \
+                     configuration must be of type bool, found: {longest_only:#?}"
+                )
+            })?;
+        }
+        Ok(hunspell)
+    }
+
+    fn parse_decompounder(value: &serde_json::Value) -> Result<SearchDecompounder, anyhow::Error> {
+        let mut decompounder = SearchDecompounder::default();
+        if let Some(words) = value.get("decompounder_words") {
+            decompounder.words = match words {
+                serde_json::Value::String(s) => SearchDecompounder::parse_words(s),
+                serde_json::Value::Array(_) => {
+                    serde_json::from_value(words.clone()).map_err(|_| {
+                        anyhow::anyhow!("decompounder_words must be an array of strings")
+                    })?
+                }
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "decompounder_words must be a string or array of strings"
+                    ));
+                }
+            };
+        }
+        for (key, size) in [
+            ("min_word_size", &mut decompounder.min_word_size),
+            ("min_subword_size", &mut decompounder.min_subword_size),
+            ("max_subword_size", &mut decompounder.max_subword_size),
+        ] {
+            if let Some(found) = value.get(key) {
+                *size = found.as_u64().ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "a '{key}' value passed to the pg_search tokenizer configuration \
+                         must be of type u64, found: {found:#?}"
+                    )
+                })? as usize;
+            }
+        }
+        if let Some(only_longest_match) = value.get("only_longest_match") {
+            decompounder.only_longest_match = only_longest_match.as_bool().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "a 'only_longest_match' value passed to the pg_search tokenizer configuration \
+                     must be of type bool, found: {only_longest_match:#?}"
+                )
+            })?;
+        }
+        decompounder.validate()?;
+        Ok(decompounder)
+    }
+
+    fn parse_word_delimiter(
+        value: &serde_json::Value,
+    ) -> Result<Option<WordDelimiterOptions>, anyhow::Error> {
+        match value {
+            serde_json::Value::Bool(enabled) => Ok(enabled.then(WordDelimiterOptions::default)),
+            serde_json::Value::Object(_) => Ok(Some(
+                serde_json::from_value(value.clone())
+                    .map_err(|e| anyhow::anyhow!("invalid 'word_delimiter' options: {e}"))?,
+            )),
+            _ => Err(anyhow::anyhow!(
+                "a 'word_delimiter' value passed to the pg_search tokenizer configuration \
+                 must be of type bool or object, found: {value:#?}"
+            )),
+        }
+    }
+
+    fn parse_shingles(value: &serde_json::Value) -> Result<SearchShingles, anyhow::Error> {
+        let mut shingles = SearchShingles::default();
+        if let Some(min_shingle_size) = value.get("min_shingle_size") {
+            shingles.min_shingle_size = min_shingle_size.as_u64().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "a 'min_shingle_size' value passed to the pg_search tokenizer configuration \
+                     must be of type u64, found: {min_shingle_size:#?}"
+                )
+            })? as usize;
+            // like Elasticsearch, the maximum follows a larger minimum unless it's given
+            shingles.max_shingle_size = shingles.max_shingle_size.max(shingles.min_shingle_size);
+        }
+        if let Some(max_shingle_size) = value.get("max_shingle_size") {
+            shingles.max_shingle_size = max_shingle_size.as_u64().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "a 'max_shingle_size' value passed to the pg_search tokenizer configuration \
+                     must be of type u64, found: {max_shingle_size:#?}"
+                )
+            })? as usize;
+        }
+        if let Some(output_unigrams) = value.get("output_unigrams") {
+            shingles.output_unigrams = output_unigrams.as_bool().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "an 'output_unigrams' value passed to the pg_search tokenizer configuration \
+                     must be of type bool, found: {output_unigrams:#?}"
+                )
+            })?;
+        }
+        if let Some(filler_token) = value.get("filler_token") {
+            shingles.filler_token = filler_token
+                .as_str()
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "a 'filler_token' value passed to the pg_search tokenizer configuration \
+                         must be of type string, found: {filler_token:#?}"
+                    )
+                })?
+                .to_string();
+        }
+        shingles.validate()?;
+        Ok(shingles)
+    }
+
+    /// Parse char_filters from JSON - an array of objects tagged by their "type"
+    fn parse_char_filters(value: &serde_json::Value) -> Result<Vec<CharFilter>, anyhow::Error> {
+        let filters = value
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("char_filters must be an array of objects"))?;
+        filters
+            .iter()
+            .map(|filter| {
+                let filter_type = filter["type"].as_str().ok_or_else(|| {
+                    anyhow::anyhow!("a char filter requires a 'type' field, found: {filter:#?}")
+                })?;
+                let filter = match filter_type {
+                    "html_strip" => CharFilter::HtmlStrip,
+                    "mapping" => {
+                        let mappings = match &filter["mappings"] {
+                            serde_json::Value::String(s) => CharFilter::parse_mappings(s)?,
+                            serde_json::Value::Array(mappings) => mappings
+                                .iter()
+                                .map(|m| {
+                                    m.as_str().ok_or_else(|| {
+                                        anyhow::anyhow!("mappings must be an array of strings")
+                                    })
+                                })
+                                .collect::<Result<Vec<_>, _>>()?
+                                .into_iter()
+                                .map(CharFilter::parse_mappings)
+                                .collect::<Result<Vec<_>, _>>()?
+                                .into_iter()
+                                .flatten()
+                                .collect(),
+                            _ => {
+                                return Err(anyhow::anyhow!(
+                                    "mapping char filter requires a 'mappings' field"
+                                ));
+                            }
+                        };
+                        CharFilter::Mapping { mappings }
+                    }
+                    "pattern_replace" => CharFilter::PatternReplace {
+                        pattern: filter["pattern"]
+                            .as_str()
+                            .ok_or_else(|| {
+                                anyhow::anyhow!(
+                                    "pattern_replace char filter requires a string 'pattern' field"
+                                )
+                            })?
+                            .to_string(),
+                        replacement: filter["replacement"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                    },
+                    other => return Err(anyhow::anyhow!("unknown char filter type: {other}")),
+                };
+                filter.validate()?;
+                Ok(filter)
+            })
+            .collect()
+    }
+
     fn name_suffix(&self) -> String {
         let mut buffer = String::new();
         let mut is_empty = true;
@@ -240,6 +696,79 @@ impl SearchTokenizerFilters {
             write!(buffer, "{}ascii_folding={value}", sep(is_empty)).unwrap();
             is_empty = false;
         }
+        if let Some(value) = self.synonyms.as_ref() {
+            write!(buffer, "{}synonyms={:?}", sep(is_empty), value.rules).unwrap();
+            if let Some(table) = value.table.as_ref() {
+                write!(buffer, ",synonyms_table={table}").unwrap();
+            }
+            is_empty = false;
+        }
+        if let Some(value) = self.phonetic {
+            write!(buffer, "{}phonetic={value:?}", sep(is_empty)).unwrap();
+            if let Some(replace) = self.phonetic_replace {
+                write!(buffer, ",phonetic_replace={replace}").unwrap();
+            }
+            is_empty = false;
+        }
+        if let Some(value) = self.shingles.as_ref() {
+            write!(
+                buffer,
+                "{}min_shingle_size={},max_shingle_size={},output_unigrams={},filler_token={:?}",
+                sep(is_empty),
+                value.min_shingle_size,
+                value.max_shingle_size,
+                value.output_unigrams,
+                value.filler_token
+            )
+            .unwrap();
+            is_empty = false;
+        }
+        if let Some(value) = self.hunspell.as_ref() {
+            write!(buffer, "{}hunspell={}", sep(is_empty), value.dictionary).unwrap();
+            if let Some(table) = value.table.as_ref() {
+                write!(buffer, ",hunspell_table={table}").unwrap();
+            }
+            if value.longest_only {
+                write!(buffer, ",hunspell_longest_only=true").unwrap();
+            }
+            is_empty = false;
+        }
+        if let Some(value) = self.decompounder.as_ref() {
+            write!(
+                buffer,
+                "{}decompounder_words={:?}",
+                sep(is_empty),
+                value.words
+            )
+            .unwrap();
+            if let Some(table) = value.table.as_ref() {
+                write!(buffer, ",decompounder_table={table}").unwrap();
+            }
+            write!(
+                buffer,
+                ",min_word_size={},min_subword_size={},max_subword_size={},only_longest_match={}",
+                value.min_word_size,
+                value.min_subword_size,
+                value.max_subword_size,
+                value.only_longest_match
+            )
+            .unwrap();
+            is_empty = false;
+        }
+        if let Some(value) = self.word_delimiter {
+            write!(buffer, "{}{}", sep(is_empty), value.name_suffix()).unwrap();
+            is_empty = false;
+        }
+        if let Some(value) = self.char_filters.as_ref() {
+            write!(
+                buffer,
+                "{}{}",
+                sep(is_empty),
+                CharFilter::name_suffix(value)
+            )
+            .unwrap();
+            is_empty = false;
+        }
 
         if is_empty {
             "".into()
@@ -248,6 +777,25 @@ impl SearchTokenizerFilters {
         }
     }
 
+    /// Checks that the filters can be built, so that a tokenizer with them can't fail later, when
+    /// a document is indexed or a query is tokenized.  Hunspell dictionaries are checked when
+    /// they're read.
+    pub fn validate(&self) -> Result<()> {
+        self.char_filter_tokenizer(RawTokenizer::default())?;
+        self.synonym_filter()?;
+        self.decompounder_filter()?;
+        self.shingle_filter()?;
+        Ok(())
+    }
+
+    fn char_filter_tokenizer<T: Tokenizer>(&self, tokenizer: T) -> Result<CharFilterTokenizer<T>> {
+        CharFilterTokenizer::new(tokenizer, self.char_filters.as_deref().unwrap_or_default())
+    }
+
+    fn word_delimiter_filter(&self) -> Option<WordDelimiterFilter> {
+        self.word_delimiter.map(WordDelimiterFilter::new)
+    }
+
     fn token_length_filter(&self) -> Option<TokenLengthFilter> {
         match (self.remove_short, self.remove_long) {
             (None, None) => None,
@@ -262,10 +810,41 @@ impl SearchTokenizerFilters {
         }
     }
 
+    fn synonym_filter(&self) -> Result<Option<SynonymFilter>> {
+        let Some(synonyms) = self.synonyms.as_ref() else {
+            return Ok(None);
+        };
+        let map = SynonymMap::from_rules(&synonyms.all_rules(), self.lower_caser().is_some())?;
+        Ok(Some(SynonymFilter::new(map)))
+    }
+
+    fn decompounder_filter(&self) -> Result<Option<DecompounderFilter>> {
+        self.decompounder
+            .as_ref()
+            .map(SearchDecompounder::filter)
+            .transpose()
+    }
+
     fn stemmer(&self) -> Option<Stemmer> {
         self.stemmer.map(Stemmer::new)
     }
 
+    fn hunspell_filter(&self) -> Result<Option<HunspellFilter>> {
+        let Some(hunspell) = self.hunspell.as_ref() else {
+            return Ok(None);
+        };
+        let dictionary = hunspell::dictionary(
+            &hunspell.dictionary,
+            hunspell.table.as_deref(),
+            hunspell.version,
+        )?;
+        Ok(Some(HunspellFilter::new(
+            dictionary,
+            hunspell.longest_only,
+            self.lower_caser().is_some(),
+        )))
+    }
+
     /// Returns StopWordFilters for all specified languages.
     /// Uses Tantivy's built-in StopWordFilter::new() for each language.
     fn stopwords_languages(&self) -> Vec<StopWordFilter> {
@@ -286,6 +865,18 @@ impl SearchTokenizerFilters {
             .map(|stopwords| StopWordFilter::remove(stopwords.clone()))
     }
 
+    fn phonetic_filter(&self) -> Option<PhoneticFilter> {
+        self.phonetic
+            .map(|algorithm| PhoneticFilter::new(algorithm, self.phonetic_replace.unwrap_or(true)))
+    }
+
+    fn shingle_filter(&self) -> Result<Option<ShingleFilter>> {
+        self.shingles
+            .as_ref()
+            .map(SearchShingles::filter)
+            .transpose()
+    }
+
     fn alpha_num_only(&self) -> Option<AlphaNumOnlyFilter> {
         match self.alpha_num_only {
             Some(true) => Some(AlphaNumOnlyFilter), // Only enable if explicitly requested.
@@ -313,26 +904,41 @@ impl SearchTokenizerFilters {
 }
 
 macro_rules! add_filters {
-    ($tokenizer:expr, $filters:expr $(, $extra_filter:expr )* $(,)?) => {{
+    ($stages:expr, $tokenizer:expr, $filters:expr $(, $extra_filter:expr )* $(,)?) => {{
         // Build the analyzer with static filters first
-        let mut builder = tantivy::tokenizer::TextAnalyzer::builder($tokenizer)
-            .filter($filters.token_length_filter())
-            .filter($filters.trim_filter())
-            .filter($filters.lower_caser())
-            .filter($filters.stemmer())
-            .filter($filters.stopwords())
-            .filter($filters.ascii_folding())
+        let mut builder = tantivy::tokenizer::TextAnalyzer::builder(
+            $filters.char_filter_tokenizer($tokenizer)?,
+        )
+            .filter($stages.stage("tokenizer", Some(NoFilter)))
+            // Split before lowercasing, so that case changes are still visible
+            .filter($stages.stage("word_delimiter", $filters.word_delimiter_filter()))
+            .filter($stages.stage("token_length", $filters.token_length_filter()))
+            .filter($stages.stage("trim", $filters.trim_filter()))
+            .filter($stages.stage("lowercase", $filters.lower_caser()))
+            .filter($stages.stage("synonyms", $filters.synonym_filter()?))
+            // Sub-words are found before stemming, so that they are stemmed like any other word
+            .filter($stages.stage("decompounder", $filters.decompounder_filter()?))
+            .filter($stages.stage("stemmer", $filters.stemmer()))
+            .filter($stages.stage("hunspell", $filters.hunspell_filter()?))
+            .filter($stages.stage("stopwords", $filters.stopwords()))
+            .filter($stages.stage("ascii_folding", $filters.ascii_folding()))
+            .filter($stages.stage("phonetic", $filters.phonetic_filter()))
             $(
                 .filter($extra_filter)
             )*
-            .filter($filters.alpha_num_only())
+            .filter($stages.stage("alpha_num_only", $filters.alpha_num_only()))
             // Convert to type-erased builder for dynamic filter application
             .dynamic();
         // Apply stopword language filters dynamically in a for loop
         for stopword_filter in $filters.stopwords_languages() {
-            builder = builder.filter_dynamic(stopword_filter);
+            builder = builder
+                .filter_dynamic($stages.stage("stopwords_language", Some(stopword_filter)));
+        }
+        // Shingles are built last, from the tokens every other filter has kept
+        if let Some(shingle_filter) = $filters.shingle_filter()? {
+            builder = builder.filter_dynamic($stages.stage("shingles", Some(shingle_filter)));
         }
-        builder.build()
+        Ok(builder.build())
     }};
 }
 
@@ -553,22 +1159,42 @@ impl SearchTokenizer {
         }
     }
 
-    pub fn to_tantivy_tokenizer(&self) -> Option<tantivy::tokenizer::TextAnalyzer> {
-        let analyzer = match self {
+    pub fn to_tantivy_tokenizer(&self) -> Result<tantivy::tokenizer::TextAnalyzer> {
+        self.analyzer(&())
+    }
+
+    /// Tokenizes `text`, returning the tokens after the tokenizer and after each filter that's
+    /// enabled, in the order they're applied.
+    pub fn explain(&self, text: &str) -> Result<Vec<AnalyzerStage>> {
+        use tantivy::tokenizer::TokenStream;
+
+        let stages = StageTokens::default();
+        let mut analyzer = self.analyzer(&stages)?;
+        let mut token_stream = analyzer.token_stream(text);
+        while token_stream.advance() {}
+        Ok(stages.into_stages())
+    }
+
+    fn analyzer<R: StageRecorder>(&self, stages: &R) -> Result<TextAnalyzer> {
+        match self {
             SearchTokenizer::Simple(filters) => {
-                add_filters!(SimpleTokenizer::default(), filters)
+                add_filters!(stages, SimpleTokenizer::default(), filters)
             }
             // the keyword tokenizer is a special case that does not have filters
-            SearchTokenizer::Keyword => TextAnalyzer::builder(RawTokenizer::default()).build(),
+            SearchTokenizer::Keyword => Ok(TextAnalyzer::builder(RawTokenizer::default())
+                .filter(stages.stage("tokenizer", Some(NoFilter)))
+                .build()),
             #[allow(deprecated)]
             SearchTokenizer::KeywordDeprecated => {
-                TextAnalyzer::builder(RawTokenizer::default()).build()
+                Ok(TextAnalyzer::builder(RawTokenizer::default())
+                    .filter(stages.stage("tokenizer", Some(NoFilter)))
+                    .build())
             }
             SearchTokenizer::LiteralNormalized(filters) => {
-                add_filters!(RawTokenizer::default(), filters)
+                add_filters!(stages, RawTokenizer::default(), filters)
             }
             SearchTokenizer::WhiteSpace(filters) => {
-                add_filters!(WhitespaceTokenizer::default(), filters)
+                add_filters!(stages, WhitespaceTokenizer::default(), filters)
             }
             // this Tokenizer is deprecated because it's bugged. `filters.lower_caser()` provides defaults, but that is the
             // opposite of what the `raw` tokenizer should do.
@@ -577,10 +1203,14 @@ impl SearchTokenizer {
             // that is, doesn't mutate the input tokens
             #[allow(deprecated)]
             SearchTokenizer::Raw(filters) => {
-                add_filters!(RawTokenizer::default(), filters)
+                add_filters!(stages, RawTokenizer::default(), filters)
             }
             SearchTokenizer::RegexTokenizer { pattern, filters } => {
-                add_filters!(RegexTokenizer::new(pattern.as_str()).unwrap(), filters)
+                add_filters!(
+                    stages,
+                    RegexTokenizer::new(pattern.as_str()).unwrap(),
+                    filters
+                )
             }
             SearchTokenizer::Ngram {
                 min_gram,
@@ -589,6 +1219,7 @@ impl SearchTokenizer {
                 positions,
                 filters,
             } => add_filters!(
+                stages,
                 NgramTokenizer::new(*min_gram, *max_gram, *prefix_only, *positions)
                     .unwrap_or_else(|e| panic!("{}", e)),
                 filters
@@ -605,32 +1236,42 @@ impl SearchTokenizer {
                     .collect::<Result<Vec<_>, _>>()
                     .unwrap_or_else(|e| panic!("{}", e));
                 add_filters!(
+                    stages,
                     EdgeNgramTokenizer::new(*min_gram, *max_gram, classes)
                         .unwrap_or_else(|e| panic!("{}", e)),
                     filters
                 )
             }
             SearchTokenizer::ChineseCompatible(filters) => {
-                add_filters!(ChineseTokenizer, filters)
+                add_filters!(stages, ChineseTokenizer, filters)
             }
             SearchTokenizer::SourceCode(filters) => {
                 // for backwards compatibility, the source_code tokenizer defaults to ascii_folding
                 // if it's not explicitly set
                 if filters.ascii_folding().is_none() {
-                    add_filters!(CodeTokenizer::default(), filters, AsciiFoldingFilter)
+                    add_filters!(
+                        stages,
+                        CodeTokenizer::default(),
+                        filters,
+                        stages.stage("ascii_folding", Some(AsciiFoldingFilter))
+                    )
                 } else {
-                    add_filters!(CodeTokenizer::default(), filters)
+                    add_filters!(stages, CodeTokenizer::default(), filters)
                 }
             }
             SearchTokenizer::ChineseLinderaDeprecated(filters)
             | SearchTokenizer::LinderaDeprecated(LinderaLanguage::Chinese, filters) => {
-                add_filters!(LinderaChineseTokenizer::new(true), filters)
+                add_filters!(stages, LinderaChineseTokenizer::new(true), filters)
             }
             SearchTokenizer::ChineseLindera {
                 filters,
                 keep_whitespace,
             } => {
-                add_filters!(LinderaChineseTokenizer::new(*keep_whitespace), filters)
+                add_filters!(
+                    stages,
+                    LinderaChineseTokenizer::new(*keep_whitespace),
+                    filters
+                )
             }
             SearchTokenizer::Lindera {
                 language: LinderaLanguage::Chinese,
@@ -642,19 +1283,24 @@ impl SearchTokenizer {
                 reading_form: _,
             } => {
                 add_filters!(
+                    stages,
                     LinderaChineseTokenizer::with_options(*keep_whitespace, *nfkc),
                     filters
                 )
             }
             SearchTokenizer::JapaneseLinderaDeprecated(filters)
             | SearchTokenizer::LinderaDeprecated(LinderaLanguage::Japanese, filters) => {
-                add_filters!(LinderaJapaneseTokenizer::new(true), filters)
+                add_filters!(stages, LinderaJapaneseTokenizer::new(true), filters)
             }
             SearchTokenizer::JapaneseLindera {
                 filters,
                 keep_whitespace,
             } => {
-                add_filters!(LinderaJapaneseTokenizer::new(*keep_whitespace), filters)
+                add_filters!(
+                    stages,
+                    LinderaJapaneseTokenizer::new(*keep_whitespace),
+                    filters
+                )
             }
             SearchTokenizer::Lindera {
                 language: LinderaLanguage::Japanese,
@@ -664,19 +1310,24 @@ impl SearchTokenizer {
                 reading_form,
             } => {
                 add_filters!(
+                    stages,
                     LinderaJapaneseTokenizer::with_options(*keep_whitespace, *nfkc, *reading_form),
                     filters
                 )
             }
             SearchTokenizer::KoreanLinderaDeprecated(filters)
             | SearchTokenizer::LinderaDeprecated(LinderaLanguage::Korean, filters) => {
-                add_filters!(LinderaKoreanTokenizer::new(true), filters)
+                add_filters!(stages, LinderaKoreanTokenizer::new(true), filters)
             }
             SearchTokenizer::KoreanLindera {
                 filters,
                 keep_whitespace,
             } => {
-                add_filters!(LinderaKoreanTokenizer::new(*keep_whitespace), filters)
+                add_filters!(
+                    stages,
+                    LinderaKoreanTokenizer::new(*keep_whitespace),
+                    filters
+                )
             }
             SearchTokenizer::Lindera {
                 language: LinderaLanguage::Korean,
@@ -686,12 +1337,13 @@ impl SearchTokenizer {
                 reading_form,
             } => {
                 add_filters!(
+                    stages,
                     LinderaKoreanTokenizer::with_options(*keep_whitespace, *nfkc, *reading_form),
                     filters
                 )
             }
             SearchTokenizer::ICUTokenizer(filters) => {
-                add_filters!(ICUTokenizer, filters)
+                add_filters!(stages, ICUTokenizer, filters)
             }
             SearchTokenizer::Jieba {
                 chinese_convert,
@@ -703,9 +1355,10 @@ impl SearchTokenizer {
                         tantivy_jieba::JiebaTokenizer::with_ordinal_position_mode(true);
                     let convert_tokenizer =
                         ChineseConvertTokenizer::new(base_tokenizer, *convert_mode);
-                    add_filters!(convert_tokenizer, filters)
+                    add_filters!(stages, convert_tokenizer, filters)
                 } else {
                     add_filters!(
+                        stages,
                         tantivy_jieba::JiebaTokenizer::with_ordinal_position_mode(true),
                         filters
                     )
@@ -726,14 +1379,12 @@ impl SearchTokenizer {
                 remove_emojis,
                 filters,
             } => {
-                add_filters!(UnicodeWordsTokenizer::new(*remove_emojis), filters)
+                add_filters!(stages, UnicodeWordsTokenizer::new(*remove_emojis), filters)
             }
-        };
-
-        Some(analyzer)
+        }
     }
 
-    fn filters(&self) -> &SearchTokenizerFilters {
+    pub fn filters(&self) -> &SearchTokenizerFilters {
         match self {
             SearchTokenizer::Simple(filters) => filters,
             SearchTokenizer::Keyword => SearchTokenizerFilters::keyword(),
@@ -763,6 +1414,37 @@ impl SearchTokenizer {
         }
     }
 
+    /// The tokenizer's filters, unless it's one of the keyword tokenizers, whose filters are fixed.
+    pub fn filters_mut(&mut self) -> Option<&mut SearchTokenizerFilters> {
+        match self {
+            SearchTokenizer::Simple(filters) => Some(filters),
+            SearchTokenizer::Keyword => None,
+            #[allow(deprecated)]
+            SearchTokenizer::KeywordDeprecated => None,
+            #[allow(deprecated)]
+            SearchTokenizer::Raw(filters) => Some(filters),
+            SearchTokenizer::LiteralNormalized(filters) => Some(filters),
+            SearchTokenizer::WhiteSpace(filters) => Some(filters),
+            SearchTokenizer::RegexTokenizer { filters, .. } => Some(filters),
+            SearchTokenizer::ChineseCompatible(filters) => Some(filters),
+            SearchTokenizer::SourceCode(filters) => Some(filters),
+            SearchTokenizer::Ngram { filters, .. } => Some(filters),
+            SearchTokenizer::EdgeNgram { filters, .. } => Some(filters),
+            SearchTokenizer::ChineseLinderaDeprecated(filters) => Some(filters),
+            SearchTokenizer::ChineseLindera { filters, .. } => Some(filters),
+            SearchTokenizer::JapaneseLinderaDeprecated(filters) => Some(filters),
+            SearchTokenizer::JapaneseLindera { filters, .. } => Some(filters),
+            SearchTokenizer::KoreanLinderaDeprecated(filters) => Some(filters),
+            SearchTokenizer::KoreanLindera { filters, .. } => Some(filters),
+            SearchTokenizer::LinderaDeprecated(_, filters) => Some(filters),
+            SearchTokenizer::Lindera { filters, .. } => Some(filters),
+            SearchTokenizer::ICUTokenizer(filters) => Some(filters),
+            SearchTokenizer::Jieba { filters, .. } => Some(filters),
+            SearchTokenizer::UnicodeWordsDeprecated { filters, .. } => Some(filters),
+            SearchTokenizer::UnicodeWords { filters, .. } => Some(filters),
+        }
+    }
+
     pub fn normalizer(&self) -> Option<SearchNormalizer> {
         self.filters().normalizer()
     }
@@ -795,6 +1477,12 @@ pub static LANGUAGES: Lazy<HashMap<Language, &str>> = Lazy::new(|| {
 
 impl SearchTokenizer {
     pub fn name(&self) -> String {
+        // the analyzer's configuration is only filled in once the index is built, so it can't
+        // take part in the name
+        if let Some(analyzer) = self.filters().analyzer.as_ref() {
+            return format!("analyzer_{analyzer}");
+        }
+
         let filters_suffix = self.filters().name_suffix();
         match self {
             SearchTokenizer::Simple(_filters) => format!("default{filters_suffix}"),
@@ -993,6 +1681,15 @@ mod tests {
                     trim: None,
                     normalizer: None,
                     alpha_num_only: None,
+                    synonyms: None,
+                    char_filters: None,
+                    phonetic: None,
+                    phonetic_replace: None,
+                    shingles: None,
+                    word_delimiter: None,
+                    decompounder: None,
+                    hunspell: None,
+                    analyzer: None,
                 }
             }
         );
@@ -1018,6 +1715,15 @@ mod tests {
                 trim: None,
                 normalizer: None,
                 alpha_num_only: None,
+                synonyms: None,
+                char_filters: None,
+                phonetic: None,
+                phonetic_replace: None,
+                shingles: None,
+                word_delimiter: None,
+                decompounder: None,
+                hunspell: None,
+                analyzer: None,
             },
         };
 
@@ -1065,6 +1771,15 @@ mod tests {
                     trim: None,
                     normalizer: None,
                     alpha_num_only: None,
+                    synonyms: None,
+                    char_filters: None,
+                    phonetic: None,
+                    phonetic_replace: None,
+                    shingles: None,
+                    word_delimiter: None,
+                    decompounder: None,
+                    hunspell: None,
+                    analyzer: None,
                 }
             }
         );
@@ -1121,6 +1836,15 @@ mod tests {
                     trim: None,
                     normalizer: None,
                     alpha_num_only: None,
+                    synonyms: None,
+                    char_filters: None,
+                    phonetic: None,
+                    phonetic_replace: None,
+                    shingles: None,
+                    word_delimiter: None,
+                    decompounder: None,
+                    hunspell: None,
+                    analyzer: None,
                 }
             }
         );
@@ -1176,6 +1900,15 @@ mod tests {
                     trim: Some(true),
                     normalizer: None,
                     alpha_num_only: None,
+                    synonyms: None,
+                    char_filters: None,
+                    phonetic: None,
+                    phonetic_replace: None,
+                    shingles: None,
+                    word_delimiter: None,
+                    decompounder: None,
+                    hunspell: None,
+                    analyzer: None,
                 }
             }
         );
@@ -1228,6 +1961,15 @@ mod tests {
                 trim: Some(true),
                 normalizer: None,
                 alpha_num_only: None,
+                synonyms: None,
+                char_filters: None,
+                phonetic: None,
+                phonetic_replace: None,
+                shingles: None,
+                word_delimiter: None,
+                decompounder: None,
+                hunspell: None,
+                analyzer: None,
             })
         );
 
@@ -1413,7 +2155,7 @@ mod tests {
         // Create in-memory index and register tokenizers
         let directory = RamDirectory::create();
         let mut index = Index::create(directory, schema.clone(), Default::default()).unwrap();
-        let tokenizer_manager = crate::create_tokenizer_manager(tokenizers);
+        let tokenizer_manager = crate::create_tokenizer_manager(tokenizers).unwrap();
         index.set_tokenizers(tokenizer_manager);
 
         let content_field = schema.get_field("content").unwrap();
@@ -1445,4 +2187,371 @@ mod tests {
             "tokenizer (name={tokenizer_name}) whitespace did not keep/remove whitespace as expected"
         );
     }
+
+    #[rstest]
+    fn test_synonyms_filter() {
+        use tantivy::tokenizer::TokenStream;
+
+        let json = r#"{
+            "type": "default",
+            "synonyms": ["tv, television", "telly => television"]
+        }"#;
+
+        let tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(
+            tokenizer.name(),
+            r#"default[synonyms=["tv, television", "telly => television"]]"#
+        );
+
+        let mut analyzer = tokenizer.to_tantivy_tokenizer().unwrap();
+        let mut token_stream = analyzer.token_stream("Cheap TV and a telly");
+
+        let mut tokens = Vec::new();
+        while token_stream.advance() {
+            let token = token_stream.token();
+            tokens.push((token.text.clone(), token.position));
+        }
+
+        // rules are lowercased to match the lowercased tokens
+        assert_eq!(
+            tokens,
+            vec![
+                ("cheap".to_string(), 0),
+                ("tv".to_string(), 1),
+                ("television".to_string(), 1),
+                ("and".to_string(), 2),
+                ("a".to_string(), 3),
+                ("television".to_string(), 4),
+            ]
+        );
+
+        let json = r#"{"type": "default", "synonyms": "tv"}"#;
+        assert!(SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).is_err());
+    }
+
+    #[rstest]
+    fn test_char_filters() {
+        use tantivy::tokenizer::TokenStream;
+
+        let json = r#"{
+            "type": "default",
+            "char_filters": [
+                {"type": "html_strip"},
+                {"type": "mapping", "mappings": ["& => and"]}
+            ]
+        }"#;
+
+        let tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(
+            tokenizer.name(),
+            r#"default[char_filters=[html_strip,mapping=[("&", "and")]]]"#
+        );
+
+        let text = "<p>Salt &amp; Pepper</p>";
+        let mut analyzer = tokenizer.to_tantivy_tokenizer().unwrap();
+        let mut token_stream = analyzer.token_stream(text);
+
+        let mut tokens = Vec::new();
+        while token_stream.advance() {
+            let token = token_stream.token();
+            tokens.push((
+                token.text.clone(),
+                &text[token.offset_from..token.offset_to],
+            ));
+        }
+
+        // offsets point at the original, unfiltered text
+        assert_eq!(
+            tokens,
+            vec![
+                ("salt".to_string(), "Salt"),
+                ("and".to_string(), "&amp;"),
+                ("pepper".to_string(), "Pepper"),
+            ]
+        );
+
+        let json =
+            r#"{"type": "default", "char_filters": [{"type": "pattern_replace", "pattern": "("}]}"#;
+        assert!(SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).is_err());
+    }
+
+    #[rstest]
+    fn test_phonetic_filter() {
+        use tantivy::tokenizer::TokenStream;
+
+        let json = r#"{"type": "default", "phonetic": "double_metaphone"}"#;
+        let tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(tokenizer.name(), "default[phonetic=DoubleMetaphone]");
+
+        let mut analyzer = tokenizer.to_tantivy_tokenizer().unwrap();
+        let mut encode = |text: &str| {
+            let mut token_stream = analyzer.token_stream(text);
+            let mut codes = Vec::new();
+            while token_stream.advance() {
+                codes.push(token_stream.token().text.clone());
+            }
+            codes
+        };
+        let smith = encode("Smith");
+        let schmidt = encode("Schmidt");
+        assert!(smith.iter().any(|code| schmidt.contains(code)));
+
+        let json = r#"{"type": "default", "phonetic": "caverphone"}"#;
+        assert!(SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).is_err());
+    }
+
+    #[rstest]
+    fn test_shingle_filter() {
+        use tantivy::tokenizer::TokenStream;
+
+        let json = r#"{
+            "type": "default",
+            "stopwords": ["the"],
+            "max_shingle_size": 3,
+            "output_unigrams": false
+        }"#;
+        let tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(
+            tokenizer.name(),
+            r#"default[stopwords=["the"],min_shingle_size=2,max_shingle_size=3,output_unigrams=false,filler_token="_"]"#
+        );
+
+        let mut analyzer = tokenizer.to_tantivy_tokenizer().unwrap();
+        let mut token_stream = analyzer.token_stream("The quick brown fox");
+        let mut tokens = Vec::new();
+        while token_stream.advance() {
+            tokens.push(token_stream.token().text.clone());
+        }
+        assert_eq!(
+            tokens,
+            vec![
+                "_ quick",
+                "_ quick brown",
+                "quick brown",
+                "quick brown fox",
+                "brown fox"
+            ]
+        );
+
+        let json = r#"{"type": "default", "min_shingle_size": 3, "max_shingle_size": 2}"#;
+        assert!(SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).is_err());
+
+        // options that weren't parsed, like those of a stored tokenizer, are an error to build
+        let tokenizer = SearchTokenizer::Simple(SearchTokenizerFilters {
+            shingles: Some(SearchShingles {
+                min_shingle_size: 3,
+                max_shingle_size: 2,
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        assert!(tokenizer.filters().validate().is_err());
+        assert!(tokenizer.to_tantivy_tokenizer().is_err());
+    }
+
+    #[test]
+    fn test_word_delimiter_filter() {
+        use tantivy::tokenizer::TokenStream;
+
+        let json = r#"{
+            "type": "whitespace",
+            "word_delimiter": {"preserve_original": true}
+        }"#;
+        let tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(
+            tokenizer.name(),
+            "whitespace[word_delimiter=[preserve_original=true]]"
+        );
+
+        let mut analyzer = tokenizer.to_tantivy_tokenizer().unwrap();
+        let mut token_stream = analyzer.token_stream("PowerShot SD500");
+        let mut tokens = Vec::new();
+        while token_stream.advance() {
+            let token = token_stream.token();
+            tokens.push((token.text.clone(), token.position));
+        }
+        assert_eq!(
+            tokens,
+            vec![
+                ("powershot".to_string(), 0),
+                ("power".to_string(), 0),
+                ("shot".to_string(), 1),
+                ("sd500".to_string(), 2),
+                ("sd".to_string(), 2),
+                ("500".to_string(), 3),
+            ]
+        );
+
+        let json = r#"{"type": "whitespace", "word_delimiter": "yes"}"#;
+        assert!(SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_decompounder_filter() {
+        use tantivy::tokenizer::TokenStream;
+
+        let json = r#"{
+            "type": "default",
+            "decompounder_words": ["kranken", "versicherung", "beitrag"],
+            "only_longest_match": true
+        }"#;
+        let tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(
+            tokenizer.name(),
+            r#"default[decompounder_words=["kranken", "versicherung", "beitrag"],min_word_size=5,min_subword_size=2,max_subword_size=15,only_longest_match=true]"#
+        );
+
+        let mut analyzer = tokenizer.to_tantivy_tokenizer().unwrap();
+        let mut token_stream = analyzer.token_stream("Krankenversicherungsbeitrag");
+        let mut tokens = Vec::new();
+        while token_stream.advance() {
+            let token = token_stream.token();
+            tokens.push((token.text.clone(), token.position));
+        }
+        assert_eq!(
+            tokens,
+            vec![
+                ("krankenversicherungsbeitrag".to_string(), 0),
+                ("kranken".to_string(), 0),
+                ("versicherung".to_string(), 0),
+                ("beitrag".to_string(), 0),
+            ]
+        );
+
+        let json = r#"{"type": "default", "decompounder_words": "kranken", "min_subword_size": 4, "max_subword_size": 3}"#;
+        assert!(SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_hunspell_filter() {
+        use tantivy::tokenizer::TokenStream;
+
+        fn load(name: &str, table: Option<&str>) -> Result<(Vec<u8>, Vec<u8>)> {
+            assert_eq!((name, table), ("en_TEST", None));
+            Ok((
+                b"SET UTF-8\nSFX S Y 1\nSFX S y ies [^aeiou]y\n".to_vec(),
+                b"1\ncity/S\n".to_vec(),
+            ))
+        }
+        hunspell::set_dictionary_loader(load);
+
+        let json = r#"{"type": "default", "hunspell": "en_TEST"}"#;
+        let tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(tokenizer.name(), "default[hunspell=en_TEST]");
+
+        let mut analyzer = tokenizer.to_tantivy_tokenizer().unwrap();
+        let mut token_stream = analyzer.token_stream("Cities and towns");
+        let mut tokens = Vec::new();
+        while token_stream.advance() {
+            tokens.push(token_stream.token().text.clone());
+        }
+        assert_eq!(tokens, vec!["city", "and", "towns"]);
+
+        // a version other than the current one can't be loaded
+        let version = hunspell::load("en_TEST", None).unwrap();
+        assert!(hunspell::dictionary("en_TEST", None, Some(version)).is_ok());
+        assert!(hunspell::dictionary("en_TEST", None, Some(version + 1)).is_err());
+
+        let json = r#"{"type": "default", "hunspell": true}"#;
+        assert!(SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_analyzer() {
+        let json = r#"{"type": "ngram", "min_gram": 0, "max_gram": 0, "prefix_only": false, "analyzer": "trigrams"}"#;
+        let mut tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(tokenizer.filters().analyzer.as_deref(), Some("trigrams"));
+        assert_eq!(tokenizer.name(), "analyzer_trigrams");
+
+        // filling in the analyzer's configuration doesn't change the name
+        if let SearchTokenizer::Ngram {
+            min_gram, max_gram, ..
+        } = &mut tokenizer
+        {
+            *min_gram = 3;
+            *max_gram = 3;
+        }
+        assert_eq!(tokenizer.name(), "analyzer_trigrams");
+
+        let json = r#"{"type": "default", "analyzer": 1}"#;
+        assert!(SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_explain_stages() {
+        let json = r#"{"type": "default", "stemmer": "English", "stopwords": ["the"]}"#;
+        let tokenizer =
+            SearchTokenizer::from_json_value(&serde_json::from_str(json).unwrap()).unwrap();
+
+        let stages = tokenizer
+            .explain("The Runners ran")
+            .unwrap()
+            .into_iter()
+            .map(|stage| {
+                let tokens = stage
+                    .tokens
+                    .into_iter()
+                    .map(|token| {
+                        (
+                            token.text,
+                            token.position,
+                            token.offset_from,
+                            token.offset_to,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                (stage.name, tokens)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            stages,
+            vec![
+                (
+                    "tokenizer",
+                    vec![
+                        ("The".to_string(), 0, 0, 3),
+                        ("Runners".to_string(), 1, 4, 11),
+                        ("ran".to_string(), 2, 12, 15),
+                    ]
+                ),
+                (
+                    "lowercase",
+                    vec![
+                        ("the".to_string(), 0, 0, 3),
+                        ("runners".to_string(), 1, 4, 11),
+                        ("ran".to_string(), 2, 12, 15),
+                    ]
+                ),
+                (
+                    "stemmer",
+                    vec![
+                        ("the".to_string(), 0, 0, 3),
+                        ("runner".to_string(), 1, 4, 11),
+                        ("ran".to_string(), 2, 12, 15),
+                    ]
+                ),
+                (
+                    "stopwords",
+                    vec![
+                        ("runner".to_string(), 1, 4, 11),
+                        ("ran".to_string(), 2, 12, 15),
+                    ]
+                ),
+            ]
+        );
+
+        // the keyword tokenizer has no filters, so its only stage is the tokenizer
+        let stages = SearchTokenizer::Keyword.explain("The Runners").unwrap();
+        assert_eq!(stages.len(), 1);
+        assert_eq!(stages[0].name, "tokenizer");
+        assert_eq!(stages[0].tokens[0].text, "The Runners");
+    }
 }
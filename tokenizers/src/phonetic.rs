@@ -0,0 +1,254 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use rphonetic::{
+    BeiderMorse, BeiderMorseBuilder, ConfigFiles, DoubleMetaphone, Encoder, Metaphone, Soundex,
+};
+use serde::{Deserialize, Serialize};
+use tantivy::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// The Beider-Morse rules are large, so they are only loaded once and shared by every filter.
+static BEIDER_MORSE_RULES: Lazy<ConfigFiles> = Lazy::new(ConfigFiles::default);
+
+/// Phonetic encoding algorithms
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, strum::EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum PhoneticAlgorithm {
+    /// American Soundex, e.g. `Robert` -> `R163`
+    Soundex,
+    /// The original Metaphone algorithm
+    Metaphone,
+    /// Double Metaphone, which emits both a primary and an alternate code
+    DoubleMetaphone,
+    /// Beider-Morse Phonetic Matching, which emits every plausible pronunciation
+    BeiderMorse,
+}
+
+enum PhoneticEncoder {
+    Soundex(Soundex),
+    Metaphone(Metaphone),
+    DoubleMetaphone(DoubleMetaphone),
+    BeiderMorse(Box<BeiderMorse<'static>>),
+}
+
+impl PhoneticEncoder {
+    fn new(algorithm: PhoneticAlgorithm) -> Self {
+        match algorithm {
+            PhoneticAlgorithm::Soundex => PhoneticEncoder::Soundex(Soundex::default()),
+            PhoneticAlgorithm::Metaphone => PhoneticEncoder::Metaphone(Metaphone::default()),
+            PhoneticAlgorithm::DoubleMetaphone => {
+                PhoneticEncoder::DoubleMetaphone(DoubleMetaphone::default())
+            }
+            PhoneticAlgorithm::BeiderMorse => PhoneticEncoder::BeiderMorse(Box::new(
+                BeiderMorseBuilder::new(&BEIDER_MORSE_RULES).build(),
+            )),
+        }
+    }
+
+    /// Returns the distinct codes for `text`, which is empty if the text has nothing to encode
+    /// (digits or punctuation, for instance).
+    fn encode(&self, text: &str) -> Vec<String> {
+        let encoded = match self {
+            PhoneticEncoder::Soundex(soundex) => vec![soundex.encode(text)],
+            PhoneticEncoder::Metaphone(metaphone) => vec![metaphone.encode(text)],
+            PhoneticEncoder::DoubleMetaphone(double_metaphone) => vec![
+                double_metaphone.encode(text),
+                double_metaphone.encode_alternate(text),
+            ],
+            PhoneticEncoder::BeiderMorse(beider_morse) => beider_morse
+                .encode(text)
+                .split(['|', '(', ')', '-'])
+                .map(String::from)
+                .collect(),
+        };
+
+        let mut codes = Vec::with_capacity(encoded.len());
+        for code in encoded {
+            if !code.is_empty() && !codes.contains(&code) {
+                codes.push(code);
+            }
+        }
+        codes
+    }
+}
+
+/// `PhoneticFilter` replaces each token with its phonetic codes, or adds the codes alongside the
+/// token. This matches the behavior of Elasticsearch's phonetic token filter.
+///
+/// Every code is emitted at the position of the token it was computed from, so phrase queries
+/// keep working. Tokens that have no phonetic code, such as numbers, are passed through as-is.
+#[derive(Clone)]
+pub struct PhoneticFilter {
+    encoder: Arc<PhoneticEncoder>,
+    replace: bool,
+}
+
+impl PhoneticFilter {
+    /// Creates a `PhoneticFilter`. If `replace` is false, the original tokens are kept.
+    pub fn new(algorithm: PhoneticAlgorithm, replace: bool) -> PhoneticFilter {
+        PhoneticFilter {
+            encoder: Arc::new(PhoneticEncoder::new(algorithm)),
+            replace,
+        }
+    }
+}
+
+impl TokenFilter for PhoneticFilter {
+    type Tokenizer<T: Tokenizer> = PhoneticFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> PhoneticFilterWrapper<T> {
+        PhoneticFilterWrapper {
+            encoder: self.encoder,
+            replace: self.replace,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PhoneticFilterWrapper<T: Tokenizer> {
+    encoder: Arc<PhoneticEncoder>,
+    replace: bool,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for PhoneticFilterWrapper<T> {
+    type TokenStream<'a> = PhoneticFilterStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        PhoneticFilterStream {
+            encoder: &self.encoder,
+            replace: self.replace,
+            tail: self.inner.token_stream(text),
+            pending: VecDeque::new(),
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct PhoneticFilterStream<'a, T> {
+    encoder: &'a PhoneticEncoder,
+    replace: bool,
+    tail: T,
+    /// Codes of the current token that haven't been returned yet.
+    pending: VecDeque<Token>,
+    token: Token,
+}
+
+impl<T: TokenStream> TokenStream for PhoneticFilterStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        if let Some(token) = self.pending.pop_front() {
+            self.token = token;
+            return true;
+        }
+        if !self.tail.advance() {
+            return false;
+        }
+
+        let original = self.tail.token();
+        let codes = self.encoder.encode(&original.text);
+        if codes.is_empty() {
+            self.token = original.clone();
+            return true;
+        }
+
+        if !self.replace {
+            self.pending.push_back(original.clone());
+        }
+        for code in codes {
+            if self.replace || code != original.text {
+                self.pending.push_back(Token {
+                    text: code,
+                    ..original.clone()
+                });
+            }
+        }
+        self.token = self.pending.pop_front().expect("pending is not empty");
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::tokenizer::{SimpleTokenizer, TextAnalyzer};
+
+    fn tokens(algorithm: PhoneticAlgorithm, replace: bool, text: &str) -> Vec<(String, usize)> {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(PhoneticFilter::new(algorithm, replace))
+            .build();
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        stream.process(&mut |token| tokens.push((token.text.clone(), token.position)));
+        tokens
+    }
+
+    #[test]
+    fn test_soundex() {
+        assert_eq!(
+            tokens(PhoneticAlgorithm::Soundex, true, "Robert Rupert"),
+            vec![("R163".to_string(), 0), ("R163".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_double_metaphone_matches_spelling_variants() {
+        let smith = tokens(PhoneticAlgorithm::DoubleMetaphone, true, "Smith");
+        let schmidt = tokens(PhoneticAlgorithm::DoubleMetaphone, true, "Schmidt");
+        assert!(
+            smith.iter().any(|code| schmidt.contains(code)),
+            "{smith:?} and {schmidt:?} share no code"
+        );
+    }
+
+    #[test]
+    fn test_keep_original() {
+        let tokens = tokens(PhoneticAlgorithm::Soundex, false, "Robert 42");
+        assert_eq!(
+            tokens,
+            vec![
+                ("Robert".to_string(), 0),
+                ("R163".to_string(), 0),
+                ("42".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_beider_morse_emits_codes_at_the_same_position() {
+        let tokens = tokens(PhoneticAlgorithm::BeiderMorse, true, "Angelo");
+        assert!(tokens.len() > 1);
+        assert!(
+            tokens
+                .iter()
+                .all(|(code, position)| !code.is_empty() && *position == 0)
+        );
+    }
+}
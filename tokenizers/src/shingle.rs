@@ -0,0 +1,283 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use tantivy::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
+
+const TOKEN_SEPARATOR: &str = " ";
+
+/// `ShingleFilter` emits word n-grams ("shingles") built from consecutive tokens.
+/// This matches the behavior of Elasticsearch's shingle token filter.
+///
+/// A shingle takes the position of its first token and spans its tokens through
+/// `position_length`. Positions left empty by an earlier filter, like a removed stopword, are
+/// filled with `filler_token`, so that shingles never join words that weren't adjacent.
+#[derive(Clone)]
+pub struct ShingleFilter {
+    min_size: usize,
+    max_size: usize,
+    output_unigrams: bool,
+    filler_token: String,
+}
+
+impl ShingleFilter {
+    pub fn new(
+        min_size: usize,
+        max_size: usize,
+        output_unigrams: bool,
+        filler_token: String,
+    ) -> Result<ShingleFilter> {
+        if min_size < 2 {
+            anyhow::bail!("min_shingle_size must be at least 2, got {min_size}");
+        }
+        if max_size < min_size {
+            anyhow::bail!(
+                "max_shingle_size must be at least min_shingle_size ({min_size}), got {max_size}"
+            );
+        }
+        Ok(ShingleFilter {
+            min_size,
+            max_size,
+            output_unigrams,
+            filler_token,
+        })
+    }
+}
+
+impl TokenFilter for ShingleFilter {
+    type Tokenizer<T: Tokenizer> = ShingleFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> ShingleFilterWrapper<T> {
+        ShingleFilterWrapper {
+            filter: self,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ShingleFilterWrapper<T: Tokenizer> {
+    filter: ShingleFilter,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for ShingleFilterWrapper<T> {
+    type TokenStream<'a> = ShingleFilterStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        ShingleFilterStream {
+            filter: &self.filter,
+            tail: self.inner.token_stream(text),
+            tail_exhausted: false,
+            window: VecDeque::new(),
+            next_position: 0,
+            output: VecDeque::new(),
+            token: Token::default(),
+        }
+    }
+}
+
+/// A token in the shingle window, or a filler standing in for an empty position.
+struct Slot {
+    token: Token,
+    is_filler: bool,
+}
+
+pub struct ShingleFilterStream<'a, T> {
+    filter: &'a ShingleFilter,
+    tail: T,
+    tail_exhausted: bool,
+    /// Consecutive positions, starting with the one the next shingles start at.
+    window: VecDeque<Slot>,
+    /// The position following the last token read from `tail`.
+    next_position: usize,
+    /// Tokens ready to be returned, in position order.
+    output: VecDeque<Token>,
+    token: Token,
+}
+
+impl<T: TokenStream> ShingleFilterStream<'_, T> {
+    fn fill_window(&mut self) {
+        while !self.tail_exhausted && self.window.len() < self.filter.max_size {
+            if !self.tail.advance() {
+                self.tail_exhausted = true;
+                break;
+            }
+
+            let token = self.tail.token().clone();
+            // Only as many fillers as could fit in a shingle are needed to keep the words on
+            // either side of a gap apart.
+            let gap = token.position.saturating_sub(self.next_position);
+            for i in gap.saturating_sub(self.filter.max_size - 1)..gap {
+                self.window.push_back(Slot {
+                    token: Token {
+                        offset_from: token.offset_from,
+                        offset_to: token.offset_from,
+                        position: self.next_position + i,
+                        text: self.filter.filler_token.clone(),
+                        position_length: 1,
+                    },
+                    is_filler: true,
+                });
+            }
+            self.next_position = token.position + 1;
+            self.window.push_back(Slot {
+                token,
+                is_filler: false,
+            });
+        }
+    }
+
+    /// Emits the unigram and the shingles starting at the front of the window.
+    fn process_next(&mut self) {
+        let first = self.window.front().expect("window is not empty");
+        if self.filter.output_unigrams && !first.is_filler {
+            self.output.push_back(first.token.clone());
+        }
+
+        for size in self.filter.min_size..=self.filter.max_size.min(self.window.len()) {
+            let slots = self.window.range(..size);
+            if slots.clone().all(|slot| slot.is_filler) {
+                continue;
+            }
+
+            let mut text = String::new();
+            for (i, slot) in slots.enumerate() {
+                if i > 0 {
+                    text.push_str(TOKEN_SEPARATOR);
+                }
+                text.push_str(&slot.token.text);
+            }
+            self.output.push_back(Token {
+                offset_from: first.token.offset_from,
+                offset_to: self.window[size - 1].token.offset_to,
+                position: first.token.position,
+                text,
+                position_length: size,
+            });
+        }
+
+        self.window.pop_front();
+    }
+}
+
+impl<T: TokenStream> TokenStream for ShingleFilterStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        while self.output.is_empty() {
+            self.fill_window();
+            if self.window.is_empty() {
+                return false;
+            }
+            self.process_next();
+        }
+
+        self.token = self.output.pop_front().expect("output is not empty");
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShingleFilter;
+    use tantivy::tokenizer::{SimpleTokenizer, StopWordFilter, TextAnalyzer};
+
+    fn tokens(filter: ShingleFilter, text: &str) -> Vec<(String, usize, usize)> {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(StopWordFilter::remove(vec!["the".to_string()]))
+            .filter(filter)
+            .build();
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        stream.process(&mut |token| {
+            tokens.push((token.text.clone(), token.position, token.position_length));
+        });
+        tokens
+    }
+
+    fn shingles(min_size: usize, max_size: usize, output_unigrams: bool) -> ShingleFilter {
+        ShingleFilter::new(min_size, max_size, output_unigrams, "_".to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_bigrams_with_unigrams() {
+        assert_eq!(
+            tokens(shingles(2, 2, true), "quick brown fox"),
+            vec![
+                ("quick".to_string(), 0, 1),
+                ("quick brown".to_string(), 0, 2),
+                ("brown".to_string(), 1, 1),
+                ("brown fox".to_string(), 1, 2),
+                ("fox".to_string(), 2, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shingles_only() {
+        assert_eq!(
+            tokens(shingles(2, 3, false), "quick brown fox"),
+            vec![
+                ("quick brown".to_string(), 0, 2),
+                ("quick brown fox".to_string(), 0, 3),
+                ("brown fox".to_string(), 1, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filler_token() {
+        assert_eq!(
+            tokens(shingles(2, 2, false), "quick the fox"),
+            vec![("quick _".to_string(), 0, 2), ("_ fox".to_string(), 1, 2),]
+        );
+    }
+
+    #[test]
+    fn test_leading_filler_token() {
+        assert_eq!(
+            tokens(shingles(2, 2, true), "the fox"),
+            vec![("_ fox".to_string(), 0, 2), ("fox".to_string(), 1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_offsets_span_the_shingle() {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(shingles(2, 2, false))
+            .build();
+        let mut stream = analyzer.token_stream("quick  brown");
+        let mut offsets = vec![];
+        stream.process(&mut |token| offsets.push((token.offset_from, token.offset_to)));
+        assert_eq!(offsets, vec![(0, 12)]);
+    }
+
+    #[test]
+    fn test_invalid_sizes() {
+        assert!(ShingleFilter::new(1, 2, true, "_".to_string()).is_err());
+        assert!(ShingleFilter::new(3, 2, true, "_".to_string()).is_err());
+    }
+}
@@ -0,0 +1,454 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! A synonym token filter driven by Solr-style rules.
+//!
+//! Two kinds of rules are supported:
+//!
+//! - Equivalence rules, `tv, television, telly`: every term in the list is expanded to all of
+//!   the others, and the original token is kept.
+//! - One-way rules, `tv, telly => television`: every term on the left is replaced by every term
+//!   on the right.
+//!
+//! Terms may contain several words (`united states => usa`, `tv => television set`). Multi-word
+//! inputs only match tokens at consecutive positions. The words of every term emitted for a
+//! match take consecutive positions starting at the first matched token, and the last word of a
+//! shorter term spans the rest of the match through `position_length`.
+//!
+//! The index only records positions, so a synonym with more words than the input it replaces
+//! can't be laid over the input alone: its extra words would share positions with the tokens
+//! that follow, and phrases mixing the two forms would match.  Instead the tokens that follow
+//! are moved back to make room for it, the same way Lucene flattens a synonym graph.  Phrases
+//! over either form keep matching, but a phrase over the original input and the words after it
+//! needs enough slop to cover the difference.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use anyhow::Result;
+use tantivy::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// Separates the words of a multi-word term inside a [`SynonymMap`] key. Tokens never contain
+/// a NUL byte, so a key can't collide with a single token that happens to contain whitespace.
+const WORD_SEPARATOR: &str = "\u{0}";
+
+#[derive(Debug, Default)]
+struct SynonymEntry {
+    /// Whether the matched tokens are emitted alongside their synonyms.
+    keep_original: bool,
+    /// Each synonym, split into its words.
+    synonyms: Vec<Vec<String>>,
+}
+
+/// The compiled form of a list of synonym rules.
+#[derive(Debug, Default)]
+pub struct SynonymMap {
+    entries: HashMap<String, SynonymEntry>,
+    /// The number of words in the longest input term, which bounds the filter's lookahead.
+    max_input_words: usize,
+}
+
+impl SynonymMap {
+    /// Compiles `rules`. When `lowercase` is set, terms are lowercased so they match the output
+    /// of a lowercasing analyzer.
+    pub fn from_rules<S: AsRef<str>>(rules: &[S], lowercase: bool) -> Result<Self> {
+        let mut map = SynonymMap::default();
+
+        for rule in rules {
+            let rule = rule.as_ref().trim();
+            if rule.is_empty() || rule.starts_with('#') {
+                continue;
+            }
+
+            let parse_terms = |side: &str| -> Result<Vec<Vec<String>>> {
+                let terms = side
+                    .split(',')
+                    .map(|term| {
+                        term.split_whitespace()
+                            .map(|word| {
+                                if lowercase {
+                                    word.to_lowercase()
+                                } else {
+                                    word.to_string()
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .filter(|words| !words.is_empty())
+                    .collect::<Vec<_>>();
+                if terms.is_empty() {
+                    anyhow::bail!("synonym rule `{rule}` has an empty side");
+                }
+                Ok(terms)
+            };
+
+            match rule.split_once("=>") {
+                Some((inputs, outputs)) => {
+                    if outputs.contains("=>") {
+                        anyhow::bail!("synonym rule `{rule}` contains more than one `=>`");
+                    }
+                    let inputs = parse_terms(inputs)?;
+                    let outputs = parse_terms(outputs)?;
+                    for input in &inputs {
+                        map.add(input, &outputs, false);
+                    }
+                }
+                None => {
+                    let terms = parse_terms(rule)?;
+                    if terms.len() < 2 {
+                        anyhow::bail!(
+                            "synonym rule `{rule}` must list at least two equivalent terms"
+                        );
+                    }
+                    for term in &terms {
+                        let others = terms
+                            .iter()
+                            .filter(|other| *other != term)
+                            .cloned()
+                            .collect::<Vec<_>>();
+                        map.add(term, &others, true);
+                    }
+                }
+            }
+        }
+
+        Ok(map)
+    }
+
+    fn add(&mut self, input: &[String], outputs: &[Vec<String>], keep_original: bool) {
+        let entry = self.entries.entry(input.join(WORD_SEPARATOR)).or_default();
+        entry.keep_original |= keep_original;
+        for output in outputs {
+            if !entry.synonyms.contains(output) {
+                entry.synonyms.push(output.clone());
+            }
+        }
+        self.max_input_words = self.max_input_words.max(input.len());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn lookup<'a>(&self, words: impl Iterator<Item = &'a str>) -> Option<&SynonymEntry> {
+        let mut key = String::new();
+        for (i, word) in words.enumerate() {
+            if i > 0 {
+                key.push_str(WORD_SEPARATOR);
+            }
+            key.push_str(word);
+        }
+        self.entries.get(&key)
+    }
+}
+
+/// `SynonymFilter` expands or replaces tokens according to a [`SynonymMap`].
+#[derive(Clone)]
+pub struct SynonymFilter {
+    map: Arc<SynonymMap>,
+}
+
+impl SynonymFilter {
+    pub fn new(map: SynonymMap) -> SynonymFilter {
+        SynonymFilter { map: Arc::new(map) }
+    }
+}
+
+impl TokenFilter for SynonymFilter {
+    type Tokenizer<T: Tokenizer> = SynonymFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> SynonymFilterWrapper<T> {
+        SynonymFilterWrapper {
+            map: self.map,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SynonymFilterWrapper<T: Tokenizer> {
+    map: Arc<SynonymMap>,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for SynonymFilterWrapper<T> {
+    type TokenStream<'a> = SynonymFilterStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        SynonymFilterStream {
+            map: self.map.clone(),
+            tail: self.inner.token_stream(text),
+            tail_exhausted: false,
+            lookahead: VecDeque::new(),
+            output: VecDeque::new(),
+            shift: 0,
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct SynonymFilterStream<T> {
+    map: Arc<SynonymMap>,
+    tail: T,
+    tail_exhausted: bool,
+    /// Tokens read from `tail` that haven't been matched against the map yet.
+    lookahead: VecDeque<Token>,
+    /// Tokens ready to be returned, in position order.
+    output: VecDeque<Token>,
+    /// How far the tokens read from `tail` are moved back, to make room for synonyms longer than
+    /// the inputs they matched.
+    shift: usize,
+    token: Token,
+}
+
+impl<T: TokenStream> SynonymFilterStream<T> {
+    fn fill_lookahead(&mut self) {
+        while !self.tail_exhausted && self.lookahead.len() < self.map.max_input_words {
+            if self.tail.advance() {
+                self.lookahead.push_back(self.tail.token().clone());
+            } else {
+                self.tail_exhausted = true;
+            }
+        }
+    }
+
+    /// The number of leading lookahead tokens that sit at consecutive positions.
+    fn consecutive_prefix(&self) -> usize {
+        let mut len = usize::from(!self.lookahead.is_empty());
+        while len < self.lookahead.len()
+            && self.lookahead[len].position == self.lookahead[len - 1].position + 1
+        {
+            len += 1;
+        }
+        len
+    }
+
+    /// Matches the longest term at the front of the lookahead and moves the resulting tokens
+    /// into `output`.
+    fn process_next(&mut self) {
+        let candidates = self.consecutive_prefix();
+        let matched = (1..=candidates).rev().find_map(|len| {
+            self.map
+                .lookup(self.lookahead.iter().take(len).map(|t| t.text.as_str()))
+                .map(|entry| (len, entry))
+        });
+
+        let Some((len, entry)) = matched else {
+            let mut token = self.lookahead.pop_front().expect("lookahead is not empty");
+            token.position += self.shift;
+            self.output.push_back(token);
+            return;
+        };
+
+        let matched = self.lookahead.drain(..len).collect::<Vec<_>>();
+        let first = &matched[0];
+        let last = &matched[len - 1];
+        let position = first.position + self.shift;
+        // the positions the match takes up once its longest synonym is laid over it
+        let span = entry
+            .synonyms
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0)
+            .max(len);
+        // the last word of a term shorter than the span covers the rest of it
+        let position_length = |i: usize, words: usize| {
+            if i + 1 == words && words < span {
+                span - i
+            } else {
+                1
+            }
+        };
+        let mut emitted = Vec::new();
+
+        if entry.keep_original {
+            for (i, token) in matched.iter().enumerate() {
+                emitted.push(Token {
+                    position: position + i,
+                    position_length: position_length(i, len),
+                    ..token.clone()
+                });
+            }
+        }
+        for synonym in &entry.synonyms {
+            let words = synonym.len();
+            for (i, word) in synonym.iter().enumerate() {
+                emitted.push(Token {
+                    offset_from: first.offset_from,
+                    offset_to: last.offset_to,
+                    position: position + i,
+                    text: word.clone(),
+                    position_length: position_length(i, words),
+                });
+            }
+        }
+        self.shift += span - len;
+
+        // Stable sort, so at each position the original token stays ahead of its synonyms.
+        emitted.sort_by_key(|token| token.position);
+        self.output.extend(emitted);
+    }
+}
+
+impl<T: TokenStream> TokenStream for SynonymFilterStream<T> {
+    fn advance(&mut self) -> bool {
+        if self.map.is_empty() {
+            return self.tail.advance();
+        }
+
+        while self.output.is_empty() {
+            self.fill_lookahead();
+            if self.lookahead.is_empty() {
+                return false;
+            }
+            self.process_next();
+        }
+
+        self.token = self.output.pop_front().expect("output is not empty");
+        true
+    }
+
+    fn token(&self) -> &Token {
+        if self.map.is_empty() {
+            self.tail.token()
+        } else {
+            &self.token
+        }
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        if self.map.is_empty() {
+            self.tail.token_mut()
+        } else {
+            &mut self.token
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SynonymFilter, SynonymMap};
+    use tantivy::tokenizer::{LowerCaser, SimpleTokenizer, TextAnalyzer};
+
+    fn tokens(rules: &[&str], text: &str) -> Vec<(String, usize, usize)> {
+        let map = SynonymMap::from_rules(rules, true).unwrap();
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LowerCaser)
+            .filter(SynonymFilter::new(map))
+            .build();
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        stream.process(&mut |token| {
+            tokens.push((token.text.clone(), token.position, token.position_length));
+        });
+        tokens
+    }
+
+    fn owned(expected: &[(&str, usize, usize)]) -> Vec<(String, usize, usize)> {
+        expected
+            .iter()
+            .map(|(text, position, len)| (text.to_string(), *position, *len))
+            .collect()
+    }
+
+    #[test]
+    fn test_equivalence_rule_keeps_original() {
+        assert_eq!(
+            tokens(&["tv, television"], "Cheap TV deals"),
+            owned(&[
+                ("cheap", 0, 1),
+                ("tv", 1, 1),
+                ("television", 1, 1),
+                ("deals", 2, 1)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_one_way_rule_replaces_original() {
+        assert_eq!(
+            tokens(&["telly => television"], "telly time"),
+            owned(&[("television", 0, 1), ("time", 1, 1)])
+        );
+    }
+
+    #[test]
+    fn test_multi_word_input() {
+        assert_eq!(
+            tokens(&["united states => usa"], "the united states of america"),
+            owned(&[
+                ("the", 0, 1),
+                ("usa", 1, 2),
+                ("of", 3, 1),
+                ("america", 4, 1)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_multi_word_synonym() {
+        assert_eq!(
+            tokens(&["tv, television set"], "new tv"),
+            owned(&[
+                ("new", 0, 1),
+                ("tv", 1, 2),
+                ("television", 1, 1),
+                ("set", 2, 1)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_longer_synonym_moves_following_tokens() {
+        assert_eq!(
+            tokens(&["tv, television set"], "new tv deals today"),
+            owned(&[
+                ("new", 0, 1),
+                ("tv", 1, 2),
+                ("television", 1, 1),
+                ("set", 2, 1),
+                ("deals", 3, 1),
+                ("today", 4, 1)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_longest_match_wins() {
+        assert_eq!(
+            tokens(&["new => fresh", "new york => nyc"], "new york pizza"),
+            owned(&[("nyc", 0, 2), ("pizza", 2, 1)])
+        );
+    }
+
+    #[test]
+    fn test_partial_multi_word_match_falls_through() {
+        assert_eq!(
+            tokens(&["new york => nyc"], "new jersey"),
+            owned(&[("new", 0, 1), ("jersey", 1, 1)])
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        assert!(SynonymMap::from_rules(&["tv"], true).is_err());
+        assert!(SynonymMap::from_rules(&["tv =>"], true).is_err());
+        assert!(SynonymMap::from_rules(&["a => b => c"], true).is_err());
+        assert!(SynonymMap::from_rules(&["# a comment", ""], true).is_ok());
+    }
+}
@@ -0,0 +1,462 @@
+// Copyright (c) 2023-2026 ParadeDB, Inc.
+//
+// This file is part of ParadeDB - Postgres for Search and Analytics
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! A word delimiter filter, which splits tokens like `WiFi-6E` or `SD500` into their parts.
+//!
+//! Parts are separated by any character that isn't a letter or a digit, and optionally by case
+//! changes (`PowerShot` -> `Power`, `Shot`) and letter/digit transitions (`SD500` -> `SD`, `500`).
+//! An uppercase run followed by a lowercase letter is treated as an acronym, so `XMLHttp` splits
+//! into `XML`, `Http`.
+//!
+//! Every part takes a position of its own, and the positions of the following tokens are shifted
+//! to make room for them, so phrase queries continue to match. The original token and catenated
+//! forms are placed at the position of the first part they cover and span their parts through
+//! `position_length`.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use tantivy::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// Options for the [`WordDelimiterFilter`], named after their Elasticsearch counterparts.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct WordDelimiterOptions {
+    /// Split on a change from lowercase to uppercase.
+    pub split_on_case_change: bool,
+    /// Split on a change between letters and digits.
+    pub split_on_numerics: bool,
+    /// Keep the original token alongside its parts.
+    pub preserve_original: bool,
+    /// Also emit consecutive letter parts joined together, e.g. `wi-fi` -> `wifi`.
+    pub catenate_words: bool,
+    /// Also emit consecutive digit parts joined together, e.g. `555-1234` -> `5551234`.
+    pub catenate_numbers: bool,
+    /// Also emit all the parts joined together, e.g. `wi-fi-6` -> `wifi6`.
+    pub catenate_all: bool,
+}
+
+impl Default for WordDelimiterOptions {
+    fn default() -> Self {
+        Self {
+            split_on_case_change: true,
+            split_on_numerics: true,
+            preserve_original: false,
+            catenate_words: false,
+            catenate_numbers: false,
+            catenate_all: false,
+        }
+    }
+}
+
+impl WordDelimiterOptions {
+    const NAMES: [&str; 6] = [
+        "split_on_case_change",
+        "split_on_numerics",
+        "preserve_original",
+        "catenate_words",
+        "catenate_numbers",
+        "catenate_all",
+    ];
+
+    fn values(&self) -> [bool; 6] {
+        [
+            self.split_on_case_change,
+            self.split_on_numerics,
+            self.preserve_original,
+            self.catenate_words,
+            self.catenate_numbers,
+            self.catenate_all,
+        ]
+    }
+
+    /// Lists the options that differ from their defaults, for the tokenizer's name.
+    pub fn name_suffix(&self) -> String {
+        let changed = Self::NAMES
+            .iter()
+            .zip(self.values())
+            .zip(Self::default().values())
+            .filter(|((_, value), default)| value != default)
+            .map(|((name, value), _)| format!("{name}={value}"))
+            .collect::<Vec<_>>();
+        format!("word_delimiter=[{}]", changed.join(","))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CharType {
+    Upper,
+    Lower,
+    Digit,
+    Delimiter,
+}
+
+fn char_type(c: char) -> CharType {
+    if c.is_numeric() {
+        CharType::Digit
+    } else if c.is_uppercase() {
+        CharType::Upper
+    } else if c.is_alphabetic() {
+        CharType::Lower
+    } else {
+        CharType::Delimiter
+    }
+}
+
+/// A part of a token: its byte range within the token's text, and whether it is made of digits.
+#[derive(Debug, PartialEq, Eq)]
+struct Part {
+    range: Range<usize>,
+    is_numeric: bool,
+}
+
+fn split(text: &str, options: &WordDelimiterOptions) -> Vec<Part> {
+    let mut parts = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut chars = text.char_indices().peekable();
+    let mut previous = CharType::Delimiter;
+
+    while let Some((offset, c)) = chars.next() {
+        let current = char_type(c);
+        if current == CharType::Delimiter {
+            if let Some(start) = start.take() {
+                parts.push(Part {
+                    range: start..offset,
+                    is_numeric: previous == CharType::Digit,
+                });
+            }
+            previous = current;
+            continue;
+        }
+
+        let is_break = match (previous, current) {
+            (CharType::Delimiter, _) => false,
+            (CharType::Digit, CharType::Digit) => false,
+            (CharType::Digit, _) | (_, CharType::Digit) => options.split_on_numerics,
+            (CharType::Lower, CharType::Upper) => options.split_on_case_change,
+            // the last letter of an uppercase run starts a new part if a lowercase letter follows
+            (CharType::Upper, CharType::Upper) => {
+                options.split_on_case_change
+                    && chars
+                        .peek()
+                        .is_some_and(|(_, next)| char_type(*next) == CharType::Lower)
+            }
+            _ => false,
+        };
+
+        match start {
+            Some(part_start) if is_break => {
+                parts.push(Part {
+                    range: part_start..offset,
+                    is_numeric: previous == CharType::Digit,
+                });
+                start = Some(offset);
+            }
+            Some(_) => {}
+            None => start = Some(offset),
+        }
+        previous = current;
+    }
+
+    if let Some(start) = start {
+        parts.push(Part {
+            range: start..text.len(),
+            is_numeric: previous == CharType::Digit,
+        });
+    }
+    parts
+}
+
+/// `WordDelimiterFilter` splits tokens into their parts, on intra-word punctuation, case changes
+/// and letter/digit transitions.
+/// This matches the behavior of Elasticsearch's word_delimiter_graph token filter.
+#[derive(Clone)]
+pub struct WordDelimiterFilter {
+    options: WordDelimiterOptions,
+}
+
+impl WordDelimiterFilter {
+    pub fn new(options: WordDelimiterOptions) -> WordDelimiterFilter {
+        WordDelimiterFilter { options }
+    }
+}
+
+impl TokenFilter for WordDelimiterFilter {
+    type Tokenizer<T: Tokenizer> = WordDelimiterFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> WordDelimiterFilterWrapper<T> {
+        WordDelimiterFilterWrapper {
+            options: self.options,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct WordDelimiterFilterWrapper<T: Tokenizer> {
+    options: WordDelimiterOptions,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for WordDelimiterFilterWrapper<T> {
+    type TokenStream<'a> = WordDelimiterFilterStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        WordDelimiterFilterStream {
+            options: self.options,
+            tail: self.inner.token_stream(text),
+            extra_positions: 0,
+            output: VecDeque::new(),
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct WordDelimiterFilterStream<T> {
+    options: WordDelimiterOptions,
+    tail: T,
+    /// How many positions the parts emitted so far added to the token stream.
+    extra_positions: usize,
+    /// Tokens ready to be returned, in position order.
+    output: VecDeque<Token>,
+    token: Token,
+}
+
+impl<T: TokenStream> WordDelimiterFilterStream<T> {
+    fn process(&mut self, original: &Token) {
+        let position = original.position + self.extra_positions;
+        let parts = split(&original.text, &self.options);
+
+        if parts.len() == 1 && parts[0].range.len() == original.text.len() {
+            self.output.push_back(Token {
+                position,
+                ..original.clone()
+            });
+            return;
+        }
+
+        // Parts can only be given offsets of their own if an earlier filter hasn't changed the
+        // token's length; otherwise they share the original token's offsets.
+        let offsets_match = original.offset_to - original.offset_from == original.text.len();
+        let part_token = |range: Range<usize>, position: usize, position_length: usize| Token {
+            offset_from: if offsets_match {
+                original.offset_from + range.start
+            } else {
+                original.offset_from
+            },
+            offset_to: if offsets_match {
+                original.offset_from + range.end
+            } else {
+                original.offset_to
+            },
+            position,
+            text: original.text[range].to_string(),
+            position_length,
+        };
+
+        let mut emitted = Vec::new();
+        if self.options.preserve_original {
+            emitted.push(Token {
+                position,
+                position_length: parts.len().max(1),
+                ..original.clone()
+            });
+        }
+        for (i, part) in parts.iter().enumerate() {
+            emitted.push(part_token(part.range.clone(), position + i, 1));
+        }
+
+        let mut catenate = |ranges: Vec<Range<usize>>| {
+            for run in ranges.into_iter().filter(|run| run.len() > 1) {
+                let mut token = part_token(
+                    parts[run.start].range.start..parts[run.end - 1].range.end,
+                    position + run.start,
+                    run.len(),
+                );
+                token.text = parts[run.clone()]
+                    .iter()
+                    .map(|part| &original.text[part.range.clone()])
+                    .collect();
+                // skip forms already emitted, like `catenate_all` repeating `catenate_words`
+                if !emitted
+                    .iter()
+                    .any(|other| other.position == token.position && other.text == token.text)
+                {
+                    emitted.push(token);
+                }
+            }
+        };
+        if self.options.catenate_words {
+            catenate(runs(&parts, |part| !part.is_numeric));
+        }
+        if self.options.catenate_numbers {
+            catenate(runs(&parts, |part| part.is_numeric));
+        }
+        if self.options.catenate_all {
+            catenate(vec![0..parts.len()]);
+        }
+
+        // Stable sort, so at each position the original token stays ahead of the parts.
+        emitted.sort_by_key(|token| token.position);
+        self.output.extend(emitted);
+        self.extra_positions += parts.len().saturating_sub(1);
+    }
+}
+
+/// The ranges of consecutive parts that satisfy `predicate`.
+fn runs(parts: &[Part], predicate: impl Fn(&Part) -> bool) -> Vec<Range<usize>> {
+    let mut runs = Vec::new();
+    let mut start = None;
+    for (i, part) in parts.iter().enumerate() {
+        match (start, predicate(part)) {
+            (None, true) => start = Some(i),
+            (Some(run_start), false) => {
+                runs.push(run_start..i);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(run_start) = start {
+        runs.push(run_start..parts.len());
+    }
+    runs
+}
+
+impl<T: TokenStream> TokenStream for WordDelimiterFilterStream<T> {
+    fn advance(&mut self) -> bool {
+        while self.output.is_empty() {
+            if !self.tail.advance() {
+                return false;
+            }
+            let original = self.tail.token().clone();
+            self.process(&original);
+        }
+
+        self.token = self.output.pop_front().expect("output is not empty");
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::tokenizer::{TextAnalyzer, WhitespaceTokenizer};
+
+    fn tokens(options: WordDelimiterOptions, text: &str) -> Vec<(String, usize, usize)> {
+        let mut analyzer = TextAnalyzer::builder(WhitespaceTokenizer::default())
+            .filter(WordDelimiterFilter::new(options))
+            .build();
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        stream.process(&mut |token| {
+            tokens.push((token.text.clone(), token.position, token.position_length));
+        });
+        tokens
+    }
+
+    fn texts(options: WordDelimiterOptions, text: &str) -> Vec<String> {
+        tokens(options, text).into_iter().map(|(t, ..)| t).collect()
+    }
+
+    #[test]
+    fn test_split() {
+        let options = WordDelimiterOptions::default();
+        assert_eq!(texts(options, "WiFi-6E"), vec!["Wi", "Fi", "6", "E"]);
+        assert_eq!(
+            texts(options, "PowerShot SD500"),
+            vec!["Power", "Shot", "SD", "500"]
+        );
+        assert_eq!(
+            texts(options, "XMLHttpRequest"),
+            vec!["XML", "Http", "Request"]
+        );
+        assert_eq!(texts(options, "plain"), vec!["plain"]);
+        assert!(texts(options, "--").is_empty());
+    }
+
+    #[test]
+    fn test_split_options() {
+        let options = WordDelimiterOptions {
+            split_on_case_change: false,
+            split_on_numerics: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            texts(options, "PowerShot SD500 wi-fi"),
+            vec!["PowerShot", "SD500", "wi", "fi"]
+        );
+    }
+
+    #[test]
+    fn test_positions_make_room_for_parts() {
+        assert_eq!(
+            tokens(WordDelimiterOptions::default(), "PowerShot SD500 camera"),
+            vec![
+                ("Power".to_string(), 0, 1),
+                ("Shot".to_string(), 1, 1),
+                ("SD".to_string(), 2, 1),
+                ("500".to_string(), 3, 1),
+                ("camera".to_string(), 4, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_preserve_original_and_catenate() {
+        let options = WordDelimiterOptions {
+            preserve_original: true,
+            catenate_words: true,
+            catenate_numbers: true,
+            catenate_all: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            tokens(options, "wi-fi-555-1234"),
+            vec![
+                ("wi-fi-555-1234".to_string(), 0, 4),
+                ("wi".to_string(), 0, 1),
+                ("wifi".to_string(), 0, 2),
+                ("wifi5551234".to_string(), 0, 4),
+                ("fi".to_string(), 1, 1),
+                ("555".to_string(), 2, 1),
+                ("5551234".to_string(), 2, 2),
+                ("1234".to_string(), 3, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_part_offsets() {
+        let mut analyzer = TextAnalyzer::builder(WhitespaceTokenizer::default())
+            .filter(WordDelimiterFilter::new(WordDelimiterOptions::default()))
+            .build();
+        let mut stream = analyzer.token_stream("buy WiFi-6E");
+        let mut offsets = vec![];
+        stream.process(&mut |token| offsets.push((token.offset_from, token.offset_to)));
+        assert_eq!(offsets, vec![(0, 3), (4, 6), (6, 8), (9, 10), (10, 11)]);
+    }
+}